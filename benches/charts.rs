@@ -0,0 +1,93 @@
+//! Turning per-ball history into the four telemetry charts, the way
+//! `ui::charts` does every frame: find the axis bounds, build one dataset per
+//! ball and render the chart into a pane-sized buffer.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    symbols,
+    widgets::{Axis, Block, Borders, Chart, Dataset, Widget},
+};
+use rust_tui_ball_bounce::components::{with_ticks, History};
+use rust_tui_ball_bounce::Simulation;
+
+const HISTORY: usize = 300;
+
+type Series = fn(&History) -> &[f64];
+
+const SERIES: [Series; 4] = [|h| &h.x, |h| &h.y, |h| &h.vx, |h| &h.vy];
+
+/// Full histories for `balls` balls, and the ticks they share.
+fn histories(balls: usize) -> (Vec<f64>, Vec<History>) {
+    let mut sim = Simulation::new(80.0, 20.0);
+    sim.max_history = HISTORY;
+    for _ in 0..balls {
+        sim.add_ball(sim.next_ball_spec());
+    }
+    for _ in 0..HISTORY {
+        sim.tick();
+    }
+    let histories = sim.histories().map(|(_, history)| History::clone(&history)).collect();
+    (sim.history_ticks().to_vec(), histories)
+}
+
+fn bounds<'a>(series: impl Iterator<Item = &'a [(f64, f64)]>) -> ([f64; 2], [f64; 2]) {
+    let (mut t, mut v) = ([f64::MAX, f64::MIN], [f64::MAX, f64::MIN]);
+    for points in series {
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            t = [t[0].min(first.0), t[1].max(last.0)];
+        }
+        for &(_, value) in points {
+            v = [v[0].min(value), v[1].max(value)];
+        }
+    }
+    (t, v)
+}
+
+fn render_chart((ticks, histories): &(Vec<f64>, Vec<History>), series: Series, buf: &mut Buffer) {
+    let points: Vec<Vec<(f64, f64)>> = histories
+        .iter()
+        .map(|history| with_ticks(ticks, series(history)).collect())
+        .collect();
+    let (t_bounds, v_bounds) = bounds(points.iter().map(Vec::as_slice));
+    let datasets: Vec<Dataset> = points
+        .iter()
+        .enumerate()
+        .map(|(i, points)| {
+            Dataset::default()
+                .name(format!("B{}", i + 1))
+                .marker(symbols::Marker::Braille)
+                .data(points)
+        })
+        .collect();
+    Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL))
+        .x_axis(Axis::default().bounds(t_bounds))
+        .y_axis(Axis::default().bounds(v_bounds))
+        .render(buf.area, buf);
+}
+
+fn charts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("charts");
+    // One pane of the default layout in a 120x40 terminal
+    let mut buf = Buffer::empty(Rect::new(0, 0, 60, 13));
+    for balls in [10, 100, 1_000] {
+        let histories = histories(balls);
+        group.bench_with_input(BenchmarkId::from_parameter(balls), &histories, |b, histories| {
+            b.iter(|| {
+                for series in SERIES {
+                    buf.reset();
+                    render_chart(histories, series, &mut buf);
+                }
+                black_box(&buf);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, charts);
+criterion_main!(benches);
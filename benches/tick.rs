@@ -0,0 +1,101 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_tui_ball_bounce::force::{Drag, Gravity};
+use rust_tui_ball_bounce::hecs::Entity;
+use rust_tui_ball_bounce::kernels::{self, Balls};
+use rust_tui_ball_bounce::Simulation;
+
+/// `balls` balls at roughly the same density as the default 80x20 arena
+/// with 8 balls, moving under gravity and drag.
+fn simulation(balls: usize) -> Simulation {
+    let scale = (balls as f64 / 8.0).sqrt().max(1.0);
+    let mut sim = Simulation::new(80.0 * scale, 20.0 * scale);
+    // As in the TUI, where charts keep their own history
+    sim.max_history = 0;
+    sim.add_force(Gravity::down(0.02));
+    sim.add_force(Drag(0.001));
+    for _ in 0..balls {
+        sim.add_ball(sim.next_ball_spec());
+    }
+    // Let the initial layout settle so every sample measures a typical tick
+    for _ in 0..50 {
+        sim.tick();
+    }
+    sim
+}
+
+fn tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for balls in [10, 100, 1_000, 10_000] {
+        if balls >= 10_000 {
+            group.sample_size(10);
+        }
+        let mut sim = simulation(balls);
+        group.bench_with_input(BenchmarkId::from_parameter(balls), &balls, |b, _| {
+            b.iter(|| {
+                sim.tick();
+                black_box(sim.drain_events());
+            })
+        });
+    }
+    group.finish();
+}
+
+fn tick_with_history(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick_with_history");
+    for balls in [10, 100, 1_000] {
+        let mut sim = simulation(balls);
+        sim.max_history = 300;
+        // Fill the history so trimming is part of every tick
+        for _ in 0..300 {
+            sim.tick();
+        }
+        group.bench_with_input(BenchmarkId::from_parameter(balls), &balls, |b, _| {
+            b.iter(|| sim.tick())
+        });
+    }
+    group.finish();
+}
+
+/// `balls` balls as a step has them after gathering, at the same density
+/// as [`simulation`] but without its settling ticks, which would take
+/// minutes at 100,000 balls. Also returns the right wall and the floor.
+fn gathered(balls: usize) -> (Balls, f64, f64) {
+    let scale = (balls as f64 / 8.0).sqrt().max(1.0);
+    let mut sim = Simulation::new(80.0 * scale, 20.0 * scale);
+    for _ in 0..balls {
+        sim.add_ball(sim.next_ball_spec());
+    }
+    let mut columns = Balls::default();
+    for &ball in sim.state() {
+        columns.push(Entity::DANGLING, ball);
+    }
+    (columns, sim.area_width - 1.0, sim.area_height - sim.aspect)
+}
+
+/// The per-ball loops on their own, which the pairwise collision checks
+/// swamp in a whole tick. Compare with `--features simd`.
+fn kernels(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kernels");
+    for balls in [1_000, 10_000, 100_000] {
+        let (mut columns, _, _) = gathered(balls);
+        group.bench_with_input(BenchmarkId::new("integrate", balls), &balls, |b, _| {
+            b.iter(|| kernels::integrate(black_box(&mut columns), 1.0))
+        });
+        // Moving first, as a tick does, so some balls are always at a wall
+        let (mut columns, right, floor) = gathered(balls);
+        let mut events = Vec::new();
+        group.bench_with_input(BenchmarkId::new("walls", balls), &balls, |b, _| {
+            b.iter(|| {
+                events.clear();
+                kernels::integrate(&mut columns, 1.0);
+                black_box(kernels::bounce_off_walls(&mut columns, right, floor, &mut events))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tick, tick_with_history, kernels);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+//! Turns drawn [`Buffer`]s into the escape sequences a terminal needs to
+//! show them, for frontends that write to something other than a ratatui
+//! backend: cast files, or xterm.js in the browser.
+
+use std::fmt::Write as _;
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+
+/// Escape sequences that redraw the cells of `current` that differ from
+/// `previous`, leaving the style reset afterwards.
+pub fn encode(previous: &Buffer, current: &Buffer) -> String {
+    let mut output = String::new();
+    // Where the cursor is, and the style last set
+    let mut cursor = None;
+    let mut style = None;
+    for (x, y, cell) in previous.diff(current) {
+        if cursor != Some((x, y)) {
+            let _ = write!(output, "\x1b[{};{}H", y + 1, x + 1);
+        }
+        let cell_style = (cell.fg, cell.bg, cell.modifier);
+        if style != Some(cell_style) {
+            output.push_str(&sgr(cell));
+            style = Some(cell_style);
+        }
+        output.push_str(cell.symbol());
+        // Wide symbols move the cursor further, but the cells they cover
+        // never come up in the diff, so the next cell always moves it back
+        cursor = Some((x + 1, y));
+    }
+    if style.is_some() {
+        output.push_str("\x1b[0m");
+    }
+    output
+}
+
+/// Resets the style, then sets the cell's colors and modifiers.
+fn sgr(cell: &Cell) -> String {
+    let mut codes = vec!["0".to_string()];
+    const MODIFIERS: &[(Modifier, &str)] = &[
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for &(modifier, code) in MODIFIERS {
+        if cell.modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(color_code(cell.fg, false));
+    codes.extend(color_code(cell.bg, true));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(i) => return Some(format!("{};5;{}", base + 8, i)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    };
+    Some(code.to_string())
+}
@@ -0,0 +1,769 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use tracing::info;
+use tracing_subscriber::filter::LevelFilter;
+use rust_tui_ball_bounce::components::{History, Samples};
+use rust_tui_ball_bounce::force::Direction;
+use rust_tui_ball_bounce::scenario::{self, Source};
+use rust_tui_ball_bounce::{
+    BallState, Command, SimEvent, Simulation, SimulationBuilder, SimulationObserver, Snapshot,
+};
+
+use crate::caps::{Caps, ColorDepth, Glyphs};
+use crate::compare::Comparison;
+use crate::config::{self, Config};
+use crate::conservation::Exchange;
+use crate::control::StateStream;
+use crate::describe::Description;
+use crate::export::{self, Recording};
+use crate::game::{Game, GAME_NAMES};
+use crate::gas::GasStats;
+use crate::import;
+use crate::keys::KeyMap;
+use crate::lag::{Lag, TickMeter};
+use crate::layout::{LayoutEdit, Pane, PaneLayout, Section};
+use crate::locale::{self, Strings};
+use crate::logging::LogLevel;
+use crate::map;
+use crate::markers::Markers;
+use crate::mode::{MenuEntry, Mode, ThemePicker};
+use crate::paint::{Paint, Stain};
+use crate::picker::Picker;
+use crate::profiler::{Phase, Profiler};
+use crate::replay::Replay;
+use crate::scores::{self, Scores};
+use crate::sim_thread::{SimThread, Update};
+use crate::sweep::Sweep;
+use crate::theme::{self, Backdrop, Theme, THEMES};
+use crate::twin::Twin;
+use crate::ui::{ChartData, PaneCache, ASCII_BALL_CHARS, BALL_CHARS, FIELD_SPACING};
+use crate::units::Units;
+
+/// How long a loaded scenario's description stays over the arena, in
+/// simulated time.
+const CAPTION_TIME: Duration = Duration::from_secs(8);
+
+/// The simulation (running on its own thread) plus everything the terminal
+/// frontend layers on top: display settings, key bindings, pane layout and
+/// the current [`Mode`].
+pub struct App {
+    pub sim: SimThread,
+    // Latest state received from the simulation thread
+    pub state: Snapshot,
+    // Chart telemetry per ball id, built from the snapshots
+    pub history: BTreeMap<usize, History>,
+    // When the samples in `history` were taken, shared by every ball
+    pub history_ticks: Samples,
+    // Bumped whenever `history` changes, and on every update, so panes
+    // know when to redraw
+    pub history_version: u64,
+    // Collisions, spawns and speed changes to mark on the charts' time axis
+    pub markers: Markers,
+    pub updates: u64,
+    pub pane_cache: PaneCache,
+    pub chart_data: ChartData,
+    pub max_history: usize,
+    // Queued by input handlers and sent once per frame
+    pub commands: Vec<Command>,
+    // How often to redraw; the simulation ticks at its own rate
+    pub frame_rate: Duration,
+    pub tick_rate: Duration,
+    // Meters and seconds for readouts, when `simulation.meters_per_cell`
+    // asks for them
+    pub units: Option<Units>,
+    // Tick and draw timings, and how far drawing is cut back to keep up
+    pub lag: Lag,
+    // Shown over the arena under --stress
+    pub tick_meter: Option<TickMeter>,
+    // Per-phase frame timings, shown over the panes when `show_profiler`
+    pub profiler: Profiler,
+    pub show_profiler: bool,
+    // Arrows over the arena for the force field, sampled by the simulation
+    // only while they're shown
+    pub show_field: bool,
+    // A magnified view around the picked ball, in a corner of the arena
+    pub show_zoom: bool,
+    // The balls' stains on the arena, while painting
+    pub paint: Option<Paint>,
+    // See `display.paint_decay`
+    pub paint_decay: f64,
+    // Collisions counted for the controls panel while a gas runs
+    pub gas_stats: GasStats,
+    // The latest collision, shown in the controls panel while paused
+    pub last_collision: Option<Exchange>,
+    // The chaos experiment's twin, while it runs
+    pub twin: Option<Twin>,
+    // The last parameter sweep, running or done
+    pub sweep: Option<Sweep>,
+    // The A/B comparison, while it runs
+    pub comparison: Option<Comparison>,
+    // The name and description of the last scenario loaded from the app
+    pub caption: Option<(String, String)>,
+    pub theme: &'static Theme,
+    pub strings: &'static Strings,
+    // What the terminal was found to draw, for `auto` glyphs and colors
+    pub caps: Caps,
+    pub glyphs: Glyphs,
+    // Whether to draw with plain ASCII; the same as `glyphs` saying so until
+    // switched in the settings screen
+    pub ascii: bool,
+    pub colors: ColorDepth,
+    pub color: bool,
+    // Skip effects that only decorate motion; see `display.reduced_motion`
+    pub reduced_motion: bool,
+    pub backdrop: Backdrop,
+    // What the controls panel shows, top to bottom
+    pub status_panel: Vec<Section>,
+    // Plain text in place of the panes, for screen readers
+    pub described: bool,
+    pub description: Description,
+    // See `display.rescale_on_resize`
+    pub rescale_on_resize: bool,
+    pub keymap: KeyMap,
+    pub layout: PaneLayout,
+    // Whether the last frame was small enough for the compact layout
+    pub compact: bool,
+    // The pane arrow keys act on, picked with Tab; with none they steer the
+    // simulation and games as usual
+    pub focus: Option<Pane>,
+    // The ball picked in the controls panel's list, by position in it
+    pub ball_cursor: usize,
+    // How far back from the newest tick the charts' crosshair sits
+    pub crosshair: f64,
+    // Inside of the arena's border as last drawn, for mouse input
+    pub arena_area: Rect,
+    pub mode: Mode,
+    pub game: Option<Game>,
+    pub scores: Scores,
+    // Where scores are saved; `None` keeps them for the session only
+    pub scores_path: Option<PathBuf>,
+    // Where the last finished run placed on its game's board
+    pub placed: Option<usize>,
+    // Config file contents before any profile or command-line overrides
+    pub base_config: Config,
+    pub profile: Option<String>,
+    pub config_path: Option<PathBuf>,
+    pub notice: Option<String>,
+    // Told about every tick and event the simulation thread reports
+    pub observers: Vec<Box<dyn SimulationObserver>>,
+    // Set when logging to a file
+    pub log_level: Option<LogLevel>,
+    // Sent the state after every update, paused or not, when something
+    // watches over the control socket
+    pub state_stream: Option<StateStream>,
+    #[cfg(feature = "scripting")]
+    pub script: crate::script::Script,
+}
+
+/// A builder with the `[simulation]` and aspect settings from `config`.
+pub fn sim_builder(config: &Config) -> SimulationBuilder {
+    let settings = &config.simulation;
+    SimulationBuilder::new()
+        .paused(settings.paused)
+        .speed(settings.speed)
+        .ball_radius(settings.ball_radius)
+        .aspect(if config.display.aspect_correction { 2.0 } else { 1.0 })
+        .auto_balls(settings.balls)
+}
+
+/// Sets up an [`App`] around a simulation. Settings left out come from
+/// [`Config::default`].
+pub struct AppBuilder {
+    sim: Simulation,
+    config: Config,
+    base_config: Option<Config>,
+    profile: Option<String>,
+    config_path: Option<PathBuf>,
+    notice: Option<String>,
+    caps: Caps,
+}
+
+impl AppBuilder {
+    pub fn new(sim: Simulation) -> Self {
+        AppBuilder {
+            sim,
+            config: Config::default(),
+            base_config: None,
+            profile: None,
+            config_path: None,
+            notice: None,
+            caps: Caps::default(),
+        }
+    }
+
+    /// Display settings, key bindings, layout, tick rate and history length.
+    pub fn config(mut self, config: &Config) -> Self {
+        self.config = config.clone();
+        self
+    }
+
+    /// The config file before any profile or overrides, which profiles are
+    /// switched against. Defaults to [`config`](Self::config).
+    pub fn base_config(mut self, config: Config) -> Self {
+        self.base_config = Some(config);
+        self
+    }
+
+    pub fn profile(mut self, name: Option<String>) -> Self {
+        self.profile = name;
+        self
+    }
+
+    /// Where the layout editor saves to, and scenarios and scores are found.
+    pub fn config_path(mut self, path: Option<PathBuf>) -> Self {
+        self.config_path = path;
+        self
+    }
+
+    /// What the terminal draws, for the `auto` glyphs and colors. Defaults
+    /// to everything.
+    pub fn caps(mut self, caps: Caps) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    /// Shown in the status pane until something replaces it.
+    pub fn notice(mut self, notice: Option<String>) -> Self {
+        self.notice = notice;
+        self
+    }
+
+    /// Starts the simulation on its own thread.
+    pub fn build(self) -> App {
+        let config = self.config;
+        let state = self.sim.snapshot();
+        let mut notice = self.notice;
+        let mut scores_path = self.config_path.as_deref().and_then(Path::parent).map(|dir| dir.join("scores.toml"));
+        let scores = match scores_path.as_deref().map(scores::load).transpose() {
+            Ok(scores) => scores.unwrap_or_default(),
+            Err(err) => {
+                // Rather than overwrite a file that couldn't be read
+                scores_path = None;
+                notice.get_or_insert(format!("Scores not loaded: {}", err));
+                Scores::default()
+            }
+        };
+        let tick_rate = Duration::from_millis(config.simulation.tick_ms);
+        let mut app = App {
+            sim: SimThread::spawn(self.sim, tick_rate),
+            tick_rate,
+            units: Units::new(config.simulation.meters_per_cell, tick_rate),
+            state,
+            history: BTreeMap::new(),
+            history_ticks: Samples::default(),
+            markers: Markers::default(),
+            history_version: 0,
+            updates: 0,
+            pane_cache: PaneCache::default(),
+            chart_data: ChartData::default(),
+            max_history: config.simulation.history,
+            commands: Vec::new(),
+            frame_rate: Duration::from_millis(16),
+            lag: Lag::default(),
+            tick_meter: None,
+            profiler: Profiler::default(),
+            show_profiler: false,
+            show_field: false,
+            show_zoom: false,
+            paint: None,
+            paint_decay: 0.0,
+            gas_stats: GasStats::default(),
+            last_collision: None,
+            twin: None,
+            sweep: None,
+            comparison: None,
+            caption: None,
+            theme: &theme::DEFAULT,
+            strings: &locale::ENGLISH,
+            caps: self.caps,
+            glyphs: self.caps.glyphs,
+            ascii: false,
+            colors: self.caps.colors,
+            color: true,
+            reduced_motion: false,
+            backdrop: Backdrop::None,
+            status_panel: Section::DEFAULT.to_vec(),
+            described: false,
+            description: Description::default(),
+            rescale_on_resize: false,
+            keymap: KeyMap::default(),
+            layout: PaneLayout::default(),
+            compact: false,
+            focus: None,
+            ball_cursor: 0,
+            crosshair: 0.0,
+            arena_area: Rect::default(),
+            mode: Mode::Running,
+            game: None,
+            scores,
+            scores_path,
+            placed: None,
+            base_config: self.base_config.unwrap_or_else(|| config.clone()),
+            profile: self.profile,
+            config_path: self.config_path,
+            notice,
+            observers: Vec::new(),
+            log_level: None,
+            state_stream: None,
+            #[cfg(feature = "scripting")]
+            script: Default::default(),
+        };
+        app.apply_display(&config);
+        app.mode = app.base_mode();
+        app
+    }
+}
+
+impl App {
+    /// Sends queued commands to the simulation thread.
+    pub fn run_commands(&mut self) {
+        for command in self.commands.drain(..) {
+            info!(?command, "command");
+            self.sim.command(command);
+        }
+    }
+
+    /// Takes in an update from the simulation thread: passes it to the
+    /// observers, reacts to its events, records chart history, and keeps the
+    /// snapshot for drawing.
+    pub fn receive(&mut self, update: Update) {
+        let ticked = update.snapshot.tick_count > self.state.tick_count;
+        if let Some(times) = &update.step_times {
+            self.lag.tick_took(times.total());
+            self.profiler.step_took(times);
+        }
+        for observer in &mut self.observers {
+            if ticked {
+                observer.on_tick(&update.snapshot);
+            }
+            for event in &update.events {
+                observer.on_event(event);
+            }
+        }
+        #[cfg(feature = "scripting")]
+        self.run_script_hooks(&update);
+        for event in &update.events {
+            // Marked only where there's history to chart them against
+            if self.max_history > 0 && !matches!(self.mode, Mode::Replay(_)) && self.markers.record(update.snapshot.tick_count as f64, event) {
+                self.history_version += 1;
+            }
+            self.game_event(event);
+            if self.described {
+                self.description.event(event);
+            }
+        }
+        if let Some(paint) = &mut self.paint {
+            paint.update(&update.snapshot);
+        }
+        if update.snapshot.gas {
+            self.gas_stats.update(&self.state, &update.snapshot, &update.events);
+        }
+        if let Some(exchange) = Exchange::latest(&update.events, &update.snapshot) {
+            self.last_collision = Some(exchange);
+        }
+        match (&mut self.twin, update.twin) {
+            (Some(twin), Some(balls)) => twin.record(update.snapshot.tick_count, &update.snapshot.balls, balls),
+            // Updates sent before the twin started have none either
+            (Some(twin), None) if !twin.divergence.is_empty() => {
+                self.twin = None;
+                self.notice = Some("Twin stopped: the simulation changed".to_string());
+            }
+            _ => {}
+        }
+        if let (Some(comparison), Some(other)) = (&mut self.comparison, update.other) {
+            comparison.record(&update.snapshot, other);
+        }
+        for event in update.events {
+            match event {
+                SimEvent::ScenarioLoaded(name) => self.notice = Some(format!("Loaded {}", name)),
+                SimEvent::Reset => {
+                    self.clear_history();
+                    self.gas_stats = GasStats::default();
+                    self.last_collision = None;
+                }
+                _ => {}
+            }
+        }
+        if ticked {
+            let start = self.profiler.now();
+            self.record_history(&update.snapshot);
+            self.profiler.add(Phase::History, self.profiler.now().saturating_sub(start));
+        }
+        self.state = update.snapshot;
+        self.updates += 1;
+        self.update_game();
+        if let Some(stream) = &mut self.state_stream {
+            stream.send(&self.state);
+        }
+        if self.mode.is_base() {
+            self.mode = self.base_mode();
+        }
+    }
+
+    /// The caption's name and description while its scenario is in its first
+    /// few seconds.
+    pub fn caption(&self) -> Option<(&str, &str)> {
+        let (name, description) = self.caption.as_ref()?;
+        let elapsed = self.tick_rate.mul_f64(self.state.tick_count as f64);
+        (self.state.scenario_name.as_ref() == Some(name) && elapsed < CAPTION_TIME).then_some((name, description))
+    }
+
+    /// Running or Paused, whichever the simulation is.
+    pub fn base_mode(&self) -> Mode {
+        if self.state.paused {
+            Mode::Paused
+        } else {
+            Mode::Running
+        }
+    }
+
+    fn record_history(&mut self, snapshot: &Snapshot) {
+        // Turned off for --stress --no-history, and kept for the recording
+        // during a replay
+        if self.max_history == 0 || matches!(self.mode, Mode::Replay(_)) {
+            return;
+        }
+        let t = snapshot.tick_count as f64;
+        self.history_version += 1;
+        self.history
+            .retain(|id, _| snapshot.balls.binary_search_by_key(id, |b| b.id).is_ok());
+        self.history_ticks.push(t, self.max_history);
+        self.markers.forget_before(self.history_ticks[0]);
+        for ball in &snapshot.balls {
+            let history = self.history.entry(ball.id).or_default();
+            history.record((ball.x, ball.y), (ball.vx, ball.vy), self.max_history);
+        }
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_ticks.clear();
+        self.markers.clear();
+        self.history_version += 1;
+    }
+
+    /// Writes the chart history to `path`, or to `ball-bounce-<tick>.csv` in
+    /// the current directory, and returns where it went.
+    pub fn export(&self, path: Option<PathBuf>) -> io::Result<PathBuf> {
+        let path = path
+            .unwrap_or_else(|| PathBuf::from(format!("ball-bounce-{}.csv", self.state.tick_count)));
+        let recording = Recording {
+            snapshot: &self.state,
+            ticks: &self.history_ticks,
+            histories: self.history.iter().map(|(&id, history)| (id, history)).collect(),
+            tick_rate: self.tick_rate,
+        };
+        export::save(&path, &recording)?;
+        Ok(path)
+    }
+
+    /// Pauses the simulation to show `replay` in its place, with the
+    /// recording's histories in the charts.
+    pub fn replay(&mut self, replay: Replay) -> Mode {
+        self.commands.push(Command::SetPaused(true));
+        self.clear_history();
+        let limit = replay.ticks.len();
+        for &tick in &replay.ticks {
+            self.history_ticks.push(tick, limit);
+        }
+        for (&id, track) in &replay.tracks {
+            let history = self.history.entry(id).or_default();
+            for (((&(_, x), &(_, y)), &(_, vx)), &(_, vy)) in track.x.iter().zip(&track.y).zip(&track.vx).zip(&track.vy) {
+                history.record((x, y), (vx, vy), limit);
+            }
+        }
+        self.notice = None;
+        Mode::Replay(Box::new(replay))
+    }
+
+    /// Starts the chaos experiment with the first ball nudged by `epsilon`,
+    /// or stops it for `None`.
+    pub fn set_twin(&mut self, epsilon: Option<f64>) {
+        if epsilon.is_some() && self.state.balls.is_empty() {
+            self.notice = Some("Twin: no balls to nudge".to_string());
+            return;
+        }
+        // So commands already queued reach both or neither
+        self.run_commands();
+        self.twin = epsilon.map(Twin::new);
+        self.sim.twin(epsilon);
+    }
+
+    /// Changes how much `--log` writes.
+    pub fn set_log_level(&self, level: LevelFilter) -> Result<(), String> {
+        match &self.log_level {
+            Some(log_level) => log_level.set(level),
+            None => Err("not logging; start with --log PATH".to_string()),
+        }
+    }
+
+    /// Adds the balls in the file at `path` (see [`import`]) to the
+    /// simulation, returning how many there were.
+    pub fn import(&mut self, path: &Path) -> io::Result<usize> {
+        let specs = import::load(path)?;
+        let count = specs.len();
+        self.sim.edit(move |sim| {
+            for spec in specs {
+                sim.add_ball(spec);
+            }
+        });
+        Ok(count)
+    }
+
+    /// Replaces the walls with the ones in the map file at `path` (see
+    /// [`map`]), returning how many cells they cover.
+    pub fn load_map(&mut self, path: &Path) -> io::Result<usize> {
+        let obstacles = map::load(path, self.state.aspect)?;
+        let cells = obstacles.iter().map(|o| o.width * o.height / self.state.aspect).sum::<f64>().round() as usize;
+        self.state.obstacles = obstacles.clone();
+        self.sim.edit(move |sim| sim.obstacles = obstacles);
+        Ok(cells)
+    }
+
+    pub fn layout_edit(&mut self) -> Mode {
+        self.notice = None;
+        Mode::LayoutEdit(LayoutEdit::new())
+    }
+
+    pub fn save_layout(&mut self) {
+        let saved = match &self.config_path {
+            Some(path) => config::save_layout(path, self.profile.as_deref(), &self.layout),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "no config directory")),
+        };
+        self.notice = Some(match saved {
+            Ok(()) => "Layout saved".to_string(),
+            Err(err) => format!("Layout not saved: {}", err),
+        });
+    }
+
+    /// Switches to `theme`, redrawing every pane in it.
+    pub fn set_theme(&mut self, theme: &'static Theme) {
+        self.theme = theme;
+        self.pane_cache.clear();
+    }
+
+    pub fn theme_picker(&self) -> Mode {
+        let mut picker = Picker::new(THEMES.to_vec());
+        picker.selected = THEMES.iter().position(|t| t.name == self.theme.name).unwrap_or(0);
+        Mode::ThemePicker(ThemePicker { picker, original: self.theme })
+    }
+
+    /// Writes the current theme to the config file, under the active
+    /// profile if there is one.
+    pub fn save_theme(&mut self) {
+        let saved = match &self.config_path {
+            Some(path) => config::save_theme(path, self.profile.as_deref(), self.theme.name),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "no config directory")),
+        };
+        self.notice = Some(match saved {
+            Ok(()) => format!("Theme {} saved", self.theme.name),
+            Err(err) => format!("Theme not saved: {}", err),
+        });
+    }
+
+    pub fn scenario_picker(&mut self) -> Mode {
+        let (entries, errors) = scenario::discover(self.scenario_dir().as_deref());
+        self.notice = errors.first().map(|err| format!("Skipped {}", err));
+        Mode::ScenarioPicker(Picker::new(entries))
+    }
+
+    /// The sandbox, then the games, then the built-in scenarios they aren't
+    /// played on.
+    pub fn menu(&self) -> Mode {
+        let mut entries = vec![MenuEntry::Sandbox];
+        entries.extend(GAME_NAMES.iter().filter_map(|name| Game::by_name(name)).map(MenuEntry::Game));
+        let (scenarios, _) = scenario::discover(None);
+        entries.extend(
+            scenarios
+                .into_iter()
+                .filter(|entry| matches!(entry.source, Source::Builtin(slug) if !GAME_NAMES.contains(&slug)))
+                .map(|entry| MenuEntry::Demo(Box::new(entry))),
+        );
+        Mode::Menu(Picker::new(entries))
+    }
+
+    /// Back to free play: ends any game and, if a scenario was loaded,
+    /// restarts with the config file and profile.
+    pub fn sandbox(&mut self) {
+        if self.game.is_none() && self.state.scenario_name.is_none() {
+            return;
+        }
+        match self.profile_config(self.profile.as_deref()) {
+            Ok(config) => {
+                self.apply_config(&config);
+                self.notice = Some("Sandbox".to_string());
+            }
+            Err(err) => self.notice = Some(err),
+        }
+    }
+
+    /// `scenarios/` next to the config file.
+    pub fn scenario_dir(&self) -> Option<PathBuf> {
+        self.config_path
+            .as_deref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.join("scenarios"))
+    }
+
+    /// Applies `config` to the running app, replacing the current balls and
+    /// ending any game.
+    pub fn apply_config(&mut self, config: &Config) {
+        self.game = None;
+        self.apply_display(config);
+        self.max_history = config.simulation.history;
+        self.clear_history();
+        self.tick_rate = Duration::from_millis(config.simulation.tick_ms);
+        self.sim.set_tick_rate(self.tick_rate);
+        self.units = Units::new(config.simulation.meters_per_cell, self.tick_rate);
+        let config = config.clone();
+        self.sim.edit(move |sim| sim_builder(&config).apply(sim));
+    }
+
+    fn apply_display(&mut self, config: &Config) {
+        self.pane_cache.clear();
+        self.frame_rate = Duration::from_secs(1) / config.display.fps;
+        self.glyphs = match Glyphs::by_name(&config.display.glyphs) {
+            _ if config.display.ascii => Glyphs::Ascii,
+            Some(glyphs) => glyphs,
+            None => self.caps.glyphs,
+        };
+        self.ascii = self.glyphs == Glyphs::Ascii;
+        self.colors = ColorDepth::by_name(&config.display.colors).unwrap_or(self.caps.colors);
+        self.color = config.display.color && self.colors != ColorDepth::None;
+        self.reduced_motion = config.display.reduced_motion;
+        self.backdrop = Backdrop::by_name(&config.display.background).unwrap_or(Backdrop::None);
+        self.status_panel = config.display.status_panel.iter().filter_map(|name| Section::by_name(name)).collect();
+        self.described = config.display.described;
+        self.rescale_on_resize = config.display.rescale_on_resize;
+        self.paint_decay = config.display.paint_decay;
+        if config.display.paint != self.paint.is_some() {
+            self.toggle_paint();
+        }
+        if let Some(paint) = &mut self.paint {
+            paint.decay = self.paint_decay;
+        }
+        self.theme = theme::by_name(&config.display.theme).unwrap_or(&theme::DEFAULT);
+        self.strings = locale::by_name(&config.display.language).unwrap_or(&locale::ENGLISH);
+        self.keymap = config.keys.keymap().unwrap_or_default();
+        self.layout = config.layout.clone();
+    }
+
+    pub fn profile_picker(&self) -> Mode {
+        let mut entries = vec![None];
+        entries.extend(self.base_config.profile_names().into_iter().map(Some));
+        let mut picker = Picker::new(entries);
+        picker.selected = picker
+            .entries
+            .iter()
+            .position(|name| *name == self.profile)
+            .unwrap_or(0);
+        Mode::ProfilePicker(picker)
+    }
+
+    /// Restarts with the config file plus profile `name`, or without a
+    /// profile for `None`.
+    pub fn switch_profile(&mut self, name: Option<String>) {
+        match self.profile_config(name.as_deref()) {
+            Ok(config) => {
+                self.apply_config(&config);
+                self.notice = Some(format!("Profile: {}", name.as_deref().unwrap_or("none")));
+                self.profile = name;
+            }
+            Err(err) => self.notice = Some(err),
+        }
+    }
+
+    fn profile_config(&self, name: Option<&str>) -> Result<Config, String> {
+        match name {
+            Some(name) => self.base_config.with_profile(name),
+            None => Ok(self.base_config.clone()),
+        }
+    }
+
+    /// The shape ball `id` is drawn with, which tells it apart where
+    /// colors can't: a square or triangle as it's turned, the one it was
+    /// given, or else one derived from its id.
+    pub fn ball_glyph(&self, id: usize) -> &'static str {
+        let glyphs = if self.ascii { ASCII_BALL_CHARS } else { BALL_CHARS };
+        let balls = &self.state.balls;
+        let ball = balls.binary_search_by_key(&id, |ball| ball.id).ok().map(|i| &balls[i]);
+        if let Some(body) = ball.and_then(|ball| ball.body) {
+            return body.shape.glyph(body.angle, self.ascii);
+        }
+        glyphs[ball.and_then(|ball| ball.glyph).map_or(id, |glyph| glyph.0) % glyphs.len()]
+    }
+
+    /// Starts painting on a blank arena, or stops and wipes the painting.
+    pub fn toggle_paint(&mut self) {
+        self.paint = match self.paint {
+            Some(_) => None,
+            None => Some(Paint::new(self.paint_decay)),
+        };
+    }
+
+    pub fn clear_paint(&mut self) {
+        if let Some(paint) = &mut self.paint {
+            paint.clear();
+        }
+    }
+
+    /// Points gravity toward `direction`, saying so, and how to get some if
+    /// there's none yet.
+    pub fn turn_gravity(&mut self, direction: Direction) {
+        self.commands.push(Command::SetGravityDirection(direction));
+        self.notice = Some(match self.state.gravity {
+            0.0 => format!("Gravity: {} once it's set, e.g. set gravity 0.02", direction.name()),
+            _ => format!("Gravity: {}", direction.name()),
+        });
+    }
+
+    /// Writes the painting to `path` (see [`Paint::save`]), or to a file
+    /// named for the tick count.
+    pub fn export_paint(&self, path: Option<PathBuf>) -> io::Result<PathBuf> {
+        let Some(paint) = &self.paint else {
+            return Err(io::Error::other("not painting"));
+        };
+        let path = path.unwrap_or_else(|| PathBuf::from(format!("ball-bounce-paint-{}.txt", self.state.tick_count)));
+        let color = |stain: &Stain| stain.color.map_or(self.theme.ball_color(stain.id), Color::from);
+        paint.save(&path, self.ascii, &color)?;
+        Ok(path)
+    }
+
+    /// Shows or hides the force field arrows, and has the simulation sample
+    /// the field for them or stop.
+    pub fn toggle_field(&mut self) {
+        self.show_field = !self.show_field;
+        let (columns, rows) = FIELD_SPACING;
+        let spacing = self.show_field.then_some((columns, rows * self.state.aspect));
+        self.sim.edit(move |sim| sim.field_spacing = spacing);
+    }
+
+    /// The one chart the compact layout shows: the focused one, or else the
+    /// first in the layout.
+    pub fn compact_chart(&self) -> Pane {
+        let is_chart = |pane: &Pane| !matches!(pane, Pane::Arena | Pane::Status);
+        self.focus
+            .filter(is_chart)
+            .or_else(|| self.layout.panes.into_iter().find(is_chart))
+            .unwrap_or(Pane::XPosition)
+    }
+
+    /// The ball picked in the controls panel's list, if there are any.
+    pub fn picked_ball(&self) -> Option<&BallState> {
+        self.state.balls.get(self.ball_cursor.min(self.state.balls.len().saturating_sub(1)))
+    }
+
+    /// The color ball `id` was given, or else the theme's color for it.
+    pub fn ball_color(&self, id: usize) -> Color {
+        let balls = &self.state.balls;
+        match balls.binary_search_by_key(&id, |ball| ball.id).ok().and_then(|i| balls[i].color) {
+            Some(color) => color.into(),
+            None => self.theme.ball_color(id),
+        }
+    }
+}
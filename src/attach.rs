@@ -0,0 +1,71 @@
+//! `attach PATH`: watches an instance through its `--control` socket without
+//! being able to change it, e.g. from another tmux pane or over SSH. The
+//! viewer sends `watch` and draws each state it's sent; nothing it does
+//! reaches the instance.
+
+use std::io::{self, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use ratatui::{style::Style, text::Span};
+use rust_tui_ball_bounce::Snapshot;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::theme::Theme;
+use crate::viewer::{self, Remote};
+
+/// A watching connection to an instance, and its latest state.
+pub struct Viewer {
+    path: PathBuf,
+    rx: UnboundedReceiver<Snapshot>,
+    state: Option<Snapshot>,
+}
+
+impl Viewer {
+    pub fn connect(path: &Path) -> io::Result<Viewer> {
+        let mut stream = UnixStream::connect(path)?;
+        writeln!(stream, "watch")?;
+        let rx = viewer::read_lines(BufReader::new(stream));
+        Ok(Viewer { path: path.to_path_buf(), rx, state: None })
+    }
+}
+
+impl Remote for Viewer {
+    type Message = Snapshot;
+
+    fn messages(&mut self) -> &mut UnboundedReceiver<Snapshot> {
+        &mut self.rx
+    }
+
+    fn receive(&mut self, state: Snapshot) {
+        self.state = Some(state);
+    }
+
+    fn state(&self) -> Option<&Snapshot> {
+        self.state.as_ref()
+    }
+
+    fn title(&self, theme: &Theme, _ascii: bool) -> Vec<Span<'static>> {
+        let muted = Style::default().fg(theme.muted);
+        let mut title = vec![Span::raw(format!(" Watching {} ", self.path.display()))];
+        match &self.state {
+            Some(state) => {
+                let mut status = format!("tick {} · {} balls", state.tick_count, state.balls.len());
+                if state.paused {
+                    status.push_str(" · paused");
+                }
+                title.push(Span::styled(format!("{} ", status), muted));
+            }
+            None => title.push(Span::styled("waiting for the first tick ", muted)),
+        }
+        title
+    }
+
+    fn keys(&self) -> &'static str {
+        " read-only · q quit "
+    }
+
+    fn gone(&self) -> String {
+        format!("the instance at {} went away", self.path.display())
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Body, Glyph, Rgb};
+
+/// A copy of one ball's core components, its mass, and its [`Rgb`] color,
+/// [`Glyph`] and [`Body`] if it has them, as returned by [`Simulation::state`](crate::Simulation::state).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BallState {
+    pub id: usize,
+    pub x: f64,
+    pub y: f64,
+    pub vx: f64,
+    pub vy: f64,
+    pub radius: f64,
+    #[serde(default = "unit_mass", skip_serializing_if = "is_unit_mass")]
+    pub mass: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Rgb>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<Glyph>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Body>,
+}
+
+fn unit_mass() -> f64 {
+    1.0
+}
+
+fn is_unit_mass(mass: &f64) -> bool {
+    *mass == 1.0
+}
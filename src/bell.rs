@@ -0,0 +1,74 @@
+//! Rings the terminal bell on bounces, so a run can be followed by ear.
+
+use std::io::Write;
+use std::time::Duration;
+
+use rust_tui_ball_bounce::clock::Clock;
+use rust_tui_ball_bounce::{SimEvent, SimulationObserver};
+
+use crate::config::SoundConfig;
+
+/// Writes BEL to `out` for wall bounces and collisions, dropping collisions
+/// softer than `min_impulse` and any ring that comes too soon after the last.
+pub struct Bell<W, C> {
+    out: W,
+    clock: C,
+    min_impulse: f64,
+    // The shortest time between two rings
+    interval: Duration,
+    last: Option<Duration>,
+}
+
+impl<W: Write, C: Clock> Bell<W, C> {
+    pub fn new(out: W, clock: C, config: &SoundConfig) -> Self {
+        Bell {
+            out,
+            clock,
+            min_impulse: config.min_impulse,
+            interval: Duration::from_secs(1) / config.max_per_second,
+            last: None,
+        }
+    }
+
+    fn ring(&mut self) {
+        let now = self.clock.now();
+        if self.last.is_some_and(|last| now - last < self.interval) {
+            return;
+        }
+        self.last = Some(now);
+        let _ = self.out.write_all(b"\x07");
+        let _ = self.out.flush();
+    }
+}
+
+impl<W: Write, C: Clock> SimulationObserver for Bell<W, C> {
+    fn on_event(&mut self, event: &SimEvent) {
+        match *event {
+            SimEvent::WallBounce { .. } => self.ring(),
+            SimEvent::Collision { impulse, .. } if impulse >= self.min_impulse => self.ring(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::clock::ManualClock;
+
+    use super::*;
+
+    #[test]
+    fn soft_hits_and_rapid_rings_are_dropped() {
+        let clock = ManualClock::new();
+        let config = SoundConfig { bell: true, min_impulse: 0.5, max_per_second: 4 };
+        let mut bell = Bell::new(Vec::new(), clock.clone(), &config);
+        let collision = |impulse| SimEvent::Collision { a: 0, b: 1, x: 0.0, y: 0.0, impulse, before: [(0.0, 0.0); 2], after: [(0.0, 0.0); 2] };
+
+        bell.on_event(&collision(0.2));
+        bell.on_event(&collision(0.8));
+        bell.on_event(&SimEvent::WallBounce { id: 0 });
+        clock.advance(Duration::from_millis(250));
+        bell.on_event(&SimEvent::WallBounce { id: 0 });
+        assert_eq!(bell.out, b"\x07\x07");
+    }
+}
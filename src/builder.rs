@@ -0,0 +1,217 @@
+use crate::force::{Direction, Force};
+use crate::scenario::{self, BallGroup, BallSpec, Obstacle, Pockets, Scenario, Thermostat};
+use crate::{SimEvent, Simulation};
+
+/// Sets up a [`Simulation`]: arena, parameters, balls, forces, obstacles
+/// and pockets. Anything left unset keeps [`Simulation::new`]'s default, or the
+/// current value when applied to an existing simulation.
+///
+/// ```
+/// use rust_tui_ball_bounce::{force::Gravity, BallSpec, SimulationBuilder};
+///
+/// let sim = SimulationBuilder::new()
+///     .arena(60.0, 30.0)
+///     .ball(BallSpec::new(10.0, 5.0, 0.5, 0.0))
+///     .auto_balls(3)
+///     .force(Gravity::down(0.02))
+///     .history(100)
+///     .build();
+/// assert_eq!(sim.state().len(), 4);
+/// ```
+#[derive(Default)]
+pub struct SimulationBuilder {
+    arena: Option<(f64, f64)>,
+    aspect: Option<f64>,
+    speed: Option<f64>,
+    ball_radius: Option<f64>,
+    history: Option<usize>,
+    paused: Option<bool>,
+    seed: u64,
+    balls: Vec<BallSpec>,
+    groups: Vec<BallGroup>,
+    auto_balls: usize,
+    forces: Vec<Box<dyn Force>>,
+    gravity_direction: Direction,
+    obstacles: Vec<Obstacle>,
+    pockets: Option<Pockets>,
+    thermostat: Option<Thermostat>,
+    scenario_name: Option<String>,
+    gas: bool,
+    count_hits: bool,
+}
+
+impl SimulationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Arena size in cells; the height includes the `aspect` correction.
+    pub fn arena(mut self, width: f64, height: f64) -> Self {
+        self.arena = Some((width, height));
+        self
+    }
+
+    pub fn aspect(mut self, aspect: f64) -> Self {
+        self.aspect = Some(aspect);
+        self
+    }
+
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Radius for balls whose spec doesn't set one, including ones added later.
+    pub fn ball_radius(mut self, radius: f64) -> Self {
+        self.ball_radius = Some(radius);
+        self
+    }
+
+    /// Ticks of telemetry kept per ball; 0 turns recording off.
+    pub fn history(mut self, ticks: usize) -> Self {
+        self.history = Some(ticks);
+        self
+    }
+
+    pub fn paused(mut self, paused: bool) -> Self {
+        self.paused = Some(paused);
+        self
+    }
+
+    /// Seeds the placement of random [`groups`](Self::group).
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn ball(mut self, spec: BallSpec) -> Self {
+        self.balls.push(spec);
+        self
+    }
+
+    pub fn balls(mut self, specs: impl IntoIterator<Item = BallSpec>) -> Self {
+        self.balls.extend(specs);
+        self
+    }
+
+    /// Balls at random positions and headings, placed when the simulation is
+    /// built so they fill the arena it ends up with.
+    pub fn group(mut self, group: BallGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// `count` balls placed one after another like [`Command::AddBall`](crate::Command::AddBall).
+    pub fn auto_balls(mut self, count: usize) -> Self {
+        self.auto_balls = count;
+        self
+    }
+
+    pub fn force(mut self, force: impl Force + 'static) -> Self {
+        self.forces.push(Box::new(force));
+        self
+    }
+
+    pub fn obstacle(mut self, obstacle: Obstacle) -> Self {
+        self.obstacles.push(obstacle);
+        self
+    }
+
+    /// Replaces the obstacles so far, a scenario's included.
+    pub fn obstacles(mut self, obstacles: Vec<Obstacle>) -> Self {
+        self.obstacles = obstacles;
+        self
+    }
+
+    pub fn pockets(mut self, pockets: Pockets) -> Self {
+        self.pockets = Some(pockets);
+        self
+    }
+
+    pub fn thermostat(mut self, thermostat: Thermostat) -> Self {
+        self.thermostat = Some(thermostat);
+        self
+    }
+
+    /// Replaces the balls, forces, obstacles, pockets and thermostat with the scenario's, and takes
+    /// its seed, speed, name, whether it's a gas and whether it counts hits. Its ball radius only
+    /// applies to its own balls.
+    pub fn scenario(mut self, scenario: &Scenario) -> Self {
+        let radius = scenario.parameters.ball_radius;
+        self.seed = scenario.seed;
+        self.speed = scenario.parameters.speed.or(self.speed);
+        self.balls = scenario
+            .balls
+            .iter()
+            .map(|spec| BallSpec { radius: spec.radius.or(radius), ..spec.clone() })
+            .collect();
+        self.groups = scenario
+            .groups
+            .iter()
+            .map(|group| BallGroup { radius: group.radius.or(radius), ..group.clone() })
+            .collect();
+        self.auto_balls = 0;
+        self.forces = scenario.forces.build();
+        self.gravity_direction = scenario.forces.gravity_direction;
+        self.obstacles = scenario.obstacles.clone();
+        self.pockets = scenario.pockets;
+        self.thermostat = scenario.thermostat;
+        self.scenario_name = Some(scenario.name.clone());
+        self.gas = scenario.parameters.gas;
+        self.count_hits = scenario.parameters.count_hits;
+        self
+    }
+
+    pub fn build(self) -> Simulation {
+        let mut sim = Simulation::new(80.0, 20.0);
+        self.apply(&mut sim);
+        // A new simulation has nothing to report yet
+        sim.events.clear();
+        sim
+    }
+
+    /// Resets `sim` and sets it up from the builder, keeping whatever the
+    /// builder leaves unset.
+    pub fn apply(self, sim: &mut Simulation) {
+        sim.reset();
+        if let Some((width, height)) = self.arena {
+            sim.area_width = width;
+            sim.area_height = height;
+        }
+        if let Some(aspect) = self.aspect {
+            sim.aspect = aspect;
+        }
+        if let Some(speed) = self.speed {
+            sim.set_speed(speed);
+        }
+        if let Some(radius) = self.ball_radius {
+            sim.ball_radius = radius;
+        }
+        if let Some(history) = self.history {
+            sim.max_history = history;
+        }
+        if let Some(paused) = self.paused {
+            sim.set_paused(paused);
+        }
+
+        sim.seed = self.seed;
+        let groups = scenario::expand_groups(&self.groups, self.seed, sim.area_width, sim.area_height);
+        for spec in self.balls.into_iter().chain(groups) {
+            sim.add_ball(spec);
+        }
+        for _ in 0..self.auto_balls {
+            sim.add_ball(sim.next_ball_spec());
+        }
+        sim.forces = self.forces;
+        sim.gravity_direction = self.gravity_direction;
+        sim.obstacles = self.obstacles;
+        sim.pockets = self.pockets;
+        sim.thermostat = self.thermostat;
+        sim.gas = self.gas;
+        sim.count_hits = self.count_hits;
+        if let Some(name) = self.scenario_name {
+            sim.events.push(SimEvent::ScenarioLoaded(name.clone()));
+            sim.scenario_name = Some(name);
+        }
+    }
+}
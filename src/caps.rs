@@ -0,0 +1,184 @@
+//! What the terminal can draw, found out at startup rather than assumed.
+//! The environment gives a first guess: a locale that isn't UTF-8 or a
+//! `dumb` terminal means ASCII, the Linux console's fonts have no Braille,
+//! and `COLORTERM` and `TERM` tell the color depth. Then, where the terminal
+//! answers cursor position queries, a few glyphs are printed and measured,
+//! so one drawing them two columns wide falls back too.
+//!
+//! `display.glyphs` and `display.colors` in the config file, or `--glyphs`
+//! and `--colors`, pick instead of `auto`.
+
+use ratatui::style::Color;
+
+use crate::clip;
+use crate::term::Term;
+
+/// How the UI draws, best first: Braille dots in the charts, then half-block
+/// charts, then plain ASCII throughout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Glyphs {
+    Braille,
+    HalfBlock,
+    Ascii,
+}
+
+/// Values `display.glyphs` takes.
+pub const GLYPH_NAMES: [&str; 4] = ["auto", "braille", "half-block", "ascii"];
+
+impl Glyphs {
+    /// The glyphs called `name`; `None` for `auto` or an unknown name.
+    pub fn by_name(name: &str) -> Option<Glyphs> {
+        match name {
+            "braille" => Some(Glyphs::Braille),
+            "half-block" => Some(Glyphs::HalfBlock),
+            "ascii" => Some(Glyphs::Ascii),
+            _ => None,
+        }
+    }
+}
+
+/// How many colors the terminal shows. Anything past them is drawn as the
+/// nearest one it has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// Values `display.colors` takes.
+pub const COLOR_NAMES: [&str; 5] = ["auto", "none", "16", "256", "truecolor"];
+
+impl ColorDepth {
+    /// The depth called `name`; `None` for `auto` or an unknown name.
+    pub fn by_name(name: &str) -> Option<ColorDepth> {
+        match name {
+            "none" => Some(ColorDepth::None),
+            "16" => Some(ColorDepth::Ansi16),
+            "256" => Some(ColorDepth::Ansi256),
+            "truecolor" => Some(ColorDepth::TrueColor),
+            _ => None,
+        }
+    }
+
+    /// `color`, or the nearest this depth shows. With no color at all it's
+    /// left alone, for the monochrome pass to deal with.
+    pub fn fit(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::Ansi16, Color::Indexed(16..) | Color::Rgb(..)) => Color::Indexed(clip::nearest_ansi(color)),
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(16 + 36 * level(r) + 6 * level(g) + level(b)),
+            _ => color,
+        }
+    }
+}
+
+// The nearest of the xterm color cube's six levels: 0, 95, 135, 175, 215, 255
+fn level(channel: u8) -> u8 {
+    match channel {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (channel - 35) / 40,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Caps {
+    pub glyphs: Glyphs,
+    pub colors: ColorDepth,
+}
+
+impl Default for Caps {
+    /// Everything, as a terminal that can't be asked is taken to have.
+    fn default() -> Self {
+        Caps { glyphs: Glyphs::Braille, colors: ColorDepth::TrueColor }
+    }
+}
+
+impl Caps {
+    /// A first guess from the environment.
+    pub fn detect() -> Caps {
+        Caps::from_env(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+    }
+
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Caps {
+        let mut caps = Caps::default();
+        let term = var("TERM");
+        // Without TERM, as on Windows, there's nothing to go by
+        if let Some(term) = &term {
+            caps.colors = if term.contains("256color") { ColorDepth::Ansi256 } else { ColorDepth::Ansi16 };
+            if term.ends_with("-direct") {
+                caps.colors = ColorDepth::TrueColor;
+            }
+            if term == "linux" {
+                caps.glyphs = Glyphs::HalfBlock;
+            }
+            if term == "dumb" || term.starts_with("vt") {
+                caps = Caps { glyphs: Glyphs::Ascii, colors: ColorDepth::None };
+            }
+        }
+        if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit")) && caps.colors > ColorDepth::None {
+            caps.colors = ColorDepth::TrueColor;
+        }
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"].into_iter().find_map(&var);
+        if locale.is_some_and(|locale| !locale.to_lowercase().replace('-', "").contains("utf8")) {
+            caps.glyphs = Glyphs::Ascii;
+        }
+        caps
+    }
+
+    /// Checks the guessed glyphs on `term` itself, falling back where it
+    /// draws them wider than a column. Draws over the screen and clears it
+    /// after, so it's for before the first frame.
+    pub fn probe(mut self, term: &mut impl Term) -> Caps {
+        if self.glyphs == Glyphs::Ascii {
+            return self;
+        }
+        // A terminal that doesn't answer is taken at the environment's word
+        let mut fits = |text: &str| term.measure(text).is_none_or(|width| width as usize == text.chars().count());
+        // Ball glyphs and box drawing are ambiguous width, which terminals
+        // set up for CJK text draw two columns wide
+        if !fits("●◉○◎◆■▲★─│┌") || !fits("▀▄█") {
+            self.glyphs = Glyphs::Ascii;
+        } else if self.glyphs == Glyphs::Braille && !fits("⣿⡇⠁") {
+            self.glyphs = Glyphs::HalfBlock;
+        }
+        let _ = term.terminal().clear();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn caps(vars: &[(&str, &str)]) -> Caps {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Caps::from_env(|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn the_environment_gives_a_first_guess() {
+        let xterm = caps(&[("TERM", "xterm-256color"), ("LANG", "en_US.UTF-8")]);
+        assert_eq!(xterm, Caps { glyphs: Glyphs::Braille, colors: ColorDepth::Ansi256 });
+        let truecolor = caps(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]);
+        assert_eq!(truecolor.colors, ColorDepth::TrueColor);
+        assert_eq!(caps(&[("TERM", "linux"), ("LANG", "en_US.utf8")]).glyphs, Glyphs::HalfBlock);
+        assert_eq!(caps(&[("TERM", "xterm"), ("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]).glyphs, Glyphs::Ascii);
+        let dumb = caps(&[("TERM", "dumb"), ("COLORTERM", "truecolor")]);
+        assert_eq!(dumb, Caps { glyphs: Glyphs::Ascii, colors: ColorDepth::None });
+        assert_eq!(caps(&[]), Caps::default());
+    }
+
+    #[test]
+    fn colors_past_the_depth_come_out_nearest() {
+        assert_eq!(ColorDepth::Ansi256.fit(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(ColorDepth::Ansi256.fit(Color::Rgb(100, 100, 100)), Color::Indexed(59));
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Rgb(250, 250, 250)), Color::Indexed(15));
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Indexed(196)), ColorDepth::Ansi16.fit(Color::Rgb(255, 0, 0)));
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Yellow), Color::Yellow);
+        assert_eq!(ColorDepth::TrueColor.fit(Color::Rgb(1, 2, 3)), Color::Rgb(1, 2, 3));
+    }
+}
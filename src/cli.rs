@@ -0,0 +1,393 @@
+use std::path::PathBuf;
+
+use clap::builder::FalseyValueParser;
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+use rust_tui_ball_bounce::scenario;
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::caps::{COLOR_NAMES, GLYPH_NAMES};
+use crate::game::GAME_NAMES;
+use crate::theme::THEME_NAMES;
+
+/// Bouncing balls with live telemetry graphs, right in your terminal.
+///
+/// Every option can also be set through the environment variable shown next
+/// to it; command-line flags win over environment variables, which win over
+/// the config file.
+#[derive(Parser, Debug)]
+#[command(name = "rust-tui-ball-bounce", version, about, after_help = EXAMPLES)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Number of balls to start with
+    #[arg(long, env = "BALLBOUNCE_BALLS", value_name = "N")]
+    pub balls: Option<usize>,
+
+    /// Start with N thousand balls and a ticks-per-second readout
+    ///
+    /// A fixed load for measuring collision and drawing performance; it
+    /// takes the place of --balls.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=100), conflicts_with = "headless")]
+    pub stress: Option<u32>,
+
+    /// Don't record chart history during --stress, leaving the charts empty
+    #[arg(long, requires = "stress")]
+    pub no_history: bool,
+
+    /// Initial speed multiplier, from 0.25 to 5.0
+    #[arg(long, env = "BALLBOUNCE_SPEED", value_name = "X", value_parser = parse_speed)]
+    pub speed: Option<f64>,
+
+    /// Milliseconds between simulation ticks
+    #[arg(long, env = "BALLBOUNCE_TICK_MS", value_name = "N", value_parser = clap::value_parser!(u64).range(1..=1000))]
+    pub tick_ms: Option<u64>,
+
+    /// Show readouts in meters and seconds, a cell being M meters
+    ///
+    /// Chart axes, the controls panel and the described view convert, and
+    /// `set gravity` takes m/s², so `set gravity 9.81` is Earth's.
+    #[arg(long, env = "BALLBOUNCE_METERS_PER_CELL", value_name = "M", value_parser = parse_meters)]
+    pub meters_per_cell: Option<f64>,
+
+    /// Screen redraws per second, independent of the tick rate
+    #[arg(long, env = "BALLBOUNCE_FPS", value_name = "N", value_parser = clap::value_parser!(u32).range(1..=240))]
+    pub fps: Option<u32>,
+
+    /// Start with the simulation paused
+    #[arg(long, env = "BALLBOUNCE_PAUSED", value_parser = FalseyValueParser::new())]
+    pub paused: bool,
+
+    /// Draw with plain ASCII instead of Unicode symbols
+    #[arg(long, env = "BALLBOUNCE_ASCII", value_parser = FalseyValueParser::new())]
+    pub ascii: bool,
+
+    /// Glyphs to draw with: braille, half-block or ascii, instead of probing
+    ///
+    /// By default the terminal is checked at startup and the best it draws
+    /// correctly is used, falling back from Braille to half blocks to ASCII.
+    #[arg(long, env = "BALLBOUNCE_GLYPHS", value_name = "KIND", value_parser = GLYPH_NAMES)]
+    pub glyphs: Option<String>,
+
+    /// Colors the terminal shows: none, 16, 256 or truecolor
+    ///
+    /// By default TERM and COLORTERM tell. Colors past the depth are drawn as
+    /// the nearest one it has.
+    #[arg(long, env = "BALLBOUNCE_COLORS", value_name = "DEPTH", value_parser = COLOR_NAMES)]
+    pub colors: Option<String>,
+
+    /// Ring the terminal bell on wall bounces and collisions
+    ///
+    /// Soft collisions and rings closer together than `[sound]` in the config
+    /// file allows are dropped, so a busy arena doesn't ring constantly.
+    #[arg(long, env = "BALLBOUNCE_BELL", value_parser = FalseyValueParser::new(), conflicts_with = "headless")]
+    pub bell: bool,
+
+    /// Draw without colors (also enabled by a non-empty NO_COLOR)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Leave out decorative motion, for anyone sensitive to it
+    ///
+    /// Effects that only decorate motion, like shake, flashes or trails, are
+    /// left out; the balls and charts move as usual.
+    #[arg(long, env = "BALLBOUNCE_REDUCED_MOTION", value_parser = FalseyValueParser::new())]
+    pub reduced_motion: bool,
+
+    /// Start in the described view, toggled with F2
+    ///
+    /// The panes are replaced with plain sentences for a screen reader: the
+    /// speed, each ball's position and heading, and what happened lately,
+    /// rewritten every two seconds.
+    #[arg(long, env = "BALLBOUNCE_DESCRIBED", value_parser = FalseyValueParser::new())]
+    pub described: bool,
+
+    /// Color theme
+    #[arg(long, env = "BALLBOUNCE_THEME", value_name = "NAME", value_parser = THEME_NAMES)]
+    pub theme: Option<String>,
+
+    /// Start from a scenario: a built-in preset name or a path to a scenario file
+    #[arg(long, env = "BALLBOUNCE_SCENARIO", value_name = "NAME|PATH", long_help = scenario_help())]
+    pub scenario: Option<String>,
+
+    /// Start with the walls drawn in a text file, `#` for each wall cell
+    ///
+    /// One character per arena cell, the first line along the top. They take
+    /// the place of any walls the scenario has.
+    #[arg(long, env = "BALLBOUNCE_MAP", value_name = "PATH")]
+    pub map: Option<PathBuf>,
+
+    /// Play a game on its own scenario instead of the sandbox
+    ///
+    /// billiards: pot the racked balls with the white cue ball. Aim with
+    /// Left/Right, set the power with Up/Down and shoot with Enter, or drag
+    /// back from the cue ball with the mouse and let go.
+    ///
+    /// dodge: steer the yellow ball with the arrow keys, away from the others
+    /// as more keep coming in. The first touch ends the run; Enter stops the
+    /// ball, or starts a new run once it's over.
+    ///
+    /// targets: two players race to touch targets, orange steering with
+    /// W/A/S/D and blue with the arrow keys. The first to five wins the
+    /// level; Enter starts the next, with new walls and faster balls.
+    #[arg(long, value_name = "NAME", value_parser = GAME_NAMES, conflicts_with_all = ["scenario", "headless"])]
+    pub game: Option<String>,
+
+    /// Named profile from the config file (built-ins: demo, benchmark, teaching)
+    ///
+    /// A profile is a `[profiles.NAME]` table layered over the rest of the
+    /// config file; environment variables and flags still take precedence.
+    #[arg(long, env = "BALLBOUNCE_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Config file to use instead of ~/.config/ball-bounce/config.toml
+    #[arg(long, env = "BALLBOUNCE_CONFIG", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Record the session to an asciinema v2 cast file
+    ///
+    /// Play it back with `asciinema play PATH`, or embed it on a web page with
+    /// asciinema-player.
+    #[arg(long, value_name = "PATH", conflicts_with = "headless")]
+    pub record: Option<PathBuf>,
+
+    /// Stream every tick as JSON to WebSocket clients on ADDR, e.g. 127.0.0.1:9001
+    #[cfg(feature = "websocket")]
+    #[arg(long, env = "BALLBOUNCE_SERVE", value_name = "ADDR", conflicts_with = "headless")]
+    pub serve: Option<String>,
+
+    /// Accept commands on a Unix socket at PATH, one per line
+    ///
+    /// Takes the command palette's commands, such as `add 5`, `pause` or
+    /// `set gravity 0.1`, plus `dump state` for the current state as JSON.
+    /// Every line is answered with one line: `ok`, `error: ...`, or the JSON.
+    /// After `watch` the state is sent as JSON every tick instead, as
+    /// `attach PATH` uses.
+    #[cfg(unix)]
+    #[arg(long, env = "BALLBOUNCE_CONTROL", value_name = "PATH", conflicts_with = "headless")]
+    pub control: Option<PathBuf>,
+
+    /// Read commands from stdin, one per line, while still drawing to the terminal
+    ///
+    /// Takes the same commands as --control, e.g. `add 5` or `set gravity 0.1`.
+    /// This is on whenever stdin isn't a terminal, so a script can be piped in.
+    #[arg(long, conflicts_with = "headless")]
+    pub stdin_commands: bool,
+
+    /// Run a Rhai script at startup, keeping the hooks it defines
+    ///
+    /// The script can spawn and kick balls, set forces, read the state, and
+    /// define `on_tick(tick)` and `on_collision(a, b, impulse)` hooks. Run
+    /// more code later with the palette's `eval`.
+    #[cfg(feature = "scripting")]
+    #[arg(long, env = "BALLBOUNCE_SCRIPT", value_name = "PATH", conflicts_with = "headless")]
+    pub script: Option<PathBuf>,
+
+    /// Serve Prometheus metrics at http://ADDR/metrics, e.g. 127.0.0.1:9184
+    #[cfg(feature = "metrics")]
+    #[arg(long, env = "BALLBOUNCE_METRICS", value_name = "ADDR", conflicts_with = "headless")]
+    pub metrics: Option<String>,
+
+    /// Send collisions as raw MIDI notes to PATH, e.g. /dev/snd/midiC1D0 or a FIFO
+    ///
+    /// Harder collisions play higher and louder; each ball plays on its own
+    /// channel, wrapping after 16.
+    #[arg(long, env = "BALLBOUNCE_MIDI", value_name = "PATH", conflicts_with = "headless")]
+    pub midi: Option<PathBuf>,
+
+    /// Send collisions as OSC `/ballbounce/note` messages to ADDR over UDP
+    ///
+    /// Each message carries the channel, pitch and velocity as int32s, mapped
+    /// as for --midi.
+    #[arg(long, env = "BALLBOUNCE_OSC", value_name = "ADDR", conflicts_with = "headless")]
+    pub osc: Option<String>,
+
+    /// Write every ball's state each tick to stdout as `t x y vx vy id` lines
+    ///
+    /// For live plotting, e.g. piped into `feedgnuplot --stream`. The UI
+    /// draws to stderr instead. With --headless the lines take the place of
+    /// the statistics, which then only go to --output.
+    #[arg(long, conflicts_with = "trace")]
+    pub pipe: bool,
+
+    /// Host the simulation for `join` clients on ADDR, e.g. 0.0.0.0:7878
+    ///
+    /// Every guest sees the same arena and gets a color of its own to spawn,
+    /// fling and remove balls in.
+    #[arg(long, env = "BALLBOUNCE_SHARE", value_name = "ADDR", conflicts_with = "headless")]
+    pub share: Option<String>,
+
+    /// Append a log of actions, events and timings to PATH
+    ///
+    /// How much is logged is set by --log-level, and can be changed while
+    /// the app runs with the palette's `log` command.
+    #[arg(long, env = "BALLBOUNCE_LOG", value_name = "PATH")]
+    pub log: Option<PathBuf>,
+
+    /// Least severe messages to log: off, error, warn, info, debug or trace
+    #[arg(long, env = "BALLBOUNCE_LOG_LEVEL", value_name = "LEVEL", default_value = "info", requires = "log")]
+    pub log_level: LevelFilter,
+
+    /// Run the simulation without a terminal UI and print statistics
+    #[arg(long, requires = "ticks", help_heading = "Headless")]
+    pub headless: bool,
+
+    /// Number of ticks to simulate in headless mode
+    #[arg(long, value_name = "N", requires = "headless", help_heading = "Headless")]
+    pub ticks: Option<u64>,
+
+    /// Arena size in cells, e.g. 80x20 (headless mode has no terminal to measure)
+    #[arg(long, value_name = "WxH", value_parser = parse_arena, requires = "headless", help_heading = "Headless")]
+    pub arena: Option<(u16, u16)>,
+
+    /// Write the final statistics to a file instead of stdout
+    #[arg(long, value_name = "PATH", requires = "headless", help_heading = "Headless")]
+    pub output: Option<PathBuf>,
+
+    /// Write per-tick ball states as CSV (`-` for stdout)
+    #[arg(long, value_name = "PATH", requires = "headless", help_heading = "Headless")]
+    pub trace: Option<PathBuf>,
+
+    /// Run the first-run setup wizard even though a config file exists
+    #[arg(long)]
+    pub setup: bool,
+
+    /// Print the effective configuration as TOML and exit
+    ///
+    /// The output reflects the config file, profile, environment variables,
+    /// and flags combined, so it can be saved as a new config file.
+    #[arg(long)]
+    pub dump_config: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print a shell completion script to stdout
+    ///
+    /// For example, for bash:
+    ///   rust-tui-ball-bounce completions bash > ~/.local/share/bash-completion/completions/rust-tui-ball-bounce
+    #[command(hide = true)]
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Simulate without a terminal and write every ball's history to a file
+    ///
+    /// Balls and parameters come from the options before `export`:
+    ///
+    ///   rust-tui-ball-bounce --scenario gas export --ticks 2000 gas.csv
+    ///
+    /// The seed and parameters are written next to it, to gas.meta.toml. A
+    /// .json or .msgpack path gets the final state and histories in one file
+    /// instead.
+    Export {
+        /// Number of ticks to simulate and record
+        #[arg(long, value_name = "N", default_value_t = 1000)]
+        ticks: u64,
+
+        /// Arena size in cells, e.g. 80x20
+        #[arg(long, value_name = "WxH", value_parser = parse_arena)]
+        arena: Option<(u16, u16)>,
+
+        /// File to write: .csv, .json or .msgpack
+        path: PathBuf,
+    },
+
+    /// Simulate without a terminal and save the arena as an animated GIF
+    ///
+    /// Balls and parameters come from the options before `clip`:
+    ///
+    ///   rust-tui-ball-bounce --scenario newtons-cradle clip --ticks 600 cradle.gif
+    Clip {
+        /// Number of ticks to simulate
+        #[arg(long, value_name = "N", default_value_t = 300)]
+        ticks: u64,
+
+        /// Ticks per frame; higher makes smaller files with choppier motion
+        #[arg(long, value_name = "N", default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+        every: u64,
+
+        /// Arena size in cells, e.g. 80x20
+        #[arg(long, value_name = "WxH", value_parser = parse_arena)]
+        arena: Option<(u16, u16)>,
+
+        /// GIF file to write
+        path: PathBuf,
+    },
+
+    /// Join an arena shared with --share on ADDR, e.g. 192.168.1.20:7878
+    ///
+    /// Spawn balls in your own color with `a`, fling them with the arrow
+    /// keys, remove your newest with `x`, and leave with `q`.
+    Join {
+        addr: String,
+    },
+
+    /// Watch an instance started with --control PATH, without changing it
+    ///
+    /// Draws the instance's arena as it runs, from another tmux pane or over
+    /// SSH. Keys other than `q` do nothing.
+    #[cfg(unix)]
+    Attach {
+        path: PathBuf,
+    },
+}
+
+const EXAMPLES: &str = "\
+Examples:
+  rust-tui-ball-bounce --balls 6 --theme ocean
+  rust-tui-ball-bounce --scenario gas --speed 2
+  rust-tui-ball-bounce --tick-ms 8 --fps 30
+  rust-tui-ball-bounce --stress 5 --no-history
+  rust-tui-ball-bounce --game billiards
+  rust-tui-ball-bounce --profile teaching --ascii
+  rust-tui-ball-bounce --scenario gas --record demo.cast
+  rust-tui-ball-bounce --scenario newtons-cradle --osc 127.0.0.1:57120
+  rust-tui-ball-bounce --share 0.0.0.0:7878 (then elsewhere: join HOST:7878)
+  rust-tui-ball-bounce --control /tmp/bb.sock (then elsewhere: attach /tmp/bb.sock)
+  rust-tui-ball-bounce --headless --ticks 5000 --arena 80x20 --trace run.csv
+  rust-tui-ball-bounce --scenario gas export --ticks 2000 gas.csv
+  rust-tui-ball-bounce --scenario newtons-cradle clip cradle.gif
+  rust-tui-ball-bounce --dump-config > ~/.config/ball-bounce/config.toml
+  rust-tui-ball-bounce completions zsh > ~/.zfunc/_rust-tui-ball-bounce";
+
+// Listed from the presets themselves, so a new one shows up without editing this
+fn scenario_help() -> String {
+    format!(
+        "Start from a scenario: a built-in preset name or a path to a scenario file\n\n\
+         Built-in presets: {}. Files in ~/.config/ball-bounce/scenarios/ can also be \
+         loaded from the in-app picker.",
+        scenario::builtin_names().join(", ")
+    )
+}
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.parse().map_err(|_| format!("`{}` is not a number", s))?;
+    if (0.25..=5.0).contains(&speed) {
+        Ok(speed)
+    } else {
+        Err(format!("{} is outside the range 0.25-5.0", speed))
+    }
+}
+
+fn parse_meters(s: &str) -> Result<f64, String> {
+    let meters: f64 = s.parse().map_err(|_| format!("`{}` is not a number", s))?;
+    if meters > 0.0 && meters.is_finite() {
+        Ok(meters)
+    } else {
+        Err(format!("{} is not a positive length", meters))
+    }
+}
+
+fn parse_arena(s: &str) -> Result<(u16, u16), String> {
+    let (w, h) = s
+        .split_once('x')
+        .ok_or_else(|| format!("`{}` is not of the form WIDTHxHEIGHT", s))?;
+    let parse = |v: &str| match v.parse::<u16>() {
+        Ok(n) if n >= 2 => Ok(n),
+        _ => Err(format!("`{}` is not a size of at least 2 cells", v)),
+    };
+    Ok((parse(w)?, parse(h)?))
+}
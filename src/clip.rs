@@ -0,0 +1,188 @@
+//! Animated GIF clips of the arena, for sharing a run where a terminal can't
+//! go. The text grid is rasterized without a font: every cell becomes a
+//! block of pixels, filled for walls and holding a disc for a ball.
+
+use std::io::Write;
+use std::time::Duration;
+
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+pub const CELL_WIDTH: u16 = 8;
+pub const CELL_HEIGHT: u16 = 16;
+
+// Index 0 is the background; the rest follow the ANSI order of `palette_index`
+const PALETTE: [[u8; 3]; 17] = [
+    [0x12, 0x12, 0x18],
+    [0x00, 0x00, 0x00],
+    [0xcd, 0x31, 0x31],
+    [0x0d, 0xbc, 0x79],
+    [0xe5, 0xe5, 0x10],
+    [0x24, 0x72, 0xc8],
+    [0xbc, 0x3f, 0xbc],
+    [0x11, 0xa8, 0xcd],
+    [0xc0, 0xc0, 0xc0],
+    [0x66, 0x66, 0x66],
+    [0xf1, 0x4c, 0x4c],
+    [0x23, 0xd1, 0x8b],
+    [0xf5, 0xf5, 0x43],
+    [0x3b, 0x8e, 0xea],
+    [0xd6, 0x70, 0xd6],
+    [0x29, 0xb8, 0xdb],
+    [0xff, 0xff, 0xff],
+];
+
+/// Writes arena buffers as the frames of a looping GIF.
+pub struct ClipWriter<W: Write> {
+    encoder: Encoder<W>,
+    // Centiseconds between frames
+    delay: u16,
+}
+
+impl<W: Write> ClipWriter<W> {
+    /// A clip of `columns` × `rows` cells showing a frame every `interval`.
+    pub fn new(out: W, columns: u16, rows: u16, interval: Duration) -> Result<Self, EncodingError> {
+        let palette: Vec<u8> = PALETTE.concat();
+        let mut encoder = Encoder::new(out, columns * CELL_WIDTH, rows * CELL_HEIGHT, &palette)?;
+        encoder.set_repeat(Repeat::Infinite)?;
+        // Viewers treat delays under 2cs as 10cs
+        let delay = (interval.as_millis() / 10).clamp(2, u16::MAX as u128) as u16;
+        Ok(ClipWriter { encoder, delay })
+    }
+
+    pub fn frame(&mut self, buffer: &Buffer) -> Result<(), EncodingError> {
+        let area = buffer.area;
+        let pixels = rasterize(buffer);
+        let mut frame = Frame::from_indexed_pixels(
+            area.width * CELL_WIDTH,
+            area.height * CELL_HEIGHT,
+            pixels,
+            None,
+        );
+        frame.delay = self.delay;
+        self.encoder.write_frame(&frame)
+    }
+}
+
+/// Palette indices for the whole buffer, row by row.
+fn rasterize(buffer: &Buffer) -> Vec<u8> {
+    let area = buffer.area;
+    let (cell_width, cell_height) = (CELL_WIDTH as usize, CELL_HEIGHT as usize);
+    let width = area.width as usize * cell_width;
+    let mut pixels = vec![0; width * area.height as usize * cell_height];
+    for (i, cell) in buffer.content().iter().enumerate() {
+        let symbol = cell.symbol();
+        if symbol.trim().is_empty() {
+            continue;
+        }
+        let color = palette_index(cell.fg);
+        let solid = matches!(symbol, "█" | "#");
+        let (column, row) = (i % area.width as usize, i / area.width as usize);
+        for py in 0..cell_height {
+            for px in 0..cell_width {
+                if solid || in_disc(px, py) {
+                    pixels[(row * cell_height + py) * width + column * cell_width + px] = color;
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Whether pixel `(px, py)` of a cell falls in a disc as wide as the cell,
+/// centered in it.
+fn in_disc(px: usize, py: usize) -> bool {
+    let r = CELL_WIDTH as f64 / 2.0;
+    let dx = px as f64 + 0.5 - r;
+    let dy = py as f64 + 0.5 - CELL_HEIGHT as f64 / 2.0;
+    dx * dx + dy * dy <= r * r
+}
+
+fn palette_index(color: Color) -> u8 {
+    let ansi = match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White | Color::Reset => 15,
+        Color::Indexed(i) if i < 16 => i,
+        Color::Indexed(i) => return nearest(indexed_rgb(i)),
+        Color::Rgb(r, g, b) => return nearest([r, g, b]),
+    };
+    ansi + 1
+}
+
+/// `color` as red, green and blue, taking named colors from the palette here.
+pub fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Indexed(i) if i >= 16 => indexed_rgb(i),
+        _ => PALETTE[palette_index(color) as usize],
+    }
+}
+
+/// The ANSI color, 0-15, nearest to `color` on the palette here.
+pub fn nearest_ansi(color: Color) -> u8 {
+    palette_index(color) - 1
+}
+
+/// The xterm 256-color cube and grey ramp above the 16 ANSI colors.
+fn indexed_rgb(i: u8) -> [u8; 3] {
+    if i >= 232 {
+        let level = 8 + (i - 232) * 10;
+        return [level; 3];
+    }
+    let i = i - 16;
+    let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+    [level(i / 36), level(i / 6 % 6), level(i % 6)]
+}
+
+fn nearest(rgb: [u8; 3]) -> u8 {
+    let distance = |color: &[u8; 3]| -> i32 {
+        (0..3).map(|c| (color[c] as i32 - rgb[c] as i32).pow(2)).sum()
+    };
+    (1..PALETTE.len()).min_by_key(|&i| distance(&PALETTE[i])).unwrap_or(0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    use super::*;
+
+    #[test]
+    fn balls_become_discs_and_walls_fill_cells() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 2, 1));
+        buffer.set_string(0, 0, "●", Style::default().fg(Color::Yellow));
+        buffer.set_string(1, 0, "█", Style::default().fg(Color::Gray));
+        let pixels = rasterize(&buffer);
+        let width = 2 * CELL_WIDTH as usize;
+        let at = |x: usize, y: usize| pixels[y * width + x];
+
+        let middle = CELL_HEIGHT as usize / 2;
+        assert_eq!(at(CELL_WIDTH as usize / 2, middle), palette_index(Color::Yellow));
+        // The disc doesn't reach the corners or the top of its cell
+        assert_eq!(at(0, 0), 0);
+        assert_eq!(at(CELL_WIDTH as usize / 2, 0), 0);
+        assert_eq!(at(CELL_WIDTH as usize, 0), palette_index(Color::Gray));
+    }
+
+    #[test]
+    fn other_colors_map_to_the_nearest() {
+        assert_eq!(palette_index(Color::Rgb(250, 250, 250)), palette_index(Color::White));
+        assert_eq!(palette_index(Color::Indexed(196)), palette_index(Color::Red));
+    }
+}
@@ -0,0 +1,169 @@
+//! Time sources for running the simulation in real time. Loops ask a
+//! [`Clock`] for the time instead of calling `Instant::now()`, so tests and
+//! replays can drive time by hand with a [`ManualClock`].
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub trait Clock {
+    /// Time since some fixed starting point.
+    fn now(&self) -> Duration;
+}
+
+/// Wall-clock time since the clock was created.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so
+/// one can be handed to a loop while the test keeps another.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Says when fixed-rate ticks are due by `clock`. After an overrun the next
+/// tick is due right away rather than bursting to catch up.
+pub struct TickTimer<C> {
+    clock: C,
+    rate: Duration,
+    next: Duration,
+}
+
+impl<C: Clock> TickTimer<C> {
+    /// The first tick is due one `rate` from now.
+    pub fn new(clock: C, rate: Duration) -> Self {
+        let next = clock.now() + rate;
+        TickTimer { clock, rate, next }
+    }
+
+    pub fn rate(&self) -> Duration {
+        self.rate
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Changes the rate and restarts the wait for the next tick.
+    pub fn set_rate(&mut self, rate: Duration) {
+        self.rate = rate;
+        self.next = self.clock.now() + rate;
+    }
+
+    /// How long until the next tick is due; zero if it already is.
+    pub fn remaining(&self) -> Duration {
+        self.next.saturating_sub(self.clock.now())
+    }
+
+    /// Whether a tick is due, scheduling the one after it if so.
+    pub fn poll(&mut self) -> bool {
+        let now = self.clock.now();
+        if now < self.next {
+            return false;
+        }
+        self.next = (self.next + self.rate).max(now);
+        true
+    }
+}
+
+/// Time one step spent in each of its phases, as measured by
+/// [`Simulation::step_timed`](crate::Simulation::step_timed).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StepTimes {
+    /// Forces and moving the balls.
+    pub integrate: Duration,
+    /// Collisions and bounces off obstacles and walls.
+    pub collide: Duration,
+    /// Recording histories and expiring balls.
+    pub history: Duration,
+}
+
+impl StepTimes {
+    pub fn total(&self) -> Duration {
+        self.integrate + self.collide + self.history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn ticks_are_due_once_per_rate() {
+        let clock = ManualClock::new();
+        let mut timer = TickTimer::new(clock.clone(), RATE);
+        assert!(!timer.poll());
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(timer.remaining(), Duration::from_millis(6));
+        assert!(!timer.poll());
+        clock.advance(Duration::from_millis(6));
+        assert!(timer.poll());
+        assert!(!timer.poll());
+        assert_eq!(timer.remaining(), RATE);
+    }
+
+    #[test]
+    fn overruns_do_not_burst() {
+        let clock = ManualClock::new();
+        let mut timer = TickTimer::new(clock.clone(), RATE);
+        clock.advance(RATE * 5);
+        assert!(timer.poll());
+        // Due again straight away, but only once
+        assert!(timer.poll());
+        assert!(!timer.poll());
+    }
+
+    #[test]
+    fn changing_the_rate_restarts_the_wait() {
+        let clock = ManualClock::new();
+        let mut timer = TickTimer::new(clock.clone(), RATE);
+        clock.advance(Duration::from_millis(12));
+        timer.set_rate(Duration::from_millis(50));
+        clock.advance(Duration::from_millis(40));
+        assert!(!timer.poll());
+        clock.advance(Duration::from_millis(10));
+        assert!(timer.poll());
+    }
+}
@@ -0,0 +1,161 @@
+//! A/B comparisons. `compare gravity 0.1` starts a second simulation from
+//! the same state with gravity at 0.1, and `compare scenario gas` one with
+//! a scenario loaded instead. The simulation thread ticks it whenever the
+//! first one ticks, the arena splits to show the two side by side, and a
+//! chart follows a metric of each over time.
+//!
+//! Pausing, stepping and the speed apply to both; anything else changes
+//! only the first, which stays the one the keys and panels act on.
+
+use rust_tui_ball_bounce::scenario::Scenario;
+use rust_tui_ball_bounce::{Command, Simulation, Snapshot};
+
+use crate::sweep::Parameter;
+use crate::units::Quantity;
+
+// Samples charted for each side; older ones are dropped
+const MAX_SAMPLES: usize = 4000;
+
+/// What the second simulation differs by.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    // A parameter set to a value, as typed: in SI units when readouts are
+    Set(Parameter, f64),
+    // A scenario by name or path, loaded from scratch
+    Scenario(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    // Kinetic and potential, summed over the balls
+    Energy,
+    // Mean over the balls
+    Speed,
+    // Between balls, since the comparison started
+    Collisions,
+}
+
+impl Metric {
+    pub const NAMES: [&'static str; 3] = ["energy", "speed", "collisions"];
+
+    pub fn by_name(name: &str) -> Option<Metric> {
+        let all = [Metric::Energy, Metric::Speed, Metric::Collisions];
+        Some(all[Self::NAMES.iter().position(|&known| known == name)?])
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    pub fn quantity(self) -> Option<Quantity> {
+        match self {
+            Metric::Energy => Some(Quantity::Energy),
+            Metric::Speed => Some(Quantity::Speed),
+            Metric::Collisions => None,
+        }
+    }
+
+    /// `state`'s value, counting collisions from `collisions`.
+    pub fn measure(self, state: &Snapshot, collisions: u64) -> f64 {
+        match self {
+            Metric::Energy => state.balls.iter().map(|ball| state.energy(ball)).map(|(kinetic, potential)| kinetic + potential).sum(),
+            Metric::Speed if state.balls.is_empty() => 0.0,
+            Metric::Speed => state.balls.iter().map(|ball| ball.vx.hypot(ball.vy)).sum::<f64>() / state.balls.len() as f64,
+            Metric::Collisions => state.collisions.saturating_sub(collisions) as f64,
+        }
+    }
+}
+
+/// `sim` as it is but with `parameter` at `value`. `None` if the save
+/// doesn't restore.
+pub fn with_parameter(sim: &Simulation, parameter: Parameter, value: f64) -> Option<Simulation> {
+    let mut saved = sim.save();
+    parameter.set(&mut saved, value);
+    let mut other = Simulation::restore(saved).ok()?;
+    other.max_history = 0;
+    Some(other)
+}
+
+/// `sim` with `scenario` loaded, keeping its arena and speed.
+pub fn with_scenario(sim: &Simulation, scenario: Scenario) -> Option<Simulation> {
+    let mut other = Simulation::restore(sim.save()).ok()?;
+    other.apply(Command::LoadScenario(Box::new(scenario)));
+    other.max_history = 0;
+    other.drain_events();
+    Some(other)
+}
+
+/// A comparison as the app sees it: the other side's latest state and both
+/// sides' metric over time.
+#[derive(Debug)]
+pub struct Comparison {
+    pub change: Change,
+    pub metric: Metric,
+    pub state: Option<Snapshot>,
+    // Tick and value, for the first simulation and the other
+    pub series: [Vec<(f64, f64)>; 2],
+    // Each side's collision count when the comparison started
+    collisions: Option<[u64; 2]>,
+}
+
+impl Comparison {
+    pub fn new(change: Change, metric: Metric) -> Comparison {
+        Comparison { change, metric, state: None, series: [Vec::new(), Vec::new()], collisions: None }
+    }
+
+    /// The other side, for titles: `gravity 0.1` or the scenario.
+    pub fn label(&self) -> String {
+        match &self.change {
+            Change::Set(parameter, value) => format!("{} {}", parameter.name(), value),
+            Change::Scenario(name) => name.clone(),
+        }
+    }
+
+    /// Takes in both sides' states after a tick.
+    pub fn record(&mut self, state: &Snapshot, other: Snapshot) {
+        let collisions = *self.collisions.get_or_insert([state.collisions, other.collisions]);
+        let tick = state.tick_count as f64;
+        for (series, (state, collisions)) in self.series.iter_mut().zip([(state, collisions[0]), (&other, collisions[1])]) {
+            if series.last().is_some_and(|&(last, _)| last == tick) {
+                series.pop();
+            }
+            if series.len() == MAX_SAMPLES {
+                series.remove(0);
+            }
+            series.push((tick, self.metric.measure(state, collisions)));
+        }
+        self.state = Some(other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::{scenario, BallSpec};
+
+    use super::*;
+
+    #[test]
+    fn the_other_side_differs_by_the_change() {
+        let mut sim = Simulation::new(80.0, 20.0);
+        sim.add_ball(BallSpec::new(10.0, 5.0, 0.5, 0.0));
+        let mut heavy = with_parameter(&sim, Parameter::Gravity, 0.1).unwrap();
+        assert_eq!(heavy.state(), sim.state());
+        for _ in 0..10 {
+            sim.step(1.0);
+            heavy.step(1.0);
+        }
+        // Only the other side falls
+        assert_eq!(sim.state()[0].y, 5.0);
+        assert!(heavy.state()[0].y > 5.0);
+
+        let gas = with_scenario(&sim, scenario::builtin("gas").unwrap()).unwrap();
+        assert!(gas.state().len() > 1);
+
+        let mut comparison = Comparison::new(Change::Set(Parameter::Gravity, 0.1), Metric::Speed);
+        comparison.record(&sim.snapshot(), heavy.snapshot());
+        let [mine, theirs] = &comparison.series;
+        assert_eq!(mine[0], (10.0, 0.5));
+        assert!(theirs[0].1 > 0.5);
+        assert_eq!(comparison.label(), "gravity 0.1");
+    }
+}
@@ -0,0 +1,315 @@
+//! Per-ball components stored in the simulation's [`World`](hecs::World).
+//! Every ball has an id, position, velocity, radius and history; the rest
+//! are optional and only present on balls that use them.
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Creation order within the simulation; frontends derive color and glyph
+/// from it unless the ball has an [`Rgb`] or [`Glyph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BallId(pub usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Velocity {
+    pub vx: f64,
+    pub vy: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Radius(pub f64);
+
+/// Like charges repel and opposite charges attract with an inverse-square
+/// force; two unit charges one cell apart accelerate at one cell per tick².
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Charge(pub f64);
+
+/// Balls without one have a mass of 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mass(pub f64);
+
+/// Ticks left before the ball disappears.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ttl(pub u64);
+
+/// Radius gained every tick, or lost for a negative `rate`, until it
+/// reaches `max` or `min`. The ball's mass goes with its area, so a ball
+/// twice the size is four times as heavy.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Growth {
+    pub rate: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for Growth {
+    fn default() -> Self {
+        Growth { rate: 0.0, min: 0.1, max: 5.0 }
+    }
+}
+
+impl Growth {
+    /// This growth held to what a scenario allows: `min` and `max` within
+    /// 0.1-5 with `min` no more than `max`, and no rate unless it's finite.
+    pub fn clamped(self) -> Growth {
+        let min = if self.min.is_nan() { 0.1 } else { self.min.clamp(0.1, 5.0) };
+        let max = if self.max.is_nan() { min } else { self.max.clamp(min, 5.0) };
+        let rate = if self.rate.is_finite() { self.rate } else { 0.0 };
+        Growth { rate, min, max }
+    }
+}
+
+/// A solid outline other than a circle. Unlike a [`Glyph`], which only
+/// changes how a ball is drawn, it changes how the ball collides: the
+/// ball's radius is the distance from its center to each corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shape {
+    Square,
+    Triangle,
+}
+
+impl Shape {
+    /// The corners of the shape around `(x, y)`, `radius` out and turned
+    /// `angle` radians clockwise (as y grows downward), in order round it.
+    /// Unturned, a square sits flat and a triangle points up.
+    pub fn corners(self, x: f64, y: f64, radius: f64, angle: f64) -> Vec<(f64, f64)> {
+        use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, TAU};
+        let (count, first) = match self {
+            Shape::Square => (4, -3.0 * FRAC_PI_4),
+            Shape::Triangle => (3, -FRAC_PI_2),
+        };
+        (0..count)
+            .map(|i| {
+                let at = first + angle + TAU * i as f64 / count as f64;
+                (x + radius * at.cos(), y + radius * at.sin())
+            })
+            .collect()
+    }
+
+    /// The symbol for the shape turned `angle` radians: a square flat or on
+    /// its corner, and a triangle by the way its first corner points.
+    pub fn glyph(self, angle: f64, ascii: bool) -> &'static str {
+        use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+        match self {
+            Shape::Square if ascii => "#",
+            Shape::Square => {
+                // How far from flat, which it is again every quarter turn
+                let off = angle.rem_euclid(FRAC_PI_2);
+                if (FRAC_PI_4 / 2.0..3.0 * FRAC_PI_4 / 2.0).contains(&off) {
+                    "◆"
+                } else {
+                    "■"
+                }
+            }
+            Shape::Triangle => {
+                // Up, right, down and left, as it turns clockwise
+                let quarter = ((angle / FRAC_PI_2).round() as i64).rem_euclid(4) as usize;
+                if ascii {
+                    ["^", ">", "v", "<"][quarter]
+                } else {
+                    ["▲", "▶", "▼", "◀"][quarter]
+                }
+            }
+        }
+    }
+}
+
+/// A shaped ball's [`Shape`] and how far it's turned, in radians clockwise.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Body {
+    pub shape: Shape,
+    pub angle: f64,
+}
+
+/// How fast a shaped ball turns, in radians per tick clockwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spin(pub f64);
+
+/// Values for the telemetry graphs, oldest first. The ticks they were taken
+/// at are kept once for every ball (see
+/// [`Simulation::history_ticks`](crate::Simulation::history_ticks)), and
+/// pair up with [`with_ticks`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub x: Samples,
+    pub y: Samples,
+    pub vx: Samples,
+    pub vy: Samples,
+}
+
+impl History {
+    /// Adds a value of each kind, dropping the oldest beyond `limit`.
+    pub fn record(&mut self, (x, y): (f64, f64), (vx, vy): (f64, f64), limit: usize) {
+        self.x.push(x, limit);
+        self.y.push(y, limit);
+        self.vx.push(vx, limit);
+        self.vy.push(vy, limit);
+    }
+}
+
+/// `(tick, value)` points from a ball's `values` and the shared `ticks`.
+/// Every ball records on every tick from when it appears, so its values
+/// line up with the last of the ticks.
+pub fn with_ticks<'a>(ticks: &'a [f64], values: &'a [f64]) -> impl Iterator<Item = (f64, f64)> + 'a {
+    let ticks = &ticks[ticks.len().saturating_sub(values.len())..];
+    ticks.iter().copied().zip(values.iter().copied())
+}
+
+/// The latest samples, up to a limit, read as one slice.
+///
+/// A ring buffer would wrap and need rotating before every chart draw, so
+/// instead dropped samples are only skipped over, and moved out in one go
+/// once they outnumber the live ones. Dropping the oldest is amortized O(1)
+/// with at most twice the memory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<f64>", into = "Vec<f64>")]
+pub struct Samples {
+    values: Vec<f64>,
+    // Samples before this one have been dropped
+    start: usize,
+}
+
+impl Samples {
+    pub fn push(&mut self, value: f64, limit: usize) {
+        self.values.push(value);
+        let live = self.values.len() - self.start;
+        if live > limit {
+            self.start += live - limit;
+        }
+        if self.start > 0 && self.start >= self.values.len() - self.start {
+            self.values.drain(..self.start);
+            self.start = 0;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.start = 0;
+    }
+}
+
+impl Deref for Samples {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.values[self.start..]
+    }
+}
+
+impl<'a> IntoIterator for &'a Samples {
+    type Item = &'a f64;
+    type IntoIter = std::slice::Iter<'a, f64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<Vec<f64>> for Samples {
+    fn from(values: Vec<f64>) -> Samples {
+        Samples { values, start: 0 }
+    }
+}
+
+impl From<Samples> for Vec<f64> {
+    fn from(mut samples: Samples) -> Vec<f64> {
+        samples.values.drain(..samples.start);
+        samples.values
+    }
+}
+
+/// A color chosen for the ball, used instead of the one derived from its id.
+/// Reads and writes as `"#rrggbb"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rgb, String> {
+        let invalid = || format!("\"{}\" is not a color; expected #rrggbb", s);
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .ok_or_else(invalid)?;
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+        Ok(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl TryFrom<String> for Rgb {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Rgb, String> {
+        s.parse()
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(rgb: Rgb) -> String {
+        rgb.to_string()
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// A shape chosen for the ball, used instead of the one derived from its id:
+/// an index into [`Glyph::SHAPES`], or [`Glyph::ASCII`] where only ASCII
+/// draws. Reads and writes as the shape, `"★"`, and reads its ASCII stand-in
+/// `"x"` too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Glyph(pub usize);
+
+impl Glyph {
+    pub const SHAPES: [&'static str; 8] = ["●", "◉", "○", "◎", "◆", "■", "▲", "★"];
+    pub const ASCII: [&'static str; 8] = ["o", "O", "@", "0", "*", "#", "+", "x"];
+}
+
+impl FromStr for Glyph {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Glyph, String> {
+        let index = Glyph::SHAPES.iter().position(|&shape| shape == s);
+        let index = index.or_else(|| Glyph::ASCII.iter().position(|&shape| shape == s));
+        index
+            .map(Glyph)
+            .ok_or_else(|| format!("\"{}\" is not a ball glyph; expected one of {}", s, Glyph::SHAPES.join(" ")))
+    }
+}
+
+impl TryFrom<String> for Glyph {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Glyph, String> {
+        s.parse()
+    }
+}
+
+impl From<Glyph> for String {
+    fn from(glyph: Glyph) -> String {
+        glyph.to_string()
+    }
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(Glyph::SHAPES[self.0 % Glyph::SHAPES.len()])
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rust_tui_ball_bounce::save::SchemaVersion;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::caps;
+use crate::cli::Cli;
+use crate::keys::KeyBindings;
+use crate::layout::{self, PaneLayout, Section};
+use crate::locale;
+use crate::theme;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub version: SchemaVersion,
+    pub simulation: SimulationConfig,
+    pub display: DisplayConfig,
+    pub sound: SoundConfig,
+    pub keys: KeyBindings,
+    pub layout: PaneLayout,
+    // Named partial overlays, e.g. `[profiles.demo.display]`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+// Profiles available without any config file. A `[profiles.NAME]` table in
+// the config file is merged over the built-in profile of the same name.
+const BUILTIN_PROFILES: &str = r#"
+[demo]
+simulation = { balls = 8, speed = 1.25 }
+display = { theme = "ocean" }
+
+[benchmark]
+simulation = { balls = 200, tick_ms = 1, history = 60 }
+
+[teaching]
+simulation = { balls = 2, speed = 0.5, history = 600 }
+layout = { row_heights = [30, 35, 35] }
+"#;
+
+fn builtin_profiles() -> toml::Table {
+    BUILTIN_PROFILES.parse().expect("built-in profiles are valid TOML")
+}
+
+/// Recursively overlays `overlay` onto `base`; tables merge, other values replace.
+fn merge(base: &mut toml::Table, overlay: &toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge(base, overlay),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub balls: usize,
+    pub speed: f64,
+    pub tick_ms: u64,
+    pub paused: bool,
+    pub ball_radius: f64,
+    // Ticks of telemetry kept per ball for the graphs
+    pub history: usize,
+    // Show readouts in meters and seconds, a cell being this many meters;
+    // 0 keeps cells and ticks
+    pub meters_per_cell: f64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            balls: 1,
+            speed: 1.0,
+            tick_ms: 16,
+            paused: false,
+            ball_radius: 0.75,
+            history: 300,
+            meters_per_cell: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub theme: String,
+    // A language name, or `auto` to go by the environment's locale
+    pub language: String,
+    pub ascii: bool,
+    // Braille, half-block or ASCII, or `auto` to go by what the terminal
+    // handles; `ascii = true` picks ASCII whatever this says
+    pub glyphs: String,
+    pub color: bool,
+    // How many colors the terminal shows, or `auto` to go by the environment
+    pub colors: String,
+    // Count each terminal row as two units so motion looks isotropic
+    pub aspect_correction: bool,
+    // Redraws per second, independent of `simulation.tick_ms`
+    pub fps: u32,
+    // Open the menu on launch, unless the command line picks what to run
+    pub start_menu: bool,
+    // Show the title for a moment first
+    pub splash: bool,
+    // Leave out effects that only decorate motion
+    pub reduced_motion: bool,
+    // Drawn behind the balls: none, stars or gradient
+    pub background: String,
+    // Start in the described view, plain text in place of the panes
+    pub described: bool,
+    // When the window is resized, move the balls with the walls rather
+    // than only bringing in those left outside
+    pub rescale_on_resize: bool,
+    // The controls panel's sections, top to bottom
+    pub status_panel: Vec<String>,
+    // Start with the balls painting their paths onto the arena
+    pub paint: bool,
+    // Strength a paint stain loses per tick, from 1 when fresh; 0 keeps
+    // stains for good
+    pub paint_decay: f64,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            theme: theme::DEFAULT.name.to_string(),
+            language: "auto".to_string(),
+            ascii: false,
+            glyphs: "auto".to_string(),
+            color: true,
+            colors: "auto".to_string(),
+            aspect_correction: false,
+            fps: 60,
+            start_menu: true,
+            splash: true,
+            reduced_motion: false,
+            background: "none".to_string(),
+            described: false,
+            rescale_on_resize: false,
+            status_panel: Section::DEFAULT.iter().map(|section| section.name().to_string()).collect(),
+            paint: false,
+            paint_decay: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundConfig {
+    // Ring the terminal bell on wall bounces and collisions
+    pub bell: bool,
+    // Collisions with a smaller impulse stay silent
+    pub min_impulse: f64,
+    // Rings per second at most, however busy the arena gets
+    pub max_per_second: u32,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        SoundConfig {
+            bell: false,
+            min_impulse: 0.5,
+            max_per_second: 4,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid config file {}: {message}", path.display())]
+pub struct ConfigError {
+    path: PathBuf,
+    message: String,
+}
+
+impl Config {
+    /// Names of the built-in profiles followed by any defined only in the config file.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = builtin_profiles().keys().cloned().collect();
+        for name in self.profiles.keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    /// This config with the named profile layered on top.
+    pub fn with_profile(&self, name: &str) -> Result<Config, String> {
+        let builtin = builtin_profiles();
+        let builtin = builtin.get(name).and_then(|v| v.as_table());
+        let user = self.profiles.get(name);
+        if builtin.is_none() && user.is_none() {
+            return Err(format!(
+                "unknown profile \"{}\" (expected one of: {})",
+                name,
+                self.profile_names().join(", ")
+            ));
+        }
+
+        let mut table = toml::Table::try_from(self).map_err(|err| err.to_string())?;
+        table.remove("profiles");
+        for overlay in [builtin, user].into_iter().flatten() {
+            merge(&mut table, overlay);
+        }
+        let mut config: Config = table
+            .try_into()
+            .map_err(|err: toml::de::Error| format!("profiles.{}: {}", name, err.message()))?;
+        config
+            .validate()
+            .map_err(|err| format!("profiles.{}: {}", name, err))?;
+        config.layout = config.layout.sanitized();
+        config.profiles = self.profiles.clone();
+        Ok(config)
+    }
+
+    /// Environment overrides that don't map onto a command-line flag.
+    /// The `BALLBOUNCE_*` variables arrive through [`Cli`] instead.
+    pub fn apply_env(&mut self) {
+        // https://no-color.org: any non-empty value disables color
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            self.display.color = false;
+        }
+    }
+
+    /// Command-line flags (and their environment variables) take precedence
+    /// over the config file.
+    pub fn apply_cli(&mut self, cli: &Cli) {
+        if let Some(balls) = cli.balls {
+            self.simulation.balls = balls;
+        }
+        if let Some(thousands) = cli.stress {
+            self.simulation.balls = thousands as usize * 1000;
+        }
+        if cli.scenario.is_some() || cli.game.is_some() || cli.stress.is_some() {
+            self.display.start_menu = false;
+        }
+        if let Some(speed) = cli.speed {
+            self.simulation.speed = speed;
+        }
+        if let Some(ms) = cli.tick_ms {
+            self.simulation.tick_ms = ms;
+        }
+        if let Some(meters) = cli.meters_per_cell {
+            self.simulation.meters_per_cell = meters;
+        }
+        if let Some(fps) = cli.fps {
+            self.display.fps = fps;
+        }
+        if cli.paused {
+            self.simulation.paused = true;
+        }
+        if cli.ascii {
+            self.display.ascii = true;
+        }
+        if let Some(glyphs) = &cli.glyphs {
+            self.display.glyphs = glyphs.clone();
+        }
+        if let Some(colors) = &cli.colors {
+            self.display.colors = colors.clone();
+        }
+        if cli.bell {
+            self.sound.bell = true;
+        }
+        if cli.no_color {
+            self.display.color = false;
+        }
+        if cli.reduced_motion {
+            self.display.reduced_motion = true;
+        }
+        if cli.described {
+            self.display.described = true;
+        }
+        if let Some(theme) = &cli.theme {
+            self.display.theme = theme.clone();
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.version.check().map_err(|err| err.to_string())?;
+        let sim = &self.simulation;
+        if !(0.25..=5.0).contains(&sim.speed) {
+            return Err(format!("simulation.speed = {} is outside the range 0.25-5.0", sim.speed));
+        }
+        if !(1..=1000).contains(&sim.tick_ms) {
+            return Err(format!("simulation.tick_ms = {} is outside the range 1-1000", sim.tick_ms));
+        }
+        if !(0.1..=5.0).contains(&sim.ball_radius) {
+            return Err(format!(
+                "simulation.ball_radius = {} is outside the range 0.1-5.0",
+                sim.ball_radius
+            ));
+        }
+        if sim.history == 0 {
+            return Err("simulation.history must be at least 1".to_string());
+        }
+        if !(sim.meters_per_cell >= 0.0 && sim.meters_per_cell.is_finite()) {
+            return Err(format!("simulation.meters_per_cell = {} must be 0 or more", sim.meters_per_cell));
+        }
+        if !(1..=240).contains(&self.display.fps) {
+            return Err(format!("display.fps = {} is outside the range 1-240", self.display.fps));
+        }
+        if self.sound.min_impulse < 0.0 {
+            return Err(format!("sound.min_impulse = {} must not be negative", self.sound.min_impulse));
+        }
+        if !(1..=50).contains(&self.sound.max_per_second) {
+            return Err(format!(
+                "sound.max_per_second = {} is outside the range 1-50",
+                self.sound.max_per_second
+            ));
+        }
+        if theme::by_name(&self.display.theme).is_none() {
+            return Err(format!(
+                "display.theme = \"{}\" is not a known theme (expected one of: {})",
+                self.display.theme,
+                theme::THEME_NAMES.join(", ")
+            ));
+        }
+        if locale::by_name(&self.display.language).is_none() {
+            return Err(format!(
+                "display.language = \"{}\" is not a known language (expected one of: {})",
+                self.display.language,
+                locale::LANGUAGE_NAMES.join(", ")
+            ));
+        }
+        if !caps::GLYPH_NAMES.contains(&self.display.glyphs.as_str()) {
+            return Err(format!(
+                "display.glyphs = \"{}\" is not a known kind of glyphs (expected one of: {})",
+                self.display.glyphs,
+                caps::GLYPH_NAMES.join(", ")
+            ));
+        }
+        if !caps::COLOR_NAMES.contains(&self.display.colors.as_str()) {
+            return Err(format!(
+                "display.colors = \"{}\" is not a known color depth (expected one of: {})",
+                self.display.colors,
+                caps::COLOR_NAMES.join(", ")
+            ));
+        }
+        if theme::Backdrop::by_name(&self.display.background).is_none() {
+            return Err(format!(
+                "display.background = \"{}\" is not a known background (expected one of: {})",
+                self.display.background,
+                theme::BACKDROP_NAMES.join(", ")
+            ));
+        }
+        if let Some(name) = self.display.status_panel.iter().find(|name| Section::by_name(name).is_none()) {
+            return Err(format!(
+                "display.status_panel: \"{}\" is not a known section (expected one of: {})",
+                name,
+                layout::SECTION_NAMES.join(", ")
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.display.paint_decay) {
+            return Err(format!("display.paint_decay = {} is outside the range 0-1", self.display.paint_decay));
+        }
+        self.keys.keymap()?;
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("config is always representable as TOML")
+    }
+}
+
+/// `$XDG_CONFIG_HOME/ball-bounce/config.toml`, falling back to `~/.config`.
+pub fn config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("ball-bounce").join("config.toml"))
+}
+
+/// Loads the config file. A missing file yields the defaults; a file that
+/// can't be parsed or holds out-of-range values is an error.
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+    let error = |message: String| ConfigError {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(err) => return Err(error(err.to_string())),
+    };
+    let mut config: Config = toml::from_str(&text).map_err(|err| error(err.to_string()))?;
+    config.validate().map_err(&error)?;
+    // Every version so far reads as is; upgrade steps for older files go here
+    config.version = SchemaVersion::default();
+    for name in config.profiles.keys() {
+        config.with_profile(name).map_err(&error)?;
+    }
+    config.layout = config.layout.sanitized();
+    Ok(config)
+}
+
+/// Writes the whole config to `path`, creating its directory if needed.
+pub fn save(path: &Path, config: &Config) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, config.to_toml())
+}
+
+/// Writes the `[layout]` table (or `[profiles.NAME.layout]` when a profile is
+/// active), leaving any other keys in the file untouched.
+pub fn save_layout(path: &Path, profile: Option<&str>, layout: &PaneLayout) -> io::Result<()> {
+    let value = toml::Value::try_from(layout).map_err(io::Error::other)?;
+    save_value(path, profile, &[], "layout", value)
+}
+
+/// Sets `display.theme` (or `profiles.NAME.display.theme` when a profile is
+/// active), leaving any other keys in the file untouched.
+pub fn save_theme(path: &Path, profile: Option<&str>, theme: &str) -> io::Result<()> {
+    save_value(path, profile, &["display"], "theme", toml::Value::String(theme.to_string()))
+}
+
+// Sets `key` in the table at `tables` (under the profile's, if any), creating
+// the tables on the way
+fn save_value(
+    path: &Path,
+    profile: Option<&str>,
+    tables: &[&str],
+    key: &str,
+    value: toml::Value,
+) -> io::Result<()> {
+    let mut table: toml::Table = fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.parse().ok())
+        .unwrap_or_default();
+
+    let profile_tables = match profile {
+        Some(name) => vec!["profiles", name],
+        None => Vec::new(),
+    };
+    let mut target = &mut table;
+    for name in profile_tables.iter().chain(tables) {
+        let entry = target
+            .entry(*name)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        target = entry
+            .as_table_mut()
+            .ok_or_else(|| io::Error::other(format!("`{}` in the config file is not a table", name)))?;
+    }
+    target.insert(key.to_string(), value);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = toml::to_string_pretty(&table).map_err(io::Error::other)?;
+    fs::write(path, text)
+}
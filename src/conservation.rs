@@ -0,0 +1,80 @@
+//! The latest collision's momentum and kinetic energy, before and after, for
+//! checking the conservation laws by hand while stepping frame by frame.
+//! They come from the velocities in the collision's event and the balls'
+//! masses.
+
+use rust_tui_ball_bounce::{SimEvent, Snapshot};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exchange {
+    pub a: usize,
+    pub b: usize,
+    masses: [f64; 2],
+    before: [(f64, f64); 2],
+    after: [(f64, f64); 2],
+}
+
+/// A pair's momentum and kinetic energy at one moment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Totals {
+    pub momentum: (f64, f64),
+    pub energy: f64,
+}
+
+impl Totals {
+    fn of([ma, mb]: [f64; 2], [(vxa, vya), (vxb, vyb)]: [(f64, f64); 2]) -> Totals {
+        Totals {
+            momentum: (ma * vxa + mb * vxb, ma * vya + mb * vyb),
+            energy: 0.5 * (ma * (vxa * vxa + vya * vya) + mb * (vxb * vxb + vyb * vyb)),
+        }
+    }
+}
+
+impl Exchange {
+    /// The last collision in `events`, if there's one, weighing its balls as
+    /// `state` has them.
+    pub fn latest(events: &[SimEvent], state: &Snapshot) -> Option<Exchange> {
+        let mass = |id| state.balls.iter().find(|ball| ball.id == id).map_or(1.0, |ball| ball.mass);
+        events.iter().rev().find_map(|event| match *event {
+            SimEvent::Collision { a, b, before, after, .. } => Some(Exchange { a, b, masses: [mass(a), mass(b)], before, after }),
+            _ => None,
+        })
+    }
+
+    pub fn before(&self) -> Totals {
+        Totals::of(self.masses, self.before)
+    }
+
+    pub fn after(&self) -> Totals {
+        Totals::of(self.masses, self.after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::BallState;
+
+    use super::*;
+
+    #[test]
+    fn the_latest_collision_is_kept() {
+        let collision = |a, before, after| SimEvent::Collision { a, b: 5, x: 0.0, y: 0.0, impulse: 1.0, before, after };
+        let events = [
+            collision(0, [(1.0, 0.0), (0.0, 0.0)], [(0.0, 0.0), (1.0, 0.0)]),
+            collision(2, [(1.0, 1.0), (-1.0, 0.0)], [(-1.0, 1.0), (1.0, 0.0)]),
+            SimEvent::WallBounce { id: 2 },
+        ];
+        let state = Snapshot::default();
+        assert_eq!(Exchange::latest(&events[2..], &state), None);
+        let exchange = Exchange::latest(&events, &state).unwrap();
+        assert_eq!((exchange.a, exchange.b), (2, 5));
+        assert_eq!(exchange.before(), Totals { momentum: (0.0, 1.0), energy: 1.5 });
+        assert_eq!(exchange.after(), exchange.before());
+
+        // A heavier ball counts for more
+        let heavy = BallState { id: 5, x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, radius: 1.0, mass: 3.0, color: None, glyph: None, body: None };
+        let state = Snapshot { balls: vec![heavy], ..Snapshot::default() };
+        let exchange = Exchange::latest(&events, &state).unwrap();
+        assert_eq!(exchange.before(), Totals { momentum: (-2.0, 1.0), energy: 2.5 });
+    }
+}
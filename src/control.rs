@@ -0,0 +1,219 @@
+//! `--control PATH`: a Unix socket that scripts and other processes drive
+//! the app through. Each line is a command in the palette's language, or
+//! `dump state`, and gets a one-line reply: `ok`, `error: ...`, or the state
+//! as JSON. Commands piped to stdin take the same path, without the replies.
+//!
+//! A client that sends `watch` gets no more replies: from then on it is sent
+//! the state as a JSON [`Snapshot`] line after every update, paused or not,
+//! and whatever else it sends is ignored. That's how `attach` views an
+//! instance without being able to change it.
+
+// Only `disabled` is available off Unix
+#![cfg_attr(not(unix), allow(dead_code, unused_imports))]
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rust_tui_ball_bounce::Snapshot;
+use tokio::sync::mpsc::{self as async_mpsc, UnboundedReceiver, UnboundedSender};
+
+// Ticks queued per watcher before a slow one starts missing them
+const WATCH_BACKLOG: usize = 64;
+
+type Watchers = Arc<Mutex<Vec<SyncSender<Arc<str>>>>>;
+
+/// A line from a client, waiting for the app to answer it.
+pub struct Request {
+    pub line: String,
+    reply: mpsc::Sender<String>,
+}
+
+impl Request {
+    pub fn reply(self, reply: String) {
+        // The client may have gone already
+        let _ = self.reply.send(reply);
+    }
+}
+
+/// Hands lines from every connected client to the app one at a time. The
+/// socket file is removed when this is dropped.
+pub struct ControlServer {
+    rx: UnboundedReceiver<Request>,
+    // Also keeps `recv` pending rather than ending while there is no listener
+    tx: UnboundedSender<Request>,
+    watchers: Watchers,
+    path: Option<PathBuf>,
+}
+
+impl ControlServer {
+    /// A server nobody can connect to, for when `--control` isn't given.
+    pub fn disabled() -> ControlServer {
+        let (tx, rx) = async_mpsc::unbounded_channel();
+        ControlServer { rx, tx, watchers: Watchers::default(), path: None }
+    }
+
+    /// Listens on `path`, replacing a socket left behind by an instance that
+    /// didn't shut down cleanly.
+    #[cfg(unix)]
+    pub fn bind(path: &Path) -> io::Result<ControlServer> {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if path.exists() && UnixStream::connect(path).is_err() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        let mut server = ControlServer::disabled();
+        server.path = Some(path.to_path_buf());
+        let requests = server.tx.clone();
+        let watchers = Arc::clone(&server.watchers);
+        // Never joined: it waits in `accept` until the process exits
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let requests = requests.clone();
+                let watchers = Arc::clone(&watchers);
+                let Ok(reader) = stream.try_clone() else {
+                    continue;
+                };
+                thread::spawn(move || serve_client(BufReader::new(reader), stream, requests, Some(&watchers)));
+            }
+        });
+        Ok(server)
+    }
+
+    /// Also takes commands from stdin, one per line, until it ends. The
+    /// terminal UI reads keys from the tty instead, so both work at once.
+    pub fn read_stdin(&self) {
+        let requests = self.tx.clone();
+        thread::spawn(move || serve_client(io::stdin().lock(), io::sink(), requests, None));
+    }
+
+    /// Sends states to the clients watching.
+    pub fn state_stream(&self) -> StateStream {
+        StateStream { watchers: Arc::clone(&self.watchers) }
+    }
+
+    pub async fn recv(&mut self) -> Option<Request> {
+        self.rx.recv().await
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+pub struct StateStream {
+    watchers: Watchers,
+}
+
+impl StateStream {
+    pub fn send(&mut self, snapshot: &Snapshot) {
+        let mut watchers = self.watchers.lock().unwrap();
+        if watchers.is_empty() {
+            return;
+        }
+        let Ok(json) = serde_json::to_string(snapshot) else {
+            return;
+        };
+        let json: Arc<str> = json.into();
+        watchers.retain(|tx| !matches!(tx.try_send(Arc::clone(&json)), Err(TrySendError::Disconnected(_))));
+    }
+}
+
+/// Passes each non-blank line to the app and writes back its reply, until
+/// the client hangs up or the app stops answering. A client that can watch
+/// and asks to is handed to [`watch`] instead.
+fn serve_client(
+    reader: impl BufRead,
+    mut writer: impl Write,
+    requests: UnboundedSender<Request>,
+    watchers: Option<&Watchers>,
+) {
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let (Some(watchers), "watch") = (watchers, line.trim()) {
+            let (tx, rx) = mpsc::sync_channel(WATCH_BACKLOG);
+            watchers.lock().unwrap().push(tx);
+            return watch(writer, rx);
+        }
+        let (reply, answer) = mpsc::channel();
+        if requests.send(Request { line, reply }).is_err() {
+            return;
+        }
+        let Ok(answer) = answer.recv() else {
+            return;
+        };
+        if writeln!(writer, "{}", answer).is_err() {
+            return;
+        }
+    }
+}
+
+/// Writes ticks to the client until it goes away.
+fn watch(mut writer: impl Write, ticks: Receiver<Arc<str>>) {
+    for json in ticks {
+        if writeln!(writer, "{}", json).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_line_gets_one_reply() {
+        let (tx, mut rx) = async_mpsc::unbounded_channel::<Request>();
+        let app = thread::spawn(move || {
+            while let Some(request) = rx.blocking_recv() {
+                let reply = request.line.to_uppercase();
+                request.reply(reply);
+            }
+        });
+        let mut out = Vec::new();
+        serve_client("add 5\n\npause\n".as_bytes(), &mut out, tx, None);
+        app.join().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "ADD 5\nPAUSE\n");
+    }
+
+    #[test]
+    fn watchers_get_every_state_and_no_replies() {
+        let (tx, _rx) = async_mpsc::unbounded_channel::<Request>();
+        let watchers = Watchers::default();
+        let mut stream = StateStream { watchers: Arc::clone(&watchers) };
+        let client = {
+            let watchers = Arc::clone(&watchers);
+            thread::spawn(move || {
+                let mut out = Vec::new();
+                serve_client("watch\npause\n".as_bytes(), &mut out, tx, Some(&watchers));
+                out
+            })
+        };
+        while watchers.lock().unwrap().is_empty() {
+            thread::yield_now();
+        }
+        for tick_count in 1..=2 {
+            stream.send(&Snapshot { tick_count, ..Snapshot::default() });
+        }
+        // Hangs up on the client
+        watchers.lock().unwrap().clear();
+        let out = String::from_utf8(client.join().unwrap()).unwrap();
+        let ticks: Vec<u64> = out
+            .lines()
+            .map(|line| serde_json::from_str::<Snapshot>(line).unwrap().tick_count)
+            .collect();
+        assert_eq!(ticks, [1, 2]);
+    }
+}
@@ -0,0 +1,164 @@
+//! The described view, toggled with F2: the arena as plain sentences for a
+//! screen reader, in place of the panes. The text is rewritten every
+//! [`REFRESH`] rather than every frame, so there's time to read it, and
+//! lists the balls and what last happened to them. Balls are named by id,
+//! counting from one, so a ball keeps its name as others come and go.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use rust_tui_ball_bounce::{BallState, SimEvent, Snapshot};
+
+use crate::units::{self, Quantity, Units};
+
+pub const REFRESH: Duration = Duration::from_secs(2);
+// Balls listed one by one; the rest are counted
+const LISTED: usize = 12;
+// Events kept for the next description, the latest ones
+const RECENT: usize = 5;
+// Slower than this counts as standing still, in cells per tick
+const AT_REST: f64 = 0.01;
+
+#[derive(Debug, Default)]
+pub struct Description {
+    pub lines: Vec<String>,
+    // Sentences for what happened since, newest last
+    events: VecDeque<String>,
+    refreshed: Option<Duration>,
+}
+
+impl Description {
+    /// Notes `event` for the next description.
+    pub fn event(&mut self, event: &SimEvent) {
+        if self.events.len() == RECENT {
+            self.events.pop_front();
+        }
+        self.events.push_back(sentence(event));
+    }
+
+    /// Rewrites the text from `state` and the events since the last time,
+    /// if that was [`REFRESH`] ago or it hasn't been written yet. Lengths
+    /// and speeds are in `units` if there are any.
+    pub fn refresh(&mut self, state: &Snapshot, units: Option<Units>, now: Duration) {
+        if self.refreshed.is_some_and(|refreshed| now < refreshed + REFRESH) {
+            return;
+        }
+        self.refreshed = Some(now);
+        self.lines = describe(state, units, &self.events);
+        self.events.clear();
+    }
+
+    /// Starts over, so the next [`refresh`](Self::refresh) writes at once.
+    pub fn clear(&mut self) {
+        *self = Description::default();
+    }
+}
+
+fn describe(state: &Snapshot, units: Option<Units>, events: &VecDeque<String>) -> Vec<String> {
+    let running = if state.paused { "Paused" } else { "Running" };
+    let mut lines = vec![format!("{} at {:.2} times speed.", running, state.speed_multiplier)];
+    if let Some(name) = &state.scenario_name {
+        lines.push(format!("Scenario: {}.", name));
+    }
+    let count = match state.balls.len() {
+        1 => "1 ball".to_string(),
+        n => format!("{} balls", n),
+    };
+    lines.push(format!(
+        "{} in an arena {} wide and {} high.",
+        count,
+        units::show_with_symbol(units, Quantity::Length, state.area_width, 0),
+        units::show_with_symbol(units, Quantity::Length, state.area_height, 0)
+    ));
+    lines.push(String::new());
+    for ball in state.balls.iter().take(LISTED) {
+        lines.push(describe_ball(ball, units));
+    }
+    if state.balls.len() > LISTED {
+        lines.push(format!("And {} more.", state.balls.len() - LISTED));
+    }
+    lines.push(String::new());
+    if events.is_empty() {
+        lines.push("Nothing has happened since.".to_string());
+    } else {
+        lines.push("Since the last update:".to_string());
+        lines.extend(events.iter().cloned());
+    }
+    lines
+}
+
+fn describe_ball(ball: &BallState, units: Option<Units>) -> String {
+    let speed = ball.vx.hypot(ball.vy);
+    let motion = if speed < AT_REST {
+        "standing still".to_string()
+    } else {
+        let horizontal = if ball.vx.abs() < AT_REST { None } else if ball.vx > 0.0 { Some("right") } else { Some("left") };
+        let vertical = if ball.vy.abs() < AT_REST { None } else if ball.vy > 0.0 { Some("down") } else { Some("up") };
+        let heading = match (horizontal, vertical) {
+            (Some(h), Some(v)) => format!("{} and {}", v, h),
+            (Some(one), None) | (None, Some(one)) => one.to_string(),
+            (None, None) => unreachable!("a moving ball has a heading"),
+        };
+        format!("moving {} at {}", heading, units::show_with_symbol(units, Quantity::Speed, speed, 2))
+    };
+    let length = |value| units::show_with_symbol(units, Quantity::Length, value, 0);
+    format!("Ball {} at x {}, y {}, {}.", ball.id + 1, length(ball.x), length(ball.y), motion)
+}
+
+fn sentence(event: &SimEvent) -> String {
+    match event {
+        SimEvent::Spawned { id } => format!("Ball {} was added.", id + 1),
+        SimEvent::Removed { id } => format!("Ball {} was removed.", id + 1),
+        SimEvent::Expired { id } => format!("Ball {} ran out of time.", id + 1),
+        SimEvent::Pocketed { id } => format!("Ball {} went into a pocket.", id + 1),
+        SimEvent::Collision { a, b, .. } => format!("Balls {} and {} collided.", a + 1, b + 1),
+        SimEvent::WallBounce { id } => format!("Ball {} bounced off a wall.", id + 1),
+        SimEvent::Paused(true) => "Paused.".to_string(),
+        SimEvent::Paused(false) => "Resumed.".to_string(),
+        SimEvent::SpeedChanged(speed) => format!("Speed set to {:.2} times.", speed),
+        SimEvent::ScenarioLoaded(name) => format!("Loaded {}.", name),
+        SimEvent::Reset => "The arena was reset.".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::Simulation;
+
+    use super::*;
+
+    #[test]
+    fn descriptions_wait_to_be_read() {
+        let mut sim = Simulation::new(40.0, 20.0);
+        sim.add_ball(rust_tui_ball_bounce::BallSpec::new(10.0, 5.0, 1.0, -1.0));
+        sim.add_ball(rust_tui_ball_bounce::BallSpec::new(30.0, 15.0, 0.0, 0.0));
+        let mut description = Description::default();
+        description.event(&SimEvent::Collision { a: 0, b: 1, x: 20.0, y: 10.0, impulse: 1.0, before: [(0.0, 0.0); 2], after: [(0.0, 0.0); 2] });
+        description.refresh(&sim.snapshot(), None, Duration::ZERO);
+        assert_eq!(description.lines[2..], [
+            "",
+            "Ball 1 at x 10, y 5, moving up and right at 1.41.",
+            "Ball 2 at x 30, y 15, standing still.",
+            "",
+            "Since the last update:",
+            "Balls 1 and 2 collided.",
+        ]);
+
+        sim.step(1.0);
+        description.refresh(&sim.snapshot(), None, REFRESH / 2);
+        assert_eq!(description.lines[3], "Ball 1 at x 10, y 5, moving up and right at 1.41.");
+        description.refresh(&sim.snapshot(), None, REFRESH);
+        assert_eq!(description.lines[3], "Ball 1 at x 11, y 4, moving up and right at 1.41.");
+        assert_eq!(description.lines.last().unwrap(), "Nothing has happened since.");
+    }
+
+    #[test]
+    fn descriptions_can_be_in_meters() {
+        let mut sim = Simulation::new(40.0, 20.0);
+        sim.add_ball(rust_tui_ball_bounce::BallSpec::new(10.0, 5.0, 0.0, -0.5));
+        let mut description = Description::default();
+        description.refresh(&sim.snapshot(), Units::new(0.1, Duration::from_millis(20)), Duration::ZERO);
+        assert_eq!(description.lines[1], "1 ball in an arena 4.00 m wide and 2.00 m high.");
+        assert_eq!(description.lines[3], "Ball 1 at x 1.00 m, y 0.500 m, moving up at 2.50 m/s.");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::force::{Boids, Direction};
+use crate::scenario::Scenario;
+
+/// A request to change the simulation, applied with
+/// [`Simulation::apply`](crate::Simulation::apply). Frontends translate
+/// input into commands instead of mutating the simulation directly.
+#[derive(Clone, Debug)]
+pub enum Command {
+    TogglePause,
+    SetPaused(bool),
+    AddBall,
+    RemoveBall,
+    SpeedUp,
+    SpeedDown,
+    SetSpeed(f64),
+    // Replaces any `Gravity` force; 0 removes it
+    SetGravity(f64),
+    // Turns gravity, now and once it's set
+    SetGravityDirection(Direction),
+    // Replaces any `Boids` force; `None` removes it
+    SetBoids(Option<Boids>),
+    // One step at the current speed, even while paused
+    Step,
+    LoadScenario(Box<Scenario>),
+    Reset,
+}
+
+/// Something that happened in the simulation. Ball ids match
+/// [`BallId`](crate::components::BallId).
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimEvent {
+    Spawned { id: usize },
+    Removed { id: usize },
+    // The ball's `Ttl` ran out
+    Expired { id: usize },
+    // The ball's center went into one of the simulation's `pockets`
+    Pocketed { id: usize },
+    // Two balls met at the contact point `(x, y)`, exchanging `impulse` of
+    // momentum: their closing speed, for balls of mass 1. `before` and
+    // `after` are `a`'s and then `b`'s velocity either side of it.
+    Collision {
+        a: usize,
+        b: usize,
+        x: f64,
+        y: f64,
+        impulse: f64,
+        before: [(f64, f64); 2],
+        after: [(f64, f64); 2],
+    },
+    WallBounce { id: usize },
+    Paused(bool),
+    SpeedChanged(f64),
+    ScenarioLoaded(String),
+    Reset,
+}
@@ -0,0 +1,243 @@
+//! Writes recorded runs to files for analysis in other tools: CSV for
+//! spreadsheets and dataframes, JSON or MessagePack for programs, and
+//! Markdown or LaTeX [reports](crate::report) for people.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use rust_tui_ball_bounce::components::{with_ticks, History};
+use rust_tui_ball_bounce::save::SchemaVersion;
+use rust_tui_ball_bounce::Snapshot;
+
+use crate::report;
+
+/// A run to export: the state it ended in and each ball's history by id,
+/// recorded at `ticks`.
+pub struct Recording<'a> {
+    pub snapshot: &'a Snapshot,
+    pub ticks: &'a [f64],
+    pub histories: Vec<(usize, &'a History)>,
+    // Wall-clock length of a tick, for the time column
+    pub tick_rate: Duration,
+}
+
+/// What a CSV export can't hold in its columns: how to reproduce the run.
+#[derive(Serialize)]
+struct Metadata<'a> {
+    version: SchemaVersion,
+    scenario: Option<&'a str>,
+    seed: u64,
+    ticks: u64,
+    tick_ms: f64,
+    speed: f64,
+    arena_width: f64,
+    arena_height: f64,
+    aspect: f64,
+    balls: usize,
+    collisions: u64,
+    wall_bounces: u64,
+}
+
+/// The whole recording, for the JSON and MessagePack formats.
+#[derive(Serialize)]
+struct Document<'a> {
+    version: SchemaVersion,
+    tick_ms: f64,
+    state: &'a Snapshot,
+    histories: BTreeMap<usize, Points>,
+}
+
+/// A history as `(tick, value)` pairs, as the formats have always had it.
+#[derive(Serialize)]
+struct Points {
+    x: Vec<(f64, f64)>,
+    y: Vec<(f64, f64)>,
+    vx: Vec<(f64, f64)>,
+    vy: Vec<(f64, f64)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Csv,
+    Json,
+    MessagePack,
+    Markdown,
+    Latex,
+}
+
+impl Format {
+    /// `.json`, `.msgpack` (or `.mp`), `.md` and `.tex`; anything else is
+    /// CSV.
+    pub fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Format::Json,
+            Some("msgpack" | "mp") => Format::MessagePack,
+            Some("md") => Format::Markdown,
+            Some("tex") => Format::Latex,
+            _ => Format::Csv,
+        }
+    }
+}
+
+/// Writes the recording to `path` in the format its extension asks for.
+/// CSV gets the seed and parameters in a TOML file next to it (see
+/// [`metadata_path`]), and Markdown its charts as SVG files (see
+/// [`report::chart_path`]); the others hold everything in one file.
+pub fn save(path: &Path, recording: &Recording) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    match Format::from_path(path) {
+        Format::Csv => {
+            write_csv(&mut out, recording)?;
+            std::fs::write(metadata_path(path), metadata(recording)?)?;
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &document(recording))?;
+            writeln!(out)?;
+        }
+        Format::MessagePack => rmp_serde::encode::write_named(&mut out, &document(recording))
+            .map_err(io::Error::other)?,
+        Format::Markdown => {
+            out.write_all(report::markdown(recording, path).as_bytes())?;
+            for (name, title, values) in report::CHARTS {
+                std::fs::write(report::chart_path(path, name), report::svg(recording, title, values))?;
+            }
+        }
+        Format::Latex => out.write_all(report::latex(recording).as_bytes())?,
+    }
+    out.flush()
+}
+
+/// `run.csv` → `run.meta.toml`.
+pub fn metadata_path(path: &Path) -> PathBuf {
+    path.with_extension("meta.toml")
+}
+
+/// One row per ball per recorded tick, grouped by ball. `time` is in seconds
+/// at the recording's tick rate.
+pub fn write_csv(out: &mut impl Write, recording: &Recording) -> io::Result<()> {
+    let seconds = recording.tick_rate.as_secs_f64();
+    writeln!(out, "tick,time,ball,x,y,vx,vy")?;
+    for &(id, history) in &recording.histories {
+        let samples = with_ticks(recording.ticks, &history.x).zip(&history.y).zip(&history.vx).zip(&history.vy);
+        for ((((tick, x), &y), &vx), &vy) in samples {
+            writeln!(
+                out,
+                "{},{:.6},{},{:.6},{:.6},{:.6},{:.6}",
+                tick as u64,
+                tick * seconds,
+                id,
+                x,
+                y,
+                vx,
+                vy
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn document<'a>(recording: &Recording<'a>) -> Document<'a> {
+    Document {
+        version: SchemaVersion::default(),
+        tick_ms: recording.tick_rate.as_secs_f64() * 1000.0,
+        state: recording.snapshot,
+        histories: recording
+            .histories
+            .iter()
+            .map(|&(id, history)| {
+                let points = |values| with_ticks(recording.ticks, values).collect();
+                let points = Points {
+                    x: points(&history.x),
+                    y: points(&history.y),
+                    vx: points(&history.vx),
+                    vy: points(&history.vy),
+                };
+                (id, points)
+            })
+            .collect(),
+    }
+}
+
+fn metadata(recording: &Recording) -> io::Result<String> {
+    let snapshot = recording.snapshot;
+    let metadata = Metadata {
+        version: SchemaVersion::default(),
+        scenario: snapshot.scenario_name.as_deref(),
+        seed: snapshot.seed,
+        ticks: snapshot.tick_count,
+        tick_ms: recording.tick_rate.as_secs_f64() * 1000.0,
+        speed: snapshot.speed_multiplier,
+        arena_width: snapshot.area_width,
+        arena_height: snapshot.area_height,
+        aspect: snapshot.aspect,
+        balls: snapshot.balls.len(),
+        collisions: snapshot.collisions,
+        wall_bounces: snapshot.wall_bounces,
+    };
+    toml::to_string(&metadata).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_follow_each_ball_history() {
+        let history = History {
+            x: vec![2.0, 2.5].into(),
+            y: vec![3.0, 3.0].into(),
+            vx: vec![0.5, 0.5].into(),
+            vy: vec![0.0, 0.0].into(),
+        };
+        let snapshot = Snapshot::default();
+        let recording = Recording {
+            snapshot: &snapshot,
+            ticks: &[0.0, 1.0, 2.0],
+            histories: vec![(3, &history)],
+            tick_rate: Duration::from_millis(20),
+        };
+        let mut out = Vec::new();
+        write_csv(&mut out, &recording).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "tick,time,ball,x,y,vx,vy\n\
+             1,0.020000,3,2.000000,3.000000,0.500000,0.000000\n\
+             2,0.040000,3,2.500000,3.000000,0.500000,0.000000\n"
+        );
+    }
+
+    #[test]
+    fn documents_hold_state_and_histories() {
+        let history = History { x: vec![2.0].into(), ..History::default() };
+        let snapshot = Snapshot { tick_count: 1, seed: 7, ..Snapshot::default() };
+        let recording = Recording {
+            snapshot: &snapshot,
+            ticks: &[1.0],
+            histories: vec![(3, &history)],
+            tick_rate: Duration::from_millis(16),
+        };
+        let json = serde_json::to_value(document(&recording)).unwrap();
+        assert_eq!(json["state"]["seed"], 7);
+        assert_eq!(json["histories"]["3"]["x"][0][1], 2.0);
+    }
+
+    #[test]
+    fn format_follows_the_extension() {
+        assert_eq!(Format::from_path(Path::new("run.json")), Format::Json);
+        assert_eq!(Format::from_path(Path::new("run.msgpack")), Format::MessagePack);
+        assert_eq!(Format::from_path(Path::new("run.csv")), Format::Csv);
+        assert_eq!(Format::from_path(Path::new("run.md")), Format::Markdown);
+        assert_eq!(Format::from_path(Path::new("run.tex")), Format::Latex);
+        assert_eq!(Format::from_path(Path::new("run")), Format::Csv);
+    }
+
+    #[test]
+    fn metadata_goes_next_to_the_csv() {
+        assert_eq!(metadata_path(Path::new("out/run.csv")), Path::new("out/run.meta.toml"));
+    }
+}
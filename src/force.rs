@@ -0,0 +1,330 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use hecs::World;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Position, Velocity};
+
+/// Something that changes ball velocities every tick. Forces registered on a
+/// [`Simulation`](crate::Simulation) run in order, before balls move, and may
+/// query any components they need from the world. They must be `Send` so the
+/// simulation can run on its own thread.
+///
+/// `dt` is the length of the step in ticks (the speed multiplier), so a
+/// constant acceleration `a` changes velocity by `a * dt`.
+///
+/// Forces are [`Any`] so a particular kind can be found among them, as
+/// [`Simulation::set_gravity`](crate::Simulation::set_gravity) does.
+pub trait Force: Any + Send {
+    fn apply(&self, world: &mut World, dt: f64);
+
+    /// The acceleration a ball at rest at `(x, y)` would get, for drawing
+    /// the force field. Forces that only act on moving balls, like drag,
+    /// keep the default of none.
+    fn field(&self, _x: f64, _y: f64) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+}
+
+/// Which way gravity pulls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Down,
+    Up,
+    Left,
+    Right,
+    // Toward the middle of the arena, wherever a ball is
+    Center,
+}
+
+impl Direction {
+    /// In the order the gravity key steps through them.
+    pub const ALL: [Direction; 5] = [Direction::Down, Direction::Left, Direction::Up, Direction::Right, Direction::Center];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Down => "down",
+            Direction::Up => "up",
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Center => "center",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Direction> {
+        Direction::ALL.into_iter().find(|direction| direction.name() == name)
+    }
+
+    /// The direction after this one, going round.
+    pub fn next(self) -> Direction {
+        let at = Direction::ALL.iter().position(|&direction| direction == self).unwrap_or(0);
+        Direction::ALL[(at + 1) % Direction::ALL.len()]
+    }
+
+    /// The unit vector of the pull on a ball at `(x, y)`, with `center` the
+    /// middle of the arena. Nothing pulls a ball already at the center.
+    pub fn pull(self, x: f64, y: f64, center: (f64, f64)) -> (f64, f64) {
+        match self {
+            Direction::Down => (0.0, 1.0),
+            Direction::Up => (0.0, -1.0),
+            Direction::Left => (-1.0, 0.0),
+            Direction::Right => (1.0, 0.0),
+            Direction::Center => {
+                let (dx, dy) = (center.0 - x, center.1 - y);
+                let distance = dx.hypot(dy);
+                if distance < 1e-9 {
+                    (0.0, 0.0)
+                } else {
+                    (dx / distance, dy / distance)
+                }
+            }
+        }
+    }
+}
+
+/// Constant acceleration of `g` cells per tick² toward `direction`.
+/// `center` is where [`Direction::Center`] pulls to, which the simulation
+/// keeps at the middle of its arena.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gravity {
+    pub g: f64,
+    pub direction: Direction,
+    pub center: (f64, f64),
+}
+
+impl Gravity {
+    /// Gravity of `g` pulling straight down.
+    pub fn down(g: f64) -> Gravity {
+        Gravity { g, direction: Direction::Down, center: (0.0, 0.0) }
+    }
+}
+
+impl Force for Gravity {
+    fn apply(&self, world: &mut World, dt: f64) {
+        for (pos, vel) in world.query_mut::<(&Position, &mut Velocity)>() {
+            let (ux, uy) = self.direction.pull(pos.x, pos.y, self.center);
+            vel.vx += ux * self.g * dt;
+            vel.vy += uy * self.g * dt;
+        }
+    }
+
+    fn field(&self, x: f64, y: f64) -> (f64, f64) {
+        let (ux, uy) = self.direction.pull(x, y, self.center);
+        (ux * self.g, uy * self.g)
+    }
+}
+
+/// Fraction of velocity lost per tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drag(pub f64);
+
+impl Force for Drag {
+    fn apply(&self, world: &mut World, dt: f64) {
+        let damping = (1.0 - self.0).powf(dt);
+        for vel in world.query_mut::<&mut Velocity>() {
+            vel.vx *= damping;
+            vel.vy *= damping;
+        }
+    }
+}
+
+/// Speed lost per tick, like a ball rolling on cloth. Unlike [`Drag`] it
+/// brings balls to a stop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Friction(pub f64);
+
+impl Force for Friction {
+    fn apply(&self, world: &mut World, dt: f64) {
+        let loss = self.0 * dt;
+        for vel in world.query_mut::<&mut Velocity>() {
+            let speed = vel.vx.hypot(vel.vy);
+            let scale = if speed > loss { (speed - loss) / speed } else { 0.0 };
+            vel.vx *= scale;
+            vel.vy *= scale;
+        }
+    }
+}
+
+/// Nudges every ball toward the wind velocity `(vx, vy)`; `strength` is the
+/// fraction of the difference closed per tick.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Wind {
+    pub vx: f64,
+    pub vy: f64,
+    pub strength: f64,
+}
+
+impl Force for Wind {
+    fn apply(&self, world: &mut World, dt: f64) {
+        let k = 1.0 - (1.0 - self.strength).powf(dt);
+        for vel in world.query_mut::<&mut Velocity>() {
+            vel.vx += (self.vx - vel.vx) * k;
+            vel.vy += (self.vy - vel.vy) * k;
+        }
+    }
+
+    fn field(&self, _x: f64, _y: f64) -> (f64, f64) {
+        (self.vx * self.strength, self.vy * self.strength)
+    }
+}
+
+/// Inverse-square pull toward `(x, y)`; a negative `strength` repels.
+/// `strength` is the acceleration at a distance of one cell.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Attractor {
+    pub x: f64,
+    pub y: f64,
+    pub strength: f64,
+}
+
+impl Force for Attractor {
+    fn apply(&self, world: &mut World, dt: f64) {
+        for (pos, vel) in world.query_mut::<(&Position, &mut Velocity)>() {
+            let dx = self.x - pos.x;
+            let dy = self.y - pos.y;
+            // Clamp so a ball passing through the center isn't flung away
+            let dist_sq = (dx * dx + dy * dy).max(1.0);
+            let accel = self.strength / dist_sq * dt;
+            let dist = dist_sq.sqrt();
+            vel.vx += accel * dx / dist;
+            vel.vy += accel * dy / dist;
+        }
+    }
+
+    fn field(&self, x: f64, y: f64) -> (f64, f64) {
+        let dx = self.x - x;
+        let dy = self.y - y;
+        let dist_sq = (dx * dx + dy * dy).max(1.0);
+        let dist = dist_sq.sqrt();
+        let accel = self.strength / dist_sq;
+        (accel * dx / dist, accel * dy / dist)
+    }
+}
+
+/// Flocking: each ball steers away from neighbors that crowd it
+/// (`separation`), toward their average heading (`alignment`) and toward
+/// their center (`cohesion`). Neighbors are the balls within `radius` cells.
+/// Steered balls are kept between `min_speed` and `max_speed`, so the flock
+/// doesn't settle into a standstill. The weights scale each rule, 0 turning
+/// it off.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Boids {
+    pub separation: f64,
+    pub alignment: f64,
+    pub cohesion: f64,
+    pub radius: f64,
+    pub min_speed: f64,
+    pub max_speed: f64,
+}
+
+impl Default for Boids {
+    fn default() -> Boids {
+        Boids { separation: 1.0, alignment: 1.0, cohesion: 1.0, radius: 6.0, min_speed: 0.3, max_speed: 1.0 }
+    }
+}
+
+// Each rule's change in velocity per tick at a weight of 1: for a neighbor
+// one cell away, for a heading one cell per tick off the average and for a
+// center one cell away
+const SEPARATION: f64 = 0.05;
+const ALIGNMENT: f64 = 0.05;
+const COHESION: f64 = 0.003;
+
+impl Force for Boids {
+    fn apply(&self, world: &mut World, dt: f64) {
+        let flock: Vec<(Position, Velocity)> =
+            world.query_mut::<(&Position, &Velocity)>().into_iter().map(|(pos, vel)| (*pos, *vel)).collect();
+        let neighbors = Neighbors::new(&flock, self.radius);
+        let steers: Vec<(f64, f64)> = (0..flock.len()).map(|i| self.steer(&flock, &neighbors, i)).collect();
+        for ((_, vel), (ax, ay)) in world.query_mut::<(&Position, &mut Velocity)>().into_iter().zip(steers) {
+            if (ax, ay) == (0.0, 0.0) {
+                continue;
+            }
+            vel.vx += ax * dt;
+            vel.vy += ay * dt;
+            let speed = vel.vx.hypot(vel.vy);
+            if speed > 0.0 {
+                let scale = speed.clamp(self.min_speed, self.max_speed) / speed;
+                vel.vx *= scale;
+                vel.vy *= scale;
+            }
+        }
+    }
+}
+
+impl Boids {
+    /// The acceleration the three rules give ball `i` of `flock`.
+    fn steer(&self, flock: &[(Position, Velocity)], neighbors: &Neighbors, i: usize) -> (f64, f64) {
+        let (pos, vel) = flock[i];
+        let (mut away_x, mut away_y) = (0.0, 0.0);
+        let (mut sum_vx, mut sum_vy, mut sum_x, mut sum_y, mut count) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for j in neighbors.near(pos.x, pos.y) {
+            let (other, other_vel) = flock[j];
+            let dx = pos.x - other.x;
+            let dy = pos.y - other.y;
+            let dist_sq = dx * dx + dy * dy;
+            if j == i || dist_sq > self.radius * self.radius {
+                continue;
+            }
+            // Clamp so balls on top of each other aren't flung apart
+            let dist_sq = dist_sq.max(0.25);
+            away_x += dx / dist_sq;
+            away_y += dy / dist_sq;
+            sum_vx += other_vel.vx;
+            sum_vy += other_vel.vy;
+            sum_x += other.x;
+            sum_y += other.y;
+            count += 1.0;
+        }
+        if count == 0.0 {
+            return (0.0, 0.0);
+        }
+        let separation = self.separation * SEPARATION;
+        let alignment = self.alignment * ALIGNMENT;
+        let cohesion = self.cohesion * COHESION;
+        (
+            separation * away_x + alignment * (sum_vx / count - vel.vx) + cohesion * (sum_x / count - pos.x),
+            separation * away_y + alignment * (sum_vy / count - vel.vy) + cohesion * (sum_y / count - pos.y),
+        )
+    }
+}
+
+/// The balls binned into square cells the size of the neighbor radius, so
+/// a ball's neighbors are all in the 3x3 cells around its own and a query
+/// doesn't look at the whole flock.
+struct Neighbors {
+    size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Neighbors {
+    fn new(flock: &[(Position, Velocity)], radius: f64) -> Neighbors {
+        let size = radius.max(1.0);
+        let mut cells: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for (i, (pos, _)) in flock.iter().enumerate() {
+            cells.entry(Neighbors::cell(size, pos.x, pos.y)).or_default().push(i);
+        }
+        Neighbors { size, cells }
+    }
+
+    fn cell(size: f64, x: f64, y: f64) -> (i64, i64) {
+        ((x / size).floor() as i64, (y / size).floor() as i64)
+    }
+
+    /// The balls in the cells around `(x, y)`, some of which may be further
+    /// than the radius away.
+    fn near(&self, x: f64, y: f64) -> impl Iterator<Item = usize> + '_ {
+        let (cx, cy) = Neighbors::cell(self.size, x, y);
+        (cx - 1..=cx + 1)
+            .flat_map(move |x| (cy - 1..=cy + 1).map(move |y| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
@@ -0,0 +1,198 @@
+//! Pool on the built-in `billiards` table: pot the fifteen object balls with
+//! the white cue ball. Shots are aimed with the arrow keys, or by dragging
+//! back from the cue ball with the mouse, and can only be taken once every
+//! ball has stopped. A potted cue ball comes back on its spot.
+
+use std::f64::consts::PI;
+
+use rust_tui_ball_bounce::components::Rgb;
+use rust_tui_ball_bounce::scenario::Scenario;
+use rust_tui_ball_bounce::{BallSpec, BallState, Snapshot};
+
+/// The cue ball's color on the table, which is how it's told apart.
+pub const CUE: Rgb = Rgb(0xff, 0xff, 0xff);
+// Cells per tick at full power, under a ball's diameter so a shot can't
+// pass through a ball between ticks
+const MAX_SPEED: f64 = 0.9;
+const AIM_STEP: f64 = PI / 36.0;
+const POWER_STEP: f64 = 0.1;
+const MIN_POWER: f64 = 0.1;
+// Cells dragged back from the cue ball for a full-power shot
+const FULL_DRAG: f64 = 20.0;
+
+pub struct Billiards {
+    // Where the next shot goes as drawn on screen, in radians clockwise
+    // from the right
+    pub angle: f64,
+    // Share of full power, from MIN_POWER to 1
+    pub power: f64,
+    pub potted: u32,
+    pub shots: u32,
+    // Set once the table is cleared
+    pub over: bool,
+    // Where a potted cue ball comes back
+    spot: (f64, f64),
+    // From taking a shot until the balls are seen moving, so a shot isn't
+    // taken twice from the same state
+    shot_pending: bool,
+    // From asking for a new cue ball until it shows up
+    respotting: bool,
+    pub dragging: bool,
+}
+
+impl Billiards {
+    /// A game on `table`, whose cue ball is the one colored [`CUE`].
+    pub fn new(table: &Scenario) -> Billiards {
+        let cue = table.balls.iter().find(|ball| ball.color == Some(CUE));
+        Billiards {
+            angle: 0.0,
+            power: 0.5,
+            potted: 0,
+            shots: 0,
+            over: false,
+            spot: cue.map_or((10.0, 10.0), |ball| (ball.x, ball.y)),
+            shot_pending: false,
+            respotting: false,
+            dragging: false,
+        }
+    }
+
+    /// Whether a shot can be taken: the cue ball is on the table and
+    /// nothing is moving.
+    pub fn ready(&self, state: &Snapshot) -> bool {
+        !self.shot_pending && cue(state).is_some() && at_rest(state)
+    }
+
+    /// Turns the aim by `steps` notches, clockwise for positive ones.
+    pub fn turn(&mut self, steps: f64) {
+        self.angle = (self.angle + steps * AIM_STEP).rem_euclid(2.0 * PI);
+    }
+
+    /// Changes the power by `steps` tenths.
+    pub fn change_power(&mut self, steps: f64) {
+        self.power = (self.power + steps * POWER_STEP).clamp(MIN_POWER, 1.0);
+    }
+
+    /// Aims like a cue drawn back from the cue ball to `pointer`, both in
+    /// terminal cells: the shot goes the other way, harder the further back.
+    pub fn drag_to(&mut self, pointer: (f64, f64), cue: (f64, f64)) {
+        let (dx, dy) = (cue.0 - pointer.0, cue.1 - pointer.1);
+        if (dx, dy) == (0.0, 0.0) {
+            return;
+        }
+        self.angle = dy.atan2(dx).rem_euclid(2.0 * PI);
+        self.power = (dx.hypot(dy) / FULL_DRAG).clamp(MIN_POWER, 1.0);
+    }
+
+    /// Takes a shot if one [can be](Self::ready), returning the cue ball's
+    /// id and the velocity to give it.
+    pub fn shoot(&mut self, state: &Snapshot) -> Option<(usize, f64, f64)> {
+        if !self.ready(state) {
+            return None;
+        }
+        let cue = cue(state)?;
+        let (vx, vy) = self.velocity(state.aspect);
+        self.shot_pending = true;
+        self.shots += 1;
+        Some((cue.id, vx, vy))
+    }
+
+    /// The cue ball's velocity for the current aim, in arena units, which
+    /// stretch rows by `aspect`.
+    fn velocity(&self, aspect: f64) -> (f64, f64) {
+        let (dx, dy) = (self.angle.cos(), self.angle.sin() * aspect);
+        let speed = self.power * MAX_SPEED / dx.hypot(dy);
+        (dx * speed, dy * speed)
+    }
+
+    /// Counts `ball` going into a pocket; the cue ball doesn't score.
+    pub fn pocketed(&mut self, ball: &BallState) {
+        if ball.color != Some(CUE) {
+            self.potted += 1;
+        }
+    }
+
+    /// Catches up with a new state, returning a cue ball to add if it was
+    /// potted and everything has stopped.
+    pub fn update(&mut self, state: &Snapshot) -> Option<BallSpec> {
+        let cue = cue(state);
+        if !at_rest(state) || cue.is_none() {
+            self.shot_pending = false;
+        }
+        if cue.is_some() {
+            self.respotting = false;
+        } else if at_rest(state) && !self.respotting {
+            self.respotting = true;
+            let (x, y) = self.spot;
+            return Some(BallSpec { color: Some(CUE), ..BallSpec::new(x, y, 0.0, 0.0) });
+        }
+        None
+    }
+
+    /// Whether every object ball has been potted.
+    pub fn cleared(&self, state: &Snapshot) -> bool {
+        self.potted > 0 && state.balls.iter().all(|ball| ball.color == Some(CUE))
+    }
+}
+
+pub fn cue(state: &Snapshot) -> Option<&BallState> {
+    state.balls.iter().find(|ball| ball.color == Some(CUE))
+}
+
+fn at_rest(state: &Snapshot) -> bool {
+    state.balls.iter().all(|ball| ball.vx == 0.0 && ball.vy == 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::scenario;
+    use rust_tui_ball_bounce::SimulationBuilder;
+
+    use super::*;
+
+    #[test]
+    fn a_shot_waits_for_the_table_to_stop() {
+        let table = scenario::builtin("billiards").unwrap();
+        let mut sim = SimulationBuilder::new().arena(80.0, 40.0).aspect(2.0).scenario(&table).build();
+        let mut game = Billiards::new(&table);
+        game.power = 1.0;
+
+        let (id, vx, vy) = game.shoot(&sim.snapshot()).unwrap();
+        assert!((vx - MAX_SPEED).abs() < 1e-9 && vy == 0.0);
+        assert!(game.shoot(&sim.snapshot()).is_none());
+        sim.push_ball(id, vx, vy);
+        sim.step(1.0);
+        assert!(game.update(&sim.snapshot()).is_none());
+        assert!(!game.ready(&sim.snapshot()));
+
+        let mut potted = 0;
+        while !at_rest(&sim.snapshot()) {
+            let state = sim.snapshot();
+            sim.step(1.0);
+            for event in sim.drain_events() {
+                if let rust_tui_ball_bounce::SimEvent::Pocketed { id } = event {
+                    game.pocketed(state.balls.iter().find(|ball| ball.id == id).unwrap());
+                    potted += 1;
+                }
+            }
+        }
+        assert_eq!(game.shots, 1);
+        assert!(game.potted <= potted);
+        let state = sim.snapshot();
+        match cue(&state) {
+            Some(_) => assert!(game.ready(&state)),
+            None => assert!(game.update(&state).is_some_and(|spec| spec.color == Some(CUE))),
+        }
+    }
+
+    #[test]
+    fn dragging_back_aims_the_other_way() {
+        let mut game = Billiards::new(&scenario::builtin("billiards").unwrap());
+        game.drag_to((0.0, 10.0), (10.0, 10.0));
+        assert!(game.angle.abs() < 1e-9);
+        assert_eq!(game.power, 0.5);
+        game.drag_to((10.0, 60.0), (10.0, 10.0));
+        assert!((game.angle - 1.5 * PI).abs() < 1e-9);
+        assert_eq!(game.power, 1.0);
+    }
+}
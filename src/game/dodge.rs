@@ -0,0 +1,347 @@
+//! Survival on the built-in `dodge` arena: steer the yellow ball with the
+//! arrow keys and keep it clear of the others for as long as possible. A new
+//! ball comes in from the walls every so often, sooner each time, and the
+//! first touch ends the run. Now and then a power-up shows up somewhere in
+//! the arena, and touching it gives the player a few seconds of its effect.
+
+use rust_tui_ball_bounce::components::Rgb;
+use rust_tui_ball_bounce::rng::Rng;
+use rust_tui_ball_bounce::{BallSpec, BallState, Snapshot};
+
+/// The player's color in the arena, which is how it's told apart.
+pub const PLAYER: Rgb = Rgb(0xff, 0xd7, 0x00);
+// Arena units per tick the player moves at
+const PLAYER_SPEED: f64 = 0.6;
+// Ticks until the first new ball, each later wait a share of the one before,
+// down to the shortest
+const FIRST_SPAWN: u64 = 240;
+const SPAWN_RAMP: f64 = 0.9;
+const SHORTEST_SPAWN: u64 = 45;
+const SPAWN_SPEED: (f64, f64) = (0.3, 0.7);
+// New balls don't come in nearer the player than this
+const SAFE_DISTANCE: f64 = 12.0;
+// Ticks survived before the first power-up, and between one going and the
+// next showing up
+const PICKUP_EVERY: u64 = 420;
+// Ticks a power-up waits to be picked up, and its effect lasts
+const PICKUP_LASTS: u64 = 360;
+const EFFECT_LASTS: u64 = 300;
+const PICKUP_RADIUS: f64 = 0.5;
+const SLOW_MOTION: f64 = 0.5;
+const SHRINK: f64 = 0.5;
+
+/// What touching a pickup does for a while.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerUp {
+    // Everything moves at half speed
+    SlowMotion,
+    // The player is half its size
+    Shrink,
+    // Balls that touch the player are knocked out instead
+    Shield,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 3] = [PowerUp::SlowMotion, PowerUp::Shrink, PowerUp::Shield];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUp::SlowMotion => "Slow motion",
+            PowerUp::Shrink => "Shrink",
+            PowerUp::Shield => "Shield",
+        }
+    }
+}
+
+/// A power-up waiting in the arena, in arena units.
+pub struct Pickup {
+    pub x: f64,
+    pub y: f64,
+    pub power_up: PowerUp,
+    // Ticks survived when it goes
+    until: u64,
+}
+
+/// What an [`update`](Dodge::update) asks of the arena.
+#[derive(Default)]
+pub struct Changes {
+    pub incoming: Option<BallSpec>,
+    pub started: Option<PowerUp>,
+    pub ended: Vec<PowerUp>,
+}
+
+pub struct Dodge {
+    rng: Rng,
+    // Set once the arena is loaded, so the state from before it isn't played
+    loaded: bool,
+    // Tick the run started on
+    start: Option<u64>,
+    pub survived: u64,
+    pub over: bool,
+    wait: u64,
+    next_spawn: u64,
+    pub pickup: Option<Pickup>,
+    // Ticks survived when the next pickup shows up
+    next_pickup: u64,
+    // Each effect going, with the ticks survived when it ends
+    effects: Vec<(PowerUp, u64)>,
+    // The speed and player size the run started with, for effects to change
+    // and put back
+    normal_speed: f64,
+    normal_radius: f64,
+}
+
+impl Dodge {
+    /// A run whose new balls come from `seed`.
+    pub fn new(seed: u64) -> Dodge {
+        Dodge {
+            rng: Rng::new(seed),
+            loaded: false,
+            start: None,
+            survived: 0,
+            over: false,
+            wait: FIRST_SPAWN,
+            next_spawn: 0,
+            pickup: None,
+            next_pickup: PICKUP_EVERY,
+            effects: Vec::new(),
+            normal_speed: 1.0,
+            normal_radius: 0.5,
+        }
+    }
+
+    pub fn loaded(&mut self) {
+        self.loaded = true;
+    }
+
+    /// The player's id and the velocity that sends it along `(dx, dy)`, or
+    /// stops it if that's `(0, 0)`; `None` once the run is over.
+    pub fn steer(&self, state: &Snapshot, (dx, dy): (f64, f64)) -> Option<(usize, f64, f64)> {
+        let player = player(state).filter(|_| !self.over)?;
+        Some((player.id, dx * PLAYER_SPEED, dy * PLAYER_SPEED))
+    }
+
+    /// The other ball, if balls `a` and `b` meeting catches the player: the
+    /// run is still going and one of them is the player. A
+    /// [shield](PowerUp::Shield) decides whether that ends it.
+    pub fn caught(&self, state: &Snapshot, a: usize, b: usize) -> Option<usize> {
+        let player = player(state).filter(|_| !self.over)?;
+        match player.id {
+            id if id == a => Some(b),
+            id if id == b => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn active(&self, power_up: PowerUp) -> bool {
+        self.effects.iter().any(|&(effect, _)| effect == power_up)
+    }
+
+    /// Each effect going, with the ticks it has left.
+    pub fn effects(&self) -> impl Iterator<Item = (PowerUp, u64)> + '_ {
+        self.effects.iter().map(|&(effect, end)| (effect, end.saturating_sub(self.survived)))
+    }
+
+    /// Ends every effect early, returning the ones that were going.
+    pub fn end_effects(&mut self) -> Vec<PowerUp> {
+        self.effects.drain(..).map(|(effect, _)| effect).collect()
+    }
+
+    /// The simulation speed the effects call for.
+    pub fn speed(&self) -> f64 {
+        if self.active(PowerUp::SlowMotion) {
+            self.normal_speed * SLOW_MOTION
+        } else {
+            self.normal_speed
+        }
+    }
+
+    /// The player's radius the effects call for.
+    pub fn player_radius(&self) -> f64 {
+        if self.active(PowerUp::Shrink) {
+            self.normal_radius * SHRINK
+        } else {
+            self.normal_radius
+        }
+    }
+
+    /// Catches up with a new state: brings in a ball if one is due, and
+    /// starts and ends power-ups.
+    pub fn update(&mut self, state: &Snapshot) -> Changes {
+        let mut changes = Changes::default();
+        if !self.loaded || self.over {
+            return changes;
+        }
+        let Some(player) = player(state) else {
+            return changes;
+        };
+        let start = *self.start.get_or_insert_with(|| {
+            self.next_spawn = state.tick_count + self.wait;
+            self.normal_speed = state.speed_multiplier;
+            self.normal_radius = player.radius;
+            state.tick_count
+        });
+        self.survived = state.tick_count.saturating_sub(start);
+        self.update_power_ups(player, &mut changes);
+        if state.tick_count >= self.next_spawn {
+            self.wait = ((self.wait as f64 * SPAWN_RAMP) as u64).max(SHORTEST_SPAWN);
+            self.next_spawn = state.tick_count + self.wait;
+            changes.incoming = Some(self.incoming(state, player));
+        }
+        if self.pickup.is_none() && self.survived >= self.next_pickup {
+            self.pickup = Some(self.place_pickup(state, player));
+        }
+        changes
+    }
+
+    fn update_power_ups(&mut self, player: &BallState, changes: &mut Changes) {
+        let survived = self.survived;
+        self.effects.retain(|&(effect, end)| {
+            let going = end > survived;
+            if !going {
+                changes.ended.push(effect);
+            }
+            going
+        });
+        let Some(pickup) = &self.pickup else {
+            return;
+        };
+        let touched = (pickup.x - player.x).hypot(pickup.y - player.y) <= player.radius + PICKUP_RADIUS;
+        if !touched && survived < pickup.until {
+            return;
+        }
+        let power_up = pickup.power_up;
+        self.pickup = None;
+        self.next_pickup = survived + PICKUP_EVERY;
+        if !touched {
+            return;
+        }
+        match self.effects.iter_mut().find(|(effect, _)| *effect == power_up) {
+            // Another of the same keeps it going
+            Some((_, end)) => *end = survived + EFFECT_LASTS,
+            None => {
+                self.effects.push((power_up, survived + EFFECT_LASTS));
+                changes.started = Some(power_up);
+            }
+        }
+    }
+
+    /// A random power-up somewhere in the arena, away from the walls and
+    /// not right on the player.
+    fn place_pickup(&mut self, state: &Snapshot, player: &BallState) -> Pickup {
+        let power_up = PowerUp::ALL[(self.rng.next_u64() % PowerUp::ALL.len() as u64) as usize];
+        let (width, height) = (state.area_width - 1.0, state.area_height - 1.0);
+        let (mut x, mut y) = (width / 2.0, height / 2.0);
+        for _ in 0..8 {
+            (x, y) = (self.rng.range(1.0, (width - 1.0).max(1.0)), self.rng.range(1.0, (height - 1.0).max(1.0)));
+            if (x - player.x).hypot(y - player.y) >= SAFE_DISTANCE / 2.0 {
+                break;
+            }
+        }
+        Pickup { x, y, power_up, until: self.survived + PICKUP_LASTS }
+    }
+
+    /// A ball on a random wall heading into the arena, away from the player
+    /// where the arena has room for it.
+    fn incoming(&mut self, state: &Snapshot, player: &BallState) -> BallSpec {
+        let (width, height) = (state.area_width - 1.0, state.area_height - 1.0);
+        let mut spec = BallSpec::new(0.0, 0.0, 0.0, 0.0);
+        for _ in 0..8 {
+            let speed = self.rng.range(SPAWN_SPEED.0, SPAWN_SPEED.1);
+            // Along the wall, and across it into the arena
+            let (along, across) = (self.rng.range(-0.7, 0.7) * speed, self.rng.range(0.5, 1.0) * speed);
+            spec = match self.rng.next_u64() % 4 {
+                0 => BallSpec::new(0.0, self.rng.range(0.0, height), across, along),
+                1 => BallSpec::new(width, self.rng.range(0.0, height), -across, along),
+                2 => BallSpec::new(self.rng.range(0.0, width), 0.0, along, across),
+                _ => BallSpec::new(self.rng.range(0.0, width), height, along, -across),
+            };
+            if (spec.x - player.x).hypot(spec.y - player.y) >= SAFE_DISTANCE {
+                break;
+            }
+        }
+        spec
+    }
+}
+
+pub fn player(state: &Snapshot) -> Option<&BallState> {
+    state.balls.iter().find(|ball| ball.color == Some(PLAYER))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::scenario;
+    use rust_tui_ball_bounce::{SimEvent, SimulationBuilder};
+
+    use super::*;
+
+    #[test]
+    fn balls_come_in_sooner_until_the_player_is_caught() {
+        // Just the player, so only new balls can catch it
+        let mut arena = scenario::builtin("dodge").unwrap();
+        arena.balls.truncate(1);
+        let mut sim = SimulationBuilder::new().arena(80.0, 24.0).scenario(&arena).build();
+        let mut dodge = Dodge::new(7);
+        assert!(dodge.update(&sim.snapshot()).incoming.is_none());
+        dodge.loaded();
+
+        let mut spawned = Vec::new();
+        while !dodge.over && sim.snapshot().tick_count < 20_000 {
+            let state = sim.snapshot();
+            if let Some(spec) = dodge.update(&state).incoming {
+                assert!((spec.x - player(&state).unwrap().x).hypot(spec.y - player(&state).unwrap().y) >= SAFE_DISTANCE);
+                spawned.push(state.tick_count);
+                sim.add_ball(spec);
+            }
+            sim.step(1.0);
+            for event in sim.drain_events() {
+                if let SimEvent::Collision { a, b, .. } = event {
+                    dodge.over |= dodge.caught(&state, a, b).is_some();
+                }
+            }
+        }
+        assert!(dodge.over, "never caught");
+        assert_eq!(spawned.first(), Some(&FIRST_SPAWN));
+        let waits: Vec<_> = spawned.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        assert!(waits.windows(2).all(|pair| pair[1] <= pair[0]), "{:?}", waits);
+        assert!(dodge.update(&sim.snapshot()).incoming.is_none());
+        assert!(dodge.steer(&sim.snapshot(), (1.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn power_ups_last_a_while_once_touched() {
+        let mut arena = scenario::builtin("dodge").unwrap();
+        arena.balls.truncate(1);
+        let mut sim = SimulationBuilder::new().arena(80.0, 24.0).scenario(&arena).build();
+        let mut dodge = Dodge::new(7);
+        dodge.loaded();
+        let radius = player(&sim.snapshot()).unwrap().radius;
+
+        // The first one is left to go, and the next put on the player
+        let mut started = None;
+        let mut ended = Vec::new();
+        for tick in 0..PICKUP_EVERY * 2 + PICKUP_LASTS + EFFECT_LASTS + 2 {
+            let state = sim.snapshot();
+            if tick == PICKUP_EVERY + 1 {
+                assert!(dodge.pickup.is_some());
+            }
+            if tick == PICKUP_EVERY * 2 + PICKUP_LASTS + 1 {
+                let pickup = dodge.pickup.as_mut().expect("a second pickup");
+                let player = player(&state).unwrap();
+                (pickup.x, pickup.y, pickup.power_up) = (player.x, player.y, PowerUp::Shrink);
+            }
+            let changes = dodge.update(&state);
+            started = started.or(changes.started);
+            ended.extend(changes.ended);
+            if started.is_some() && ended.is_empty() {
+                assert_eq!(dodge.player_radius(), radius * SHRINK);
+                assert_eq!(dodge.speed(), 1.0);
+            }
+            sim.step(1.0);
+        }
+        assert_eq!(started, Some(PowerUp::Shrink));
+        assert_eq!(ended, [PowerUp::Shrink]);
+        assert_eq!(dodge.player_radius(), radius);
+        assert_eq!(dodge.effects().count(), 0);
+    }
+}
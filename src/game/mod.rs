@@ -0,0 +1,409 @@
+//! Games played in the arena. A game loads the scenario it's played on,
+//! takes the keys and mouse input it needs before the keymap sees them, and
+//! keeps score from the simulation's events. Loading any other scenario, or
+//! another profile, ends it.
+
+mod billiards;
+mod dodge;
+mod targets;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use rust_tui_ball_bounce::scenario::{self, Scenario};
+use rust_tui_ball_bounce::{Command, SimEvent};
+
+use crate::app::App;
+use crate::scores::{self, Board, Scores};
+
+pub use billiards::Billiards;
+pub use dodge::{Dodge, PowerUp};
+pub use targets::{Targets, PLAYERS, WINNING_SCORE};
+
+pub const GAME_NAMES: [&str; 3] = ["billiards", "dodge", "targets"];
+
+pub enum Game {
+    Billiards(Billiards),
+    Dodge(Dodge),
+    Targets(Targets),
+}
+
+impl Game {
+    /// The game called `name` in [`GAME_NAMES`], ready to start.
+    pub fn by_name(name: &str) -> Option<Game> {
+        match name {
+            "billiards" => Some(Game::Billiards(Billiards::new(&table("billiards")))),
+            "dodge" => Some(Game::Dodge(Dodge::new(seed()))),
+            "targets" => Some(Game::Targets(Targets::new(seed(), 1))),
+            _ => None,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Game::Billiards(_) => "Billiards",
+            Game::Dodge(_) => "Dodge",
+            Game::Targets(_) => "Targets",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Game::Billiards(_) => {
+                "Pot the fifteen racked balls with the white cue ball. Aim with the arrow keys and shoot with Enter, or drag back from the cue ball with the mouse."
+            }
+            Game::Dodge(_) => {
+                "Steer the yellow ball with the arrow keys and keep clear of the others, as more keep coming in. Power-ups help for a few seconds; the first touch ends the run."
+            }
+            Game::Targets(_) => {
+                "Two players race to touch targets: orange steers with W/A/S/D and blue with the arrow keys. The first to five wins."
+            }
+        }
+    }
+
+    /// Whether the run is over: the table cleared, the player caught, or
+    /// the match won.
+    pub fn over(&self) -> bool {
+        match self {
+            Game::Billiards(billiards) => billiards.over,
+            Game::Dodge(dodge) => dodge.over,
+            Game::Targets(targets) => targets.winner.is_some(),
+        }
+    }
+
+    /// The game's high scores in `scores`, if it keeps any.
+    pub fn board<'a>(&self, scores: &'a Scores) -> Option<&'a Board> {
+        match self {
+            Game::Billiards(_) => Some(&scores.billiards),
+            Game::Dodge(_) => Some(&scores.dodge),
+            // Scored between the players instead
+            Game::Targets(_) => None,
+        }
+    }
+
+    /// A result as shown on the game's board.
+    pub fn score_text(&self, result: f64) -> String {
+        match self {
+            Game::Billiards(_) => format!("{:.0} shots", result),
+            Game::Dodge(_) => format!("{:.1}s", result),
+            Game::Targets(_) => format!("{:.0} targets", result),
+        }
+    }
+
+    /// The scenario the game is played on.
+    pub fn scenario(&self) -> Scenario {
+        match self {
+            Game::Billiards(_) => table("billiards"),
+            Game::Dodge(_) => table("dodge"),
+            Game::Targets(targets) => targets::level(targets.level),
+        }
+    }
+}
+
+fn table(name: &str) -> Scenario {
+    scenario::builtin(name).expect("games are played on built-in scenarios")
+}
+
+// A different run each time
+fn seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+}
+
+impl App {
+    /// Sets up the game's scenario and starts keeping score.
+    pub fn start_game(&mut self, game: Game) {
+        self.commands.push(Command::LoadScenario(Box::new(game.scenario())));
+        self.commands.push(Command::SetPaused(false));
+        self.notice = Some(format!("Playing {}", game.title()));
+        self.game = Some(game);
+        self.placed = None;
+    }
+
+    /// Counts the game's finished run toward its high scores, and saves them.
+    fn record_score(&mut self, result: f64) {
+        let (board, lower_wins) = match &self.game {
+            // Fewest shots wins
+            Some(Game::Billiards(_)) => (&mut self.scores.billiards, true),
+            Some(Game::Dodge(_)) => (&mut self.scores.dodge, false),
+            Some(Game::Targets(_)) | None => return,
+        };
+        self.placed = board.record(result, lower_wins);
+        if let Some(path) = &self.scores_path {
+            if let Err(err) = scores::save(path, &self.scores) {
+                self.notice = Some(format!("Scores not saved: {}", err));
+            }
+        }
+    }
+
+    /// Reacts to an event from the update that is about to replace
+    /// `self.state`, which still holds the balls as they were before it.
+    pub(crate) fn game_event(&mut self, event: &SimEvent) {
+        match (&mut self.game, event) {
+            (Some(game), SimEvent::ScenarioLoaded(name)) if *name != game.scenario().name => {
+                let title = game.title();
+                self.end_power_ups();
+                self.notice = Some(format!("Stopped playing {}", title));
+                self.game = None;
+            }
+            (Some(Game::Billiards(billiards)), &SimEvent::Pocketed { id }) => {
+                if let Some(ball) = self.state.balls.iter().find(|ball| ball.id == id) {
+                    billiards.pocketed(ball);
+                }
+            }
+            (Some(Game::Dodge(dodge)), SimEvent::ScenarioLoaded(_)) => dodge.loaded(),
+            (Some(Game::Targets(targets)), SimEvent::ScenarioLoaded(_)) => targets.loaded(),
+            (Some(Game::Dodge(dodge)), &SimEvent::Collision { a, b, .. }) => {
+                let Some(other) = dodge.caught(&self.state, a, b) else {
+                    return;
+                };
+                if dodge.active(PowerUp::Shield) {
+                    self.sim.edit(move |sim| {
+                        sim.remove_ball_by_id(other);
+                    });
+                    return;
+                }
+                dodge.over = true;
+                let seconds = dodge.survived as f64 * self.tick_rate.as_secs_f64();
+                self.end_power_ups();
+                self.commands.push(Command::SetPaused(true));
+                self.notice = Some(format!("Caught after {:.1}s; Enter to play again", seconds));
+                self.record_score(seconds);
+            }
+            _ => {}
+        }
+    }
+
+    /// Catches the game up with the latest state.
+    pub(crate) fn update_game(&mut self) {
+        match &mut self.game {
+            Some(Game::Billiards(billiards)) => {
+                if let Some(cue) = billiards.update(&self.state) {
+                    self.sim.edit(move |sim| {
+                        sim.add_ball(cue);
+                    });
+                }
+                if !billiards.over && billiards.cleared(&self.state) {
+                    billiards.over = true;
+                    let shots = billiards.shots as f64;
+                    self.notice = Some(format!("Cleared in {} shots", billiards.shots));
+                    self.record_score(shots);
+                }
+            }
+            Some(Game::Dodge(dodge)) => {
+                let changes = dodge.update(&self.state);
+                if let Some(ball) = changes.incoming {
+                    self.sim.edit(move |sim| {
+                        sim.add_ball(ball);
+                    });
+                }
+                if let Some(power_up) = changes.started {
+                    self.notice = Some(format!("Power-up: {}", power_up.name()));
+                }
+                for power_up in changes.started.into_iter().chain(changes.ended) {
+                    self.apply_power_up(power_up);
+                }
+            }
+            Some(Game::Targets(targets)) => {
+                let Some(scorer) = targets.update(&self.state) else {
+                    return;
+                };
+                let [orange, blue] = targets.scores;
+                let name = PLAYERS[scorer].1;
+                if targets.winner.is_some() {
+                    self.commands.push(Command::SetPaused(true));
+                    let (high, low, level) = (orange.max(blue), orange.min(blue), targets.level);
+                    self.notice = Some(format!("{} wins level {}, {}-{}; Enter for level {}", name, level, high, low, level + 1));
+                } else {
+                    self.notice = Some(format!("{} scores: {}-{}", name, orange, blue));
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Brings the arena in line with dodge's effects after `power_up` starts
+    /// or ends.
+    fn apply_power_up(&mut self, power_up: PowerUp) {
+        let Some(Game::Dodge(dodge)) = &self.game else {
+            return;
+        };
+        match power_up {
+            PowerUp::SlowMotion => self.commands.push(Command::SetSpeed(dodge.speed())),
+            PowerUp::Shrink => {
+                if let Some(player) = dodge::player(&self.state) {
+                    let (id, radius) = (player.id, dodge.player_radius());
+                    self.sim.edit(move |sim| {
+                        sim.resize_ball(id, radius);
+                    });
+                }
+            }
+            // Checked as balls meet the player
+            PowerUp::Shield => {}
+        }
+    }
+
+    /// Ends dodge's effects early, putting the arena back as it was.
+    fn end_power_ups(&mut self) {
+        let Some(Game::Dodge(dodge)) = &mut self.game else {
+            return;
+        };
+        for power_up in dodge.end_effects() {
+            self.apply_power_up(power_up);
+        }
+    }
+
+    /// Handles `code` if the game uses it, returning whether it did.
+    pub(crate) fn handle_game_key(&mut self, code: KeyCode) -> bool {
+        let billiards = match &mut self.game {
+            Some(Game::Billiards(billiards)) => billiards,
+            Some(Game::Dodge(_)) => return self.handle_dodge_key(code),
+            Some(Game::Targets(_)) => return self.handle_targets_key(code),
+            None => return false,
+        };
+        match code {
+            KeyCode::Left => billiards.turn(-1.0),
+            KeyCode::Right => billiards.turn(1.0),
+            KeyCode::Up => billiards.change_power(1.0),
+            KeyCode::Down => billiards.change_power(-1.0),
+            KeyCode::Enter => {
+                if let Some((id, vx, vy)) = billiards.shoot(&self.state) {
+                    self.sim.edit(move |sim| {
+                        sim.push_ball(id, vx, vy);
+                    });
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_dodge_key(&mut self, code: KeyCode) -> bool {
+        let Some(Game::Dodge(dodge)) = &self.game else {
+            return false;
+        };
+        let heading = match code {
+            KeyCode::Left => (-1.0, 0.0),
+            KeyCode::Right => (1.0, 0.0),
+            KeyCode::Up => (0.0, -1.0),
+            KeyCode::Down => (0.0, 1.0),
+            KeyCode::Enter if dodge.over => {
+                self.start_game(Game::by_name("dodge").expect("dodge is a game"));
+                return true;
+            }
+            KeyCode::Enter => (0.0, 0.0),
+            _ => return false,
+        };
+        if let Some((id, vx, vy)) = dodge.steer(&self.state, heading) {
+            self.steer(id, vx, vy);
+        }
+        true
+    }
+
+    /// Each player's keys steer their own ball, W/A/S/D for orange and the
+    /// arrows for blue.
+    fn handle_targets_key(&mut self, code: KeyCode) -> bool {
+        let Some(Game::Targets(targets)) = &self.game else {
+            return false;
+        };
+        let (player, heading) = match code {
+            KeyCode::Char('w' | 'W') => (0, (0.0, -1.0)),
+            KeyCode::Char('a' | 'A') => (0, (-1.0, 0.0)),
+            KeyCode::Char('s' | 'S') => (0, (0.0, 1.0)),
+            KeyCode::Char('d' | 'D') => (0, (1.0, 0.0)),
+            KeyCode::Up => (1, (0.0, -1.0)),
+            KeyCode::Left => (1, (-1.0, 0.0)),
+            KeyCode::Down => (1, (0.0, 1.0)),
+            KeyCode::Right => (1, (1.0, 0.0)),
+            KeyCode::Enter if targets.winner.is_some() => {
+                self.start_game(Game::Targets(Targets::new(seed(), targets.level + 1)));
+                return true;
+            }
+            _ => return false,
+        };
+        if let Some((id, vx, vy)) = targets.steer(&self.state, player, heading) {
+            self.steer(id, vx, vy);
+        }
+        true
+    }
+
+    /// Sets ball `id` moving at `(vx, vy)`. The change is worked out against
+    /// the simulation's own velocity rather than the last state seen here,
+    /// so keys pressed faster than updates arrive, like two players' at
+    /// once, don't add up.
+    fn steer(&mut self, id: usize, vx: f64, vy: f64) {
+        self.sim.edit(move |sim| {
+            if let Some(ball) = sim.state().iter().find(|ball| ball.id == id) {
+                let (dvx, dvy) = (vx - ball.vx, vy - ball.vy);
+                sim.push_ball(id, dvx, dvy);
+            }
+        });
+    }
+
+    /// Aims and shoots with the left button: press and drag back from the
+    /// cue ball, then let go. Only while no modal is open.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if !self.mode.is_base() {
+            return;
+        }
+        let Some(Game::Billiards(billiards)) = &mut self.game else {
+            return;
+        };
+        let area = self.arena_area;
+        let Some(cue) = billiards::cue(&self.state) else {
+            return;
+        };
+        let cue = (cue.x.round(), (cue.y / self.state.aspect).round());
+        let pointer = (mouse.column as f64 - area.x as f64, mouse.row as f64 - area.y as f64);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if area.contains((mouse.column, mouse.row).into()) => {
+                billiards.dragging = true;
+                billiards.drag_to(pointer, cue);
+            }
+            MouseEventKind::Drag(MouseButton::Left) if billiards.dragging => billiards.drag_to(pointer, cue),
+            MouseEventKind::Up(MouseButton::Left) if billiards.dragging => {
+                billiards.dragging = false;
+                if let Some((id, vx, vy)) = billiards.shoot(&self.state) {
+                    self.sim.edit(move |sim| {
+                        sim.push_ball(id, vx, vy);
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Cells along the aim from the cue ball, in arena cells, for drawing: a
+/// longer line for a harder shot. Empty unless a shot can be taken.
+pub fn aim_line(app: &App) -> Vec<(f64, f64)> {
+    let Some(Game::Billiards(billiards)) = &app.game else {
+        return Vec::new();
+    };
+    let Some(cue) = billiards::cue(&app.state).filter(|_| billiards.ready(&app.state)) else {
+        return Vec::new();
+    };
+    let (x, y) = (cue.x, cue.y / app.state.aspect);
+    let length = 2.0 + billiards.power * 10.0;
+    // Steps of about half a cell, so diagonals have no gaps
+    let steps = (length * 2.0) as usize;
+    (2..=steps)
+        .map(|step| {
+            let distance = step as f64 / 2.0;
+            (x + distance * billiards.angle.cos(), y + distance * billiards.angle.sin())
+        })
+        .collect()
+}
+
+/// Where dodge's waiting power-up or the next target is, in arena cells,
+/// and the glyph to draw it with.
+pub fn marker(app: &App) -> Option<((f64, f64), &'static str)> {
+    let ((x, y), glyph) = match &app.game {
+        Some(Game::Dodge(dodge)) => {
+            let pickup = dodge.pickup.as_ref()?;
+            ((pickup.x, pickup.y), if app.ascii { "+" } else { "✚" })
+        }
+        Some(Game::Targets(targets)) => (targets.target?, if app.ascii { "X" } else { "✕" }),
+        _ => return None,
+    };
+    Some(((x, y / app.state.aspect), glyph))
+}
@@ -0,0 +1,183 @@
+//! Two players on one keyboard: orange steers with W/A/S/D and blue with
+//! the arrow keys, racing to touch the target that shows up somewhere
+//! between them. The other balls just get in the way, and the first to five
+//! wins the level. Each level is a scenario file with its own walls, played
+//! with the other balls faster than the level before.
+
+use rust_tui_ball_bounce::components::Rgb;
+use rust_tui_ball_bounce::rng::Rng;
+use rust_tui_ball_bounce::scenario::Scenario;
+use rust_tui_ball_bounce::{BallState, Snapshot};
+
+/// Each player's color in the arena, which is how it's told apart, and name.
+pub const PLAYERS: [(Rgb, &str); 2] = [(Rgb(0xff, 0x87, 0x00), "Orange"), (Rgb(0x00, 0xaf, 0xff), "Blue")];
+pub const WINNING_SCORE: u32 = 5;
+// Arena units per tick the players move at
+const PLAYER_SPEED: f64 = 0.6;
+const TARGET_RADIUS: f64 = 0.5;
+// Places tried for a target, the one most nearly as far from both players
+// winning
+const TARGET_TRIES: usize = 32;
+// Each level's layout, the first being the built-in `targets` arena. Past
+// the last, they come round again.
+const LEVELS: [&str; 3] = [
+    include_str!("../../scenarios/targets.toml"),
+    include_str!("../../scenarios/levels/targets-2.toml"),
+    include_str!("../../scenarios/levels/targets-3.toml"),
+];
+// How much faster than on the first level the other balls move, per level
+const LEVEL_SPEEDUP: f64 = 0.25;
+
+pub struct Targets {
+    rng: Rng,
+    // Set once the arena is loaded, so the state from before it isn't played
+    loaded: bool,
+    // Where the target is, in arena units
+    pub target: Option<(f64, f64)>,
+    pub scores: [u32; 2],
+    pub winner: Option<usize>,
+    // Counting from 1
+    pub level: u32,
+}
+
+impl Targets {
+    /// A match on `level` whose targets come from `seed`.
+    pub fn new(seed: u64, level: u32) -> Targets {
+        Targets { rng: Rng::new(seed), loaded: false, target: None, scores: [0; 2], winner: None, level }
+    }
+
+    pub fn loaded(&mut self) {
+        self.loaded = true;
+    }
+
+    /// Player `n`'s id and the velocity that sends it along `(dx, dy)`;
+    /// `None` once the match is won.
+    pub fn steer(&self, state: &Snapshot, n: usize, (dx, dy): (f64, f64)) -> Option<(usize, f64, f64)> {
+        let player = player(state, n).filter(|_| self.winner.is_none())?;
+        Some((player.id, dx * PLAYER_SPEED, dy * PLAYER_SPEED))
+    }
+
+    /// Catches up with a new state, returning the player who just reached
+    /// the target, if one did.
+    pub fn update(&mut self, state: &Snapshot) -> Option<usize> {
+        if !self.loaded || self.winner.is_some() {
+            return None;
+        }
+        let players = [player(state, 0)?, player(state, 1)?];
+        let Some((x, y)) = self.target else {
+            self.target = Some(self.place_target(state, players));
+            return None;
+        };
+        let scorer = players
+            .iter()
+            .position(|player| (player.x - x).hypot(player.y - y) <= player.radius + TARGET_RADIUS)?;
+        self.scores[scorer] += 1;
+        self.target = None;
+        if self.scores[scorer] >= WINNING_SCORE {
+            self.winner = Some(scorer);
+        }
+        Some(scorer)
+    }
+
+    /// A place away from the walls and obstacles that neither player is
+    /// much nearer.
+    fn place_target(&mut self, state: &Snapshot, [a, b]: [&BallState; 2]) -> (f64, f64) {
+        let (width, height) = (state.area_width - 1.0, state.area_height - 1.0);
+        let unfairness = |(x, y): (f64, f64)| ((a.x - x).hypot(a.y - y) - (b.x - x).hypot(b.y - y)).abs();
+        // Obstacles cover half a unit either side of their cells
+        let margin = 0.5 + TARGET_RADIUS;
+        let blocked = |&(x, y): &(f64, f64)| {
+            state.obstacles.iter().any(|wall| {
+                (wall.x - margin..wall.x + wall.width - 1.0 + margin).contains(&x)
+                    && (wall.y - margin..wall.y + wall.height - 1.0 + margin).contains(&y)
+            })
+        };
+        (0..TARGET_TRIES)
+            .map(|_| (self.rng.range(1.0, (width - 1.0).max(1.0)), self.rng.range(1.0, (height - 1.0).max(1.0))))
+            .filter(|place| !blocked(place))
+            .min_by(|&p, &q| unfairness(p).total_cmp(&unfairness(q)))
+            .unwrap_or(((a.x + b.x) / 2.0, (a.y + b.y) / 2.0))
+    }
+}
+
+/// Level `n`'s arena, counting from 1: its layout, with every ball but the
+/// players sped up for the level.
+pub fn level(n: u32) -> Scenario {
+    let layout = LEVELS[(n.max(1) - 1) as usize % LEVELS.len()];
+    let mut arena = Scenario::parse(layout).expect("levels are valid scenarios");
+    let speedup = 1.0 + LEVEL_SPEEDUP * n.saturating_sub(1) as f64;
+    for ball in &mut arena.balls {
+        if !PLAYERS.iter().any(|&(color, _)| ball.color == Some(color)) {
+            (ball.vx, ball.vy) = (ball.vx * speedup, ball.vy * speedup);
+        }
+    }
+    arena
+}
+
+pub fn player(state: &Snapshot, n: usize) -> Option<&BallState> {
+    state.balls.iter().find(|ball| ball.color == Some(PLAYERS[n].0))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::scenario;
+    use rust_tui_ball_bounce::SimulationBuilder;
+
+    use super::*;
+
+    #[test]
+    fn touching_targets_scores_until_someone_wins() {
+        let arena = scenario::builtin("targets").unwrap();
+        let mut sim = SimulationBuilder::new().arena(80.0, 24.0).scenario(&arena).build();
+        let mut targets = Targets::new(3, 1);
+        targets.loaded();
+
+        for round in 0..WINNING_SCORE {
+            assert_eq!(targets.update(&sim.snapshot()), None);
+            let (x, y) = targets.target.expect("a target");
+            let state = sim.snapshot();
+            let (a, b) = (player(&state, 0).unwrap(), player(&state, 1).unwrap());
+            let gap = ((a.x - x).hypot(a.y - y) - (b.x - x).hypot(b.y - y)).abs();
+            assert!(gap < 10.0, "round {}: {} nearer one player", round, gap);
+
+            // Blue goes straight to it in one step, and stops there
+            sim.push_ball(b.id, x - b.x - b.vx, y - b.y - b.vy);
+            sim.step(1.0);
+            assert_eq!(targets.update(&sim.snapshot()), Some(1));
+            let b = *player(&sim.snapshot(), 1).unwrap();
+            sim.push_ball(b.id, -b.vx, -b.vy);
+        }
+        assert_eq!((targets.scores, targets.winner), ([0, WINNING_SCORE], Some(1)));
+        assert!(targets.steer(&sim.snapshot(), 0, (1.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn levels_speed_up_and_keep_targets_off_walls() {
+        let first = level(1);
+        assert_eq!(first.name, scenario::builtin("targets").unwrap().name);
+        let again = level(LEVELS.len() as u32 + 1);
+        assert_eq!(again.name, first.name);
+        for (ball, faster) in first.balls.iter().zip(&again.balls) {
+            let speedup = if ball.color.is_some() { 1.0 } else { 1.0 + LEVEL_SPEEDUP * LEVELS.len() as f64 };
+            assert_eq!((faster.vx, faster.vy), (ball.vx * speedup, ball.vy * speedup));
+        }
+
+        for n in 2..=LEVELS.len() as u32 {
+            let arena = level(n);
+            assert!(!arena.obstacles.is_empty(), "level {}", n);
+            let mut sim = SimulationBuilder::new().arena(60.0, 13.0).scenario(&arena).build();
+            let mut targets = Targets::new(n as u64, n);
+            targets.loaded();
+            for _ in 0..20 {
+                targets.update(&sim.snapshot());
+                let (x, y) = targets.target.take().expect("a target");
+                for wall in &arena.obstacles {
+                    let inside = (wall.x - 0.5..wall.x + wall.width - 0.5).contains(&x)
+                        && (wall.y - 0.5..wall.y + wall.height - 0.5).contains(&y);
+                    assert!(!inside, "level {}: target at ({}, {}) in {:?}", n, x, y, wall);
+                }
+                sim.step(1.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Collision statistics for a gas, beside kinetic theory's. While a scenario
+//! with `gas = true` runs, the controls panel shows the mean free path, the
+//! mean time between a ball's collisions and how often they come, measured
+//! from the collision events, next to what theory expects of hard disks.
+//!
+//! The theory is the two-dimensional one: a disk of diameter d hits any other
+//! whose center is within d of its path, so it sweeps a strip 2d wide, and
+//! with n disks per unit area moving at relative speeds √2 times their mean
+//! speed the mean free path is 1 / (2√2·n·d). Measured paths come out a
+//! little longer, as whole-tick steps miss some grazing contacts.
+
+use std::f64::consts::SQRT_2;
+
+use rust_tui_ball_bounce::{SimEvent, Snapshot};
+
+/// What's been seen since the gas started.
+#[derive(Debug, Default)]
+pub struct GasStats {
+    collisions: u64,
+    // Simulated time, in ticks at 1x
+    time: f64,
+    // The distance every ball has covered, added up
+    distance: f64,
+}
+
+/// A statistic as measured, once there's been a collision to measure it
+/// by, and as theory has it, if the gas has balls that move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stat {
+    pub measured: Option<f64>,
+    pub theory: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Readouts {
+    // In arena units
+    pub free_path: Stat,
+    // In ticks, and per tick, for one ball
+    pub between: Stat,
+    pub frequency: Stat,
+}
+
+impl GasStats {
+    /// Counts the collisions in `events` and how far the balls went between
+    /// `last` and `next`.
+    pub fn update(&mut self, last: &Snapshot, next: &Snapshot, events: &[SimEvent]) {
+        self.collisions += events.iter().filter(|event| matches!(event, SimEvent::Collision { .. })).count() as u64;
+        let dt = next.tick_count.saturating_sub(last.tick_count) as f64 * next.speed_multiplier;
+        self.time += dt;
+        self.distance += next.balls.iter().map(|ball| ball.vx.hypot(ball.vy)).sum::<f64>() * dt;
+    }
+
+    pub fn readouts(&self, state: &Snapshot) -> Readouts {
+        let balls = state.balls.len() as f64;
+        // Each collision ends a free path for both balls
+        let paths = 2.0 * self.collisions as f64;
+        let measured = |value: f64| (self.collisions > 0).then_some(value);
+        let between = measured(balls * self.time / paths);
+
+        let positive = |value: f64| (value.is_finite() && value > 0.0).then_some(value);
+        let mean_speed = state.balls.iter().map(|ball| ball.vx.hypot(ball.vy)).sum::<f64>() / balls;
+        let diameter = 2.0 * state.balls.iter().map(|ball| ball.radius).sum::<f64>() / balls;
+        let density = balls / (state.area_width * state.area_height);
+        let free_path = positive(1.0 / (2.0 * SQRT_2 * density * diameter));
+        let theory_between = free_path.and_then(|path| positive(path / mean_speed));
+
+        Readouts {
+            free_path: Stat { measured: measured(self.distance / paths), theory: free_path },
+            between: Stat { measured: between, theory: theory_between },
+            frequency: Stat { measured: between.map(f64::recip), theory: theory_between.map(f64::recip) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::BallState;
+
+    use super::*;
+
+    #[test]
+    fn measurements_follow_the_collisions() {
+        let ball = |id, vx| BallState { id, x: 0.0, y: 0.0, vx, vy: 0.0, radius: 0.5, mass: 1.0, color: None, glyph: None, body: None };
+        let state = |tick_count| Snapshot {
+            tick_count,
+            speed_multiplier: 1.0,
+            area_width: 10.0,
+            area_height: 10.0,
+            balls: vec![ball(0, 1.0), ball(1, -1.0)],
+            ..Snapshot::default()
+        };
+        let mut stats = GasStats::default();
+        let readouts = stats.readouts(&state(0));
+        assert_eq!(readouts.free_path.measured, None);
+        // Two disks of diameter 1 in 100 square units
+        assert!((readouts.free_path.theory.unwrap() - 25.0 / SQRT_2).abs() < 1e-9);
+        assert_eq!(readouts.between.theory, readouts.free_path.theory);
+
+        let collision = SimEvent::Collision { a: 0, b: 1, x: 0.0, y: 0.0, impulse: 1.0, before: [(1.0, 0.0); 2], after: [(0.0, 0.0); 2] };
+        stats.update(&state(0), &state(10), &[collision]);
+        let readouts = stats.readouts(&state(10));
+        assert_eq!(readouts.free_path.measured, Some(10.0));
+        assert_eq!(readouts.between.measured, Some(10.0));
+        assert_eq!(readouts.frequency.measured, Some(0.1));
+    }
+}
@@ -0,0 +1,221 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use crate::cli::Cli;
+use crate::clip::ClipWriter;
+use crate::export::{self, Recording};
+use crate::pipe::Pipe;
+use rust_tui_ball_bounce::{widget::BallArenaWidget, Simulation};
+
+/// Runs `--ticks` ticks without touching the terminal, optionally tracing
+/// every ball each tick, then reports summary statistics. With `--pipe` the
+/// balls go to stdout and the statistics only to `--output`.
+pub fn run(sim: &mut Simulation, cli: &Cli) -> Result<()> {
+    let ticks = cli.ticks.unwrap_or(0);
+    sim.paused = false;
+
+    let mut trace: Option<Box<dyn Write>> = match cli.trace.as_deref() {
+        Some(path) if path.as_os_str() == "-" => Some(Box::new(io::stdout().lock())),
+        Some(path) => Some(Box::new(BufWriter::new(create(path)?))),
+        None => None,
+    };
+    if let Some(out) = trace.as_mut() {
+        writeln!(out, "tick,ball,x,y,vx,vy")?;
+    }
+
+    let mut pipe = cli.pipe.then(|| Pipe::new(io::stdout().lock()));
+
+    for _ in 0..ticks {
+        sim.tick();
+        if let Some(pipe) = &mut pipe {
+            pipe.write(sim.tick_count, sim.state());
+        }
+        if let Some(out) = trace.as_mut() {
+            write_trace(sim, out)?;
+        }
+    }
+    if let Some(mut out) = trace {
+        out.flush()?;
+    }
+
+    match cli.output.as_deref() {
+        Some(path) => write_report(sim, &mut BufWriter::new(create(path)?)),
+        None if cli.pipe => Ok(()),
+        None => write_report(sim, &mut io::stdout().lock()),
+    }
+    .context("could not write the report")
+}
+
+/// Runs `ticks` ticks recording every one of them, then writes the
+/// histories to `path`.
+pub fn export(sim: &mut Simulation, ticks: u64, tick_rate: Duration, path: &Path) -> Result<()> {
+    sim.paused = false;
+    sim.max_history = ticks as usize;
+    for _ in 0..ticks {
+        sim.tick();
+    }
+
+    let snapshot = sim.snapshot();
+    let histories: Vec<_> = sim.histories().collect();
+    let recording = Recording {
+        snapshot: &snapshot,
+        ticks: sim.history_ticks(),
+        histories: histories.iter().map(|(id, history)| (*id, &**history)).collect(),
+        tick_rate,
+    };
+    export::save(path, &recording).with_context(|| format!("could not export to {}", path.display()))
+}
+
+/// Runs `ticks` ticks and writes the arena every `every` ticks as a frame of
+/// a GIF, timed to play back at the tick rate.
+pub fn clip(sim: &mut Simulation, ticks: u64, every: u64, tick_rate: Duration, path: &Path) -> Result<()> {
+    sim.paused = false;
+    let area = Rect::new(
+        0,
+        0,
+        sim.area_width.round() as u16,
+        (sim.area_height / sim.aspect).round() as u16,
+    );
+    let out = BufWriter::new(create(path)?);
+    let mut clip = ClipWriter::new(out, area.width, area.height, tick_rate * every as u32)?;
+    for tick in 0..=ticks {
+        if tick.is_multiple_of(every) {
+            let mut buffer = Buffer::empty(area);
+            BallArenaWidget::new(sim).render(area, &mut buffer);
+            clip.frame(&buffer)
+                .with_context(|| format!("could not write {}", path.display()))?;
+        }
+        if tick < ticks {
+            sim.tick();
+        }
+    }
+    Ok(())
+}
+
+fn create(path: &Path) -> Result<File> {
+    File::create(path).with_context(|| format!("could not create {}", path.display()))
+}
+
+/// One `--trace` row for each ball at the current tick, by ball id, so a
+/// ball keeps its number when others are removed.
+fn write_trace(sim: &Simulation, out: &mut impl Write) -> io::Result<()> {
+    for ball in sim.state() {
+        writeln!(
+            out,
+            "{},{},{:.6},{:.6},{:.6},{:.6}",
+            sim.tick_count,
+            ball.id,
+            ball.x,
+            ball.y,
+            ball.vx,
+            ball.vy
+        )?;
+    }
+    Ok(())
+}
+
+/// Plain `key: value` lines with fixed precision, so two runs can be diffed.
+fn write_report(sim: &Simulation, out: &mut impl Write) -> io::Result<()> {
+    let balls = sim.state();
+    let speeds: Vec<f64> = balls.iter().map(|b| b.vx.hypot(b.vy)).collect();
+    let kinetic: f64 = balls.iter().zip(&speeds).map(|(b, v)| 0.5 * b.mass * v * v).sum();
+    let px: f64 = balls.iter().map(|b| b.mass * b.vx).sum();
+    let py: f64 = balls.iter().map(|b| b.mass * b.vy).sum();
+    let mean_speed = if speeds.is_empty() {
+        0.0
+    } else {
+        speeds.iter().sum::<f64>() / speeds.len() as f64
+    };
+    let max_speed = speeds.iter().copied().fold(0.0, f64::max);
+
+    writeln!(out, "ticks: {}", sim.tick_count)?;
+    writeln!(out, "arena: {}x{}", sim.area_width, sim.area_height)?;
+    writeln!(out, "balls: {}", balls.len())?;
+    writeln!(out, "ball_collisions: {}", sim.collisions)?;
+    writeln!(out, "wall_bounces: {}", sim.wall_bounces)?;
+    if sim.count_hits {
+        writeln!(out, "clicks: {}", sim.clicks())?;
+    }
+    writeln!(out, "kinetic_energy: {:.6}", kinetic)?;
+    writeln!(out, "momentum: {:.6} {:.6}", px, py)?;
+    writeln!(out, "mean_speed: {:.6}", mean_speed)?;
+    writeln!(out, "max_speed: {:.6}", max_speed)?;
+    for ball in balls {
+        writeln!(
+            out,
+            "ball {}: x={:.6} y={:.6} vx={:.6} vy={:.6}",
+            ball.id,
+            ball.x,
+            ball.y,
+            ball.vx,
+            ball.vy
+        )?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::{scenario, SimulationBuilder};
+
+    use super::*;
+
+    #[test]
+    fn balls_keep_their_ids_when_others_are_removed() {
+        let mut sim = Simulation::new(40.0, 20.0);
+        for x in [5.0, 15.0, 25.0] {
+            sim.spawn(x, 5.0, 0.0, 0.0, 0.5);
+        }
+        let mut trace = Vec::new();
+        sim.tick();
+        write_trace(&sim, &mut trace).unwrap();
+        sim.remove_ball_by_id(0);
+        sim.tick();
+        write_trace(&sim, &mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        // The tick, ball and x of each row
+        let rows: Vec<(&str, &str, &str)> = trace
+            .lines()
+            .map(|row| {
+                let mut fields = row.split(',');
+                (fields.next().unwrap(), fields.next().unwrap(), fields.next().unwrap())
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("1", "0", "5.000000"),
+                ("1", "1", "15.000000"),
+                ("1", "2", "25.000000"),
+                ("2", "1", "15.000000"),
+                ("2", "2", "25.000000"),
+            ]
+        );
+
+        let mut report = Vec::new();
+        write_report(&sim, &mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("ball 1: x=15.000000") && report.contains("ball 2: x=25.000000") && !report.contains("ball 0:"));
+    }
+
+    #[test]
+    fn the_pi_demos_click_out_the_digits_of_pi() {
+        for (name, digits) in [("pi-1", 3), ("pi-100", 31), ("pi-10000", 314)] {
+            let pi = scenario::builtin(name).unwrap();
+            let mut sim = SimulationBuilder::new().scenario(&pi).arena(80.0, 20.0).build();
+            // Long after the heavy ball has come back off the far wall
+            for _ in 0..10_000 {
+                sim.tick();
+            }
+            assert!(sim.collisions + sim.wall_bounces > digits, "{}", name);
+            let mut report = Vec::new();
+            write_report(&sim, &mut report).unwrap();
+            assert!(String::from_utf8(report).unwrap().contains(&format!("\nclicks: {}\n", digits)), "{}", name);
+        }
+    }
+}
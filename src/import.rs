@@ -0,0 +1,130 @@
+//! Reads balls from files written by other tools, so initial conditions
+//! computed elsewhere can be dropped into a running simulation.
+//!
+//! CSV files start with a header naming their columns: `x` and `y` are
+//! required, and `vx`, `vy`, `radius`, `charge`, `ttl` and `color`
+//! (`#rrggbb`) are optional, in any order. An empty cell leaves that value
+//! out. JSON and MessagePack files hold an array of objects with the same
+//! fields. Balls are checked like a scenario file's, so values one would
+//! refuse, such as a mass of 0, are refused here too.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use rust_tui_ball_bounce::BallSpec;
+use serde_json::{Map, Number, Value};
+
+use crate::export::Format;
+
+/// The balls in the file at `path`, in the format its extension asks for.
+pub fn load(path: &Path) -> io::Result<Vec<BallSpec>> {
+    let file = BufReader::new(File::open(path)?);
+    let specs = match Format::from_path(path) {
+        Format::Csv => read_csv(file)?,
+        Format::Json => serde_json::from_reader(file).map_err(invalid)?,
+        Format::MessagePack => rmp_serde::from_read(file).map_err(invalid)?,
+        Format::Markdown | Format::Latex => return Err(invalid("reports can't be imported")),
+    };
+    for (i, spec) in specs.iter().enumerate() {
+        // Held to what a scenario file allows
+        spec.validate().map_err(|err| invalid(format!("ball {}: {}", i + 1, err)))?;
+    }
+    Ok(specs)
+}
+
+/// One ball per row after the header. Blank lines are skipped.
+pub fn read_csv(input: impl Read) -> io::Result<Vec<BallSpec>> {
+    let mut lines = BufReader::new(input).lines().enumerate();
+    let header = match lines.next() {
+        Some((_, line)) => line?,
+        None => return Ok(Vec::new()),
+    };
+    let columns: Vec<String> = header.split(',').map(|name| name.trim().to_string()).collect();
+    let mut specs = Vec::new();
+    for (i, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        if cells.len() != columns.len() {
+            return Err(invalid(format!(
+                "line {}: {} cells for {} columns",
+                i + 1,
+                cells.len(),
+                columns.len()
+            )));
+        }
+        // Through a JSON object, so the columns are checked like JSON fields
+        let row: Map<String, Value> = columns
+            .iter()
+            .zip(cells)
+            .filter(|(_, cell)| !cell.is_empty())
+            .map(|(column, cell)| (column.clone(), cell_value(cell)))
+            .collect();
+        let spec = serde_json::from_value(Value::Object(row))
+            .map_err(|err| invalid(format!("line {}: {}", i + 1, err)))?;
+        specs.push(spec);
+    }
+    Ok(specs)
+}
+
+/// Whole numbers stay whole, so they can fill integer fields like `ttl`.
+fn cell_value(cell: &str) -> Value {
+    if let Ok(n) = cell.parse::<u64>() {
+        return Value::from(n);
+    }
+    match cell.parse::<f64>().ok().and_then(Number::from_f64) {
+        Some(n) => Value::Number(n),
+        None => Value::String(cell.to_string()),
+    }
+}
+
+fn invalid(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::components::Rgb;
+
+    use super::*;
+
+    #[test]
+    fn csv_columns_come_in_any_order() {
+        let csv = "y, x, radius, color, ttl\n3, 2.5, 1, #ff8800, 60\n\n1,2,,,\n";
+        let specs = read_csv(csv.as_bytes()).unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!((specs[0].x, specs[0].y, specs[0].vx), (2.5, 3.0, 0.0));
+        assert_eq!(specs[0].radius, Some(1.0));
+        assert_eq!(specs[0].color, Some(Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(specs[0].ttl, Some(60));
+        assert_eq!((specs[1].radius, specs[1].color), (None, None));
+    }
+
+    #[test]
+    fn bad_rows_name_their_line() {
+        let err = read_csv("x,y,colour\n1,2,#ffffff\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("line 2: unknown field `colour`"), "{}", err);
+        let err = read_csv("x,y\n1,2,3\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: 3 cells for 2 columns");
+    }
+
+    #[test]
+    fn balls_a_scenario_would_refuse_are_refused() {
+        let path = std::env::temp_dir().join(format!("ball-bounce-import-{}.json", std::process::id()));
+        let load_json = |json: &str| {
+            std::fs::write(&path, json).unwrap();
+            load(&path).map(|specs| specs.len()).map_err(|err| err.to_string())
+        };
+        assert_eq!(load_json(r#"[{"x": 1, "y": 2, "mass": 2.5}]"#), Ok(1));
+        assert_eq!(load_json(r#"[{"x": 1, "y": 2}, {"x": 1, "y": 2, "mass": 0}]"#), Err("ball 2: mass 0 must be more than 0".to_string()));
+        assert_eq!(load_json(r#"[{"x": 1, "y": 2, "mass": -1}]"#), Err("ball 1: mass -1 must be more than 0".to_string()));
+        let err = load_json(r#"[{"x": 1, "y": 2, "growth": {"rate": -0.1, "min": 0}}]"#).unwrap_err();
+        assert!(err.starts_with("ball 1: growth of -0.1 between 0 and 5"), "{}", err);
+        let err = load_json(r#"[{"x": 1, "y": 2, "spin": 5}]"#).unwrap_err();
+        assert!(err.contains("needs a shape"), "{}", err);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,639 @@
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use tracing::{debug, info};
+use rust_tui_ball_bounce::scenario::{self, Source};
+use rust_tui_ball_bounce::Command;
+
+use crate::app::App;
+use crate::compare::{self, Change, Comparison};
+use crate::game::Game;
+use crate::keys::Action;
+use crate::layout::{LayoutEdit, Pane, PaneLayout};
+use crate::mode::{self, Flock, MenuEntry, Mode, ObstacleEdit, Palette, Restyle, Setting, ThemePicker};
+use crate::palette::{self, PaletteCommand};
+use crate::picker::Picker;
+use crate::replay::{self, Replay};
+use crate::sim_thread::Build;
+use crate::sweep::{Plan, Sweep};
+use crate::theme::THEMES;
+use crate::units::Quantity;
+
+/// Hands a key press to the current mode, which picks the mode to switch
+/// to. Simulation changes are queued on `app.commands` rather than applied
+/// here. Returns `true` when the key asks to quit.
+pub fn handle_key(app: &mut App, code: KeyCode) -> bool {
+    debug!(?code, "key");
+    let next = match mem::replace(&mut app.mode, Mode::Running) {
+        Mode::Splash(splash) => Some(*splash.then),
+        Mode::Running => app.handle_main_key(code, false),
+        Mode::Paused => app.handle_main_key(code, true),
+        Mode::Help | Mode::Sweep => Some(app.base_mode()),
+        Mode::Menu(picker) => Some(app.handle_menu_key(picker, code)),
+        Mode::CommandPalette(palette) => app.handle_palette_key(palette, code),
+        Mode::Settings(picker) => Some(app.handle_settings_key(picker, code)),
+        Mode::ScenarioPicker(picker) => Some(app.handle_scenario_key(picker, code)),
+        Mode::ProfilePicker(picker) => Some(app.handle_profile_key(picker, code)),
+        Mode::ThemePicker(picker) => Some(app.handle_theme_key(picker, code)),
+        Mode::ObstacleEdit(edit) => Some(app.handle_obstacle_key(edit, code)),
+        Mode::LayoutEdit(edit) => Some(app.handle_layout_key(edit, code)),
+        Mode::Restyle(restyle) => Some(app.handle_restyle_key(restyle, code)),
+        Mode::Flock(flock) => Some(app.handle_flock_key(flock, code)),
+        Mode::Replay(replay) => Some(app.handle_replay_key(replay, code)),
+    };
+    match next {
+        Some(mode) => {
+            app.mode = mode;
+            false
+        }
+        None => true,
+    }
+}
+
+/// Clicks and drags along a replay's timeline seek; otherwise the mouse is
+/// the game's.
+pub fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    let Mode::Replay(replay) = &mut app.mode else {
+        return app.handle_mouse(mouse);
+    };
+    let Some(timeline) = replay::timeline_area(app.arena_area) else {
+        return;
+    };
+    let track = replay::track_area(timeline);
+    let seek = match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => mouse.row == track.y,
+        MouseEventKind::Drag(MouseButton::Left) => (timeline.top()..timeline.bottom()).contains(&mouse.row),
+        _ => false,
+    };
+    if seek && (timeline.left()..timeline.right()).contains(&mouse.column) {
+        let along = mouse.column.saturating_sub(track.x) as f64 / track.width.saturating_sub(1).max(1) as f64;
+        replay.seek(along);
+    }
+}
+
+/// Runs a line from the control socket and returns the reply, or `None` if
+/// it asks to quit. Takes the palette's commands, except those that open a
+/// screen, plus `dump state` for the latest state as JSON.
+pub fn handle_control(app: &mut App, line: &str) -> Option<String> {
+    info!(line = line.trim(), "control");
+    if line.split_whitespace().eq(["dump", "state"]) {
+        return Some(match serde_json::to_string(&app.state) {
+            Ok(json) => json,
+            Err(err) => format!("error: {}", err),
+        });
+    }
+    let command = match palette::parse(line) {
+        Ok(command) => command,
+        Err(err) => return Some(format!("error: {}", err)),
+    };
+    match command {
+        PaletteCommand::Layout
+        | PaletteCommand::Walls
+        | PaletteCommand::Menu
+        | PaletteCommand::Sweep(_)
+        | PaletteCommand::Replay(_)
+        | PaletteCommand::Settings
+        | PaletteCommand::Help => {
+            Some("error: only available in the app".to_string())
+        }
+        PaletteCommand::Scenario(name) => Some(match app.find_scenario(&name) {
+            Ok(scenario) => {
+                app.load_scenario(scenario);
+                "ok".to_string()
+            }
+            Err(err) => format!("error: {}", err),
+        }),
+        PaletteCommand::Export(path) => Some(match app.export(path.map(PathBuf::from)) {
+            Ok(path) => format!("ok {}", path.display()),
+            Err(err) => format!("error: {}", err),
+        }),
+        PaletteCommand::Log(level) => Some(match app.set_log_level(level) {
+            Ok(()) => "ok".to_string(),
+            Err(err) => format!("error: {}", err),
+        }),
+        PaletteCommand::Import(path) => Some(match app.import(Path::new(&path)) {
+            Ok(count) => format!("ok {}", count),
+            Err(err) => format!("error: {}", err),
+        }),
+        PaletteCommand::Eval(code) => Some(match app.eval(&code) {
+            Ok(Some(value)) => format!("ok {}", value),
+            Ok(None) => "ok".to_string(),
+            Err(err) => format!("error: {}", err),
+        }),
+        // The mode it returns is for the palette; a client leaves it alone
+        command => app.run_palette_command(command).map(|_| "ok".to_string()),
+    }
+}
+
+impl App {
+    /// Tab and Shift+Tab move the focus, Esc lets go of it, and the arrow
+    /// keys act on the focused pane: picking a ball in the controls panel's
+    /// list, or in a chart also moving the crosshair. The arena leaves them
+    /// to the simulation and games. Returns whether `code` was used.
+    fn handle_focus_key(&mut self, code: KeyCode) -> bool {
+        match self.keymap.action(code) {
+            Some(Action::FocusNext) => {
+                self.move_focus(1);
+                return true;
+            }
+            Some(Action::FocusPrevious) => {
+                self.move_focus(-1);
+                return true;
+            }
+            _ => {}
+        }
+        let Some(pane) = self.focus else {
+            return false;
+        };
+        let last = self.state.balls.len().saturating_sub(1);
+        match (pane, code) {
+            (_, KeyCode::Esc) => self.focus = None,
+            (Pane::Arena | Pane::Quiver, _) => return false,
+            (_, KeyCode::Up) => self.ball_cursor = self.ball_cursor.min(last).saturating_sub(1),
+            (_, KeyCode::Down) => self.ball_cursor = (self.ball_cursor + 1).min(last),
+            (Pane::Status, _) => return false,
+            (_, KeyCode::Left | KeyCode::Right) => {
+                let span = match (self.history_ticks.first(), self.history_ticks.last()) {
+                    (Some(first), Some(last)) => last - first,
+                    _ => 0.0,
+                };
+                let step = (span / 50.0).max(1.0);
+                let back = if code == KeyCode::Left { step } else { -step };
+                self.crosshair = (self.crosshair + back).clamp(0.0, span);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Focuses the pane `by` places on in the layout, passing through no
+    /// focus at either end. The compact layout has no controls panel, and
+    /// shows whichever chart is focused.
+    fn move_focus(&mut self, by: isize) {
+        let panes: Vec<Pane> = self.layout.panes.into_iter().filter(|&pane| !(self.compact && pane == Pane::Status)).collect();
+        let at = self.focus.and_then(|pane| panes.iter().position(|&p| p == pane)).unwrap_or(panes.len());
+        let next = (at as isize + by).rem_euclid(panes.len() as isize + 1) as usize;
+        self.focus = panes.get(next).copied();
+    }
+
+    /// Running and Paused share bindings; Step only works while paused. A
+    /// game gets first pick of the keys, after Enter on the controls panel,
+    /// which restyles the ball picked in its list.
+    fn handle_main_key(&mut self, code: KeyCode, paused: bool) -> Option<Mode> {
+        if let (Some(Pane::Status), KeyCode::Enter, Some(ball)) = (self.focus, code, self.picked_ball()) {
+            return Some(Mode::Restyle(Restyle::new(ball.id, ball.color, ball.glyph)));
+        }
+        if self.handle_focus_key(code) || self.handle_game_key(code) {
+            return Some(self.base_mode());
+        }
+        match self.keymap.action(code) {
+            Some(Action::Quit) => return None,
+            Some(Action::Pause) => self.commands.push(Command::TogglePause),
+            Some(Action::AddBall) => self.commands.push(Command::AddBall),
+            Some(Action::RemoveBall) => self.commands.push(Command::RemoveBall),
+            Some(Action::SpeedUp) => self.commands.push(Command::SpeedUp),
+            Some(Action::SpeedDown) => self.commands.push(Command::SpeedDown),
+            Some(Action::Step) if paused => self.commands.push(Command::Step),
+            Some(Action::EditLayout) => return Some(self.layout_edit()),
+            Some(Action::Scenarios) => return Some(self.scenario_picker()),
+            Some(Action::Profiles) => return Some(self.profile_picker()),
+            Some(Action::Themes) => return Some(self.theme_picker()),
+            Some(Action::Flock) => return Some(Mode::Flock(Flock::new(self.state.boids))),
+            Some(Action::Menu) => return Some(self.menu()),
+            Some(Action::Help) => return Some(Mode::Help),
+            Some(Action::Palette) => return Some(Mode::CommandPalette(Palette::default())),
+            Some(Action::Settings) => return Some(Mode::Settings(Picker::new(Setting::ALL.to_vec()))),
+            Some(Action::EditObstacles) => return Some(self.obstacle_edit()),
+            Some(Action::Export) => self.export_with_notice(None),
+            Some(Action::Profiler) => self.show_profiler = !self.show_profiler,
+            Some(Action::Field) => self.toggle_field(),
+            Some(Action::Paint) => self.toggle_paint(),
+            Some(Action::ClearPaint) => self.clear_paint(),
+            Some(Action::TurnGravity) => self.turn_gravity(self.state.gravity_direction.next()),
+            Some(Action::Zoom) => self.show_zoom = !self.show_zoom,
+            Some(Action::Described) => {
+                self.described = !self.described;
+                self.description.clear();
+            }
+            // Handled above, before the game
+            Some(Action::FocusNext | Action::FocusPrevious) => {}
+            Some(Action::Step) | None => {}
+        }
+        Some(self.base_mode())
+    }
+
+    fn handle_palette_key(&mut self, mut palette: Palette, code: KeyCode) -> Option<Mode> {
+        match code {
+            KeyCode::Esc => return Some(self.base_mode()),
+            KeyCode::Enter => match palette::parse(&palette.input) {
+                Ok(command) => {
+                    info!(input = palette.input, "palette");
+                    return self.run_palette_command(command);
+                }
+                Err(err) => palette.error = Some(err),
+            },
+            KeyCode::Tab => {
+                if let Some(completed) = palette::complete(&palette.input) {
+                    palette.input = completed;
+                }
+            }
+            KeyCode::Backspace if palette.input.is_empty() => return Some(self.base_mode()),
+            KeyCode::Backspace => {
+                palette.input.pop();
+                palette.error = None;
+            }
+            KeyCode::Char(c) => {
+                palette.input.push(c);
+                palette.error = None;
+            }
+            _ => {}
+        }
+        Some(Mode::CommandPalette(palette))
+    }
+
+    fn run_palette_command(&mut self, command: PaletteCommand) -> Option<Mode> {
+        match command {
+            PaletteCommand::Pause => self.commands.push(Command::SetPaused(true)),
+            PaletteCommand::Resume => self.commands.push(Command::SetPaused(false)),
+            PaletteCommand::Step(n) => self.commands.extend((0..n).map(|_| Command::Step)),
+            PaletteCommand::Add(n) => self.commands.extend((0..n).map(|_| Command::AddBall)),
+            PaletteCommand::Remove(n) => self.commands.extend((0..n).map(|_| Command::RemoveBall)),
+            PaletteCommand::Speed(speed) => self.commands.push(Command::SetSpeed(speed)),
+            PaletteCommand::Gravity(gravity) => {
+                // In m/s² when readouts are in meters
+                let gravity = self.units.map_or(gravity, |units| units.to_sim(Quantity::Acceleration, gravity));
+                self.commands.push(Command::SetGravity(gravity));
+            }
+            PaletteCommand::GravityDirection(direction) => self.turn_gravity(direction),
+            PaletteCommand::Clear => self.commands.push(Command::Reset),
+            PaletteCommand::Scenario(name) => match self.find_scenario(&name) {
+                Ok(scenario) => self.load_scenario(scenario),
+                Err(err) => self.notice = Some(err.to_string()),
+            },
+            PaletteCommand::Profile(name) => self.switch_profile(name),
+            PaletteCommand::Game(Some(name)) => {
+                if let Some(game) = Game::by_name(&name) {
+                    self.start_game(game);
+                }
+            }
+            PaletteCommand::Game(None) => {
+                self.game = None;
+                self.notice = None;
+            }
+            PaletteCommand::Layout => return Some(self.layout_edit()),
+            PaletteCommand::Walls => return Some(self.obstacle_edit()),
+            PaletteCommand::Menu => return Some(self.menu()),
+            PaletteCommand::Export(path) => self.export_with_notice(path.map(PathBuf::from)),
+            PaletteCommand::Log(level) => {
+                self.notice = Some(match self.set_log_level(level) {
+                    Ok(()) => format!("Logging at {}", level),
+                    Err(err) => err,
+                });
+            }
+            PaletteCommand::Import(path) => {
+                self.notice = Some(match self.import(Path::new(&path)) {
+                    Ok(count) => format!("Imported {} balls from {}", count, path),
+                    Err(err) => format!("Import failed: {}", err),
+                });
+            }
+            PaletteCommand::Map(path) => {
+                self.notice = Some(match self.load_map(Path::new(&path)) {
+                    Ok(cells) => format!("Loaded {} wall cells from {}", cells, path),
+                    Err(err) => format!("Map failed: {}", err),
+                });
+            }
+            PaletteCommand::Paint(on) => {
+                if on != Some(self.paint.is_some()) {
+                    self.toggle_paint();
+                }
+            }
+            PaletteCommand::ClearPaint => self.clear_paint(),
+            PaletteCommand::ExportPaint(path) => {
+                self.notice = Some(match self.export_paint(path.map(PathBuf::from)) {
+                    Ok(path) => format!("Exported {}", path.display()),
+                    Err(err) => format!("Export failed: {}", err),
+                });
+            }
+            PaletteCommand::Replay(path) => match Replay::load(Path::new(&path)) {
+                Ok(replay) => return Some(self.replay(replay)),
+                Err(err) => self.notice = Some(format!("Replay failed: {}", err)),
+            },
+            PaletteCommand::Eval(code) => match self.eval(&code) {
+                Ok(Some(value)) => self.notice = Some(format!("= {}", value)),
+                Ok(None) => {}
+                Err(err) => self.notice = Some(format!("eval: {}", err)),
+            },
+            PaletteCommand::Twin(epsilon) => self.set_twin(epsilon),
+            PaletteCommand::Sweep(Some(plan)) => {
+                // In SI units when readouts are
+                let to_sim = |value| match (self.units, plan.parameter.quantity()) {
+                    (Some(units), Some(quantity)) => units.to_sim(quantity, value),
+                    _ => value,
+                };
+                let plan = Plan { from: to_sim(plan.from), to: to_sim(plan.to), ..plan };
+                self.sweep = Some(Sweep::start(&self.sim, plan));
+                return Some(Mode::Sweep);
+            }
+            PaletteCommand::Sweep(None) if self.sweep.is_some() => return Some(Mode::Sweep),
+            PaletteCommand::Sweep(None) => self.notice = Some("No sweep yet".to_string()),
+            PaletteCommand::Compare(Some((change, metric))) => self.compare(change, metric),
+            PaletteCommand::Compare(None) => {
+                self.comparison = None;
+                self.sim.compare(None);
+            }
+            PaletteCommand::Settings => return Some(Mode::Settings(Picker::new(Setting::ALL.to_vec()))),
+            PaletteCommand::Flock => return Some(Mode::Flock(Flock::new(self.state.boids))),
+            PaletteCommand::Help => return Some(Mode::Help),
+            PaletteCommand::Quit => return None,
+        }
+        Some(self.base_mode())
+    }
+
+    fn export_with_notice(&mut self, path: Option<PathBuf>) {
+        self.notice = Some(match self.export(path) {
+            Ok(path) => format!("Exported {}", path.display()),
+            Err(err) => format!("Export failed: {}", err),
+        });
+    }
+
+    fn eval(&mut self, code: &str) -> Result<Option<String>, String> {
+        #[cfg(feature = "scripting")]
+        return self.eval_script(code);
+        #[cfg(not(feature = "scripting"))]
+        {
+            let _ = code;
+            Err("built without the scripting feature".to_string())
+        }
+    }
+
+    /// A scenario from the user's scenario directory by file stem, or else a
+    /// built-in by name or a file by path.
+    fn find_scenario(&self, name: &str) -> Result<scenario::Scenario, scenario::ScenarioError> {
+        let (entries, _) = scenario::discover(self.scenario_dir().as_deref());
+        let found = entries.into_iter().find(|entry| match &entry.source {
+            Source::File(path) => path.file_stem().is_some_and(|stem| stem == name),
+            Source::Builtin(_) => false,
+        });
+        match found {
+            Some(entry) => Ok(entry.scenario),
+            None => scenario::resolve(name),
+        }
+    }
+
+    /// Starts comparing with a simulation like this one but for `change`.
+    fn compare(&mut self, change: Change, metric: compare::Metric) {
+        let build: Build = match &change {
+            &Change::Set(parameter, value) => {
+                // In SI units when readouts are
+                let value = match (self.units, parameter.quantity()) {
+                    (Some(units), Some(quantity)) => units.to_sim(quantity, value),
+                    _ => value,
+                };
+                Box::new(move |sim| compare::with_parameter(sim, parameter, value))
+            }
+            Change::Scenario(name) => match self.find_scenario(name) {
+                Ok(scenario) => Box::new(move |sim| compare::with_scenario(sim, scenario)),
+                Err(err) => {
+                    self.notice = Some(err.to_string());
+                    return;
+                }
+            },
+        };
+        // So commands already queued reach the other simulation too
+        self.run_commands();
+        self.comparison = Some(Comparison::new(change, metric));
+        self.sim.compare(Some(build));
+    }
+
+    /// Loads `scenario`, with its description over the arena for a start.
+    fn load_scenario(&mut self, scenario: scenario::Scenario) {
+        self.caption = (!scenario.description.is_empty()).then(|| (scenario.name.clone(), scenario.description.clone()));
+        self.commands.push(Command::LoadScenario(Box::new(scenario)));
+    }
+
+    fn handle_settings_key(&mut self, mut picker: Picker<Setting>, code: KeyCode) -> Mode {
+        let step = match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                picker.move_selection(-1);
+                0
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                picker.move_selection(1);
+                0
+            }
+            KeyCode::Left | KeyCode::Char('h') => -1,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter | KeyCode::Char(' ') => 1,
+            KeyCode::Esc | KeyCode::Char('q') => return self.base_mode(),
+            _ => 0,
+        };
+        if step != 0 {
+            match picker.current() {
+                Some(Setting::Theme) => {
+                    let current = THEMES.iter().position(|t| t.name == self.theme.name).unwrap_or(0);
+                    let next = (current as isize + step).rem_euclid(THEMES.len() as isize) as usize;
+                    self.set_theme(THEMES[next]);
+                }
+                Some(Setting::Background) => self.backdrop = self.backdrop.cycle(step),
+                Some(Setting::Ascii) => self.ascii = !self.ascii,
+                Some(Setting::Color) => self.color = !self.color,
+                None => {}
+            }
+        }
+        Mode::Settings(picker)
+    }
+
+    fn handle_restyle_key(&mut self, mut restyle: Restyle, code: KeyCode) -> Mode {
+        let step = match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                restyle.rows.move_selection(-1);
+                0
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                restyle.rows.move_selection(1);
+                0
+            }
+            KeyCode::Left | KeyCode::Char('h') => -1,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => 1,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => return self.base_mode(),
+            _ => 0,
+        };
+        if step != 0 {
+            restyle.cycle(step);
+            let Restyle { id, color, glyph, .. } = restyle;
+            self.sim.edit(move |sim| {
+                sim.restyle_ball(id, color, glyph);
+            });
+        }
+        Mode::Restyle(restyle)
+    }
+
+    fn handle_flock_key(&mut self, mut flock: Flock, code: KeyCode) -> Mode {
+        let step = match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                flock.rows.move_selection(-1);
+                0
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                flock.rows.move_selection(1);
+                0
+            }
+            KeyCode::Left | KeyCode::Char('h') => -1,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter | KeyCode::Char(' ') => 1,
+            KeyCode::Esc | KeyCode::Char('q') => return self.base_mode(),
+            _ => 0,
+        };
+        if step != 0 {
+            flock.cycle(step);
+            self.commands.push(Command::SetBoids(flock.boids()));
+        }
+        Mode::Flock(flock)
+    }
+
+    fn handle_scenario_key(&mut self, mut picker: Picker<scenario::Entry>, code: KeyCode) -> Mode {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => picker.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => picker.move_selection(1),
+            KeyCode::Enter => {
+                if let Some(entry) = picker.current().cloned() {
+                    self.load_scenario(entry.scenario);
+                }
+                return self.base_mode();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return self.base_mode(),
+            _ => {}
+        }
+        Mode::ScenarioPicker(picker)
+    }
+
+    fn handle_menu_key(&mut self, mut picker: Picker<MenuEntry>, code: KeyCode) -> Mode {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => picker.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => picker.move_selection(1),
+            KeyCode::Enter if picker.current().is_some() => {
+                match picker.entries.swap_remove(picker.selected) {
+                    MenuEntry::Sandbox => self.sandbox(),
+                    MenuEntry::Game(game) => self.start_game(game),
+                    MenuEntry::Demo(entry) => self.load_scenario(entry.scenario),
+                }
+                return self.base_mode();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return self.base_mode(),
+            _ => {}
+        }
+        Mode::Menu(picker)
+    }
+
+    fn handle_profile_key(&mut self, mut picker: Picker<Option<String>>, code: KeyCode) -> Mode {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => picker.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => picker.move_selection(1),
+            KeyCode::Enter => {
+                if let Some(name) = picker.current().cloned() {
+                    self.switch_profile(name);
+                }
+                return self.base_mode();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return self.base_mode(),
+            _ => {}
+        }
+        Mode::ProfilePicker(picker)
+    }
+
+    fn handle_theme_key(&mut self, mut themes: ThemePicker, code: KeyCode) -> Mode {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => themes.picker.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => themes.picker.move_selection(1),
+            KeyCode::Enter => {
+                self.save_theme();
+                return self.base_mode();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.set_theme(themes.original);
+                return self.base_mode();
+            }
+            _ => return Mode::ThemePicker(themes),
+        }
+        if let Some(&theme) = themes.picker.current() {
+            self.set_theme(theme);
+        }
+        Mode::ThemePicker(themes)
+    }
+
+    fn obstacle_edit(&mut self) -> Mode {
+        self.notice = None;
+        // Start in the middle of the arena
+        Mode::ObstacleEdit(ObstacleEdit {
+            x: (self.state.area_width / 2.0) as u16,
+            y: (self.state.area_height / self.state.aspect / 2.0) as u16,
+        })
+    }
+
+    fn handle_obstacle_key(&mut self, mut edit: ObstacleEdit, code: KeyCode) -> Mode {
+        let columns = self.state.area_width.max(1.0) as u16;
+        let rows = (self.state.area_height / self.state.aspect).max(1.0) as u16;
+        match code {
+            KeyCode::Left | KeyCode::Char('h') => edit.x = edit.x.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => edit.x = (edit.x + 1).min(columns - 1),
+            KeyCode::Up | KeyCode::Char('k') => edit.y = edit.y.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => edit.y = (edit.y + 1).min(rows - 1),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let row = self.state.aspect;
+                let (x, y) = (edit.x as f64, edit.y as f64 * row);
+                mode::toggle_wall(&mut self.state.obstacles, x, y, row);
+                self.sim.edit(move |sim| mode::toggle_wall(&mut sim.obstacles, x, y, row));
+            }
+            KeyCode::Char('c') => {
+                self.state.obstacles.clear();
+                self.sim.edit(|sim| sim.obstacles.clear());
+            }
+            KeyCode::Esc | KeyCode::Char('w') => return self.base_mode(),
+            _ => {}
+        }
+        Mode::ObstacleEdit(edit)
+    }
+
+    fn handle_replay_key(&mut self, mut replay: Box<Replay>, code: KeyCode) -> Mode {
+        match code {
+            KeyCode::Char(' ') => replay.toggle(),
+            KeyCode::Left | KeyCode::Char('h') => replay.step(-1),
+            KeyCode::Right | KeyCode::Char('l') => replay.step(1),
+            KeyCode::Up | KeyCode::Char('k') => replay.change_speed(true),
+            KeyCode::Down | KeyCode::Char('j') => replay.change_speed(false),
+            KeyCode::Home => replay.seek(0.0),
+            KeyCode::End => replay.seek(1.0),
+            // The charts go back to the simulation's own history
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.clear_history();
+                return self.base_mode();
+            }
+            _ => {}
+        }
+        Mode::Replay(replay)
+    }
+
+    fn handle_layout_key(&mut self, mut edit: LayoutEdit, code: KeyCode) -> Mode {
+        match code {
+            KeyCode::Left | KeyCode::Char('h') => edit.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => edit.move_cursor(0, 1),
+            KeyCode::Up | KeyCode::Char('k') => edit.move_cursor(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => edit.move_cursor(1, 0),
+            KeyCode::Enter => match edit.picked.take() {
+                Some(slot) => self.layout.swap(slot, edit.cursor),
+                None => edit.picked = Some(edit.cursor),
+            },
+            KeyCode::Char(']') => self.layout.resize_width(edit.cursor, 1),
+            KeyCode::Char('[') => self.layout.resize_width(edit.cursor, -1),
+            KeyCode::Char('}') => self.layout.resize_height(edit.cursor, 1),
+            KeyCode::Char('{') => self.layout.resize_height(edit.cursor, -1),
+            KeyCode::Char('c') => self.layout.cycle_chart(edit.cursor),
+            KeyCode::Char('0') => self.layout = PaneLayout::default(),
+            KeyCode::Char('L') | KeyCode::Esc => {
+                self.save_layout();
+                return self.base_mode();
+            }
+            _ => {}
+        }
+        Mode::LayoutEdit(edit)
+    }
+}
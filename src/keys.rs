@@ -0,0 +1,229 @@
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Pause,
+    AddBall,
+    RemoveBall,
+    SpeedUp,
+    SpeedDown,
+    EditLayout,
+    Scenarios,
+    Profiles,
+    Themes,
+    Flock,
+    Menu,
+    Help,
+    Palette,
+    Settings,
+    EditObstacles,
+    Export,
+    Profiler,
+    Described,
+    Field,
+    Zoom,
+    Paint,
+    ClearPaint,
+    TurnGravity,
+    FocusNext,
+    FocusPrevious,
+    // Only while paused
+    Step,
+}
+
+/// Key names per action, as written in the `[keys]` table of the config file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub quit: Vec<String>,
+    pub pause: Vec<String>,
+    pub add_ball: Vec<String>,
+    pub remove_ball: Vec<String>,
+    pub speed_up: Vec<String>,
+    pub speed_down: Vec<String>,
+    pub edit_layout: Vec<String>,
+    pub scenarios: Vec<String>,
+    pub profiles: Vec<String>,
+    pub themes: Vec<String>,
+    pub flock: Vec<String>,
+    pub menu: Vec<String>,
+    pub help: Vec<String>,
+    pub palette: Vec<String>,
+    pub settings: Vec<String>,
+    pub edit_obstacles: Vec<String>,
+    pub export: Vec<String>,
+    pub profiler: Vec<String>,
+    pub described: Vec<String>,
+    pub field: Vec<String>,
+    pub zoom: Vec<String>,
+    pub paint: Vec<String>,
+    pub clear_paint: Vec<String>,
+    pub turn_gravity: Vec<String>,
+    pub focus_next: Vec<String>,
+    pub focus_previous: Vec<String>,
+    pub step: Vec<String>,
+}
+
+fn names(keys: &[&str]) -> Vec<String> {
+    keys.iter().map(|k| k.to_string()).collect()
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            quit: names(&["q", "esc"]),
+            pause: names(&["space", "p"]),
+            add_ball: names(&["+", "=", "a"]),
+            remove_ball: names(&["-", "_", "r"]),
+            speed_up: names(&["up"]),
+            speed_down: names(&["down"]),
+            edit_layout: names(&["L"]),
+            scenarios: names(&["s"]),
+            profiles: names(&["P"]),
+            themes: names(&["t"]),
+            flock: names(&["f"]),
+            menu: names(&["m"]),
+            help: names(&["?", "f1"]),
+            palette: names(&[":"]),
+            settings: names(&["o"]),
+            edit_obstacles: names(&["w"]),
+            export: names(&["e"]),
+            profiler: names(&["f3"]),
+            described: names(&["f2"]),
+            field: names(&["v"]),
+            zoom: names(&["z"]),
+            paint: names(&["b"]),
+            clear_paint: names(&["c"]),
+            turn_gravity: names(&["g"]),
+            focus_next: names(&["tab"]),
+            focus_previous: names(&["backtab"]),
+            step: names(&["."]),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keymap(&self) -> Result<KeyMap, String> {
+        let groups = [
+            ("quit", &self.quit, Action::Quit),
+            ("pause", &self.pause, Action::Pause),
+            ("add_ball", &self.add_ball, Action::AddBall),
+            ("remove_ball", &self.remove_ball, Action::RemoveBall),
+            ("speed_up", &self.speed_up, Action::SpeedUp),
+            ("speed_down", &self.speed_down, Action::SpeedDown),
+            ("edit_layout", &self.edit_layout, Action::EditLayout),
+            ("scenarios", &self.scenarios, Action::Scenarios),
+            ("profiles", &self.profiles, Action::Profiles),
+            ("themes", &self.themes, Action::Themes),
+            ("flock", &self.flock, Action::Flock),
+            ("menu", &self.menu, Action::Menu),
+            ("help", &self.help, Action::Help),
+            ("palette", &self.palette, Action::Palette),
+            ("settings", &self.settings, Action::Settings),
+            ("edit_obstacles", &self.edit_obstacles, Action::EditObstacles),
+            ("export", &self.export, Action::Export),
+            ("profiler", &self.profiler, Action::Profiler),
+            ("described", &self.described, Action::Described),
+            ("field", &self.field, Action::Field),
+            ("zoom", &self.zoom, Action::Zoom),
+            ("paint", &self.paint, Action::Paint),
+            ("clear_paint", &self.clear_paint, Action::ClearPaint),
+            ("turn_gravity", &self.turn_gravity, Action::TurnGravity),
+            ("focus_next", &self.focus_next, Action::FocusNext),
+            ("focus_previous", &self.focus_previous, Action::FocusPrevious),
+            ("step", &self.step, Action::Step),
+        ];
+
+        let mut bindings: Vec<(KeyCode, Action)> = Vec::new();
+        for (field, keys, action) in groups {
+            for name in keys {
+                let code = parse_key(name).map_err(|err| format!("keys.{}: {}", field, err))?;
+                if let Some((_, other)) = bindings.iter().find(|(c, _)| *c == code) {
+                    return Err(format!(
+                        "keys.{}: \"{}\" is already bound to {:?}",
+                        field, name, other
+                    ));
+                }
+                bindings.push((code, action));
+            }
+        }
+        Ok(KeyMap { bindings })
+    }
+}
+
+pub struct KeyMap {
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyBindings::default().keymap().expect("default key bindings are valid")
+    }
+}
+
+impl KeyMap {
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|&(_, action)| action)
+    }
+
+    /// Human-readable list of the keys bound to `action`, e.g. "Space/P".
+    pub fn label(&self, action: Action, ascii: bool) -> String {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|&(code, _)| key_label(code, ascii))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+pub fn parse_key(name: &str) -> Result<KeyCode, String> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    let code = match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+            _ => return Err(format!("unknown key \"{}\"", name)),
+        },
+    };
+    Ok(code)
+}
+
+fn key_label(code: KeyCode, ascii: bool) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) if c.is_uppercase() => {
+            format!("{}{}", if ascii { "Shift+" } else { "⇧" }, c)
+        }
+        KeyCode::Char(c) => c.to_uppercase().to_string(),
+        KeyCode::Up if !ascii => "↑".to_string(),
+        KeyCode::Down if !ascii => "↓".to_string(),
+        KeyCode::Left if !ascii => "←".to_string(),
+        KeyCode::Right if !ascii => "→".to_string(),
+        KeyCode::BackTab => if ascii { "Shift+Tab" } else { "⇧Tab" }.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
@@ -0,0 +1,171 @@
+//! Degrading gracefully when ticks and draws don't fit the frame budget.
+//! Rather than let updates queue up behind slow frames, the app first draws
+//! charts with fewer points, then skips frames; ticks are never skipped. The
+//! arena says it's lagging while any of that is going on, or while ticks
+//! alone take longer than the tick rate allows.
+//!
+//! [`TickMeter`] is the `--stress` readout of how many ticks the simulation
+//! actually manages per second.
+
+use std::time::Duration;
+
+/// `(chart step, frame step)` per level: every `chart step`th history point
+/// is charted, and every `frame step`th frame drawn.
+const LEVELS: [(usize, u64); 4] = [(1, 1), (2, 1), (2, 2), (4, 4)];
+// Share of the frame budget in use above which to degrade further, and
+// below which to recover a level
+const OVER: f64 = 1.0;
+const UNDER: f64 = 0.5;
+// Frames to wait after changing level, so the averages catch up first
+const SETTLE: u32 = 30;
+// Weight of each new timing in the running averages
+const SMOOTHING: f64 = 0.1;
+
+#[derive(Debug, Default)]
+pub struct Lag {
+    // Running averages, in seconds
+    tick: f64,
+    draw: f64,
+    level: usize,
+    frame: u64,
+    settle: u32,
+}
+
+impl Lag {
+    pub fn tick_took(&mut self, time: Duration) {
+        self.tick = average(self.tick, time);
+    }
+
+    pub fn draw_took(&mut self, time: Duration) {
+        self.draw = average(self.draw, time);
+    }
+
+    /// Called when a frame is due: adjusts the level, then says whether to
+    /// draw this one.
+    pub fn frame(&mut self, tick_rate: Duration, frame_rate: Duration) -> bool {
+        self.frame += 1;
+        if self.settle > 0 {
+            self.settle -= 1;
+        } else {
+            let load = self.load(tick_rate, frame_rate);
+            let level = if load > OVER {
+                (self.level + 1).min(LEVELS.len() - 1)
+            } else if load < UNDER {
+                self.level.saturating_sub(1)
+            } else {
+                self.level
+            };
+            if level != self.level {
+                self.level = level;
+                self.settle = SETTLE;
+            }
+        }
+        self.frame.is_multiple_of(LEVELS[self.level].1)
+    }
+
+    /// Time spent per frame on ticks and drawing, as a share of the frame.
+    fn load(&self, tick_rate: Duration, frame_rate: Duration) -> f64 {
+        let frame = frame_rate.as_secs_f64();
+        let ticks_per_frame = frame / tick_rate.as_secs_f64().max(f64::EPSILON);
+        let drawing = self.draw / LEVELS[self.level].1 as f64;
+        (self.tick * ticks_per_frame + drawing) / frame.max(f64::EPSILON)
+    }
+
+    /// Chart every this many history points.
+    pub fn chart_step(&self) -> usize {
+        LEVELS[self.level].0
+    }
+
+    pub fn lagging(&self, tick_rate: Duration) -> bool {
+        self.level > 0 || self.tick > tick_rate.as_secs_f64()
+    }
+}
+
+/// Ticks per second, measured over about a second at a time.
+#[derive(Debug, Default)]
+pub struct TickMeter {
+    // Tick count and time the current measurement started at
+    start: Option<(u64, Duration)>,
+    rate: Option<u64>,
+}
+
+impl TickMeter {
+    /// Called every frame with the latest tick count and the time.
+    pub fn sample(&mut self, tick: u64, now: Duration) {
+        match self.start {
+            Some((start_tick, start)) if now.saturating_sub(start) >= Duration::from_secs(1) => {
+                // A reset starts the count over, so that second reads as 0
+                let ticks = tick.saturating_sub(start_tick);
+                self.rate = Some((ticks as f64 / (now - start).as_secs_f64()).round() as u64);
+                self.start = Some((tick, now));
+            }
+            Some(_) => {}
+            None => self.start = Some((tick, now)),
+        }
+    }
+
+    /// `None` until the first second has gone by.
+    pub fn rate(&self) -> Option<u64> {
+        self.rate
+    }
+}
+
+fn average(average: f64, time: Duration) -> f64 {
+    average + (time.as_secs_f64() - average) * SMOOTHING
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(10);
+    const FRAME: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn slow_frames_degrade_and_recover() {
+        let mut lag = Lag::default();
+        // 2 ticks of 4ms and a 15ms draw: 23ms of a 20ms frame
+        for _ in 0..100 {
+            lag.tick_took(Duration::from_millis(4));
+            lag.draw_took(Duration::from_millis(15));
+        }
+        assert!(lag.frame(TICK, FRAME));
+        assert_eq!(lag.chart_step(), 2);
+        assert!(lag.lagging(TICK));
+
+        // Still over once the settling is done, so frames are skipped
+        let drawn = (0..SETTLE + 10).filter(|_| lag.frame(TICK, FRAME)).count();
+        assert!(drawn < (SETTLE + 10) as usize, "{}", drawn);
+
+        for _ in 0..100 {
+            lag.tick_took(Duration::from_millis(1));
+            lag.draw_took(Duration::from_millis(2));
+        }
+        for _ in 0..SETTLE * 4 {
+            lag.frame(TICK, FRAME);
+        }
+        assert_eq!(lag.chart_step(), 1);
+        assert!(!lag.lagging(TICK));
+    }
+
+    #[test]
+    fn tick_rate_is_measured_each_second() {
+        let mut meter = TickMeter::default();
+        meter.sample(10, Duration::ZERO);
+        meter.sample(400, Duration::from_millis(500));
+        assert_eq!(meter.rate(), None);
+        meter.sample(810, Duration::from_secs(2));
+        assert_eq!(meter.rate(), Some(400));
+        meter.sample(1000, Duration::from_millis(2500));
+        assert_eq!(meter.rate(), Some(400));
+    }
+
+    #[test]
+    fn slow_ticks_alone_show_as_lagging() {
+        let mut lag = Lag::default();
+        for _ in 0..100 {
+            lag.tick_took(Duration::from_millis(12));
+        }
+        assert!(lag.lagging(TICK));
+    }
+}
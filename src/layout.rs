@@ -0,0 +1,275 @@
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
+
+pub const ROWS: usize = 3;
+pub const COLS: usize = 2;
+pub const SLOTS: usize = ROWS * COLS;
+
+const MIN_ROW_PERCENT: u16 = 10;
+const MIN_SPLIT_PERCENT: u16 = 20;
+const MAX_SPLIT_PERCENT: u16 = 80;
+const MIN_STATUS_WIDTH: u16 = 20;
+const MAX_STATUS_WIDTH: u16 = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pane {
+    Arena,
+    Status,
+    XPosition,
+    YPosition,
+    XVelocity,
+    YVelocity,
+    // Every ball as an arrow along its velocity
+    Quiver,
+}
+
+impl Pane {
+    pub const DEFAULT: [Pane; SLOTS] = [
+        Pane::Arena,
+        Pane::Status,
+        Pane::XPosition,
+        Pane::YPosition,
+        Pane::XVelocity,
+        Pane::YVelocity,
+    ];
+
+    /// The panes that can fill the slots besides the arena's and the
+    /// controls panel's, four at a time.
+    pub const CHARTS: [Pane; 5] = [
+        Pane::XPosition,
+        Pane::YPosition,
+        Pane::XVelocity,
+        Pane::YVelocity,
+        Pane::Quiver,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Pane::Arena => "Arena",
+            Pane::Status => "Status",
+            Pane::XPosition => "X Position",
+            Pane::YPosition => "Y Position",
+            Pane::XVelocity => "X Velocity",
+            Pane::YVelocity => "Y Velocity",
+            Pane::Quiver => "Quiver",
+        }
+    }
+}
+
+/// A part of the controls panel. `display.status_panel` lists the ones to
+/// show, top to bottom; notices always come last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Section {
+    // Running or paused
+    Status,
+    // How many balls
+    Counts,
+    Speed,
+    // The profile and scenario, if any
+    Scenario,
+    // Hits where a scenario counts them, a gas's statistics, and the latest
+    // collision while paused
+    Collisions,
+    // The game's score and best
+    Game,
+    // Up to four balls' positions, from the picked one's group
+    Balls,
+    // The same balls' energy bars
+    Energy,
+    // Collisions per tick over the charted history
+    Rate,
+    Hints,
+}
+
+/// Values `display.status_panel` lists.
+pub const SECTION_NAMES: [&str; 10] =
+    ["status", "counts", "speed", "scenario", "collisions", "game", "balls", "energy", "rate", "hints"];
+
+impl Section {
+    pub const ALL: [Section; 10] = [
+        Section::Status,
+        Section::Counts,
+        Section::Speed,
+        Section::Scenario,
+        Section::Collisions,
+        Section::Game,
+        Section::Balls,
+        Section::Energy,
+        Section::Rate,
+        Section::Hints,
+    ];
+
+    /// Everything but the rate.
+    pub const DEFAULT: [Section; 9] = [
+        Section::Status,
+        Section::Counts,
+        Section::Speed,
+        Section::Scenario,
+        Section::Collisions,
+        Section::Game,
+        Section::Balls,
+        Section::Energy,
+        Section::Hints,
+    ];
+
+    pub fn by_name(name: &str) -> Option<Section> {
+        Some(Section::ALL[SECTION_NAMES.iter().position(|&known| known == name)?])
+    }
+
+    pub fn name(self) -> &'static str {
+        SECTION_NAMES[self as usize]
+    }
+}
+
+/// Arrangement of six panes on a 3x2 grid, plus the size of each row and
+/// column: the arena, the controls panel and four of [`Pane::CHARTS`]. Slots are numbered row-major: slot 0 is top-left, slot 5 bottom-right.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaneLayout {
+    pub panes: [Pane; SLOTS],
+    pub row_heights: [u16; ROWS],
+    // Width of the left pane in each row, as a percentage
+    pub col_splits: [u16; ROWS],
+    // The status pane keeps a fixed width in whichever row it lands in
+    pub status_width: u16,
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        PaneLayout {
+            panes: Pane::DEFAULT,
+            row_heights: [34, 33, 33],
+            col_splits: [50, 50, 50],
+            status_width: 32,
+        }
+    }
+}
+
+impl PaneLayout {
+    /// Returns the layout with any out-of-range values replaced by defaults,
+    /// e.g. after loading a hand-edited config file.
+    pub fn sanitized(self) -> Self {
+        let default = PaneLayout::default();
+        let distinct = self.panes.iter().enumerate().all(|(i, p)| !self.panes[..i].contains(p));
+        let is_complete = distinct && [Pane::Arena, Pane::Status].iter().all(|p| self.panes.contains(p));
+        let heights_ok = self.row_heights.iter().all(|&h| h >= MIN_ROW_PERCENT)
+            && self.row_heights.iter().sum::<u16>() == 100;
+        let splits_ok = self
+            .col_splits
+            .iter()
+            .all(|s| (MIN_SPLIT_PERCENT..=MAX_SPLIT_PERCENT).contains(s));
+
+        PaneLayout {
+            panes: if is_complete { self.panes } else { default.panes },
+            row_heights: if heights_ok { self.row_heights } else { default.row_heights },
+            col_splits: if splits_ok { self.col_splits } else { default.col_splits },
+            status_width: self.status_width.clamp(MIN_STATUS_WIDTH, MAX_STATUS_WIDTH),
+        }
+    }
+
+    /// Splits `area` into one rect per slot, in slot order.
+    pub fn split(&self, area: Rect) -> [(Pane, Rect); SLOTS] {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(self.row_heights.map(Constraint::Percentage))
+            .split(area);
+
+        let mut out = [(Pane::Arena, Rect::default()); SLOTS];
+        for row in 0..ROWS {
+            let left = self.panes[row * COLS];
+            let right = self.panes[row * COLS + 1];
+            let constraints = if right == Pane::Status {
+                [Constraint::Min(20), Constraint::Length(self.status_width)]
+            } else if left == Pane::Status {
+                [Constraint::Length(self.status_width), Constraint::Min(20)]
+            } else {
+                [
+                    Constraint::Percentage(self.col_splits[row]),
+                    Constraint::Percentage(100 - self.col_splits[row]),
+                ]
+            };
+            let cols = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(constraints)
+                .split(rows[row]);
+            out[row * COLS] = (left, cols[0]);
+            out[row * COLS + 1] = (right, cols[1]);
+        }
+        out
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.panes.swap(a, b);
+    }
+
+    /// Puts the next chart not already shown in `slot`, if it holds a chart.
+    pub fn cycle_chart(&mut self, slot: usize) {
+        let Some(at) = Pane::CHARTS.iter().position(|&p| p == self.panes[slot]) else {
+            return;
+        };
+        let next = (1..Pane::CHARTS.len())
+            .map(|step| Pane::CHARTS[(at + step) % Pane::CHARTS.len()])
+            .find(|pane| !self.panes.contains(pane));
+        if let Some(pane) = next {
+            self.panes[slot] = pane;
+        }
+    }
+
+    /// Grows (positive `delta`) or shrinks the pane in `slot` horizontally.
+    pub fn resize_width(&mut self, slot: usize, delta: i16) {
+        let row = slot / COLS;
+        let is_left = slot.is_multiple_of(COLS);
+        let pane = self.panes[slot];
+        let neighbour = self.panes[if is_left { slot + 1 } else { slot - 1 }];
+
+        if pane == Pane::Status || neighbour == Pane::Status {
+            // Width in cells; the other pane takes whatever is left
+            let sign = if pane == Pane::Status { 1 } else { -1 };
+            self.status_width = self
+                .status_width
+                .saturating_add_signed(sign * delta * 2)
+                .clamp(MIN_STATUS_WIDTH, MAX_STATUS_WIDTH);
+        } else {
+            let sign = if is_left { 1 } else { -1 };
+            self.col_splits[row] = self.col_splits[row]
+                .saturating_add_signed(sign * delta * 5)
+                .clamp(MIN_SPLIT_PERCENT, MAX_SPLIT_PERCENT);
+        }
+    }
+
+    /// Grows (positive `delta`) or shrinks the row containing `slot`, taking
+    /// the space from (or giving it to) the adjacent row.
+    pub fn resize_height(&mut self, slot: usize, delta: i16) {
+        let row = slot / COLS;
+        let other = if row + 1 < ROWS { row + 1 } else { row - 1 };
+        let step = delta * 2;
+        let new_row = self.row_heights[row] as i16 + step;
+        let new_other = self.row_heights[other] as i16 - step;
+        if new_row >= MIN_ROW_PERCENT as i16 && new_other >= MIN_ROW_PERCENT as i16 {
+            self.row_heights[row] = new_row as u16;
+            self.row_heights[other] = new_other as u16;
+        }
+    }
+}
+
+/// Cursor state while the layout editor is open.
+pub struct LayoutEdit {
+    pub cursor: usize,
+    // Slot picked up with Enter, waiting for a second Enter to swap
+    pub picked: Option<usize>,
+}
+
+impl LayoutEdit {
+    pub fn new() -> Self {
+        LayoutEdit { cursor: 0, picked: None }
+    }
+
+    pub fn move_cursor(&mut self, drow: isize, dcol: isize) {
+        let row = (self.cursor / COLS) as isize + drow;
+        let col = (self.cursor % COLS) as isize + dcol;
+        let row = row.clamp(0, ROWS as isize - 1) as usize;
+        let col = col.clamp(0, COLS as isize - 1) as usize;
+        self.cursor = row * COLS + col;
+    }
+}
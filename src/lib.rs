@@ -0,0 +1,689 @@
+//! Bouncing-ball simulation: balls, elastic collisions, pluggable forces, obstacles and
+//! scenario files, independent of any terminal UI.
+//!
+//! Balls are entities in a [`hecs::World`] with the components in
+//! [`components`]; each tick runs forces and then the integration, collision,
+//! history and lifetime systems over them. Frontends drive it with
+//! [`Command`]s and read back what happened as [`SimEvent`]s, and with the
+//! `widget` feature (part of the default `tui`) can draw the arena with
+//! `widget::BallArenaWidget`.
+//!
+//! ```
+//! use rust_tui_ball_bounce::{BallSpec, Simulation};
+//!
+//! let mut sim = Simulation::new(80.0, 20.0);
+//! sim.add_ball(BallSpec::new(10.0, 5.0, 0.5, 0.0));
+//! sim.add_ball(sim.next_ball_spec());
+//! for _ in 0..100 {
+//!     sim.step(1.0);
+//! }
+//! assert_eq!(sim.state().len(), 2);
+//! assert_eq!(sim.tick_count, 100);
+//! ```
+
+mod ball;
+mod builder;
+pub mod clock;
+pub mod components;
+pub mod event;
+pub mod force;
+mod observer;
+mod physics;
+pub mod rng;
+mod sat;
+pub mod save;
+pub mod scenario;
+mod snapshot;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "widget")]
+pub mod ansi;
+#[cfg(feature = "widget")]
+pub mod widget;
+
+/// The per-ball loops of a step over balls stored as parallel arrays,
+/// exposed so `benches/tick.rs` can time them without the collisions
+/// around them. Not a supported API.
+#[doc(hidden)]
+pub mod kernels {
+    pub use crate::physics::{bounce_off_walls, integrate, Balls};
+}
+
+pub use ball::BallState;
+pub use builder::SimulationBuilder;
+pub use event::{Command, SimEvent};
+pub use hecs;
+pub use observer::SimulationObserver;
+pub use scenario::BallSpec;
+pub use snapshot::{FieldSample, Snapshot};
+
+use std::any::Any;
+
+use clock::{Clock, StepTimes};
+use components::{BallId, Body, Charge, Glyph, Growth, History, Mass, Position, Radius, Rgb, Samples, Spin, Ttl, Velocity};
+use force::{Boids, Direction, Force, Gravity};
+use hecs::{Entity, Ref, World};
+use rng::Rng;
+use scenario::{Obstacle, Pockets, Scenario, Thermostat};
+
+/// Balls in a rectangular arena. Positions are in arena cells and velocities
+/// in cells per tick, scaled by `speed_multiplier`.
+pub struct Simulation {
+    pub world: World,
+    pub paused: bool,
+    pub tick_count: u64,
+    pub collisions: u64,
+    pub wall_bounces: u64,
+    pub ball_counter: usize, // total balls ever created, for unique ids
+    pub area_width: f64,
+    pub area_height: f64,
+    pub speed_multiplier: f64,
+    pub ball_radius: f64,
+    // Ticks of telemetry kept per ball in `History`; 0 turns recording off
+    pub max_history: usize,
+    // When the samples in every ball's `History` were taken
+    history_ticks: Samples,
+    // Applied in order at the start of every tick
+    pub forces: Vec<Box<dyn Force>>,
+    // Which way gravity set through `set_gravity` pulls, kept while there's
+    // none so it applies once there is
+    pub gravity_direction: Direction,
+    // Across and down between the points each snapshot samples the force
+    // field at, in arena units; `None` samples nothing
+    pub field_spacing: Option<(f64, f64)>,
+    pub obstacles: Vec<Obstacle>,
+    // Balls that reach one are removed
+    pub pockets: Option<Pockets>,
+    // Walls that heat or cool the balls leaving them
+    pub thermostat: Option<Thermostat>,
+    pub scenario_name: Option<String>,
+    // Set by scenarios of a gas; see `Parameters::gas`
+    pub gas: bool,
+    // Set by scenarios that count hits; see `Parameters::count_hits`
+    pub count_hits: bool,
+    // The hits counted by the time the balls were moving apart for good,
+    // after which any more are left out of `clicks`
+    pub final_clicks: Option<u64>,
+    // Seed the balls were placed with, kept so runs can be reproduced
+    pub seed: u64,
+    // Height of one terminal row in arena units (2.0 with aspect correction);
+    // the floor sits one row above `area_height`
+    pub aspect: f64,
+    // Events since the last drain; each tick discards any left unread
+    events: Vec<SimEvent>,
+    // Ordered by id, as of the last step or change made through methods
+    balls: Vec<BallState>,
+    // The balls' core components as parallel arrays while stepping, kept
+    // between steps to reuse the buffers
+    columns: physics::Balls,
+}
+
+impl Simulation {
+    pub fn new(width: f64, height: f64) -> Simulation {
+        Simulation {
+            world: World::new(),
+            paused: false,
+            tick_count: 0,
+            collisions: 0,
+            wall_bounces: 0,
+            ball_counter: 0,
+            area_width: width,
+            area_height: height,
+            speed_multiplier: 1.0,
+            ball_radius: 0.75,
+            max_history: 300,
+            history_ticks: Samples::default(),
+            forces: Vec::new(),
+            gravity_direction: Direction::Down,
+            field_spacing: None,
+            obstacles: Vec::new(),
+            pockets: None,
+            thermostat: None,
+            scenario_name: None,
+            gas: false,
+            count_hits: false,
+            final_clicks: None,
+            seed: 0,
+            aspect: 1.0,
+            events: Vec::new(),
+            balls: Vec::new(),
+            columns: physics::Balls::default(),
+        }
+    }
+
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::TogglePause => self.set_paused(!self.paused),
+            Command::SetPaused(paused) => self.set_paused(paused),
+            Command::AddBall => {
+                self.add_ball(self.next_ball_spec());
+            }
+            Command::RemoveBall => self.remove_ball(),
+            Command::SpeedUp => self.speed_up(),
+            Command::SpeedDown => self.speed_down(),
+            Command::SetSpeed(speed) => self.set_speed(speed),
+            Command::SetGravity(gravity) => self.set_gravity(gravity),
+            Command::SetGravityDirection(direction) => self.set_gravity_direction(direction),
+            Command::SetBoids(boids) => self.set_boids(boids),
+            Command::Step => self.step(self.speed_multiplier),
+            Command::LoadScenario(scenario) => self.load_scenario(&scenario),
+            Command::Reset => self.reset(),
+        }
+    }
+
+    /// Takes the events emitted since the last call. Read them after every
+    /// [`tick`](Self::tick) or [`step`](Self::step): the next one drops
+    /// whatever is left.
+    pub fn drain_events(&mut self) -> Vec<SimEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused != paused {
+            self.paused = paused;
+            self.events.push(SimEvent::Paused(paused));
+        }
+    }
+
+    /// Where [`Command::AddBall`] puts the next ball: positions and velocities
+    /// vary with the ball count so balls added in a row don't overlap.
+    pub fn next_ball_spec(&self) -> BallSpec {
+        let idx = self.ball_counter;
+        let x = 5.0 + (idx as f64 * 7.3) % self.area_width.max(20.0);
+        let y = 3.0 + (idx as f64 * 4.1) % self.area_height.max(10.0);
+        let vx = 0.5 + (idx as f64 * 0.17) % 0.8;
+        let vy = 0.3 + (idx as f64 * 0.13) % 0.6;
+        // Alternate directions
+        let vx = if idx.is_multiple_of(2) { vx } else { -vx };
+        let vy = if idx.is_multiple_of(3) { vy } else { -vy };
+        BallSpec::new(x, y, vx, vy)
+    }
+
+    /// Adds a ball, with `ball_radius` unless the spec sets one, and returns
+    /// its id. A growing ball's limits are [`Growth::clamped`] and it starts
+    /// within them.
+    pub fn add_ball(&mut self, spec: BallSpec) -> usize {
+        let id = self.ball_counter;
+        let growth = spec.growth.map(Growth::clamped);
+        let radius = spec.radius.unwrap_or(self.ball_radius);
+        let radius = growth.map_or(radius, |growth| radius.clamp(growth.min, growth.max));
+        let entity = self.spawn(spec.x, spec.y, spec.vx, spec.vy, radius);
+        if let Some(charge) = spec.charge {
+            let _ = self.world.insert_one(entity, Charge(charge));
+        }
+        if let Some(mass) = spec.mass {
+            let _ = self.world.insert_one(entity, Mass(mass));
+            if let Some(ball) = self.balls.last_mut() {
+                ball.mass = mass;
+            }
+        }
+        if let Some(ttl) = spec.ttl {
+            let _ = self.world.insert_one(entity, Ttl(ttl));
+        }
+        if let Some(growth) = growth {
+            // Its mass changes as it grows, so it needs one to change
+            if spec.mass.is_none() {
+                let _ = self.world.insert_one(entity, Mass(1.0));
+            }
+            let _ = self.world.insert_one(entity, growth);
+        }
+        if let Some(shape) = spec.shape {
+            let body = Body { shape, angle: spec.angle.unwrap_or(0.0).to_radians() };
+            let _ = self.world.insert_one(entity, body);
+            if let Some(ball) = self.balls.last_mut() {
+                ball.body = Some(body);
+            }
+            if let Some(spin) = spec.spin {
+                let _ = self.world.insert_one(entity, Spin(spin.to_radians()));
+            }
+        }
+        if let Some(color) = spec.color {
+            let _ = self.world.insert_one(entity, color);
+            // `spawn` just pushed this ball's state
+            if let Some(ball) = self.balls.last_mut() {
+                ball.color = Some(color);
+            }
+        }
+        if let Some(glyph) = spec.glyph {
+            let _ = self.world.insert_one(entity, glyph);
+            if let Some(ball) = self.balls.last_mut() {
+                ball.glyph = Some(glyph);
+            }
+        }
+        id
+    }
+
+    /// Spawns a ball with the core components and returns its entity, so
+    /// callers can attach optional ones like [`Charge`] or [`Ttl`].
+    pub fn spawn(&mut self, x: f64, y: f64, vx: f64, vy: f64, radius: f64) -> Entity {
+        let id = BallId(self.ball_counter);
+        self.ball_counter += 1;
+        self.events.push(SimEvent::Spawned { id: id.0 });
+        self.balls.push(BallState { id: id.0, x, y, vx, vy, radius, mass: 1.0, color: None, glyph: None, body: None });
+        self.world.spawn((
+            id,
+            Position { x, y },
+            Velocity { vx, vy },
+            Radius(radius),
+            History::default(),
+        ))
+    }
+
+    /// Removes the most recently created ball.
+    pub fn remove_ball(&mut self) {
+        let newest = self.world.query::<&BallId>().iter().map(|id| id.0).max();
+        if let Some(id) = newest {
+            self.remove_ball_by_id(id);
+        }
+    }
+
+    /// Removes ball `id`. Returns `false` if there is no such ball.
+    pub fn remove_ball_by_id(&mut self, id: usize) -> bool {
+        let entity = self
+            .world
+            .query::<(Entity, &BallId)>()
+            .iter()
+            .find(|(_, ball)| ball.0 == id)
+            .map(|(entity, _)| entity);
+        let Some(entity) = entity else {
+            return false;
+        };
+        let _ = self.world.despawn(entity);
+        self.balls.retain(|ball| ball.id != id);
+        self.events.push(SimEvent::Removed { id });
+        true
+    }
+
+    /// Adds `(dvx, dvy)` to the velocity of ball `id`. Returns `false` if
+    /// there is no such ball.
+    pub fn push_ball(&mut self, id: usize, dvx: f64, dvy: f64) -> bool {
+        let Some((_, vel)) = self
+            .world
+            .query_mut::<(&BallId, &mut Velocity)>()
+            .into_iter()
+            .find(|(ball, _)| ball.0 == id)
+        else {
+            return false;
+        };
+        vel.vx += dvx;
+        vel.vy += dvy;
+        if let Some(ball) = self.balls.iter_mut().find(|ball| ball.id == id) {
+            ball.vx += dvx;
+            ball.vy += dvy;
+        }
+        true
+    }
+
+    /// Gives ball `id` a new radius. Returns `false` if there is no such
+    /// ball.
+    pub fn resize_ball(&mut self, id: usize, radius: f64) -> bool {
+        let Some((_, size)) = self
+            .world
+            .query_mut::<(&BallId, &mut Radius)>()
+            .into_iter()
+            .find(|(ball, _)| ball.0 == id)
+        else {
+            return false;
+        };
+        size.0 = radius;
+        if let Some(ball) = self.balls.iter_mut().find(|ball| ball.id == id) {
+            ball.radius = radius;
+        }
+        true
+    }
+
+    /// Gives ball `id` the color and glyph to draw it with; `None` goes back
+    /// to the one derived from its id. Returns `false` if there is no such
+    /// ball.
+    pub fn restyle_ball(&mut self, id: usize, color: Option<Rgb>, glyph: Option<Glyph>) -> bool {
+        let entity = self
+            .world
+            .query::<(Entity, &BallId)>()
+            .iter()
+            .find(|(_, ball)| ball.0 == id)
+            .map(|(entity, _)| entity);
+        let Some(entity) = entity else {
+            return false;
+        };
+        if let Some(color) = color {
+            let _ = self.world.insert_one(entity, color);
+        } else {
+            let _ = self.world.remove_one::<Rgb>(entity);
+        }
+        if let Some(glyph) = glyph {
+            let _ = self.world.insert_one(entity, glyph);
+        } else {
+            let _ = self.world.remove_one::<Glyph>(entity);
+        }
+        if let Some(ball) = self.balls.iter_mut().find(|ball| ball.id == id) {
+            ball.color = color;
+            ball.glyph = glyph;
+        }
+        true
+    }
+
+    /// Changes the arena to `width` x `height`, moving any ball left outside
+    /// it in to the nearest wall. With `rescale`, every ball first moves in
+    /// proportion, keeping its place relative to the walls; obstacles and
+    /// pockets stay where they are either way.
+    pub fn resize(&mut self, width: f64, height: f64, rescale: bool) {
+        let old = (self.area_width - 1.0, self.area_height - self.aspect);
+        self.area_width = width;
+        self.area_height = height;
+        let (right, floor) = ((width - 1.0).max(0.0), (height - self.aspect).max(0.0));
+        let fit = |v: f64, old: f64, new: f64| {
+            let v = if rescale && old > 0.0 { v * new / old } else { v };
+            v.clamp(0.0, new)
+        };
+        for pos in self.world.query_mut::<&mut Position>() {
+            pos.x = fit(pos.x, old.0, right);
+            pos.y = fit(pos.y, old.1, floor);
+        }
+        for ball in &mut self.balls {
+            ball.x = fit(ball.x, old.0, right);
+            ball.y = fit(ball.y, old.1, floor);
+        }
+    }
+
+    pub fn ball_count(&self) -> usize {
+        self.world.query::<&BallId>().iter().count()
+    }
+
+    /// Every ball, ordered by id. Reflects the last step and any change made
+    /// through `Simulation` methods; edits made straight to `world` show up
+    /// after the next step.
+    pub fn state(&self) -> &[BallState] {
+        &self.balls
+    }
+
+    /// The ticks the samples in each [`History`] were taken at, oldest
+    /// first. A ball's values line up with the last of them; see
+    /// [`components::with_ticks`].
+    pub fn history_ticks(&self) -> &[f64] {
+        &self.history_ticks
+    }
+
+    /// Each ball's id and recorded [`History`], ordered by id.
+    pub fn histories(&self) -> impl Iterator<Item = (usize, Ref<'_, History>)> {
+        let mut histories: Vec<_> = self
+            .world
+            .iter()
+            .filter_map(|entity| Some((entity.get::<&BallId>()?.0, entity.get::<&History>()?)))
+            .collect();
+        histories.sort_by_key(|&(id, _)| id);
+        histories.into_iter()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            tick_count: self.tick_count,
+            paused: self.paused,
+            collisions: self.collisions,
+            wall_bounces: self.wall_bounces,
+            speed_multiplier: self.speed_multiplier,
+            area_width: self.area_width,
+            area_height: self.area_height,
+            aspect: self.aspect,
+            scenario_name: self.scenario_name.clone(),
+            gas: self.gas,
+            count_hits: self.count_hits,
+            clicks: self.clicks(),
+            seed: self.seed,
+            obstacles: self.obstacles.clone(),
+            gravity: self.gravity(),
+            gravity_direction: self.gravity_direction,
+            boids: self.boids(),
+            pockets: self.pockets,
+            balls: self.balls.clone(),
+            field: self.sample_field(),
+        }
+    }
+
+    /// The net acceleration the forces give a ball at rest at `(x, y)`,
+    /// with charged balls pushing on it as on a unit positive charge.
+    pub fn field_at(&self, x: f64, y: f64) -> (f64, f64) {
+        let (mut ax, mut ay) = self.forces.iter().fold((0.0, 0.0), |(ax, ay), force| {
+            let (fx, fy) = force.field(x, y);
+            (ax + fx, ay + fy)
+        });
+        for (pos, charge) in self.world.query::<(&Position, &Charge)>().iter() {
+            let dx = x - pos.x;
+            let dy = y - pos.y;
+            let dist_sq = (dx * dx + dy * dy).max(1.0);
+            let dist = dist_sq.sqrt();
+            ax += charge.0 / dist_sq * dx / dist;
+            ay += charge.0 / dist_sq * dy / dist;
+        }
+        (ax, ay)
+    }
+
+    // The field at the middle of each `field_spacing` cell, or nothing if
+    // no force has one
+    fn sample_field(&self) -> Vec<FieldSample> {
+        let Some((dx, dy)) = self.field_spacing else {
+            return Vec::new();
+        };
+        let (cols, rows) = ((self.area_width / dx) as usize, (self.area_height / dy) as usize);
+        let samples: Vec<FieldSample> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| ((col as f64 + 0.5) * dx, (row as f64 + 0.5) * dy)))
+            .map(|(x, y)| {
+                let (ax, ay) = self.field_at(x, y);
+                FieldSample { x, y, ax, ay }
+            })
+            .collect();
+        if samples.iter().all(|sample| sample.ax == 0.0 && sample.ay == 0.0) {
+            return Vec::new();
+        }
+        samples
+    }
+
+    pub fn speed_up(&mut self) {
+        self.set_speed(self.speed_multiplier + 0.25);
+    }
+
+    pub fn speed_down(&mut self) {
+        self.set_speed(self.speed_multiplier - 0.25);
+    }
+
+    /// Sets the speed multiplier, clamped to 0.25–5.0.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed_multiplier = speed.clamp(0.25, 5.0);
+        self.events.push(SimEvent::SpeedChanged(self.speed_multiplier));
+    }
+
+    pub fn add_force(&mut self, force: impl Force + 'static) {
+        self.forces.push(Box::new(force));
+    }
+
+    /// The total of the [`Gravity`] forces.
+    pub fn gravity(&self) -> f64 {
+        self.forces.iter().filter_map(|force| (&**force as &dyn Any).downcast_ref::<Gravity>()).map(|gravity| gravity.g).sum()
+    }
+
+    /// Replaces any [`Gravity`] force with one of `gravity` toward
+    /// `gravity_direction`, applied first like a scenario's, or removes it
+    /// for 0.
+    pub fn set_gravity(&mut self, gravity: f64) {
+        self.forces.retain(|force| !(&**force as &dyn Any).is::<Gravity>());
+        if gravity != 0.0 {
+            let center = self.center();
+            self.forces.insert(0, Box::new(Gravity { g: gravity, direction: self.gravity_direction, center }));
+        }
+    }
+
+    /// Turns every [`Gravity`] force toward `direction`, and any set later.
+    pub fn set_gravity_direction(&mut self, direction: Direction) {
+        self.gravity_direction = direction;
+        self.gravities().for_each(|gravity| gravity.direction = direction);
+    }
+
+    fn gravities(&mut self) -> impl Iterator<Item = &mut Gravity> {
+        self.forces.iter_mut().filter_map(|force| (&mut **force as &mut dyn Any).downcast_mut::<Gravity>())
+    }
+
+    // The middle of the space balls' centers are kept in
+    fn center(&self) -> (f64, f64) {
+        ((self.area_width - 1.0) / 2.0, (self.area_height - self.aspect) / 2.0)
+    }
+
+    /// The [`Boids`] force, if the balls are flocking.
+    pub fn boids(&self) -> Option<Boids> {
+        self.forces.iter().find_map(|force| (&**force as &dyn Any).downcast_ref::<Boids>()).copied()
+    }
+
+    /// Replaces any [`Boids`] force with `boids`, in the same place among
+    /// the forces or otherwise last, or stops the flocking for `None`.
+    pub fn set_boids(&mut self, boids: Option<Boids>) {
+        let at = self.forces.iter().position(|force| (&**force as &dyn Any).is::<Boids>());
+        if let Some(at) = at {
+            self.forces.remove(at);
+        }
+        if let Some(boids) = boids {
+            self.forces.insert(at.unwrap_or(self.forces.len()), Box::new(boids));
+        }
+    }
+
+    /// Removes every ball, force, obstacle, pocket and thermostat and zeroes
+    /// the counters.
+    pub fn reset(&mut self) {
+        self.world.clear();
+        self.balls.clear();
+        self.history_ticks.clear();
+        self.ball_counter = 0;
+        self.tick_count = 0;
+        self.collisions = 0;
+        self.wall_bounces = 0;
+        self.forces.clear();
+        self.gravity_direction = Direction::Down;
+        self.obstacles.clear();
+        self.pockets = None;
+        self.thermostat = None;
+        self.scenario_name = None;
+        self.gas = false;
+        self.count_hits = false;
+        self.final_clicks = None;
+        self.events.push(SimEvent::Reset);
+    }
+
+    /// The hits a scenario with `count_hits` shows: every collision and
+    /// wall bounce until the balls are moving apart for good.
+    pub fn clicks(&self) -> u64 {
+        self.final_clicks.unwrap_or(self.collisions + self.wall_bounces)
+    }
+
+    /// Replaces the balls, forces, obstacles and pockets with the scenario's, sized to
+    /// the current arena.
+    pub fn load_scenario(&mut self, scenario: &Scenario) {
+        SimulationBuilder::new().scenario(scenario).apply(self);
+    }
+
+    /// Advances one step at the current speed, unless paused.
+    pub fn tick(&mut self) {
+        if self.paused {
+            return;
+        }
+        self.step(self.speed_multiplier);
+    }
+
+    /// Advances one step of `dt` ticks, paused or not. The result depends only
+    /// on the current state and `dt`, so the same inputs always give the same
+    /// run.
+    pub fn step(&mut self, dt: f64) {
+        self.run_step(dt, |_| {});
+    }
+
+    /// Like [`step`](Self::step), also timing each phase by `clock`.
+    pub fn step_timed(&mut self, dt: f64, clock: &impl Clock) -> StepTimes {
+        let mut times = StepTimes::default();
+        let mut last = clock.now();
+        self.run_step(dt, |phase| {
+            let now = clock.now();
+            let time = match phase {
+                Phase::Integrate => &mut times.integrate,
+                Phase::Collide => &mut times.collide,
+                Phase::History => &mut times.history,
+            };
+            *time = now.saturating_sub(last);
+            last = now;
+        });
+        times
+    }
+
+    /// The step itself, calling `done` as each phase finishes.
+    fn run_step(&mut self, dt: f64, mut done: impl FnMut(Phase)) {
+        self.events.clear();
+        self.tick_count += 1;
+        let _span = tracing::trace_span!("tick", tick = self.tick_count).entered();
+
+        // The arena may have been resized since gravity was set
+        let center = self.center();
+        self.gravities().for_each(|gravity| gravity.center = center);
+        for force in &self.forces {
+            force.apply(&mut self.world, dt);
+        }
+        physics::electrostatics(&mut self.world, dt);
+        physics::grow(&mut self.world, dt);
+        physics::spin(&mut self.world, dt);
+
+        // Movement and collisions run on a copy ordered by id, then are
+        // written back
+        let balls = &mut self.columns;
+        physics::gather(&self.world, balls);
+        physics::integrate(balls, dt);
+        done(Phase::Integrate);
+
+        let collisions = physics::resolve_collisions(balls);
+        self.collisions += collisions.len() as u64;
+        self.events.extend(collisions);
+        for i in 0..balls.len() {
+            for obstacle in &self.obstacles {
+                physics::bounce_off_obstacle(balls, i, obstacle);
+            }
+        }
+        let (right, floor) = (self.area_width - 1.0, self.area_height - self.aspect);
+        let bounced = self.events.len();
+        self.wall_bounces += physics::bounce_bodies_off_walls(balls, right, floor, &mut self.events);
+        self.wall_bounces += physics::bounce_off_walls(balls, right, floor, &mut self.events);
+        if let Some(thermostat) = &self.thermostat {
+            // Seeded by the tick, so a run draws the same speeds every time
+            let mut rng = Rng::new(self.seed ^ self.tick_count.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            physics::thermalize(balls, thermostat, right, floor, &self.events[bounced..], &mut rng);
+        }
+        physics::scatter(&mut self.world, balls);
+        let sunk = match &self.pockets {
+            Some(pockets) => physics::sink(&mut self.world, balls, pockets, right, floor),
+            None => Vec::new(),
+        };
+        self.events.extend(sunk.iter().map(|&id| SimEvent::Pocketed { id }));
+        done(Phase::Collide);
+
+        physics::record_history(&mut self.world, &mut self.history_ticks, self.tick_count as f64, self.max_history);
+        let expired = physics::expire(&mut self.world);
+        self.balls.clear();
+        self.balls
+            .extend(balls.states().filter(|ball| !expired.contains(&ball.id) && !sunk.contains(&ball.id)));
+        self.events
+            .extend(expired.into_iter().map(|id| SimEvent::Expired { id }));
+        if self.count_hits && self.final_clicks.is_none() && moving_apart(&self.balls) {
+            self.final_clicks = Some(self.collisions + self.wall_bounces);
+        }
+        done(Phase::History);
+    }
+}
+
+// Whether balls in a row along x can never meet each other or the left wall
+// again: none moving left, and none faster than a ball to its right. Only the
+// far wall could bring them back, which the hit-counting demos leave out of
+// the count.
+fn moving_apart(balls: &[BallState]) -> bool {
+    let mut row: Vec<&BallState> = balls.iter().collect();
+    row.sort_by(|a, b| a.x.total_cmp(&b.x));
+    row.iter().all(|ball| ball.vx >= 0.0) && row.windows(2).all(|pair| pair[0].vx <= pair[1].vx)
+}
+
+// The phases of a step, in order, as `step_timed` times them
+enum Phase {
+    Integrate,
+    Collide,
+    History,
+}
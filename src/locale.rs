@@ -0,0 +1,436 @@
+//! The UI's words in each language: pane titles, the controls panel and the
+//! help screen. A language is a [`Strings`] table picked by name like a
+//! theme, with `display.language` in the config file; `auto` goes by the
+//! `LC_ALL`, `LC_MESSAGES` and `LANG` environment variables. Notices, game
+//! messages, the menus and the command palette's commands are English in
+//! every language so far.
+//!
+//! Status labels are padded to eight columns, so keep them to that.
+
+use crate::keys::Action;
+
+pub struct Strings {
+    pub name: &'static str,
+    pub arena: fn(usize) -> String,
+    pub controls: &'static str,
+    pub x_position: &'static str,
+    pub y_position: &'static str,
+    pub x_velocity: &'static str,
+    pub y_velocity: &'static str,
+    pub quiver: &'static str,
+    // Over the arena during the chaos experiment
+    pub divergence: &'static str,
+    // The replay timeline, titled with the file's name
+    pub replay: fn(&str) -> String,
+    // While comparing: the other arena, titled with what it differs by and
+    // its ball count, and the chart of both, with the metric charted
+    pub compared: fn(&str, usize) -> String,
+    pub comparison: fn(&str) -> String,
+    // The zoom inset, titled with how much it magnifies
+    pub zoom: fn(u16) -> String,
+    pub time: &'static str,
+    pub lagging: &'static str,
+    // Status labels
+    pub status: &'static str,
+    pub balls: &'static str,
+    pub speed: &'static str,
+    pub profile: &'static str,
+    pub scene: &'static str,
+    pub game: &'static str,
+    pub score: &'static str,
+    pub aim: &'static str,
+    pub survived: &'static str,
+    pub power: &'static str,
+    pub level: &'static str,
+    pub goal: &'static str,
+    pub best: &'static str,
+    // A gas's collision statistics, measured and with theory's in brackets
+    pub gas: &'static str,
+    pub seen_theory: &'static str,
+    pub free_path: &'static str,
+    pub between: &'static str,
+    pub frequency: &'static str,
+    // Labels the latest collision's readout, while paused
+    pub hit: &'static str,
+    // Hits on balls and walls, in scenarios that count them
+    pub clicks: &'static str,
+    // Collisions per second over the charted history
+    pub hit_rate: &'static str,
+    pub running: &'static str,
+    pub paused: &'static str,
+    // Balls past the few listed
+    pub more: fn(usize) -> String,
+    // The energy bars' legend
+    pub kinetic: &'static str,
+    pub potential: &'static str,
+    // Controls panel hints, by what they do rather than which key
+    pub hints: &'static [(Action, &'static str)],
+    pub select_pane: &'static str,
+    pub pick_swap: &'static str,
+    pub width: &'static str,
+    pub height: &'static str,
+    pub reset_layout: &'static str,
+    // Puts another chart in the selected slot
+    pub change_pane: &'static str,
+    pub save_exit: &'static str,
+    pub move_cursor: &'static str,
+    pub toggle_wall: &'static str,
+    pub clear_walls: &'static str,
+    pub done: &'static str,
+    pub play_pause: &'static str,
+    pub step_tick: &'static str,
+    pub replay_speed: &'static str,
+    pub start_end: &'static str,
+    pub seek: &'static str,
+    pub aim_power: &'static str,
+    pub aim_hint: &'static str,
+    pub power_hint: &'static str,
+    pub shoot: &'static str,
+    pub aim_shoot: &'static str,
+    pub move_ball: &'static str,
+    pub play_again: &'static str,
+    pub stop: &'static str,
+    pub move_orange: &'static str,
+    pub move_blue: &'static str,
+    pub next_level: &'static str,
+    // While a pane has the focus
+    pub pick_ball: &'static str,
+    pub restyle: &'static str,
+    pub crosshair: &'static str,
+    pub unfocus: &'static str,
+    // Help screen
+    pub help_title: &'static str,
+    pub keys: &'static str,
+    pub commands: &'static str,
+    pub help: &'static [(Action, &'static str)],
+    // A parameter sweep's chart, titled with the metric and the parameter,
+    // and a note of runs that measured nothing
+    pub sweep: fn(&str, &str) -> String,
+    pub unsettled: fn(usize) -> String,
+    // The message shown in a terminal under the minimum size
+    pub resize: &'static str,
+    pub now: &'static str,
+}
+
+impl Strings {
+    /// The controls panel's word for `action`.
+    pub fn hint(&self, action: Action) -> &'static str {
+        self.hints.iter().find(|(a, _)| *a == action).map_or("", |&(_, hint)| hint)
+    }
+}
+
+pub static ENGLISH: Strings = Strings {
+    name: "en",
+    arena: |balls| format!(" Ball Arena ({} balls) ", balls),
+    controls: " Controls ",
+    x_position: " X Position Over Time ",
+    y_position: " Y Position Over Time ",
+    x_velocity: " X Velocity Over Time ",
+    y_velocity: " Y Velocity Over Time ",
+    quiver: " Velocities ",
+    divergence: " Twin Divergence ",
+    replay: |name| format!(" Replay: {} ", name),
+    compared: |change, balls| format!(" B: {} ({} balls) ", change, balls),
+    comparison: |metric| format!(" A vs B: {} ", metric),
+    zoom: |factor| format!(" Zoom {}x ", factor),
+    time: "Time",
+    lagging: " LAGGING ",
+    status: "Status:",
+    balls: "Balls:",
+    speed: "Speed:",
+    profile: "Profile:",
+    scene: "Scene:",
+    game: "Game:",
+    score: "Score:",
+    aim: "Aim:",
+    survived: "Time:",
+    power: "Power:",
+    level: "Level:",
+    goal: "Goal:",
+    best: "Best:",
+    gas: "Gas:",
+    seen_theory: "seen (theory)",
+    free_path: "Path:",
+    between: "Every:",
+    frequency: "Rate:",
+    hit: "Hit:",
+    clicks: "Clicks:",
+    hit_rate: "Hits/s:",
+    running: "RUNNING",
+    paused: "PAUSED",
+    more: |more| format!("... +{} more", more),
+    kinetic: "KE",
+    potential: "PE",
+    hints: &[
+        (Action::Pause, "Pause/Start"),
+        (Action::Step, "Step"),
+        (Action::AddBall, "Add ball"),
+        (Action::RemoveBall, "Remove ball"),
+        (Action::SpeedUp, "Speed up"),
+        (Action::SpeedDown, "Speed down"),
+        (Action::Scenarios, "Scenarios"),
+        (Action::Menu, "Menu"),
+        (Action::Profiles, "Profiles"),
+        (Action::EditLayout, "Edit layout"),
+        (Action::Help, "Help"),
+        (Action::Quit, "Quit"),
+        (Action::FocusNext, "Next pane"),
+    ],
+    select_pane: "Select pane",
+    pick_swap: "Pick / swap",
+    width: "Width -/+",
+    height: "Height -/+",
+    reset_layout: "Reset layout",
+    change_pane: "Change chart",
+    save_exit: "Save & exit",
+    move_cursor: "Move cursor",
+    toggle_wall: "Add/remove wall",
+    clear_walls: "Clear walls",
+    done: "Done",
+    play_pause: "Play/pause",
+    step_tick: "Step",
+    replay_speed: "Speed +/-",
+    start_end: "Start/end",
+    seek: "Seek",
+    aim_power: "Aim, power",
+    aim_hint: "Aim",
+    power_hint: "Power",
+    shoot: "Shoot",
+    aim_shoot: "Aim & shoot",
+    move_ball: "Move",
+    play_again: "Play again",
+    stop: "Stop",
+    move_orange: "Move orange",
+    move_blue: "Move blue",
+    next_level: "Next level",
+    pick_ball: "Pick ball",
+    restyle: "Color & glyph",
+    crosshair: "Crosshair",
+    unfocus: "Unfocus",
+    help_title: " Help  [any key] close ",
+    keys: "Keys",
+    commands: "Commands",
+    help: &[
+        (Action::Pause, "Pause / resume"),
+        (Action::Step, "Step once while paused"),
+        (Action::AddBall, "Add a ball"),
+        (Action::RemoveBall, "Remove the newest ball"),
+        (Action::SpeedUp, "Speed up"),
+        (Action::SpeedDown, "Slow down"),
+        (Action::Scenarios, "Load a scenario"),
+        (Action::Profiles, "Switch profile"),
+        (Action::Menu, "Sandbox, games and demos"),
+        (Action::EditLayout, "Edit the pane layout"),
+        (Action::EditObstacles, "Draw obstacles"),
+        (Action::Export, "Export the chart history"),
+        (Action::Settings, "Display settings"),
+        (Action::Themes, "Pick a theme"),
+        (Action::Flock, "Flocking weights"),
+        (Action::Profiler, "Frame timings"),
+        (Action::Described, "Described view, as text"),
+        (Action::Field, "Force field arrows"),
+        (Action::Zoom, "Zoom in on the picked ball"),
+        (Action::Paint, "Paint the balls' paths"),
+        (Action::ClearPaint, "Clear the painting"),
+        (Action::TurnGravity, "Turn gravity: down, left, up, right, center"),
+        (Action::FocusNext, "Focus the next pane"),
+        (Action::FocusPrevious, "Focus the previous pane"),
+        (Action::Palette, "Command palette"),
+        (Action::Help, "This help"),
+        (Action::Quit, "Quit"),
+    ],
+    sweep: |metric, parameter| format!(" Sweep: {} by {}  [any key] close ", metric, parameter),
+    unsettled: |runs| format!("Never settled: {}", runs),
+    resize: "Please resize to at least",
+    now: "now",
+};
+
+pub static SPANISH: Strings = Strings {
+    name: "es",
+    arena: |balls| format!(" Arena ({} bolas) ", balls),
+    controls: " Controles ",
+    x_position: " Posición X en el tiempo ",
+    y_position: " Posición Y en el tiempo ",
+    x_velocity: " Velocidad X en el tiempo ",
+    y_velocity: " Velocidad Y en el tiempo ",
+    quiver: " Velocidades ",
+    divergence: " Divergencia del gemelo ",
+    replay: |name| format!(" Repetición: {} ", name),
+    compared: |change, balls| format!(" B: {} ({} bolas) ", change, balls),
+    comparison: |metric| format!(" A frente a B: {} ", metric),
+    zoom: |factor| format!(" Lupa {}x ", factor),
+    time: "Tiempo",
+    lagging: " CON RETRASO ",
+    status: "Estado:",
+    balls: "Bolas:",
+    speed: "Ritmo:",
+    profile: "Perfil:",
+    scene: "Escena:",
+    game: "Juego:",
+    score: "Puntos:",
+    aim: "Mira:",
+    survived: "Tiempo:",
+    power: "Poder:",
+    level: "Nivel:",
+    goal: "Meta:",
+    best: "Récord:",
+    gas: "Gas:",
+    seen_theory: "medido (teoría)",
+    free_path: "Camino:",
+    between: "Cada:",
+    frequency: "Tasa:",
+    hit: "Choque:",
+    clicks: "Clics:",
+    hit_rate: "Choq/s:",
+    running: "EN MARCHA",
+    paused: "EN PAUSA",
+    more: |more| format!("... y {} más", more),
+    kinetic: "EC",
+    potential: "EP",
+    hints: &[
+        (Action::Pause, "Pausa/Inicio"),
+        (Action::Step, "Un paso"),
+        (Action::AddBall, "Añadir bola"),
+        (Action::RemoveBall, "Quitar bola"),
+        (Action::SpeedUp, "Más rápido"),
+        (Action::SpeedDown, "Más lento"),
+        (Action::Scenarios, "Escenarios"),
+        (Action::Menu, "Menú"),
+        (Action::Profiles, "Perfiles"),
+        (Action::EditLayout, "Paneles"),
+        (Action::Help, "Ayuda"),
+        (Action::Quit, "Salir"),
+        (Action::FocusNext, "Otro panel"),
+    ],
+    select_pane: "Elegir panel",
+    pick_swap: "Tomar/cambiar",
+    width: "Ancho -/+",
+    height: "Alto -/+",
+    reset_layout: "Restablecer",
+    change_pane: "Otro gráfico",
+    save_exit: "Guardar y salir",
+    move_cursor: "Mover cursor",
+    toggle_wall: "Poner/quitar muro",
+    clear_walls: "Borrar muros",
+    done: "Listo",
+    play_pause: "Reproducir/pausa",
+    step_tick: "Paso",
+    replay_speed: "Ritmo +/-",
+    start_end: "Inicio/fin",
+    seek: "Buscar",
+    aim_power: "Mira, fuerza",
+    aim_hint: "Apuntar",
+    power_hint: "Fuerza",
+    shoot: "Tirar",
+    aim_shoot: "Apuntar y tirar",
+    move_ball: "Mover",
+    play_again: "Otra vez",
+    stop: "Parar",
+    move_orange: "Mover naranja",
+    move_blue: "Mover azul",
+    next_level: "Siguiente nivel",
+    pick_ball: "Elegir bola",
+    restyle: "Color y forma",
+    crosshair: "Mover cursor",
+    unfocus: "Soltar foco",
+    help_title: " Ayuda  [cualquier tecla] cerrar ",
+    keys: "Teclas",
+    commands: "Comandos",
+    help: &[
+        (Action::Pause, "Pausar / reanudar"),
+        (Action::Step, "Un paso, en pausa"),
+        (Action::AddBall, "Añadir una bola"),
+        (Action::RemoveBall, "Quitar la bola más nueva"),
+        (Action::SpeedUp, "Más rápido"),
+        (Action::SpeedDown, "Más lento"),
+        (Action::Scenarios, "Cargar un escenario"),
+        (Action::Profiles, "Cambiar de perfil"),
+        (Action::Menu, "Juego libre, juegos y demos"),
+        (Action::EditLayout, "Ordenar los paneles"),
+        (Action::EditObstacles, "Dibujar obstáculos"),
+        (Action::Export, "Exportar el historial"),
+        (Action::Settings, "Ajustes de pantalla"),
+        (Action::Themes, "Elegir un tema"),
+        (Action::Flock, "Pesos de la bandada"),
+        (Action::Profiler, "Tiempos por fotograma"),
+        (Action::Described, "Vista descrita, en texto"),
+        (Action::Field, "Flechas del campo de fuerzas"),
+        (Action::Zoom, "Lupa sobre la bola elegida"),
+        (Action::Paint, "Pintar los recorridos"),
+        (Action::ClearPaint, "Borrar la pintura"),
+        (Action::TurnGravity, "Girar la gravedad: abajo, izquierda, arriba, derecha, centro"),
+        (Action::FocusNext, "Enfocar el panel siguiente"),
+        (Action::FocusPrevious, "Enfocar el panel anterior"),
+        (Action::Palette, "Paleta de comandos"),
+        (Action::Help, "Esta ayuda"),
+        (Action::Quit, "Salir"),
+    ],
+    sweep: |metric, parameter| format!(" Barrido: {} según {}  [cualquier tecla] cerrar ", metric, parameter),
+    unsettled: |runs| format!("Sin asentarse: {}", runs),
+    resize: "Agranda la terminal al menos a",
+    now: "ahora",
+};
+
+pub static LANGUAGES: &[&Strings] = &[&ENGLISH, &SPANISH];
+
+/// Values `display.language` takes: `auto` or a language's name.
+pub const LANGUAGE_NAMES: [&str; 3] = ["auto", "en", "es"];
+
+/// The language called `name`, or for `auto` the one the environment asks
+/// for, falling back to English.
+pub fn by_name(name: &str) -> Option<&'static Strings> {
+    if name == "auto" {
+        let env = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty());
+        return Some(env.and_then(|value| from_locale(&value)).unwrap_or(&ENGLISH));
+    }
+    LANGUAGES.iter().copied().find(|strings| strings.name == name)
+}
+
+/// The language of a POSIX locale like `es_MX.UTF-8`, if there's one.
+fn from_locale(locale: &str) -> Option<&'static Strings> {
+    let language = locale.split(['_', '.', '@', '-']).next()?;
+    LANGUAGES.iter().copied().find(|strings| strings.name == language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_language_covers_every_key() {
+        for strings in LANGUAGES {
+            let actions = |table: &[(Action, &str)]| table.iter().map(|&(action, _)| action).collect::<Vec<_>>();
+            assert_eq!(actions(strings.help), actions(ENGLISH.help), "{}", strings.name);
+            assert_eq!(actions(strings.hints), actions(ENGLISH.hints), "{}", strings.name);
+            let labels = [
+                strings.status,
+                strings.balls,
+                strings.speed,
+                strings.profile,
+                strings.scene,
+                strings.game,
+                strings.score,
+                strings.aim,
+                strings.survived,
+                strings.power,
+                strings.level,
+                strings.goal,
+                strings.best,
+            ];
+            for label in labels {
+                assert!(label.chars().count() <= 8, "{}: {}", strings.name, label);
+            }
+        }
+    }
+
+    #[test]
+    fn locales_pick_their_language() {
+        assert_eq!(from_locale("es_MX.UTF-8").map(|s| s.name), Some("es"));
+        assert_eq!(from_locale("en").map(|s| s.name), Some("en"));
+        assert!(from_locale("C.UTF-8").is_none());
+        assert!(by_name("fr").is_none());
+    }
+}
@@ -0,0 +1,62 @@
+//! `--log PATH`: [`tracing`] output to a file, since the terminal is taken
+//! by the UI. `info` logs user actions, `debug` adds keys and simulation
+//! events, and `trace` times every tick and frame. The palette's `log`
+//! command changes the level while the app runs.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use rust_tui_ball_bounce::{SimEvent, SimulationObserver};
+use tracing::{debug, trace};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, Registry};
+
+/// Changes the level of the subscriber [`init`] installed.
+pub struct LogLevel {
+    handle: reload::Handle<LevelFilter, Registry>,
+}
+
+impl LogLevel {
+    pub fn set(&self, level: LevelFilter) -> Result<(), String> {
+        self.handle.modify(|filter| *filter = level).map_err(|err| err.to_string())
+    }
+}
+
+/// Sends everything at `level` and above to the file at `path`, appending
+/// so one file can follow several runs.
+pub fn init(path: &Path, level: LevelFilter) -> io::Result<LogLevel> {
+    let file = File::options().create(true).append(true).open(path)?;
+    let (filter, handle) = reload::Layer::new(level);
+    let format = tracing_subscriber::fmt::layer()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        // Span closes carry the tick and frame timings
+        .with_span_events(FmtSpan::CLOSE);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(format)
+        .try_init()
+        .map_err(io::Error::other)?;
+    Ok(LogLevel { handle })
+}
+
+/// Logs simulation events, for the app's observers.
+pub struct EventLog;
+
+impl SimulationObserver for EventLog {
+    fn on_event(&mut self, event: &SimEvent) {
+        match *event {
+            SimEvent::Collision { a, b, impulse, .. } => debug!(a, b, impulse, "collision"),
+            SimEvent::WallBounce { id } => trace!(id, "wall bounce"),
+            SimEvent::Spawned { id } => debug!(id, "spawned"),
+            SimEvent::Removed { id } => debug!(id, "removed"),
+            SimEvent::Expired { id } => debug!(id, "expired"),
+            SimEvent::Pocketed { id } => debug!(id, "pocketed"),
+            _ => debug!(?event),
+        }
+    }
+}
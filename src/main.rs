@@ -1,672 +1,476 @@
-use std::io;
-use std::time::{Duration, Instant};
-
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    symbols,
-    text::{Line, Span},
-    widgets::{Axis, Block, Borders, Chart, Dataset, Paragraph},
-    Terminal,
-};
-
-const TICK_RATE: Duration = Duration::from_millis(16); // ~60 FPS
-const BALL_CHARS: &[&str] = &["●", "◉", "○", "◎", "◆", "■", "▲", "★"];
-const MAX_HISTORY: usize = 300;
-
-const BALL_COLORS: &[Color] = &[
-    Color::Yellow,
-    Color::Green,
-    Color::Red,
-    Color::Blue,
-    Color::Magenta,
-    Color::Cyan,
-    Color::LightRed,
-    Color::LightGreen,
-];
-
-const BALL_RADIUS: f64 = 0.75;
-
-struct Ball {
-    x: f64,
-    y: f64,
-    vx: f64,
-    vy: f64,
-    radius: f64,
-    color: Color,
-    char_idx: usize,
-    x_history: Vec<(f64, f64)>,
-    y_history: Vec<(f64, f64)>,
-    vx_history: Vec<(f64, f64)>,
-    vy_history: Vec<(f64, f64)>,
-}
-
-impl Ball {
-    fn new(x: f64, y: f64, vx: f64, vy: f64, index: usize) -> Self {
-        Ball {
-            x,
-            y,
-            vx,
-            vy,
-            radius: BALL_RADIUS,
-            color: BALL_COLORS[index % BALL_COLORS.len()],
-            char_idx: index % BALL_CHARS.len(),
-            x_history: Vec::new(),
-            y_history: Vec::new(),
-            vx_history: Vec::new(),
-            vy_history: Vec::new(),
+mod app;
+#[cfg(unix)]
+mod attach;
+mod bell;
+mod caps;
+mod cli;
+mod clip;
+mod compare;
+mod config;
+mod conservation;
+mod control;
+mod describe;
+mod export;
+mod game;
+mod gas;
+mod headless;
+mod import;
+mod input;
+mod keys;
+mod lag;
+mod locale;
+mod layout;
+mod logging;
+mod map;
+mod markers;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod mode;
+mod paint;
+mod palette;
+mod picker;
+mod profiler;
+mod pipe;
+mod record;
+mod replay;
+mod report;
+mod scores;
+#[cfg(feature = "websocket")]
+mod serve;
+#[cfg(feature = "scripting")]
+mod script;
+mod share;
+mod sim_thread;
+mod sonify;
+mod splash;
+mod sweep;
+mod term;
+mod theme;
+mod twin;
+mod ui;
+mod units;
+mod viewer;
+mod wizard;
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal};
+use std::mem;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use futures::StreamExt;
+use tokio::time::{self, MissedTickBehavior};
+
+use app::{App, AppBuilder};
+use bell::Bell;
+use clap::{CommandFactory, Parser};
+use cli::{Cli, Command};
+use caps::{Caps, Glyphs};
+use config::Config;
+use control::ControlServer;
+use game::Game;
+use lag::TickMeter;
+use logging::{EventLog, LogLevel};
+use mode::Mode;
+use pipe::Pipe;
+use share::{Guest, ShareServer};
+use record::CastRecorder;
+use rust_tui_ball_bounce::clock::{Clock, SystemClock};
+use rust_tui_ball_bounce::scenario::{self, Scenario};
+use rust_tui_ball_bounce::Simulation;
+use sonify::{Midi, Osc, Sonifier};
+use splash::Splash;
+use term::{Input, SystemTerm, Term, TermGuard};
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
         }
     }
 }
 
-struct App {
-    balls: Vec<Ball>,
-    paused: bool,
-    tick_count: u64,
-    ball_counter: usize, // total balls ever created, for unique color/char assignment
-    area_width: f64,
-    area_height: f64,
-    speed_multiplier: f64,
-}
-
-impl App {
-    fn new() -> App {
-        let mut app = App {
-            balls: Vec::new(),
-            paused: false,
-            tick_count: 0,
-            ball_counter: 0,
-            area_width: 80.0,
-            area_height: 20.0,
-            speed_multiplier: 1.0,
-        };
-        app.add_ball();
-        app
+fn run() -> Result<()> {
+    let cli = Cli::parse();
+    if let Some(Command::Completions { shell }) = cli.command {
+        let mut command = Cli::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+        return Ok(());
     }
-
-    fn add_ball(&mut self) {
-        // Vary initial position and velocity so balls don't overlap
-        let idx = self.ball_counter;
-        let x = 5.0 + (idx as f64 * 7.3) % self.area_width.max(20.0);
-        let y = 3.0 + (idx as f64 * 4.1) % self.area_height.max(10.0);
-        let vx = 0.5 + (idx as f64 * 0.17) % 0.8;
-        let vy = 0.3 + (idx as f64 * 0.13) % 0.6;
-        // Alternate directions
-        let vx = if idx % 2 == 0 { vx } else { -vx };
-        let vy = if idx % 3 == 0 { vy } else { -vy };
-
-        self.balls.push(Ball::new(x, y, vx, vy, idx));
-        self.ball_counter += 1;
+    let log_level = match &cli.log {
+        Some(path) => Some(
+            logging::init(path, cli.log_level).with_context(|| format!("could not log to {}", path.display()))?,
+        ),
+        None => None,
+    };
+    let config_path = cli.config.clone().or_else(config::config_path);
+
+    let base_config = match &config_path {
+        Some(path) => config::load(path)?,
+        None => Config::default(),
+    };
+
+    if cli.dump_config {
+        print!("{}", effective_config(&base_config, &cli)?.to_toml());
+        return Ok(());
     }
 
-    fn remove_ball(&mut self) {
-        if !self.balls.is_empty() {
-            self.balls.pop();
-        }
+    let scenario = cli.scenario.as_deref().map(scenario::resolve).transpose()?;
+    let map = match &cli.map {
+        Some(path) => Some(fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?),
+        None => None,
+    };
+
+    if let Some(Command::Export { ticks, arena, path }) = &cli.command {
+        let config = effective_config(&base_config, &cli)?;
+        let mut sim = build_sim(&config, *arena, scenario.as_ref(), map.as_deref());
+        let tick_rate = Duration::from_millis(config.simulation.tick_ms);
+        return headless::export(&mut sim, *ticks, tick_rate, path);
     }
 
-    fn speed_up(&mut self) {
-        self.speed_multiplier = (self.speed_multiplier + 0.25).min(5.0);
+    if let Some(Command::Clip { ticks, every, arena, path }) = &cli.command {
+        let config = effective_config(&base_config, &cli)?;
+        let mut sim = build_sim(&config, *arena, scenario.as_ref(), map.as_deref());
+        let tick_rate = Duration::from_millis(config.simulation.tick_ms);
+        return headless::clip(&mut sim, *ticks, *every, tick_rate, path);
     }
 
-    fn speed_down(&mut self) {
-        self.speed_multiplier = (self.speed_multiplier - 0.25).max(0.25);
+    if cli.headless {
+        let config = effective_config(&base_config, &cli)?;
+        let mut sim = build_sim(&config, cli.arena, scenario.as_ref(), map.as_deref());
+        return headless::run(&mut sim, &cli);
     }
 
-    fn tick(&mut self) {
-        if self.paused {
-            return;
-        }
-
-        self.tick_count += 1;
-        let t = self.tick_count as f64;
-
-        // Update positions
-        for ball in &mut self.balls {
-            ball.x += ball.vx * self.speed_multiplier;
-            ball.y += ball.vy * self.speed_multiplier;
-        }
-
-        // Ball-to-ball elastic collisions
-        let n = self.balls.len();
-        for i in 0..n {
-            for j in (i + 1)..n {
-                let dx = self.balls[j].x - self.balls[i].x;
-                let dy = self.balls[j].y - self.balls[i].y;
-                let dist_sq = dx * dx + dy * dy;
-                let min_dist = self.balls[i].radius + self.balls[j].radius;
-
-                if dist_sq < min_dist * min_dist && dist_sq > 0.0 {
-                    let dist = dist_sq.sqrt();
-                    // Collision normal
-                    let nx = dx / dist;
-                    let ny = dy / dist;
-
-                    // Relative velocity along collision normal
-                    let dvx = self.balls[i].vx - self.balls[j].vx;
-                    let dvy = self.balls[i].vy - self.balls[j].vy;
-                    let dvn = dvx * nx + dvy * ny;
-
-                    // Only resolve if balls are moving toward each other
-                    if dvn > 0.0 {
-                        // Equal mass elastic collision: swap normal components
-                        self.balls[i].vx -= dvn * nx;
-                        self.balls[i].vy -= dvn * ny;
-                        self.balls[j].vx += dvn * nx;
-                        self.balls[j].vy += dvn * ny;
-                    }
-
-                    // Separate overlapping balls
-                    let overlap = min_dist - dist;
-                    let sep = overlap / 2.0 + 0.01;
-                    self.balls[i].x -= sep * nx;
-                    self.balls[i].y -= sep * ny;
-                    self.balls[j].x += sep * nx;
-                    self.balls[j].y += sep * ny;
-                }
-            }
-        }
-
-        // Wall bounces and history recording
-        let w = self.area_width;
-        let h = self.area_height;
-        for ball in &mut self.balls {
-            if ball.x <= 0.0 {
-                ball.x = 0.0;
-                ball.vx = ball.vx.abs();
-            }
-            if ball.x >= w - 1.0 {
-                ball.x = w - 1.0;
-                ball.vx = -ball.vx.abs();
-            }
-            if ball.y <= 0.0 {
-                ball.y = 0.0;
-                ball.vy = ball.vy.abs();
-            }
-            if ball.y >= h - 1.0 {
-                ball.y = h - 1.0;
-                ball.vy = -ball.vy.abs();
-            }
-
-            ball.x_history.push((t, ball.x));
-            ball.y_history.push((t, ball.y));
-            ball.vx_history.push((t, ball.vx));
-            ball.vy_history.push((t, ball.vy));
-
-            if ball.x_history.len() > MAX_HISTORY {
-                ball.x_history.remove(0);
-            }
-            if ball.y_history.len() > MAX_HISTORY {
-                ball.y_history.remove(0);
-            }
-            if ball.vx_history.len() > MAX_HISTORY {
-                ball.vx_history.remove(0);
-            }
-            if ball.vy_history.len() > MAX_HISTORY {
-                ball.vy_history.remove(0);
-            }
-        }
+    if cli.pipe {
+        term::draw_to_stderr();
     }
-}
-
-fn main() -> Result<(), io::Error> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    let res = run_app(&mut terminal);
-
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
-    if let Err(err) = res {
-        eprintln!("Error: {:?}", err);
+    if let Some(Command::Join { addr }) = &cli.command {
+        let config = effective_config(&base_config, &cli)?;
+        let guest = Guest::connect(addr).with_context(|| format!("could not connect to {}", addr))?;
+        term::install_panic_hook();
+        let term = SystemTerm::new().context("could not set up the terminal")?;
+        return viewer::run(guest, &mut *TermGuard::new(term), &config);
+    }
+    #[cfg(unix)]
+    if let Some(Command::Attach { path }) = &cli.command {
+        let config = effective_config(&base_config, &cli)?;
+        let watcher = attach::Viewer::connect(path)
+            .with_context(|| format!("could not connect to {}", path.display()))?;
+        term::install_panic_hook();
+        let term = SystemTerm::new().context("could not set up the terminal")?;
+        return viewer::run(watcher, &mut *TermGuard::new(term), &config);
     }
 
-    Ok(())
+    term::install_panic_hook();
+    let term = SystemTerm::new().context("could not set up the terminal")?;
+    // Dropped on the way out of this function, so the terminal is back to
+    // normal before `main` prints any error
+    let mut term = TermGuard::new(term);
+    run_tui(&mut *term, &cli, base_config, config_path, scenario.as_ref(), map.as_deref(), log_level)
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(), io::Error> {
-    let mut app = App::new();
-    let mut last_tick = Instant::now();
-
-    loop {
-        terminal.draw(|f| ui(f, &mut app))?;
+/// Layers the profile, environment, and command-line flags over the config file.
+fn effective_config(base_config: &Config, cli: &Cli) -> Result<Config> {
+    let mut config = match &cli.profile {
+        Some(name) => base_config.with_profile(name).map_err(anyhow::Error::msg)?,
+        None => base_config.clone(),
+    };
+    config.apply_env();
+    config.apply_cli(cli);
+    Ok(config)
+}
 
-        let timeout = TICK_RATE
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+/// The simulation to start with: the config's, or the scenario's, with the
+/// walls in the `map` text instead when there is one.
+fn build_sim(config: &Config, arena: Option<(u16, u16)>, scenario: Option<&Scenario>, map: Option<&str>) -> Simulation {
+    let mut builder = app::sim_builder(config);
+    let aspect = if config.display.aspect_correction { 2.0 } else { 1.0 };
+    if let Some((width, height)) = arena {
+        builder = builder.arena(width as f64, height as f64 * aspect);
+    }
+    if let Some(scenario) = scenario {
+        builder = builder.scenario(scenario);
+    }
+    if let Some(text) = map {
+        builder = builder.obstacles(map::parse(text, aspect));
+    }
+    builder.build()
+}
 
-        if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                    KeyCode::Char(' ') | KeyCode::Char('p') => {
-                        app.paused = !app.paused;
-                    }
-                    KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Char('a') => {
-                        app.add_ball();
-                    }
-                    KeyCode::Char('-') | KeyCode::Char('_') | KeyCode::Char('r') => {
-                        app.remove_ball();
-                    }
-                    KeyCode::Up => {
-                        app.speed_up();
-                    }
-                    KeyCode::Down => {
-                        app.speed_down();
-                    }
-                    _ => {}
-                }
-            }
+fn run_tui(
+    term: &mut impl Term,
+    cli: &Cli,
+    mut base_config: Config,
+    config_path: Option<PathBuf>,
+    scenario: Option<&Scenario>,
+    map: Option<&str>,
+    log_level: Option<LogLevel>,
+) -> Result<()> {
+    // Try the fancier glyphs on the terminal unless told which to use
+    let display = effective_config(&base_config, cli)?.display;
+    let caps = if display.ascii || display.glyphs != "auto" { Caps::detect() } else { Caps::detect().probe(term) };
+
+    let mut notice = None;
+    if cli.setup || wizard::is_first_run(config_path.as_deref()) {
+        // Start the glyphs step on ASCII where the terminal fell back to it
+        base_config.display.ascii |= caps.glyphs == Glyphs::Ascii;
+        let Some(config) = wizard::run(term, base_config)? else {
+            return Ok(());
+        };
+        if let Some(path) = &config_path {
+            notice = Some(match config::save(path, &config) {
+                Ok(()) => format!("Saved {}", path.display()),
+                Err(err) => format!("Config not saved: {}", err),
+            });
         }
+        base_config = config;
+    }
 
-        if last_tick.elapsed() >= TICK_RATE {
-            app.tick();
-            last_tick = Instant::now();
+    let recorder = match &cli.record {
+        Some(path) => {
+            let file = File::create(path).with_context(|| format!("could not create {}", path.display()))?;
+            Some(CastRecorder::new(BufWriter::new(file), SystemClock::new()))
         }
+        None => None,
+    };
+
+    #[cfg(unix)]
+    let control = match &cli.control {
+        Some(path) => ControlServer::bind(path)
+            .with_context(|| format!("could not listen on {}", path.display()))?,
+        None => ControlServer::disabled(),
+    };
+    #[cfg(not(unix))]
+    let control = ControlServer::disabled();
+    if cli.stdin_commands || !io::stdin().is_terminal() {
+        control.read_stdin();
     }
-}
-
-fn ui(f: &mut ratatui::Frame, app: &mut App) {
-    let size = f.area();
-
-    let main_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage(34),
-            Constraint::Percentage(33),
-            Constraint::Percentage(33),
-        ])
-        .split(size);
-
-    let top_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Min(20),
-            Constraint::Length(32),
-        ])
-        .split(main_chunks[0]);
-
-    let mid_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(50),
-            Constraint::Percentage(50),
-        ])
-        .split(main_chunks[1]);
-
-    let bottom_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(50),
-            Constraint::Percentage(50),
-        ])
-        .split(main_chunks[2]);
-
-    let ball_area = top_chunks[0];
-    let inner_width = if ball_area.width > 2 { ball_area.width - 2 } else { 1 };
-    let inner_height = if ball_area.height > 2 { ball_area.height - 2 } else { 1 };
-    app.area_width = inner_width as f64;
-    app.area_height = inner_height as f64;
 
-    draw_ball_arena(f, app, ball_area);
-    draw_status(f, app, top_chunks[1]);
-    draw_x_graph(f, app, mid_chunks[0]);
-    draw_y_graph(f, app, mid_chunks[1]);
-    draw_vx_graph(f, app, bottom_chunks[0]);
-    draw_vy_graph(f, app, bottom_chunks[1]);
-}
-
-fn draw_ball_arena(f: &mut ratatui::Frame, app: &App, area: Rect) {
-    let block = Block::default()
-        .title(format!(" Ball Arena ({} balls) ", app.balls.len()))
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
-
-    let inner = block.inner(area);
-    f.render_widget(block, area);
-
-    for ball in &app.balls {
-        let bx = ball.x.round() as u16;
-        let by = ball.y.round() as u16;
-
-        if bx < inner.width && by < inner.height {
-            let ball_rect = Rect::new(inner.x + bx, inner.y + by, 1, 1);
-            let ball_widget = Paragraph::new(BALL_CHARS[ball.char_idx])
-                .style(Style::default().fg(ball.color).add_modifier(Modifier::BOLD));
-            f.render_widget(ball_widget, ball_rect);
+    #[cfg(feature = "websocket")]
+    let server = match &cli.serve {
+        Some(addr) => {
+            let server = serve::WebSocketServer::bind(addr.as_str())
+                .with_context(|| format!("could not listen on {}", addr))?;
+            notice.get_or_insert_with(|| format!("Serving on ws://{}", addr));
+            Some(server)
         }
+        None => None,
+    };
+
+    let share = match &cli.share {
+        Some(addr) => {
+            let server = ShareServer::bind(addr.as_str()).with_context(|| format!("could not listen on {}", addr))?;
+            notice.get_or_insert_with(|| format!("Sharing on {}", addr));
+            server
+        }
+        None => ShareServer::disabled(),
+    };
+
+    #[cfg(feature = "metrics")]
+    let metrics = match &cli.metrics {
+        Some(addr) => Some(
+            metrics::MetricsServer::bind(addr, SystemClock::new())
+                .with_context(|| format!("could not listen on {}", addr))?,
+        ),
+        None => None,
+    };
+
+    let midi = match &cli.midi {
+        Some(path) => {
+            let file = File::create(path).with_context(|| format!("could not open {}", path.display()))?;
+            Some(Sonifier::new(Midi(file)))
+        }
+        None => None,
+    };
+    let osc = match &cli.osc {
+        Some(addr) => Some(Sonifier::new(
+            Osc::connect(addr.as_str()).with_context(|| format!("could not send to {}", addr))?,
+        )),
+        None => None,
+    };
+
+    let config = effective_config(&base_config, cli)?;
+    let mut app = AppBuilder::new(build_sim(&config, cli.arena, scenario, map))
+        .config(&config)
+        .base_config(base_config)
+        .profile(cli.profile.clone())
+        .config_path(config_path)
+        .notice(notice)
+        .caps(caps)
+        .build();
+    if log_level.is_some() {
+        app.observers.push(Box::new(EventLog));
     }
-}
-
-fn draw_status(f: &mut ratatui::Frame, app: &App, area: Rect) {
-    let status = if app.paused { "⏸  PAUSED" } else { "▶  RUNNING" };
-    let status_color = if app.paused { Color::Red } else { Color::Green };
-
-    let mut text = vec![
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  Status: ", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::styled(status, Style::default().fg(status_color).add_modifier(Modifier::BOLD)),
-        ]),
-        Line::from(vec![
-            Span::styled("  Balls:  ", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::styled(format!("{}", app.balls.len()), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-        ]),
-        Line::from(vec![
-            Span::styled("  Speed:  ", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::styled(format!("{:.2}x", app.speed_multiplier), Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD)),
-        ]),
-        Line::from(""),
-    ];
-
-    // Show info for up to 4 balls
-    for (_i, ball) in app.balls.iter().enumerate().take(4) {
-        text.push(Line::from(vec![
-            Span::styled(
-                format!("  {} ", BALL_CHARS[ball.char_idx]),
-                Style::default().fg(ball.color),
-            ),
-            Span::styled(
-                format!("x:{:.0} y:{:.0}", ball.x, ball.y),
-                Style::default().fg(Color::DarkGray),
-            ),
-        ]));
+    app.log_level = log_level;
+    if cli.stress.is_some() {
+        app.tick_meter = Some(TickMeter::default());
     }
-    if app.balls.len() > 4 {
-        text.push(Line::from(Span::styled(
-            format!("  ... +{} more", app.balls.len() - 4),
-            Style::default().fg(Color::DarkGray),
-        )));
+    if cli.no_history {
+        app.max_history = 0;
     }
-
-    text.push(Line::from(""));
-    text.push(Line::from(Span::styled(
-        "  ────────────────────────",
-        Style::default().fg(Color::DarkGray),
-    )));
-    text.push(Line::from(""));
-    text.push(Line::from(Span::styled(
-        "  [Space/P]  Pause/Start",
-        Style::default().fg(Color::Yellow),
-    )));
-    text.push(Line::from(Span::styled(
-        "  [+/A]      Add ball",
-        Style::default().fg(Color::Green),
-    )));
-    text.push(Line::from(Span::styled(
-        "  [-/R]      Remove ball",
-        Style::default().fg(Color::Red),
-    )));
-    text.push(Line::from(Span::styled(
-        "  [↑]        Speed up",
-        Style::default().fg(Color::LightGreen),
-    )));
-    text.push(Line::from(Span::styled(
-        "  [↓]        Speed down",
-        Style::default().fg(Color::LightRed),
-    )));
-    text.push(Line::from(Span::styled(
-        "  [Q/Esc]    Quit",
-        Style::default().fg(Color::Yellow),
-    )));
-
-    let paragraph = Paragraph::new(text).block(
-        Block::default()
-            .title(" Controls ")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Yellow)),
-    );
-
-    f.render_widget(paragraph, area);
-}
-
-fn draw_x_graph(f: &mut ratatui::Frame, app: &App, area: Rect) {
-    // Compute global time bounds
-    let (t_min, t_max) = global_time_bounds(app);
-    let x_max = app.area_width.max(1.0);
-
-    let datasets: Vec<Dataset> = app
-        .balls
-        .iter()
-        .enumerate()
-        .map(|(i, ball)| {
-            Dataset::default()
-                .name(format!("B{}", i + 1))
-                .marker(symbols::Marker::Braille)
-                .style(Style::default().fg(ball.color))
-                .data(&ball.x_history)
-        })
-        .collect();
-
-    let chart = Chart::new(datasets)
-        .block(
-            Block::default()
-                .title(" X Position Over Time ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
-        )
-        .x_axis(
-            Axis::default()
-                .title("Time")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([t_min, t_max])
-                .labels(vec![
-                    Span::raw(format!("{:.0}", t_min)),
-                    Span::raw(format!("{:.0}", t_max)),
-                ]),
-        )
-        .y_axis(
-            Axis::default()
-                .title("X")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([0.0, x_max])
-                .labels(vec![
-                    Span::raw("0"),
-                    Span::raw(format!("{:.0}", x_max)),
-                ]),
-        );
-
-    f.render_widget(chart, area);
-}
-
-fn draw_y_graph(f: &mut ratatui::Frame, app: &App, area: Rect) {
-    let (t_min, t_max) = global_time_bounds(app);
-    let y_max = app.area_height.max(1.0);
-
-    let datasets: Vec<Dataset> = app
-        .balls
-        .iter()
-        .enumerate()
-        .map(|(i, ball)| {
-            Dataset::default()
-                .name(format!("B{}", i + 1))
-                .marker(symbols::Marker::Braille)
-                .style(Style::default().fg(ball.color))
-                .data(&ball.y_history)
-        })
-        .collect();
-
-    let chart = Chart::new(datasets)
-        .block(
-            Block::default()
-                .title(" Y Position Over Time ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Magenta)),
-        )
-        .x_axis(
-            Axis::default()
-                .title("Time")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([t_min, t_max])
-                .labels(vec![
-                    Span::raw(format!("{:.0}", t_min)),
-                    Span::raw(format!("{:.0}", t_max)),
-                ]),
-        )
-        .y_axis(
-            Axis::default()
-                .title("Y")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([0.0, y_max])
-                .labels(vec![
-                    Span::raw("0"),
-                    Span::raw(format!("{:.0}", y_max)),
-                ]),
-        );
-
-    f.render_widget(chart, area);
-}
-
-fn draw_vx_graph(f: &mut ratatui::Frame, app: &App, area: Rect) {
-    let (t_min, t_max) = global_time_bounds(app);
-    let (v_min, v_max) = velocity_bounds(app, true);
-
-    let datasets: Vec<Dataset> = app
-        .balls
-        .iter()
-        .enumerate()
-        .map(|(i, ball)| {
-            Dataset::default()
-                .name(format!("B{}", i + 1))
-                .marker(symbols::Marker::Braille)
-                .style(Style::default().fg(ball.color))
-                .data(&ball.vx_history)
-        })
-        .collect();
-
-    let chart = Chart::new(datasets)
-        .block(
-            Block::default()
-                .title(" X Velocity Over Time ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::LightCyan)),
-        )
-        .x_axis(
-            Axis::default()
-                .title("Time")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([t_min, t_max])
-                .labels(vec![
-                    Span::raw(format!("{:.0}", t_min)),
-                    Span::raw(format!("{:.0}", t_max)),
-                ]),
-        )
-        .y_axis(
-            Axis::default()
-                .title("Vx")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([v_min, v_max])
-                .labels(vec![
-                    Span::raw(format!("{:.1}", v_min)),
-                    Span::raw("0"),
-                    Span::raw(format!("{:.1}", v_max)),
-                ]),
-        );
-
-    f.render_widget(chart, area);
+    if let Some(game) = cli.game.as_deref().and_then(Game::by_name) {
+        app.start_game(game);
+        app.run_commands();
+    }
+    if config.display.start_menu {
+        app.mode = app.menu();
+    }
+    if config.display.splash && !app.described {
+        let size = term.terminal().size()?;
+        let then = mem::replace(&mut app.mode, Mode::Running);
+        app.mode = Mode::Splash(Box::new(Splash::new(size.width, size.height, app.reduced_motion, then)));
+    }
+    if let Some(midi) = midi {
+        app.observers.push(Box::new(midi));
+    }
+    if let Some(osc) = osc {
+        app.observers.push(Box::new(osc));
+    }
+    if config.sound.bell {
+        app.observers.push(Box::new(Bell::new(term::output(), SystemClock::new(), &config.sound)));
+    }
+    if cli.pipe {
+        app.observers.push(Box::new(Pipe::new(io::stdout())));
+    }
+    #[cfg(unix)]
+    if cli.control.is_some() {
+        app.state_stream = Some(control.state_stream());
+    }
+    if cli.share.is_some() {
+        app.observers.push(Box::new(share.broadcaster()));
+    }
+    #[cfg(feature = "websocket")]
+    if let Some(server) = server {
+        app.observers.push(Box::new(server));
+    }
+    #[cfg(feature = "metrics")]
+    if let Some(metrics) = metrics {
+        app.observers.push(Box::new(metrics));
+    }
+    #[cfg(feature = "scripting")]
+    if let Some(path) = &cli.script {
+        // Draw once first so the script sees the arena at its real size
+        term.terminal().draw(|f| ui::draw(f, &mut app))?;
+        app.load_script(path)
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("could not run {}", path.display()))?;
+    }
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()?
+        .block_on(run_app(term, &mut app, recorder, control, share))
 }
 
-fn draw_vy_graph(f: &mut ratatui::Frame, app: &App, area: Rect) {
-    let (t_min, t_max) = global_time_bounds(app);
-    let (v_min, v_max) = velocity_bounds(app, false);
-
-    let datasets: Vec<Dataset> = app
-        .balls
-        .iter()
-        .enumerate()
-        .map(|(i, ball)| {
-            Dataset::default()
-                .name(format!("B{}", i + 1))
-                .marker(symbols::Marker::Braille)
-                .style(Style::default().fg(ball.color))
-                .data(&ball.vy_history)
-        })
-        .collect();
-
-    let chart = Chart::new(datasets)
-        .block(
-            Block::default()
-                .title(" Y Velocity Over Time ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::LightMagenta)),
-        )
-        .x_axis(
-            Axis::default()
-                .title("Time")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([t_min, t_max])
-                .labels(vec![
-                    Span::raw(format!("{:.0}", t_min)),
-                    Span::raw(format!("{:.0}", t_max)),
-                ]),
-        )
-        .y_axis(
-            Axis::default()
-                .title("Vy")
-                .style(Style::default().fg(Color::Gray))
-                .bounds([v_min, v_max])
-                .labels(vec![
-                    Span::raw(format!("{:.1}", v_min)),
-                    Span::raw("0"),
-                    Span::raw(format!("{:.1}", v_max)),
-                ]),
-        );
-
-    f.render_widget(chart, area);
+fn frame_timer(frame_rate: Duration) -> time::Interval {
+    let mut frames = time::interval(frame_rate);
+    frames.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    frames
 }
 
-fn velocity_bounds(app: &App, is_x: bool) -> (f64, f64) {
-    let mut v_min = f64::MAX;
-    let mut v_max = f64::MIN;
-
-    for ball in &app.balls {
-        let history = if is_x { &ball.vx_history } else { &ball.vy_history };
-        for &(_, v) in history {
-            if v < v_min { v_min = v; }
-            if v > v_max { v_max = v; }
-        }
-    }
-
-    if v_min >= v_max {
-        (-1.0, 1.0)
-    } else {
-        // Add a small margin
-        let margin = (v_max - v_min) * 0.1;
-        (v_min - margin, v_max + margin)
+/// Multiplexes terminal input, control requests, guest requests, simulation
+/// updates and the redraw timer, recording each drawn frame if asked to.
+async fn run_app(
+    term: &mut impl Term,
+    app: &mut App,
+    mut recorder: Option<CastRecorder<BufWriter<File>, SystemClock>>,
+    mut control: ControlServer,
+    mut share: ShareServer,
+) -> Result<()> {
+    let result = event_loop(term, app, &mut recorder, &mut control, &mut share).await;
+    if let Some(recorder) = &mut recorder {
+        recorder.flush().context("could not write the recording")?;
     }
+    result
 }
 
-fn global_time_bounds(app: &App) -> (f64, f64) {
-    let mut t_min = f64::MAX;
-    let mut t_max = f64::MIN;
+async fn event_loop(
+    term: &mut impl Term,
+    app: &mut App,
+    recorder: &mut Option<CastRecorder<BufWriter<File>, SystemClock>>,
+    control: &mut ControlServer,
+    share: &mut ShareServer,
+) -> Result<()> {
+    let mut input = term.input();
+    let mut frames = frame_timer(app.frame_rate);
+    let clock = SystemClock::new();
 
-    for ball in &app.balls {
-        if let Some(first) = ball.x_history.first() {
-            t_min = t_min.min(first.0);
+    loop {
+        // Switching profile can change the frame rate
+        if frames.period() != app.frame_rate {
+            frames = frame_timer(app.frame_rate);
         }
-        if let Some(last) = ball.x_history.last() {
-            t_max = t_max.max(last.0);
+        tokio::select! {
+            _ = frames.tick() => {
+                if let Some(meter) = &mut app.tick_meter {
+                    meter.sample(app.state.tick_count, clock.now());
+                }
+                if let Some(sweep) = &mut app.sweep {
+                    sweep.poll();
+                }
+                match &mut app.mode {
+                    Mode::Replay(replay) => replay.advance(app.frame_rate),
+                    Mode::Splash(splash) => splash.advance(app.frame_rate),
+                    _ => {}
+                }
+                if matches!(&app.mode, Mode::Splash(splash) if splash.over()) {
+                    if let Mode::Splash(splash) = mem::replace(&mut app.mode, Mode::Running) {
+                        app.mode = *splash.then;
+                    }
+                }
+                if !app.lag.frame(app.tick_rate, app.frame_rate) {
+                    continue;
+                }
+                let start = clock.now();
+                if app.described {
+                    app.description.refresh(&app.state, app.units, start);
+                }
+                let frame = tracing::trace_span!("draw").in_scope(|| term.terminal().draw(|f| ui::draw(f, app)))?;
+                if let Some(recorder) = recorder {
+                    recorder.frame(frame.buffer).context("could not write the recording")?;
+                }
+                let took = clock.now() - start;
+                app.lag.draw_took(took);
+                app.profiler.frame_drawn(took);
+            }
+            event = input.next() => match event {
+                Some(Ok(Input::Key(code))) => {
+                    if input::handle_key(app, code) {
+                        return Ok(());
+                    }
+                    app.run_commands();
+                }
+                Some(Ok(Input::Mouse(mouse))) => input::handle_mouse(app, mouse),
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err).context("could not read terminal input"),
+                None => return Ok(()),
+            },
+            Some(request) = control.recv() => match input::handle_control(app, &request.line) {
+                Some(reply) => {
+                    // Piped commands have nobody to read their replies
+                    if let Some(err) = reply.strip_prefix("error: ") {
+                        app.notice = Some(format!("{}: {}", request.line.trim(), err));
+                    }
+                    request.reply(reply);
+                    app.run_commands();
+                }
+                None => {
+                    request.reply("ok".to_string());
+                    return Ok(());
+                }
+            },
+            Some(request) = share.recv() => app.handle_guest(request),
+            Some(update) = app.sim.recv() => {
+                app.receive(update);
+                // A game can answer an update with commands, like pausing
+                // when a run ends
+                app.run_commands();
+            }
         }
     }
-
-    if t_min >= t_max {
-        (0.0, 1.0)
-    } else {
-        (t_min, t_max)
-    }
 }
@@ -0,0 +1,93 @@
+//! Walls drawn as ASCII art, so mazes and levels can be made in any text
+//! editor. Each character is one arena cell: `#` is a wall and anything else
+//! is open floor. The first line is the arena's top row and the first
+//! column its left edge; walls past the arena's edges are out of reach.
+//!
+//! ```text
+//! ##########
+//! #   #    #
+//! #   #  # #
+//! #      # #
+//! ##########
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rust_tui_ball_bounce::scenario::Obstacle;
+
+/// The walls in the map file at `path`, with each line `row` arena units
+/// tall.
+pub fn load(path: &Path, row: f64) -> io::Result<Vec<Obstacle>> {
+    Ok(parse(&fs::read_to_string(path)?, row))
+}
+
+/// The walls in `text`, as few obstacles as it takes: a run of `#` along a
+/// line is one obstacle, stretched down over any lines below with the same
+/// run.
+pub fn parse(text: &str, row: f64) -> Vec<Obstacle> {
+    let mut obstacles: Vec<Obstacle> = Vec::new();
+    // The obstacle each run on the line above went into, by its start and length
+    let mut above: HashMap<(usize, usize), usize> = HashMap::new();
+    for (y, line) in text.lines().enumerate() {
+        let mut here = HashMap::new();
+        for (start, len) in runs(line) {
+            let index = match above.get(&(start, len)) {
+                Some(&index) => {
+                    obstacles[index].height += row;
+                    index
+                }
+                None => {
+                    obstacles.push(Obstacle { x: start as f64, y: y as f64 * row, width: len as f64, height: row });
+                    obstacles.len() - 1
+                }
+            };
+            here.insert((start, len), index);
+        }
+        above = here;
+    }
+    obstacles
+}
+
+/// The column each run of `#` in `line` starts at, and its length.
+fn runs(line: &str) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (x, c) in line.chars().chain([' ']).enumerate() {
+        match (c == '#', start) {
+            (true, None) => start = Some(x),
+            (false, Some(from)) => {
+                runs.push((from, x - from));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_stack_into_blocks() {
+        let map = "####\n#  #\n#  #\n####\n\n  ..##";
+        let obstacles = parse(map, 2.0);
+        let block = |x, y, width, height| Obstacle { x, y, width, height };
+        assert_eq!(
+            obstacles,
+            [
+                block(0.0, 0.0, 4.0, 2.0),
+                block(0.0, 2.0, 1.0, 4.0),
+                block(3.0, 2.0, 1.0, 4.0),
+                block(0.0, 6.0, 4.0, 2.0),
+                block(4.0, 10.0, 2.0, 2.0),
+            ]
+        );
+        let cells: f64 = obstacles.iter().map(|o| o.width * o.height / 2.0).sum();
+        assert_eq!(cells, map.matches('#').count() as f64);
+    }
+}
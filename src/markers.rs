@@ -0,0 +1,106 @@
+//! Events pinned to the charts' time axis, so a kink in a line can be put
+//! down to what caused it: `×` where the picked ball hit another, `+` where
+//! a ball was added and `◆` where the speed was changed.
+
+use std::collections::VecDeque;
+
+use rust_tui_ball_bounce::SimEvent;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Marker {
+    // Shown only while `a` or `b` is the picked ball
+    Collision { a: usize, b: usize },
+    Spawn { id: usize },
+    SpeedChange,
+}
+
+impl Marker {
+    /// The marker for `event`, if it's one the charts mark.
+    fn of(event: &SimEvent) -> Option<Marker> {
+        match *event {
+            SimEvent::Collision { a, b, .. } => Some(Marker::Collision { a, b }),
+            SimEvent::Spawned { id } => Some(Marker::Spawn { id }),
+            SimEvent::SpeedChanged(_) => Some(Marker::SpeedChange),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(self, ascii: bool) -> &'static str {
+        match self {
+            Marker::Collision { .. } if ascii => "x",
+            Marker::Collision { .. } => "×",
+            Marker::Spawn { .. } => "+",
+            Marker::SpeedChange if ascii => "*",
+            Marker::SpeedChange => "◆",
+        }
+    }
+}
+
+/// The markers for the charted history, oldest first, at the tick they
+/// came in on.
+#[derive(Debug, Default)]
+pub struct Markers {
+    marks: VecDeque<(f64, Marker)>,
+}
+
+impl Markers {
+    /// Adds a marker at tick `t` for `event`, if it's one the charts mark.
+    /// Returns whether it was.
+    pub fn record(&mut self, t: f64, event: &SimEvent) -> bool {
+        let Some(marker) = Marker::of(event) else {
+            return false;
+        };
+        self.marks.push_back((t, marker));
+        true
+    }
+
+    /// Drops the markers from before tick `t`, where the history now starts.
+    pub fn forget_before(&mut self, t: f64) {
+        while self.marks.front().is_some_and(|&(at, _)| at < t) {
+            self.marks.pop_front();
+        }
+    }
+
+    /// How many collisions are marked, between any balls.
+    pub fn collisions(&self) -> usize {
+        self.marks.iter().filter(|(_, marker)| matches!(marker, Marker::Collision { .. })).count()
+    }
+
+    pub fn clear(&mut self) {
+        self.marks.clear();
+    }
+
+    /// The markers to show with ball `picked` picked: every one but other
+    /// balls' collisions.
+    pub fn shown(&self, picked: Option<usize>) -> impl Iterator<Item = (f64, Marker)> + '_ {
+        self.marks.iter().copied().filter(move |&(_, marker)| match marker {
+            Marker::Collision { a, b } => picked.is_some_and(|id| id == a || id == b),
+            _ => true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_picked_balls_collisions_are_shown() {
+        let mut markers = Markers::default();
+        let collision = |a, b| SimEvent::Collision { a, b, x: 0.0, y: 0.0, impulse: 1.0, before: [(0.0, 0.0); 2], after: [(0.0, 0.0); 2] };
+        assert!(markers.record(1.0, &SimEvent::Spawned { id: 2 }));
+        assert!(markers.record(2.0, &collision(0, 1)));
+        assert!(markers.record(3.0, &collision(1, 2)));
+        assert!(!markers.record(3.0, &SimEvent::WallBounce { id: 2 }));
+        assert!(markers.record(4.0, &SimEvent::SpeedChanged(2.0)));
+
+        let ticks = |markers: &Markers, picked| markers.shown(picked).map(|(t, _)| t).collect::<Vec<_>>();
+        assert_eq!(ticks(&markers, Some(2)), [1.0, 3.0, 4.0]);
+        assert_eq!(ticks(&markers, Some(1)), [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(ticks(&markers, None), [1.0, 4.0]);
+        assert_eq!(markers.collisions(), 2);
+
+        markers.forget_before(3.0);
+        assert_eq!(ticks(&markers, Some(0)), [4.0]);
+    }
+}
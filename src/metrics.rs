@@ -0,0 +1,207 @@
+//! `--metrics`: serves Prometheus metrics about the running simulation at
+//! `/metrics`, for scraping into Grafana and the like.
+
+use std::fmt::Write as _;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rust_tui_ball_bounce::clock::Clock;
+use rust_tui_ball_bounce::{SimulationObserver, Snapshot};
+use tiny_http::{Header, Response, Server};
+
+// How long collisions are counted over for the per-second rate
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default, PartialEq)]
+struct Metrics {
+    balls: usize,
+    kinetic_energy: f64,
+    ticks: u64,
+    collisions: u64,
+    wall_bounces: u64,
+    collisions_per_second: f64,
+    tick_seconds: f64,
+}
+
+impl Metrics {
+    /// The text exposition format.
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: f64| {
+            let _ = writeln!(out, "# HELP ballbounce_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE ballbounce_{} {}", name, kind);
+            let _ = writeln!(out, "ballbounce_{} {}", name, value);
+        };
+        metric("balls", "gauge", "Balls in the arena.", self.balls as f64);
+        metric(
+            "kinetic_energy",
+            "gauge",
+            "Total kinetic energy.",
+            self.kinetic_energy,
+        );
+        metric(
+            "ticks_total",
+            "counter",
+            "Ticks simulated.",
+            self.ticks as f64,
+        );
+        metric(
+            "collisions_total",
+            "counter",
+            "Ball-ball collisions.",
+            self.collisions as f64,
+        );
+        metric(
+            "wall_bounces_total",
+            "counter",
+            "Bounces off walls and obstacles.",
+            self.wall_bounces as f64,
+        );
+        metric(
+            "collisions_per_second",
+            "gauge",
+            "Ball-ball collisions over the last second.",
+            self.collisions_per_second,
+        );
+        metric(
+            "tick_seconds",
+            "gauge",
+            "Wall-clock time between the last two ticks.",
+            self.tick_seconds,
+        );
+        out
+    }
+}
+
+/// Keeps the metrics up to date from each tick and serves them from a
+/// thread of its own.
+pub struct MetricsServer<C> {
+    metrics: Arc<Mutex<Metrics>>,
+    clock: C,
+    // The previous tick's count, collisions and wall bounces, which all
+    // reset with the simulation
+    last: Option<[u64; 3]>,
+    last_tick: Option<Duration>,
+    // Start of the current rate window and collisions counted in it
+    window_start: Duration,
+    window_collisions: u64,
+}
+
+impl<C: Clock> MetricsServer<C> {
+    /// Listens for scrapes on `addr`, e.g. `127.0.0.1:9184`.
+    pub fn bind(addr: &str, clock: C) -> io::Result<MetricsServer<C>> {
+        let server = Server::http(addr).map_err(io::Error::other)?;
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        let served = Arc::clone(&metrics);
+        // Never joined: it waits for requests until the process exits
+        thread::spawn(move || {
+            let content_type = Header::from_bytes("Content-Type", "text/plain; version=0.0.4")
+                .expect("the header is valid");
+            for request in server.incoming_requests() {
+                let response = if request.url() == "/metrics" {
+                    let body = served.lock().unwrap().render();
+                    Response::from_string(body).with_header(content_type.clone())
+                } else {
+                    Response::from_string("Not found; try /metrics\n").with_status_code(404)
+                };
+                let _ = request.respond(response);
+            }
+        });
+        Ok(MetricsServer::new(metrics, clock))
+    }
+
+    fn new(metrics: Arc<Mutex<Metrics>>, clock: C) -> MetricsServer<C> {
+        let window_start = clock.now();
+        MetricsServer {
+            metrics,
+            clock,
+            last: None,
+            last_tick: None,
+            window_start,
+            window_collisions: 0,
+        }
+    }
+}
+
+impl<C: Clock> SimulationObserver for MetricsServer<C> {
+    fn on_tick(&mut self, snapshot: &Snapshot) {
+        let now = self.clock.now();
+        // Counters restart from zero when the simulation is reset
+        let counters = [
+            snapshot.tick_count,
+            snapshot.collisions,
+            snapshot.wall_bounces,
+        ];
+        let [ticks, collisions, wall_bounces] = match self.last {
+            Some(last) if counters[0] >= last[0] => {
+                [0, 1, 2].map(|i| counters[i].saturating_sub(last[i]))
+            }
+            _ => counters,
+        };
+
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.balls = snapshot.balls.len();
+        metrics.kinetic_energy = snapshot
+            .balls
+            .iter()
+            .map(|b| 0.5 * b.mass * (b.vx * b.vx + b.vy * b.vy))
+            .sum();
+        metrics.ticks += ticks;
+        metrics.collisions += collisions;
+        metrics.wall_bounces += wall_bounces;
+        if let Some(last_tick) = self.last_tick {
+            metrics.tick_seconds = (now - last_tick).as_secs_f64();
+        }
+
+        self.window_collisions += collisions;
+        let elapsed = now - self.window_start;
+        if elapsed >= RATE_WINDOW {
+            metrics.collisions_per_second = self.window_collisions as f64 / elapsed.as_secs_f64();
+            self.window_start = now;
+            self.window_collisions = 0;
+        }
+
+        self.last = Some(counters);
+        self.last_tick = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::clock::ManualClock;
+
+    use super::*;
+
+    fn snapshot(tick_count: u64, collisions: u64) -> Snapshot {
+        Snapshot {
+            tick_count,
+            collisions,
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn counters_survive_resets_and_rates_cover_a_second() {
+        let clock = ManualClock::new();
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        let mut server = MetricsServer::new(Arc::clone(&metrics), clock.clone());
+
+        server.on_tick(&snapshot(1, 2));
+        clock.advance(Duration::from_millis(500));
+        server.on_tick(&snapshot(2, 5));
+        // Reset, then one more collision
+        clock.advance(Duration::from_millis(500));
+        server.on_tick(&snapshot(1, 1));
+
+        let metrics = metrics.lock().unwrap();
+        assert_eq!(metrics.ticks, 3);
+        assert_eq!(metrics.collisions, 6);
+        assert_eq!(metrics.collisions_per_second, 6.0);
+        assert_eq!(metrics.tick_seconds, 0.5);
+        assert!(metrics
+            .render()
+            .contains("\nballbounce_collisions_total 6\n"));
+    }
+}
@@ -0,0 +1,244 @@
+use rust_tui_ball_bounce::components::{Glyph, Rgb};
+use rust_tui_ball_bounce::force::Boids;
+use rust_tui_ball_bounce::scenario::{self, Obstacle};
+
+use crate::game::Game;
+use crate::layout::LayoutEdit;
+use crate::picker::Picker;
+use crate::replay::Replay;
+use crate::splash::Splash;
+use crate::theme::Theme;
+
+/// What the UI is doing, and so what keys mean. Each mode owns the state it
+/// needs; [`handle_key`](crate::input::handle_key) hands a key press to the
+/// current mode, which decides the next one.
+pub enum Mode {
+    // The title at start-up; any key goes on to the mode it holds
+    Splash(Box<Splash>),
+    Running,
+    Paused,
+    // Key and command reference; any key closes it
+    Help,
+    // The last parameter sweep's chart; any key closes it
+    Sweep,
+    // Sandbox, the games and the demos; also shown at start-up
+    Menu(Picker<MenuEntry>),
+    CommandPalette(Palette),
+    Settings(Picker<Setting>),
+    ScenarioPicker(Picker<scenario::Entry>),
+    ProfilePicker(Picker<Option<String>>),
+    ThemePicker(ThemePicker),
+    ObstacleEdit(ObstacleEdit),
+    LayoutEdit(LayoutEdit),
+    // The color and glyph of a ball picked in the controls panel's list
+    Restyle(Restyle),
+    Flock(Flock),
+    // An export playing back in the arena, with the simulation paused
+    Replay(Box<Replay>),
+}
+
+impl Mode {
+    /// Running or Paused: no modal is open.
+    pub fn is_base(&self) -> bool {
+        matches!(self, Mode::Running | Mode::Paused)
+    }
+}
+
+/// A row in the menu.
+pub enum MenuEntry {
+    // Free play with the config's balls
+    Sandbox,
+    Game(Game),
+    // A built-in scenario to watch
+    Demo(Box<scenario::Entry>),
+}
+
+/// The line being typed into the `:` palette.
+#[derive(Default)]
+pub struct Palette {
+    pub input: String,
+    // Why the last Enter didn't run anything
+    pub error: Option<String>,
+}
+
+/// The theme list. The theme under the cursor is applied as it moves, so
+/// the UI behind the list previews it; Esc puts `original` back.
+pub struct ThemePicker {
+    pub picker: Picker<&'static Theme>,
+    pub original: &'static Theme,
+}
+
+/// A row in the settings modal. Changes last for the session; the config
+/// file is left alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    Theme,
+    Background,
+    Ascii,
+    Color,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 4] = [Setting::Theme, Setting::Background, Setting::Ascii, Setting::Color];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::Theme => "Theme",
+            Setting::Background => "Background",
+            Setting::Ascii => "ASCII glyphs",
+            Setting::Color => "Color",
+        }
+    }
+}
+
+/// Colors a ball can be given in the restyle picker, after the theme's.
+pub const BALL_PALETTE: [(&str, Rgb); 10] = [
+    ("red", Rgb(0xff, 0x55, 0x55)),
+    ("orange", Rgb(0xff, 0x88, 0x33)),
+    ("yellow", Rgb(0xff, 0xdd, 0x33)),
+    ("lime", Rgb(0x99, 0xee, 0x44)),
+    ("green", Rgb(0x33, 0xbb, 0x55)),
+    ("cyan", Rgb(0x33, 0xdd, 0xdd)),
+    ("blue", Rgb(0x55, 0x99, 0xff)),
+    ("purple", Rgb(0xaa, 0x66, 0xff)),
+    ("pink", Rgb(0xff, 0x77, 0xcc)),
+    ("white", Rgb(0xee, 0xee, 0xee)),
+];
+
+/// A row in the restyle picker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Look {
+    Color,
+    Glyph,
+}
+
+/// The picker for one ball's color and glyph. Each change is made as it's
+/// picked, so the arena shows it straight away; `None` is the one derived
+/// from the ball's id.
+pub struct Restyle {
+    pub id: usize,
+    pub rows: Picker<Look>,
+    pub color: Option<Rgb>,
+    pub glyph: Option<Glyph>,
+}
+
+impl Restyle {
+    pub fn new(id: usize, color: Option<Rgb>, glyph: Option<Glyph>) -> Restyle {
+        Restyle { id, rows: Picker::new(vec![Look::Color, Look::Glyph]), color, glyph }
+    }
+
+    /// Moves the selected row's choice `step` places along its list, which
+    /// wraps around through the default.
+    pub fn cycle(&mut self, step: isize) {
+        let next = |at: usize, len: usize| (at as isize + step).rem_euclid(len as isize + 1) as usize;
+        match self.rows.current() {
+            Some(Look::Color) => {
+                // Colors from elsewhere, like a scenario's, count as the default
+                let at = self.color.and_then(|color| BALL_PALETTE.iter().position(|&(_, rgb)| rgb == color));
+                let at = next(at.map_or(0, |i| i + 1), BALL_PALETTE.len());
+                self.color = at.checked_sub(1).map(|i| BALL_PALETTE[i].1);
+            }
+            Some(Look::Glyph) => {
+                let at = next(self.glyph.map_or(0, |glyph| glyph.0 % Glyph::SHAPES.len() + 1), Glyph::SHAPES.len());
+                self.glyph = at.checked_sub(1).map(Glyph);
+            }
+            None => {}
+        }
+    }
+
+    /// The name of the color picked, or `None` for the theme's.
+    pub fn color_name(&self) -> Option<&'static str> {
+        let color = self.color?;
+        Some(BALL_PALETTE.iter().find(|&&(_, rgb)| rgb == color).map_or("custom", |&(name, _)| name))
+    }
+}
+
+/// A row in the flocking modal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    // Whether the balls flock at all
+    Flocking,
+    Separation,
+    Alignment,
+    Cohesion,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 4] = [Rule::Flocking, Rule::Separation, Rule::Alignment, Rule::Cohesion];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Rule::Flocking => "Flocking",
+            Rule::Separation => "Separation",
+            Rule::Alignment => "Alignment",
+            Rule::Cohesion => "Cohesion",
+        }
+    }
+}
+
+/// How far one press moves a weight, and the most it can be.
+pub const WEIGHT_STEP: f64 = 0.25;
+pub const MAX_WEIGHT: f64 = 5.0;
+
+/// The boids weights, sent to the simulation as each one changes. The
+/// weights are kept while flocking is off, so turning it back on picks up
+/// where it left off.
+pub struct Flock {
+    pub rows: Picker<Rule>,
+    pub on: bool,
+    pub boids: Boids,
+}
+
+impl Flock {
+    /// The modal for the simulation's `boids`, or the default weights, off,
+    /// when it has none.
+    pub fn new(boids: Option<Boids>) -> Flock {
+        Flock { rows: Picker::new(Rule::ALL.to_vec()), on: boids.is_some(), boids: boids.unwrap_or_default() }
+    }
+
+    /// Flips flocking on the first row, or moves the selected weight `step`
+    /// places.
+    pub fn cycle(&mut self, step: isize) {
+        let weight = match self.rows.current() {
+            Some(Rule::Flocking) => {
+                self.on = !self.on;
+                return;
+            }
+            Some(Rule::Separation) => &mut self.boids.separation,
+            Some(Rule::Alignment) => &mut self.boids.alignment,
+            Some(Rule::Cohesion) => &mut self.boids.cohesion,
+            None => return,
+        };
+        *weight = (*weight + step as f64 * WEIGHT_STEP).clamp(0.0, MAX_WEIGHT);
+    }
+
+    /// What the simulation should flock by, or `None` for not at all.
+    pub fn boids(&self) -> Option<Boids> {
+        self.on.then_some(self.boids)
+    }
+}
+
+/// A cursor over the arena, in terminal cells from its top-left corner.
+pub struct ObstacleEdit {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Removes the obstacle covering `(x, y)`, or else adds a one-cell wall
+/// there. `row` is the height of a terminal row in arena units.
+pub fn toggle_wall(obstacles: &mut Vec<Obstacle>, x: f64, y: f64, row: f64) {
+    let covers = |o: &Obstacle| {
+        x + 0.5 > o.x && x + 0.5 < o.x + o.width && y + row / 2.0 > o.y && y + row / 2.0 < o.y + o.height
+    };
+    match obstacles.iter().position(covers) {
+        Some(index) => {
+            obstacles.remove(index);
+        }
+        None => obstacles.push(Obstacle {
+            x,
+            y,
+            width: 1.0,
+            height: row,
+        }),
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{SimEvent, Snapshot};
+
+/// Hooks for following a running simulation from the outside: exporters,
+/// loggers, sound and the like. Every method defaults to doing nothing, so
+/// implement only the ones you need.
+///
+/// The terminal app calls these for every update from its simulation thread:
+/// `on_tick` first when the update comes from a new tick, then
+/// [`on_event`](Self::on_event) for each event. To drive one yourself, pass
+/// it what [`Simulation::drain_events`](crate::Simulation::drain_events)
+/// returns after each step.
+pub trait SimulationObserver {
+    fn on_tick(&mut self, _snapshot: &Snapshot) {}
+
+    /// Two balls met at the contact point `(x, y)`.
+    fn on_collision(&mut self, _a: usize, _b: usize, _x: f64, _y: f64) {}
+
+    fn on_spawn(&mut self, _id: usize) {}
+
+    /// The ball was removed, went into a pocket, or its lifetime ran out.
+    fn on_remove(&mut self, _id: usize) {}
+
+    /// Calls the hook for `event`, if it has one. Override it to see every
+    /// event, wall bounces and pausing included; the hooks above are then
+    /// only called if the override does so.
+    fn on_event(&mut self, event: &SimEvent) {
+        match *event {
+            SimEvent::Collision { a, b, x, y, .. } => self.on_collision(a, b, x, y),
+            SimEvent::Spawned { id } => self.on_spawn(id),
+            SimEvent::Removed { id } | SimEvent::Expired { id } | SimEvent::Pocketed { id } => self.on_remove(id),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Paint mode: every ball stains the cells it passes through with its
+//! color, so the arena slowly fills in with a drawing of where the balls
+//! have been. Stains fade by `display.paint_decay` of their strength a
+//! tick, or never at 0, and are drawn in the cells nothing else is in.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ratatui::style::Color;
+use rust_tui_ball_bounce::components::Rgb;
+use rust_tui_ball_bounce::Snapshot;
+
+use crate::clip;
+
+/// Glyphs for a stain, strongest first.
+pub const SHADES: [&str; 3] = ["▓", "▒", "░"];
+pub const ASCII_SHADES: [&str; 3] = ["%", "+", "."];
+
+// A ball that moved further than this between updates jumped there, as
+// from a scenario loading, rather than passing through the cells between
+const MAX_STROKE: f64 = 8.0;
+
+/// The ball that stained a cell last, and how much of the stain is left:
+/// 1 when fresh, fading to nothing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stain {
+    pub id: usize,
+    // The ball's own color, if it has one rather than the theme's
+    pub color: Option<Rgb>,
+    pub strength: f64,
+}
+
+impl Stain {
+    /// Which of [`SHADES`] the stain is drawn with.
+    pub fn shade(&self) -> usize {
+        match self.strength {
+            s if s > 2.0 / 3.0 => 0,
+            s if s > 1.0 / 3.0 => 1,
+            _ => 2,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Paint {
+    // Strength lost per tick
+    pub decay: f64,
+    // By column and row of the arena
+    stains: HashMap<(u16, u16), Stain>,
+    // Each ball's cell at the last update, in fractional columns and rows
+    last: HashMap<usize, (f64, f64)>,
+    tick_count: u64,
+}
+
+impl Paint {
+    pub fn new(decay: f64) -> Paint {
+        Paint { decay, ..Paint::default() }
+    }
+
+    /// Fades the stains by the ticks since the last update and stains the
+    /// cells each ball passed through on its way to where it is in `state`.
+    pub fn update(&mut self, state: &Snapshot) {
+        let ticks = state.tick_count.saturating_sub(self.tick_count) as f64;
+        self.tick_count = state.tick_count;
+        if self.decay > 0.0 && ticks > 0.0 {
+            let fade = self.decay * ticks;
+            self.stains.retain(|_, stain| {
+                stain.strength -= fade;
+                stain.strength > 0.0
+            });
+        }
+        let (columns, rows) = (state.area_width, state.area_height / state.aspect);
+        let mut last = HashMap::with_capacity(state.balls.len());
+        for ball in &state.balls {
+            let to = (ball.x, ball.y / state.aspect);
+            let from = self.last.get(&ball.id).copied().unwrap_or(to);
+            let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+            let length = dx.hypot(dy);
+            // Half a cell at a time, so no cell along the way is skipped
+            let steps = if length > MAX_STROKE { 0 } else { (length * 2.0).ceil() as usize };
+            for step in 0..=steps {
+                let t = if steps == 0 { 1.0 } else { step as f64 / steps as f64 };
+                let (x, y) = ((from.0 + dx * t).round(), (from.1 + dy * t).round());
+                if x >= 0.0 && y >= 0.0 && x < columns && y < rows {
+                    self.stains.insert((x as u16, y as u16), Stain { id: ball.id, color: ball.color, strength: 1.0 });
+                }
+            }
+            last.insert(ball.id, to);
+        }
+        self.last = last;
+    }
+
+    pub fn clear(&mut self) {
+        self.stains.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.stains.len()
+    }
+
+    pub fn stains(&self) -> impl Iterator<Item = ((u16, u16), &Stain)> {
+        self.stains.iter().map(|(&cell, stain)| (cell, stain))
+    }
+
+    /// The painting as lines of text, one character per cell and no spaces
+    /// at the ends of lines. With `color`, which gives each stain its
+    /// color, every stain is wrapped in the escape codes for it, so `cat`
+    /// in a truecolor terminal shows it as it was.
+    pub fn to_text(&self, ascii: bool, color: Option<&dyn Fn(&Stain) -> Color>) -> String {
+        let shades = if ascii { ASCII_SHADES } else { SHADES };
+        let rows = self.stains.keys().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        let mut text = String::new();
+        for y in 0..rows {
+            let columns = self.stains.keys().filter(|&&(_, row)| row == y).map(|&(x, _)| x + 1).max().unwrap_or(0);
+            for x in 0..columns {
+                match (self.stains.get(&(x, y)), color) {
+                    (None, _) => text.push(' '),
+                    (Some(stain), None) => text.push_str(shades[stain.shade()]),
+                    (Some(stain), Some(color)) => {
+                        let [r, g, b] = clip::rgb(color(stain));
+                        let _ = write!(text, "\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, shades[stain.shade()]);
+                    }
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Writes the painting to `path` as text, in color if it ends in `.ans`.
+    pub fn save(&self, path: &Path, ascii: bool, color: &dyn Fn(&Stain) -> Color) -> io::Result<()> {
+        let ansi = path.extension().is_some_and(|ext| ext == "ans");
+        fs::write(path, self.to_text(ascii, ansi.then_some(color)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::BallState;
+
+    use super::*;
+
+    fn state(tick_count: u64, x: f64, y: f64) -> Snapshot {
+        let ball = BallState { id: 0, x, y, vx: 0.0, vy: 0.0, radius: 0.5, mass: 1.0, color: None, glyph: None, body: None };
+        Snapshot { tick_count, area_width: 20.0, area_height: 10.0, aspect: 1.0, balls: vec![ball], ..Snapshot::default() }
+    }
+
+    #[test]
+    fn strokes_fill_the_cells_between_and_fade() {
+        let mut paint = Paint::new(0.25);
+        paint.update(&state(0, 1.0, 1.0));
+        paint.update(&state(1, 4.0, 1.0));
+        assert_eq!(paint.to_text(true, None), "\n %%%%\n");
+        // A jump across the arena leaves no stroke behind it
+        paint.update(&state(3, 18.0, 1.0));
+        assert_eq!(paint.to_text(true, None), "\n ++++             %\n");
+        paint.update(&state(5, 18.0, 1.0));
+        assert_eq!(paint.len(), 1);
+        paint.clear();
+        assert_eq!(paint.to_text(false, None), "");
+    }
+}
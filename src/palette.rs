@@ -0,0 +1,390 @@
+//! The `:` command palette: a one-line language for what the keys do, plus
+//! things they can't, like an exact speed or a scenario file by path.
+
+use tracing_subscriber::filter::LevelFilter;
+use rust_tui_ball_bounce::force::Direction;
+
+use crate::compare::{self, Change};
+use crate::game::GAME_NAMES;
+use crate::sweep::{Metric, Parameter, Plan};
+use crate::twin::DEFAULT_EPSILON;
+
+/// A command typed into the `:` palette.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaletteCommand {
+    Pause,
+    Resume,
+    Step(u32),
+    Add(u32),
+    Remove(u32),
+    Speed(f64),
+    Gravity(f64),
+    GravityDirection(Direction),
+    Clear,
+    Scenario(String),
+    // `None` goes back to the config file without a profile
+    Profile(Option<String>),
+    // One of `GAME_NAMES`, or `None` to stop playing
+    Game(Option<String>),
+    Layout,
+    Walls,
+    // A text file of walls, `#` for each wall cell
+    Map(String),
+    // Turn painting on or off, or `None` to switch it
+    Paint(Option<bool>),
+    ClearPaint,
+    // `None` picks a file name from the tick count
+    ExportPaint(Option<String>),
+    Menu,
+    // `None` picks a file name from the tick count
+    Export(Option<String>),
+    Import(String),
+    // An export to play back in the arena
+    Replay(String),
+    Log(LevelFilter),
+    // Rhai code, run with the `scripting` feature
+    Eval(String),
+    // Nudge for the chaos experiment's twin, or `None` to stop it
+    Twin(Option<f64>),
+    // `None` shows the last sweep again
+    Sweep(Option<Plan>),
+    // What the simulation compared with differs by and the metric to
+    // chart, or `None` to stop comparing
+    Compare(Option<(Change, compare::Metric)>),
+    Settings,
+    Flock,
+    Help,
+    Quit,
+}
+
+/// Usage and description of every command, in the order the palette lists them.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("pause", "Pause the simulation"),
+    ("resume", "Resume the simulation"),
+    ("step [n]", "Advance n steps, 1 by default"),
+    ("add [n]", "Add n balls"),
+    ("remove [n]", "Remove the n newest balls"),
+    ("speed <x>", "Set the speed, 0.25 to 5"),
+    ("set gravity <g|dir>", "Set gravity, or which way it pulls"),
+    ("clear", "Remove every ball, force and obstacle"),
+    ("scenario <name|path>", "Load a built-in or file scenario"),
+    ("profile <name|none>", "Switch config profile"),
+    ("game <name|off>", "Play billiards, dodge or targets, or stop"),
+    ("layout", "Edit the pane layout"),
+    ("walls", "Draw obstacles in the arena"),
+    ("map <path>", "Replace the walls with a text file's # cells"),
+    ("paint [on|off|clear|export [path]]", "Paint the balls' paths, or save the painting as text"),
+    ("menu", "Pick sandbox, a game or a demo"),
+    ("export [path]", "Write the chart history to a file"),
+    ("import <path>", "Add balls from a CSV or JSON file"),
+    ("replay <path>", "Play back a JSON or MessagePack export"),
+    ("eval <code>", "Run a line of Rhai script"),
+    ("log <level>", "Set the --log level, off to trace"),
+    ("twin [epsilon|off]", "Run a nudged twin and chart the divergence"),
+    ("sweep <param> <from> <to> [metric]", "Chart a metric over runs with a parameter varied"),
+    ("compare <param> <value>|scenario <name>|off [metric]", "Run a second simulation side by side"),
+    ("settings", "Change display settings"),
+    ("flock", "Turn flocking on or off and set its weights"),
+    ("help", "List keys and commands"),
+    ("quit", "Quit"),
+];
+
+/// Commands whose name starts with the first word of `input`.
+pub fn matches(input: &str) -> impl Iterator<Item = &'static (&'static str, &'static str)> + '_ {
+    let word = input.split_whitespace().next().unwrap_or("");
+    COMMANDS
+        .iter()
+        .filter(move |(usage, _)| usage.starts_with(word))
+}
+
+/// Completes the command name when only one command starts with what's typed.
+pub fn complete(input: &str) -> Option<String> {
+    if input.contains(char::is_whitespace) {
+        return None;
+    }
+    let mut found = matches(input);
+    let (usage, _) = found.next()?;
+    if found.next().is_some() {
+        return None;
+    }
+    let name = usage.split(' ').next().unwrap_or(usage);
+    Some(if name.len() < usage.len() { format!("{} ", name) } else { name.to_string() })
+}
+
+pub fn parse(line: &str) -> Result<PaletteCommand, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("type a command, or Esc to close".to_string());
+    };
+    let args: Vec<&str> = words.collect();
+
+    let command = match name {
+        "step" => return Ok(PaletteCommand::Step(count(name, &args)?)),
+        "add" => return Ok(PaletteCommand::Add(count(name, &args)?)),
+        "remove" => return Ok(PaletteCommand::Remove(count(name, &args)?)),
+        "speed" => {
+            let speed = argument(name, &args)?
+                .parse::<f64>()
+                .ok()
+                .filter(|speed| (0.25..=5.0).contains(speed))
+                .ok_or("speed: expected a number from 0.25 to 5")?;
+            return Ok(PaletteCommand::Speed(speed));
+        }
+        "set" => {
+            let [setting, value] = args[..] else {
+                return Err("set: expected a setting and a value, e.g. set gravity 0.05".to_string());
+            };
+            if let ("gravity", Some(direction)) = (setting, Direction::parse(value)) {
+                return Ok(PaletteCommand::GravityDirection(direction));
+            }
+            let value: f64 = value
+                .parse()
+                .ok()
+                .filter(|value: &f64| value.is_finite())
+                .ok_or_else(|| format!("set: \"{}\" is not a number", value))?;
+            return match setting {
+                "gravity" => Ok(PaletteCommand::Gravity(value)),
+                "speed" => parse(&format!("speed {}", value)),
+                other => Err(format!("set: unknown setting \"{}\"", other)),
+            };
+        }
+        // Paths may contain spaces
+        "scenario" if !args.is_empty() => return Ok(PaletteCommand::Scenario(args.join(" "))),
+        "scenario" => return Err("scenario: expected a name or path".to_string()),
+        "export" if !args.is_empty() => return Ok(PaletteCommand::Export(Some(args.join(" ")))),
+        "export" => PaletteCommand::Export(None),
+        "import" if !args.is_empty() => return Ok(PaletteCommand::Import(args.join(" "))),
+        "import" => return Err("import: expected a path".to_string()),
+        "replay" if !args.is_empty() => return Ok(PaletteCommand::Replay(args.join(" "))),
+        "replay" => return Err("replay: expected a path".to_string()),
+        "map" if !args.is_empty() => return Ok(PaletteCommand::Map(args.join(" "))),
+        "map" => return Err("map: expected a path".to_string()),
+        "paint" => {
+            return match args.as_slice() {
+                [] => Ok(PaletteCommand::Paint(None)),
+                ["on"] => Ok(PaletteCommand::Paint(Some(true))),
+                ["off"] => Ok(PaletteCommand::Paint(Some(false))),
+                ["clear"] => Ok(PaletteCommand::ClearPaint),
+                ["export"] => Ok(PaletteCommand::ExportPaint(None)),
+                ["export", path @ ..] => Ok(PaletteCommand::ExportPaint(Some(path.join(" ")))),
+                [other, ..] => Err(format!("paint: expected on, off, clear or export, not \"{}\"", other)),
+            }
+        }
+        // Passed on untouched, spacing and all
+        "eval" if !args.is_empty() => {
+            return Ok(PaletteCommand::Eval(line.trim()[name.len()..].trim_start().to_string()))
+        }
+        "eval" => return Err("eval: expected some code".to_string()),
+        "log" => {
+            let level = argument(name, &args)?;
+            return level
+                .parse()
+                .map(PaletteCommand::Log)
+                .map_err(|_| format!("log: expected off, error, warn, info, debug or trace, not \"{}\"", level));
+        }
+        "profile" => {
+            return Ok(match argument(name, &args)? {
+                "none" => PaletteCommand::Profile(None),
+                profile => PaletteCommand::Profile(Some(profile.to_string())),
+            })
+        }
+        "game" => {
+            return match argument(name, &args)? {
+                "off" => Ok(PaletteCommand::Game(None)),
+                game if GAME_NAMES.contains(&game) => Ok(PaletteCommand::Game(Some(game.to_string()))),
+                other => Err(format!("game: unknown game \"{}\" (games: {})", other, GAME_NAMES.join(", "))),
+            }
+        }
+        "twin" if args.is_empty() => PaletteCommand::Twin(Some(DEFAULT_EPSILON)),
+        "twin" => {
+            return match argument(name, &args)? {
+                "off" => Ok(PaletteCommand::Twin(None)),
+                epsilon => epsilon
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|epsilon| epsilon.is_finite() && *epsilon > 0.0)
+                    .map(|epsilon| PaletteCommand::Twin(Some(epsilon)))
+                    .ok_or_else(|| format!("twin: expected a positive number or off, not \"{}\"", epsilon)),
+            }
+        }
+        "sweep" if args.is_empty() => PaletteCommand::Sweep(None),
+        "sweep" => return sweep(&args).map(|plan| PaletteCommand::Sweep(Some(plan))),
+        "compare" if args == ["off"] => return Ok(PaletteCommand::Compare(None)),
+        "compare" => return comparison(&args).map(|comparison| PaletteCommand::Compare(Some(comparison))),
+        "pause" => PaletteCommand::Pause,
+        "resume" => PaletteCommand::Resume,
+        "clear" => PaletteCommand::Clear,
+        "layout" => PaletteCommand::Layout,
+        "walls" => PaletteCommand::Walls,
+        "menu" => PaletteCommand::Menu,
+        "settings" => PaletteCommand::Settings,
+        "flock" => PaletteCommand::Flock,
+        "help" => PaletteCommand::Help,
+        "quit" | "q" => PaletteCommand::Quit,
+        other => return Err(format!("unknown command \"{}\"", other)),
+    };
+    match args.first() {
+        Some(extra) => Err(format!("{}: unexpected \"{}\"", name, extra)),
+        None => Ok(command),
+    }
+}
+
+/// `sweep`'s parameter, range and optional metric, final energy unless
+/// another is named.
+fn sweep(args: &[&str]) -> Result<Plan, String> {
+    let (parameter, from, to, metric) = match args[..] {
+        [parameter, from, to] => (parameter, from, to, "energy"),
+        [parameter, from, to, metric] => (parameter, from, to, metric),
+        _ => return Err("sweep: expected a parameter, a range and maybe a metric, e.g. sweep friction 0 0.01 settle".to_string()),
+    };
+    let number = |value: &str| {
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| format!("sweep: \"{}\" is not a number", value))
+    };
+    Ok(Plan {
+        parameter: Parameter::by_name(parameter)
+            .ok_or_else(|| format!("sweep: unknown parameter \"{}\" (parameters: {})", parameter, Parameter::NAMES.join(", ")))?,
+        from: number(from)?,
+        to: number(to)?,
+        metric: Metric::by_name(metric)
+            .ok_or_else(|| format!("sweep: unknown metric \"{}\" (metrics: {})", metric, Metric::NAMES.join(", ")))?,
+    })
+}
+
+/// `compare`'s arguments: a parameter and a value, or `scenario` and a name
+/// or path, then maybe a metric.
+fn comparison(args: &[&str]) -> Result<(Change, compare::Metric), String> {
+    // A path may end in any word, so after `scenario` only a metric's name
+    // is taken for one
+    let (metric, args) = match args.split_last() {
+        Some((last, rest)) if rest.len() >= 2 && (rest[0] != "scenario" || compare::Metric::by_name(last).is_some()) => {
+            let metric = compare::Metric::by_name(last).ok_or_else(|| {
+                format!("compare: unknown metric \"{}\" (metrics: {})", last, compare::Metric::NAMES.join(", "))
+            })?;
+            (metric, rest)
+        }
+        _ => (compare::Metric::Energy, args),
+    };
+    match args {
+        ["scenario", name @ ..] if !name.is_empty() => Ok((Change::Scenario(name.join(" ")), metric)),
+        &[parameter, value] => {
+            let parameter = Parameter::by_name(parameter).ok_or_else(|| {
+                format!("compare: unknown parameter \"{}\" (parameters: {})", parameter, Parameter::NAMES.join(", "))
+            })?;
+            let value = value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(|| format!("compare: \"{}\" is not a number", value))?;
+            Ok((Change::Set(parameter, value), metric))
+        }
+        _ => Err("compare: expected a parameter and a value or a scenario, e.g. compare gravity 0.1".to_string()),
+    }
+}
+
+/// The single argument of `name`.
+fn argument<'a>(name: &str, args: &[&'a str]) -> Result<&'a str, String> {
+    match args {
+        [arg] => Ok(arg),
+        [] => Err(format!("{}: missing argument", name)),
+        [_, extra, ..] => Err(format!("{}: unexpected \"{}\"", name, extra)),
+    }
+}
+
+/// An optional positive count, 1 when left out.
+fn count(name: &str, args: &[&str]) -> Result<u32, String> {
+    if args.is_empty() {
+        return Ok(1);
+    }
+    argument(name, args)?
+        .parse::<u32>()
+        .ok()
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("{}: expected a positive count", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_arguments() {
+        assert_eq!(parse("pause"), Ok(PaletteCommand::Pause));
+        assert_eq!(parse("  step  "), Ok(PaletteCommand::Step(1)));
+        assert_eq!(parse("add 5"), Ok(PaletteCommand::Add(5)));
+        assert_eq!(parse("speed 2.5"), Ok(PaletteCommand::Speed(2.5)));
+        assert_eq!(parse("profile none"), Ok(PaletteCommand::Profile(None)));
+        assert_eq!(parse("game billiards"), Ok(PaletteCommand::Game(Some("billiards".to_string()))));
+        assert_eq!(parse("game off"), Ok(PaletteCommand::Game(None)));
+        assert_eq!(parse("menu"), Ok(PaletteCommand::Menu));
+        assert_eq!(parse("export"), Ok(PaletteCommand::Export(None)));
+        assert_eq!(parse("import balls.csv"), Ok(PaletteCommand::Import("balls.csv".to_string())));
+        assert_eq!(parse("replay run 2.json"), Ok(PaletteCommand::Replay("run 2.json".to_string())));
+        assert_eq!(parse("map maze.txt"), Ok(PaletteCommand::Map("maze.txt".to_string())));
+        assert_eq!(parse("paint"), Ok(PaletteCommand::Paint(None)));
+        assert_eq!(parse("paint off"), Ok(PaletteCommand::Paint(Some(false))));
+        assert_eq!(parse("paint export art.ans"), Ok(PaletteCommand::ExportPaint(Some("art.ans".to_string()))));
+        assert_eq!(parse("set gravity 0.1"), Ok(PaletteCommand::Gravity(0.1)));
+        assert_eq!(parse("set gravity left"), Ok(PaletteCommand::GravityDirection(Direction::Left)));
+        assert_eq!(parse("log debug"), Ok(PaletteCommand::Log(LevelFilter::DEBUG)));
+        assert_eq!(parse("twin"), Ok(PaletteCommand::Twin(Some(DEFAULT_EPSILON))));
+        assert_eq!(parse("twin 1e-3"), Ok(PaletteCommand::Twin(Some(1e-3))));
+        assert_eq!(parse("twin off"), Ok(PaletteCommand::Twin(None)));
+        assert_eq!(
+            parse("sweep drag 0 0.1 settle"),
+            Ok(PaletteCommand::Sweep(Some(Plan { parameter: Parameter::Drag, from: 0.0, to: 0.1, metric: Metric::Settle })))
+        );
+        assert_eq!(
+            parse("compare gravity 0.1 speed"),
+            Ok(PaletteCommand::Compare(Some((Change::Set(Parameter::Gravity, 0.1), compare::Metric::Speed))))
+        );
+        assert_eq!(
+            parse("compare scenario my gas.toml"),
+            Ok(PaletteCommand::Compare(Some((Change::Scenario("my gas.toml".to_string()), compare::Metric::Energy))))
+        );
+        assert_eq!(parse("compare off"), Ok(PaletteCommand::Compare(None)));
+        assert_eq!(
+            parse(" eval print(\"a  b\")"),
+            Ok(PaletteCommand::Eval("print(\"a  b\")".to_string()))
+        );
+        assert_eq!(
+            parse("scenario my scenes/gas.toml"),
+            Ok(PaletteCommand::Scenario("my scenes/gas.toml".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse("").is_err());
+        assert!(parse("jump").is_err());
+        assert!(parse("pause now").is_err());
+        assert!(parse("add 0").is_err());
+        assert!(parse("speed 9").is_err());
+        assert!(parse("speed").is_err());
+        assert!(parse("profile a b").is_err());
+        assert!(parse("game chess").is_err());
+        assert!(parse("replay").is_err());
+        assert!(parse("paint red").is_err());
+        assert!(parse("set gravity").is_err());
+        assert!(parse("set mass 2").is_err());
+        assert!(parse("twin -1").is_err());
+        assert!(parse("sweep mass 1 2").is_err());
+        assert!(parse("sweep drag 0").is_err());
+        assert!(parse("compare").is_err());
+        assert!(parse("compare gravity 0.1 mass").is_err());
+        assert!(parse("compare scenario").is_err());
+    }
+
+    #[test]
+    fn completes_unique_prefixes() {
+        assert_eq!(complete("sp").as_deref(), Some("speed "));
+        assert_eq!(complete("pau").as_deref(), Some("pause"));
+        // scenario and settings, and pause and paint
+        assert_eq!(complete("s"), None);
+        assert_eq!(complete("pa"), None);
+        assert_eq!(complete("speed 1"), None);
+    }
+}
@@ -0,0 +1,612 @@
+use hecs::{Entity, World};
+
+use crate::components::{BallId, Body, Charge, Glyph, Growth, History, Mass, Position, Radius, Rgb, Samples, Spin, Ttl, Velocity};
+use crate::event::SimEvent;
+use crate::rng::Rng;
+use crate::sat::{self, Outline};
+use crate::scenario::{Obstacle, Pockets, Thermostat};
+use crate::BallState;
+
+/// Pairwise inverse-square forces between charged balls.
+pub(crate) fn electrostatics(world: &mut World, dt: f64) {
+    let charges: Vec<(Entity, Position, f64)> = world
+        .query_mut::<(Entity, &Position, &Charge)>()
+        .into_iter()
+        .map(|(e, pos, charge)| (e, *pos, charge.0))
+        .collect();
+    for &(entity, pos, q) in &charges {
+        let (mut ax, mut ay) = (0.0, 0.0);
+        for &(other, other_pos, other_q) in &charges {
+            if other == entity {
+                continue;
+            }
+            let dx = pos.x - other_pos.x;
+            let dy = pos.y - other_pos.y;
+            let dist_sq = (dx * dx + dy * dy).max(1.0);
+            let accel = q * other_q / dist_sq;
+            let dist = dist_sq.sqrt();
+            ax += accel * dx / dist;
+            ay += accel * dy / dist;
+        }
+        if let Ok(vel) = world.query_one_mut::<&mut Velocity>(entity) {
+            vel.vx += ax * dt;
+            vel.vy += ay * dt;
+        }
+    }
+}
+
+/// Grows or shrinks every ball with a [`Growth`] by its rate, scaling its
+/// mass with its area.
+pub(crate) fn grow(world: &mut World, dt: f64) {
+    for (radius, mass, growth) in world.query_mut::<(&mut Radius, &mut Mass, &Growth)>() {
+        let grown = (radius.0 + growth.rate * dt).clamp(growth.min, growth.max);
+        mass.0 *= (grown / radius.0).powi(2);
+        radius.0 = grown;
+    }
+}
+
+/// Turns every shaped ball by its [`Spin`].
+pub(crate) fn spin(world: &mut World, dt: f64) {
+    for (body, spin) in world.query_mut::<(&mut Body, &Spin)>() {
+        body.angle = (body.angle + spin.0 * dt).rem_euclid(std::f64::consts::TAU);
+    }
+}
+
+/// Every ball's core components in parallel arrays, ordered by id. Each
+/// step copies them out of the world so integration and collisions run over
+/// contiguous `f64`s, then writes them back.
+#[derive(Debug, Default)]
+pub struct Balls {
+    pub entities: Vec<Entity>,
+    pub ids: Vec<usize>,
+    pub xs: Vec<f64>,
+    pub ys: Vec<f64>,
+    pub vxs: Vec<f64>,
+    pub vys: Vec<f64>,
+    pub radii: Vec<f64>,
+    pub masses: Vec<f64>,
+    pub colors: Vec<Option<Rgb>>,
+    pub glyphs: Vec<Option<Glyph>>,
+    pub bodies: Vec<Option<Body>>,
+}
+
+impl Balls {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// The left, right, top and bottom of what of ball `i` meets the walls:
+    /// its center, or a shaped ball's outermost corners.
+    fn reach(&self, i: usize) -> (f64, f64, f64, f64) {
+        let (x, y) = (self.xs[i], self.ys[i]);
+        match self.bodies[i] {
+            Some(body) => body.shape.corners(x, y, self.radii[i], body.angle).into_iter().fold(
+                (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+                |(left, right, top, bottom), (x, y)| (left.min(x), right.max(x), top.min(y), bottom.max(y)),
+            ),
+            None => (x, x, y, y),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entities.clear();
+        self.ids.clear();
+        self.xs.clear();
+        self.ys.clear();
+        self.vxs.clear();
+        self.vys.clear();
+        self.radii.clear();
+        self.masses.clear();
+        self.colors.clear();
+        self.glyphs.clear();
+        self.bodies.clear();
+    }
+
+    pub fn push(&mut self, entity: Entity, ball: BallState) {
+        self.entities.push(entity);
+        self.ids.push(ball.id);
+        self.xs.push(ball.x);
+        self.ys.push(ball.y);
+        self.vxs.push(ball.vx);
+        self.vys.push(ball.vy);
+        self.radii.push(ball.radius);
+        self.masses.push(ball.mass);
+        self.colors.push(ball.color);
+        self.glyphs.push(ball.glyph);
+        self.bodies.push(ball.body);
+    }
+
+    /// Every ball as a [`BallState`], for the view frontends read.
+    pub fn states(&self) -> impl Iterator<Item = BallState> + '_ {
+        (0..self.len()).map(|i| BallState {
+            id: self.ids[i],
+            x: self.xs[i],
+            y: self.ys[i],
+            vx: self.vxs[i],
+            vy: self.vys[i],
+            radius: self.radii[i],
+            mass: self.masses[i],
+            color: self.colors[i],
+            glyph: self.glyphs[i],
+            body: self.bodies[i],
+        })
+    }
+
+    fn sort_by_id(&mut self) {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by_key(|&i| self.ids[i]);
+        fn permute<T: Copy>(values: &mut Vec<T>, order: &[usize]) {
+            *values = order.iter().map(|&i| values[i]).collect();
+        }
+        permute(&mut self.entities, &order);
+        permute(&mut self.ids, &order);
+        permute(&mut self.xs, &order);
+        permute(&mut self.ys, &order);
+        permute(&mut self.vxs, &order);
+        permute(&mut self.vys, &order);
+        permute(&mut self.radii, &order);
+        permute(&mut self.masses, &order);
+        permute(&mut self.colors, &order);
+        permute(&mut self.glyphs, &order);
+        permute(&mut self.bodies, &order);
+    }
+}
+
+/// Copies every ball's core components out of the world into `balls`,
+/// ordered by id so pairwise collision handling is deterministic.
+pub(crate) fn gather(world: &World, balls: &mut Balls) {
+    balls.clear();
+    let mut query = world.query::<(Entity, &BallId, &Position, &Velocity, &Radius, Option<&Mass>, Option<&Rgb>, Option<&Glyph>, Option<&Body>)>();
+    for (e, id, pos, vel, radius, mass, color, glyph, body) in query.iter() {
+        let ball = BallState {
+            id: id.0,
+            x: pos.x,
+            y: pos.y,
+            vx: vel.vx,
+            vy: vel.vy,
+            radius: radius.0,
+            mass: mass.map_or(1.0, |mass| mass.0),
+            color: color.copied(),
+            glyph: glyph.copied(),
+            body: body.copied(),
+        };
+        balls.push(e, ball);
+    }
+    // Balls usually come out in order already, unless some have extra components
+    if !balls.ids.is_sorted() {
+        balls.sort_by_id();
+    }
+}
+
+/// Writes positions and velocities from [`gather`]ed balls back into the world.
+pub(crate) fn scatter(world: &mut World, balls: &Balls) {
+    for (i, &entity) in balls.entities.iter().enumerate() {
+        if let Ok((pos, vel)) = world.query_one_mut::<(&mut Position, &mut Velocity)>(entity) {
+            *pos = Position { x: balls.xs[i], y: balls.ys[i] };
+            *vel = Velocity { vx: balls.vxs[i], vy: balls.vys[i] };
+        }
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+pub fn integrate(balls: &mut Balls, dt: f64) {
+    for (x, vx) in balls.xs.iter_mut().zip(&balls.vxs) {
+        *x += vx * dt;
+    }
+    for (y, vy) in balls.ys.iter_mut().zip(&balls.vys) {
+        *y += vy * dt;
+    }
+}
+
+#[cfg(feature = "simd")]
+pub use crate::simd::{bounce_off_walls, integrate};
+
+pub(crate) fn record_history(world: &mut World, ticks: &mut Samples, t: f64, max_history: usize) {
+    if max_history == 0 {
+        return;
+    }
+    ticks.push(t, max_history);
+    for (pos, vel, history) in world.query_mut::<(&Position, &Velocity, &mut History)>() {
+        history.record((pos.x, pos.y), (vel.vx, vel.vy), max_history);
+    }
+}
+
+/// Counts down every [`Ttl`] and despawns balls that reach zero, returning
+/// their ids.
+pub(crate) fn expire(world: &mut World) -> Vec<usize> {
+    let expired: Vec<(Entity, usize)> = world
+        .query_mut::<(Entity, &BallId, &mut Ttl)>()
+        .into_iter()
+        .filter_map(|(e, id, ttl)| {
+            ttl.0 = ttl.0.saturating_sub(1);
+            (ttl.0 == 0).then_some((e, id.0))
+        })
+        .collect();
+    for &(entity, _) in &expired {
+        let _ = world.despawn(entity);
+    }
+    expired.into_iter().map(|(_, id)| id).collect()
+}
+
+/// Despawns every ball whose center is in one of `pockets`, for balls kept
+/// within `[0, right] x [0, floor]`, and returns their ids.
+pub(crate) fn sink(world: &mut World, balls: &Balls, pockets: &Pockets, right: f64, floor: f64) -> Vec<usize> {
+    let centers = pockets.centers(right, floor);
+    let mut sunk = Vec::new();
+    for i in 0..balls.len() {
+        let (x, y) = (balls.xs[i], balls.ys[i]);
+        if centers.iter().any(|&(cx, cy)| (x - cx).hypot(y - cy) < pockets.radius) {
+            let _ = world.despawn(balls.entities[i]);
+            sunk.push(balls.ids[i]);
+        }
+    }
+    sunk
+}
+
+/// Resolves every overlapping pair as an elastic collision and pushes the
+/// pair apart. Returns a [`SimEvent::Collision`] for each pair that
+/// was approaching.
+pub(crate) fn resolve_collisions(balls: &mut Balls) -> Vec<SimEvent> {
+    let mut collisions = Vec::new();
+    let n = balls.len();
+    let Balls { ids, xs, ys, vxs, vys, radii, masses, bodies, .. } = balls;
+    // Sliced to `n` so the compiler can drop most bounds checks
+    let (xs, ys, vxs, vys, radii, masses, bodies) =
+        (&mut xs[..n], &mut ys[..n], &mut vxs[..n], &mut vys[..n], &radii[..n], &masses[..n], &bodies[..n]);
+    for i in 0..n {
+        // Only this pass changes ball `i`, so it's kept in registers until the end
+        let (mut xi, mut yi, mut vxi, mut vyi, ri, mi) = (xs[i], ys[i], vxs[i], vys[i], radii[i], masses[i]);
+        for j in (i + 1)..n {
+            let dx = xs[j] - xi;
+            let dy = ys[j] - yi;
+            let dist_sq = dx * dx + dy * dy;
+            let min_dist = ri + radii[j];
+
+            if dist_sq < min_dist * min_dist && dist_sq > 0.0 {
+                let mj = masses[j];
+                // Collision normal, and how far the balls overlap along it
+                let (nx, ny, overlap) = if bodies[i].is_none() && bodies[j].is_none() {
+                    let dist = dist_sq.sqrt();
+                    (dx / dist, dy / dist, min_dist - dist)
+                } else {
+                    // A shaped ball's corners reach this far, but its sides may not
+                    match sat::contact(&outline(bodies[i], xi, yi, ri), &outline(bodies[j], xs[j], ys[j], radii[j])) {
+                        Some(contact) => contact,
+                        None => continue,
+                    }
+                };
+
+                // Relative velocity along collision normal
+                let dvx = vxi - vxs[j];
+                let dvy = vyi - vys[j];
+                let dvn = dvx * nx + dvy * ny;
+
+                // Only resolve if balls are moving toward each other
+                if dvn > 0.0 {
+                    let share = ri / min_dist;
+                    let before = [(vxi, vyi), (vxs[j], vys[j])];
+                    // Elastic collision: the momentum passed along the
+                    // normal, which for equal masses swaps their normal
+                    // components
+                    let impulse = 2.0 * mi * mj / (mi + mj) * dvn;
+                    vxi -= impulse / mi * nx;
+                    vyi -= impulse / mi * ny;
+                    vxs[j] += impulse / mj * nx;
+                    vys[j] += impulse / mj * ny;
+                    collisions.push(SimEvent::Collision {
+                        a: ids[i],
+                        b: ids[j],
+                        x: xi + dx * share,
+                        y: yi + dy * share,
+                        impulse,
+                        before,
+                        after: [(vxi, vyi), (vxs[j], vys[j])],
+                    });
+                }
+
+                // Separate overlapping balls, the lighter one moving further
+                let push = overlap + 0.02;
+                let (sep_i, sep_j) = (push * mj / (mi + mj), push * mi / (mi + mj));
+                xi -= sep_i * nx;
+                yi -= sep_i * ny;
+                xs[j] += sep_j * nx;
+                ys[j] += sep_j * ny;
+            }
+        }
+        (xs[i], ys[i], vxs[i], vys[i]) = (xi, yi, vxi, vyi);
+    }
+    collisions
+}
+
+/// A ball's outline at `(x, y)`: a circle, or its [`Body`]'s corners.
+fn outline(body: Option<Body>, x: f64, y: f64, radius: f64) -> Outline {
+    match body {
+        Some(body) => Outline::Polygon(body.shape.corners(x, y, radius, body.angle)),
+        None => Outline::Circle { x, y, radius },
+    }
+}
+
+/// Keeps every shaped ball's corners within `[0, right] x [0, floor]`,
+/// reflecting its velocity off any wall one reached and adding a
+/// [`SimEvent::WallBounce`] for each ball that hit one. Returns the number
+/// of velocity components flipped. Round balls are left to
+/// [`bounce_off_walls`], which stops their centers at the walls.
+pub(crate) fn bounce_bodies_off_walls(balls: &mut Balls, right: f64, floor: f64, events: &mut Vec<SimEvent>) -> u64 {
+    let mut flips = 0;
+    for i in 0..balls.len() {
+        if balls.bodies[i].is_none() {
+            continue;
+        }
+        let (left, far_right, top, bottom) = balls.reach(i);
+        let before = (balls.vxs[i], balls.vys[i]);
+        let (vx, vy) = (&mut balls.vxs[i], &mut balls.vys[i]);
+        if left < 0.0 {
+            balls.xs[i] -= left;
+            *vx = vx.abs();
+        } else if far_right > right {
+            balls.xs[i] -= far_right - right;
+            *vx = -vx.abs();
+        }
+        if top < 0.0 {
+            balls.ys[i] -= top;
+            *vy = vy.abs();
+        } else if bottom > floor {
+            balls.ys[i] -= bottom - floor;
+            *vy = -vy.abs();
+        }
+        let bounces = u64::from(*vx != before.0) + u64::from(*vy != before.1);
+        if bounces > 0 {
+            flips += bounces;
+            events.push(SimEvent::WallBounce { id: balls.ids[i] });
+        }
+    }
+    flips
+}
+
+/// Bounces every ball off the walls like [`bounce_ball_off_walls`], adding
+/// a [`SimEvent::WallBounce`] for each ball that hit one. Returns the number
+/// of velocity components flipped.
+#[cfg(not(feature = "simd"))]
+pub fn bounce_off_walls(balls: &mut Balls, right: f64, floor: f64, events: &mut Vec<SimEvent>) -> u64 {
+    let mut flips = 0;
+    for i in 0..balls.len() {
+        let bounces = bounce_ball_off_walls(balls, i, right, floor);
+        if bounces > 0 {
+            flips += bounces;
+            events.push(SimEvent::WallBounce { id: balls.ids[i] });
+        }
+    }
+    flips
+}
+
+/// Keeps ball `i` within `[0, right] x [0, floor]`, reflecting its velocity
+/// off any wall it reached. Returns the number of velocity components flipped.
+pub(crate) fn bounce_ball_off_walls(balls: &mut Balls, i: usize, right: f64, floor: f64) -> u64 {
+    let (x, y) = (&mut balls.xs[i], &mut balls.ys[i]);
+    let (vx, vy) = (&mut balls.vxs[i], &mut balls.vys[i]);
+    let before = (*vx, *vy);
+    if *x <= 0.0 {
+        *x = 0.0;
+        *vx = vx.abs();
+    }
+    if *x >= right {
+        *x = right;
+        *vx = -vx.abs();
+    }
+    if *y <= 0.0 {
+        *y = 0.0;
+        *vy = vy.abs();
+    }
+    if *y >= floor {
+        *y = floor;
+        *vy = -vy.abs();
+    }
+    u64::from(*vx != before.0) + u64::from(*vy != before.1)
+}
+
+/// Sends each ball with a [`SimEvent::WallBounce`] in `bounced` off at a
+/// velocity drawn for the temperature of the wall it hit, if that has one.
+/// The speed away from the wall follows a Rayleigh distribution and the
+/// speed along it a normal one, each with variance `T / m`, as for the
+/// molecules a gas at that temperature sends into the wall.
+pub(crate) fn thermalize(balls: &mut Balls, thermostat: &Thermostat, right: f64, floor: f64, bounced: &[SimEvent], rng: &mut Rng) {
+    for event in bounced {
+        let SimEvent::WallBounce { id } = *event else {
+            continue;
+        };
+        let Ok(i) = balls.ids.binary_search(&id) else {
+            continue;
+        };
+        // Corners put back against a wall can land a rounding error short of it
+        let (left, far_right, top, bottom) = balls.reach(i);
+        let (left, far_right, top, bottom) = (left - 1e-9, far_right + 1e-9, top - 1e-9, bottom + 1e-9);
+        // The wall's inward normal along x and y, for the first wall the
+        // ball is against that has a temperature
+        let walls = [
+            (left <= 0.0, thermostat.left, (1.0, 0.0)),
+            (far_right >= right, thermostat.right, (-1.0, 0.0)),
+            (top <= 0.0, thermostat.top, (0.0, 1.0)),
+            (bottom >= floor, thermostat.bottom, (0.0, -1.0)),
+        ];
+        let Some((temperature, (nx, ny))) = walls.into_iter().find_map(|(hit, t, normal)| t.filter(|_| hit).map(|t| (t, normal))) else {
+            continue;
+        };
+        let sigma = (temperature / balls.masses[i]).sqrt();
+        // 1 - u, so the logarithm never sees 0
+        let away = sigma * (-2.0 * (1.0 - rng.next_f64()).ln()).sqrt();
+        let along = sigma * (-2.0 * (1.0 - rng.next_f64()).ln()).sqrt() * (std::f64::consts::TAU * rng.next_f64()).cos();
+        balls.vxs[i] = away * nx - along * ny;
+        balls.vys[i] = away * ny + along * nx;
+    }
+}
+
+// Obstacles cover whole cells, so cell `n` spans `[n - 0.5, n + 0.5]`.
+pub(crate) fn bounce_off_obstacle(balls: &mut Balls, i: usize, obstacle: &Obstacle) {
+    let (x, y, radius) = (balls.xs[i], balls.ys[i], balls.radii[i]);
+    let left = obstacle.x - 0.5;
+    let right = obstacle.x + obstacle.width - 0.5;
+    let top = obstacle.y - 0.5;
+    let bottom = obstacle.y + obstacle.height - 0.5;
+
+    let cx = x.clamp(left, right);
+    let cy = y.clamp(top, bottom);
+    let dx = x - cx;
+    let dy = y - cy;
+    let dist_sq = dx * dx + dy * dy;
+
+    let (nx, ny, depth) = if dist_sq > 0.0 {
+        if dist_sq >= radius * radius {
+            return;
+        }
+        let dist = dist_sq.sqrt();
+        (dx / dist, dy / dist, radius - dist)
+    } else {
+        // Center is inside the block: push out along the shallowest side
+        let exits = [
+            (-1.0, 0.0, x - left),
+            (1.0, 0.0, right - x),
+            (0.0, -1.0, y - top),
+            (0.0, 1.0, bottom - y),
+        ];
+        let (nx, ny, d) = exits
+            .into_iter()
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .expect("four exits");
+        (nx, ny, d + radius)
+    };
+
+    balls.xs[i] += nx * depth;
+    balls.ys[i] += ny * depth;
+    let vn = balls.vxs[i] * nx + balls.vys[i] * ny;
+    if vn < 0.0 {
+        balls.vxs[i] -= 2.0 * vn * nx;
+        balls.vys[i] -= 2.0 * vn * ny;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn ball(id: usize, x: f64, y: f64, vx: f64, vy: f64) -> BallState {
+        BallState { id, x, y, vx, vy, radius: 0.75, mass: 1.0, color: None, glyph: None, body: None }
+    }
+
+    fn columns(states: &[BallState]) -> Balls {
+        let mut balls = Balls::default();
+        for &ball in states {
+            balls.push(Entity::DANGLING, ball);
+        }
+        balls
+    }
+
+    fn momentum(balls: &Balls) -> (f64, f64) {
+        (balls.vxs.iter().sum(), balls.vys.iter().sum())
+    }
+
+    #[test]
+    fn head_on_collision_swaps_velocities() {
+        let mut balls = columns(&[ball(0, 10.0, 5.0, 1.0, 0.0), ball(1, 11.0, 5.0, -1.0, 0.0)]);
+        let events = resolve_collisions(&mut balls);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], SimEvent::Collision { impulse, .. } if impulse == 2.0));
+        assert_eq!(balls.vxs, [-1.0, 1.0]);
+        assert!(balls.xs[1] - balls.xs[0] >= 1.5);
+    }
+
+    #[test]
+    fn separating_balls_are_pushed_apart_without_an_event() {
+        let mut balls = columns(&[ball(0, 10.0, 5.0, -1.0, 0.0), ball(1, 11.0, 5.0, 1.0, 0.0)]);
+        assert!(resolve_collisions(&mut balls).is_empty());
+        assert_eq!(balls.vxs, [-1.0, 1.0]);
+        assert!(balls.xs[1] - balls.xs[0] >= 1.5);
+    }
+
+    #[test]
+    fn walls_clamp_and_reflect() {
+        let mut balls = columns(&[ball(0, -2.0, 25.0, -1.0, 0.5)]);
+        assert_eq!(bounce_ball_off_walls(&mut balls, 0, 79.0, 19.0), 2);
+        let b = balls.states().next().unwrap();
+        assert_eq!((b.x, b.y, b.vx, b.vy), (0.0, 19.0, 1.0, -0.5));
+    }
+
+    #[test]
+    fn obstacle_pushes_ball_out_and_reflects() {
+        let obstacle = Obstacle { x: 10.0, y: 10.0, width: 4.0, height: 1.0 };
+        let mut balls = columns(&[ball(0, 11.0, 9.0, 0.0, 1.0)]);
+        bounce_off_obstacle(&mut balls, 0, &obstacle);
+        let b = balls.states().next().unwrap();
+        assert!(b.y <= 9.5 - b.radius + 1e-9);
+        assert_eq!(b.vy, -1.0);
+    }
+
+    #[test]
+    fn gathered_balls_are_ordered_by_id() {
+        let mut world = World::new();
+        let core = |id, x| (BallId(id), Position { x, y: 0.0 }, Velocity { vx: 0.0, vy: 0.0 }, Radius(0.5));
+        world.spawn(core(2, 2.0));
+        // A different archetype, so it comes out of the query separately
+        let first = world.spawn(core(0, 0.0));
+        world.insert_one(first, Ttl(5)).unwrap();
+        world.spawn(core(1, 1.0));
+        let mut balls = Balls::default();
+        gather(&world, &mut balls);
+        assert_eq!(balls.ids, [0, 1, 2]);
+        assert_eq!(balls.xs, [0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn expired_balls_are_despawned() {
+        let mut world = World::new();
+        world.spawn((BallId(0), Ttl(1)));
+        world.spawn((BallId(1), Ttl(2)));
+        assert_eq!(expire(&mut world), vec![0]);
+        assert_eq!(world.len(), 1);
+    }
+
+    fn arb_ball(id: usize) -> impl Strategy<Value = BallState> {
+        (0.0..20.0, 0.0..20.0, -2.0..2.0, -2.0..2.0, 0.25..2.0).prop_map(
+            move |(x, y, vx, vy, radius)| BallState { id, x, y, vx, vy, radius, mass: 1.0, color: None, glyph: None, body: None },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn collisions_conserve_momentum(
+            balls in (0..8usize).prop_flat_map(|n| (0..n).map(arb_ball).collect::<Vec<_>>())
+        ) {
+            let mut balls = columns(&balls);
+            let (px, py) = momentum(&balls);
+            resolve_collisions(&mut balls);
+            let (qx, qy) = momentum(&balls);
+            prop_assert!((px - qx).abs() < 1e-9 && (py - qy).abs() < 1e-9);
+        }
+
+        // Resolving one pair can push a ball into a third, so this holds for pairs only
+        #[test]
+        fn resolved_pairs_do_not_overlap(a in arb_ball(0), b in arb_ball(1)) {
+            let mut balls = columns(&[a, b]);
+            resolve_collisions(&mut balls);
+            let states: Vec<_> = balls.states().collect();
+            let (a, b) = (states[0], states[1]);
+            let dist = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+            prop_assume!(dist > 0.0);
+            prop_assert!(dist >= a.radius + b.radius - 1e-9);
+        }
+
+        #[test]
+        fn walls_keep_balls_in_bounds(mut b in arb_ball(0), dx in -40.0..40.0, dy in -40.0..40.0) {
+            b.x += dx;
+            b.y += dy;
+            let mut balls = columns(&[b]);
+            bounce_ball_off_walls(&mut balls, 0, 15.0, 12.0);
+            let b = balls.states().next().unwrap();
+            prop_assert!((0.0..=15.0).contains(&b.x) && (0.0..=12.0).contains(&b.y));
+        }
+    }
+}
@@ -0,0 +1,20 @@
+/// Cursor state for a modal list picker (scenarios, profiles, ...).
+pub struct Picker<T> {
+    pub entries: Vec<T>,
+    pub selected: usize,
+}
+
+impl<T> Picker<T> {
+    pub fn new(entries: Vec<T>) -> Self {
+        Picker { entries, selected: 0 }
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
+
+    pub fn current(&self) -> Option<&T> {
+        self.entries.get(self.selected)
+    }
+}
@@ -0,0 +1,61 @@
+//! `--pipe`: every ball's state each tick as plain `t x y vx vy id` lines,
+//! for live plotting with gnuplot or `feedgnuplot`. `t` is the tick count;
+//! positions and velocities are in arena cells, with `y` growing downward as
+//! on screen.
+
+use std::io::Write;
+
+use rust_tui_ball_bounce::{BallState, SimulationObserver, Snapshot};
+
+pub struct Pipe<W> {
+    out: W,
+    // Cleared once the reader goes away, so the app carries on without it
+    open: bool,
+}
+
+impl<W: Write> Pipe<W> {
+    pub fn new(out: W) -> Self {
+        Pipe { out, open: true }
+    }
+
+    /// Writes a line per ball and flushes, so a reader sees each tick whole.
+    pub fn write(&mut self, tick: u64, balls: &[BallState]) {
+        if !self.open {
+            return;
+        }
+        let mut result = Ok(());
+        for ball in balls {
+            result = result.and_then(|()| {
+                writeln!(
+                    self.out,
+                    "{} {:.6} {:.6} {:.6} {:.6} {}",
+                    tick, ball.x, ball.y, ball.vx, ball.vy, ball.id
+                )
+            });
+        }
+        self.open = result.and_then(|()| self.out.flush()).is_ok();
+    }
+}
+
+impl<W: Write> SimulationObserver for Pipe<W> {
+    fn on_tick(&mut self, snapshot: &Snapshot) {
+        self.write(snapshot.tick_count, &snapshot.balls);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_line_per_ball() {
+        let ball = |id, x| BallState { id, x, y: 2.0, vx: 0.5, vy: -0.25, radius: 1.0, mass: 1.0, color: None, glyph: None, body: None };
+        let mut pipe = Pipe::new(Vec::new());
+        pipe.write(7, &[ball(0, 1.0), ball(3, 4.5)]);
+        assert_eq!(
+            String::from_utf8(pipe.out).unwrap(),
+            "7 1.000000 2.000000 0.500000 -0.250000 0\n\
+             7 4.500000 2.000000 0.500000 -0.250000 3\n"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Where each frame's time goes, for the overlay toggled with F3. Ticks
+//! report their phases as they arrive, chart building adds its time while
+//! the charts draw, and the rest of the draw is counted once the frame is
+//! done. Each phase is summed over a frame, so several ticks between two
+//! frames add up, and then averaged over recent frames.
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use rust_tui_ball_bounce::clock::{Clock, StepTimes, SystemClock};
+
+// Weight of each new frame in the running averages
+const SMOOTHING: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Integrate,
+    Collide,
+    History,
+    ChartPrep,
+    Draw,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [Phase::Integrate, Phase::Collide, Phase::History, Phase::ChartPrep, Phase::Draw];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::Integrate => "integrate",
+            Phase::Collide => "collide",
+            Phase::History => "history",
+            Phase::ChartPrep => "chart prep",
+            Phase::Draw => "draw",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Profiler {
+    clock: SystemClock,
+    // This frame's time so far per phase; cells so the charts can add to
+    // it while drawing from a shared `App`
+    frame: [Cell<Duration>; 5],
+    // Per frame, in seconds
+    averages: [f64; 5],
+}
+
+impl Profiler {
+    pub fn add(&self, phase: Phase, time: Duration) {
+        let total = &self.frame[phase as usize];
+        total.set(total.get() + time);
+    }
+
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Runs `f`, counting its time toward `phase`.
+    pub fn time<T>(&self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let start = self.now();
+        let result = f();
+        self.add(phase, self.now().saturating_sub(start));
+        result
+    }
+
+    pub fn step_took(&self, times: &StepTimes) {
+        self.add(Phase::Integrate, times.integrate);
+        self.add(Phase::Collide, times.collide);
+        self.add(Phase::History, times.history);
+    }
+
+    /// Ends the frame, given the whole draw's time, which includes the chart
+    /// prep already counted.
+    pub fn frame_drawn(&mut self, draw: Duration) {
+        let chart_prep = self.frame[Phase::ChartPrep as usize].get();
+        self.add(Phase::Draw, draw.saturating_sub(chart_prep));
+        for (average, total) in self.averages.iter_mut().zip(&self.frame) {
+            *average += (total.take().as_secs_f64() - *average) * SMOOTHING;
+        }
+    }
+
+    pub fn average(&self, phase: Phase) -> Duration {
+        Duration::from_secs_f64(self.averages[phase as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_add_up_per_frame() {
+        let mut profiler = Profiler::default();
+        let ms = Duration::from_millis;
+        for _ in 0..200 {
+            // Two ticks and a draw per frame
+            for _ in 0..2 {
+                profiler.step_took(&StepTimes { integrate: ms(1), collide: ms(3), history: ms(0) });
+            }
+            profiler.add(Phase::ChartPrep, ms(2));
+            profiler.frame_drawn(ms(5));
+        }
+        let average = |phase| profiler.average(phase).as_secs_f64() * 1000.0;
+        assert!((average(Phase::Integrate) - 2.0).abs() < 0.01);
+        assert!((average(Phase::Collide) - 6.0).abs() < 0.01);
+        assert!((average(Phase::ChartPrep) - 2.0).abs() < 0.01);
+        assert!((average(Phase::Draw) - 3.0).abs() < 0.01);
+        assert_eq!(average(Phase::History), 0.0);
+    }
+}
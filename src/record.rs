@@ -0,0 +1,125 @@
+//! Records the session as an [asciinema v2] cast: a JSON header line, then
+//! one `[seconds, "o", output]` line per drawn frame holding the escape
+//! sequences that turn the previous frame into this one.
+//!
+//! [asciinema v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ratatui::buffer::Buffer;
+use serde::Serialize;
+
+use rust_tui_ball_bounce::ansi::encode;
+use rust_tui_ball_bounce::clock::Clock;
+
+#[derive(Serialize)]
+struct Header {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+    title: &'static str,
+}
+
+/// Writes frames to `out` with times from `clock`. The header is written
+/// with the first frame, once the screen size is known.
+pub struct CastRecorder<W, C> {
+    out: W,
+    clock: C,
+    // The last frame written, to diff the next one against
+    previous: Option<Buffer>,
+}
+
+impl<W: Write, C: Clock> CastRecorder<W, C> {
+    pub fn new(out: W, clock: C) -> Self {
+        CastRecorder { out, clock, previous: None }
+    }
+
+    pub fn frame(&mut self, buffer: &Buffer) -> io::Result<()> {
+        let time = self.clock.now().as_secs_f64();
+        let area = buffer.area;
+        let previous = match self.previous.take() {
+            Some(previous) if previous.area == area => previous,
+            Some(_) => {
+                self.event(time, "r", &format!("{}x{}", area.width, area.height))?;
+                self.event(time, "o", "\x1b[2J")?;
+                Buffer::empty(area)
+            }
+            None => {
+                let header = Header {
+                    version: 2,
+                    width: area.width,
+                    height: area.height,
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |since| since.as_secs()),
+                    title: "rust-tui-ball-bounce",
+                };
+                serde_json::to_writer(&mut self.out, &header)?;
+                writeln!(self.out)?;
+                // Hide the cursor and start from a blank screen
+                self.event(time, "o", "\x1b[?25l\x1b[2J")?;
+                Buffer::empty(area)
+            }
+        };
+        let output = encode(&previous, buffer);
+        if !output.is_empty() {
+            self.event(time, "o", &output)?;
+        }
+        self.previous = Some(buffer.clone());
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn event(&mut self, time: f64, kind: &str, data: &str) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, &(time, kind, data))?;
+        writeln!(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Style};
+    use rust_tui_ball_bounce::clock::ManualClock;
+
+    use super::*;
+
+    #[test]
+    fn frames_become_timed_diffs() {
+        let clock = ManualClock::new();
+        let mut recorder = CastRecorder::new(Vec::new(), clock.clone());
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 2));
+        buffer.set_string(0, 0, "ab", Style::default().fg(Color::Red));
+        recorder.frame(&buffer).unwrap();
+
+        clock.advance(Duration::from_millis(500));
+        buffer.set_string(1, 1, "c", Style::default());
+        recorder.frame(&buffer).unwrap();
+        // Nothing changed, so nothing is written
+        recorder.frame(&buffer).unwrap();
+
+        clock.advance(Duration::from_millis(500));
+        recorder.frame(&Buffer::empty(Rect::new(0, 0, 3, 1))).unwrap();
+
+        let cast = String::from_utf8(recorder.out).unwrap();
+        let lines: Vec<&str> = cast.lines().collect();
+        assert!(lines[0].starts_with(r#"{"version":2,"width":4,"height":2,"#));
+        assert_eq!(
+            &lines[1..],
+            [
+                r#"[0.0,"o","\u001b[?25l\u001b[2J"]"#,
+                r#"[0.0,"o","\u001b[1;1H\u001b[0;31mab\u001b[0m"]"#,
+                r#"[0.5,"o","\u001b[2;2H\u001b[0mc\u001b[0m"]"#,
+                r#"[1.0,"r","3x1"]"#,
+                r#"[1.0,"o","\u001b[2J"]"#,
+            ]
+        );
+    }
+}
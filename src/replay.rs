@@ -0,0 +1,235 @@
+//! Replays of exported runs. `replay <path>` loads a `.json` or `.msgpack`
+//! export and shows it in the arena in place of the simulation, which is
+//! paused meanwhile, with its histories in the charts. A timeline along the
+//! bottom of the arena marks how far in the replay is: it plays, pauses,
+//! steps and changes speed from the keyboard, and a click or drag on the
+//! timeline seeks.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::time::Duration;
+
+use ratatui::layout::Rect;
+use serde::Deserialize;
+use rust_tui_ball_bounce::{BallState, Snapshot};
+
+use crate::export::Format;
+
+/// Slowest and fastest playback, against the recording's own pace.
+pub const SPEEDS: (f64, f64) = (0.25, 8.0);
+
+/// The parts of an export a replay needs.
+#[derive(Deserialize)]
+struct Document {
+    tick_ms: f64,
+    state: Snapshot,
+    histories: BTreeMap<usize, Track>,
+}
+
+/// A ball's samples as `(tick, value)` pairs, in the order recorded.
+#[derive(Deserialize)]
+pub struct Track {
+    pub x: Vec<(f64, f64)>,
+    pub y: Vec<(f64, f64)>,
+    pub vx: Vec<(f64, f64)>,
+    pub vy: Vec<(f64, f64)>,
+}
+
+/// A loaded recording and where playback is in it.
+pub struct Replay {
+    // The file's name, for the timeline's title
+    pub name: String,
+    // The state the recording ended in, which the frames take the arena,
+    // walls and balls' looks from
+    pub state: Snapshot,
+    pub tick_ms: f64,
+    // Every tick any ball was recorded at, in order
+    pub ticks: Vec<f64>,
+    pub tracks: BTreeMap<usize, Track>,
+    // The tick shown; fractional while playing
+    pub tick: f64,
+    pub playing: bool,
+    pub speed: f64,
+}
+
+impl Replay {
+    /// The export at `path`, paused at its start.
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+        Replay::read(BufReader::new(File::open(path)?), Format::from_path(path), name)
+    }
+
+    pub fn read(input: impl Read, format: Format, name: String) -> io::Result<Replay> {
+        let document: Document = match format {
+            Format::Json => serde_json::from_reader(input).map_err(invalid)?,
+            Format::MessagePack => rmp_serde::from_read(input).map_err(invalid)?,
+            _ => return Err(invalid("only .json and .msgpack exports can be replayed")),
+        };
+        let mut ticks: Vec<f64> = document.histories.values().flat_map(|track| track.x.iter().map(|&(tick, _)| tick)).collect();
+        ticks.sort_by(f64::total_cmp);
+        ticks.dedup();
+        let Some(&first) = ticks.first() else {
+            return Err(invalid("the export has no history to replay"));
+        };
+        Ok(Replay {
+            name,
+            state: document.state,
+            tick_ms: document.tick_ms,
+            ticks,
+            tracks: document.histories,
+            tick: first,
+            playing: false,
+            speed: 1.0,
+        })
+    }
+
+    pub fn first(&self) -> f64 {
+        self.ticks[0]
+    }
+
+    pub fn last(&self) -> f64 {
+        self.ticks[self.ticks.len() - 1]
+    }
+
+    /// Which of `ticks` is showing: the last one at or before `tick`.
+    pub fn index(&self) -> usize {
+        self.ticks.partition_point(|&tick| tick <= self.tick).saturating_sub(1)
+    }
+
+    /// How far through the recording playback is, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        let span = self.last() - self.first();
+        if span > 0.0 {
+            (self.tick - self.first()) / span
+        } else {
+            1.0
+        }
+    }
+
+    /// The recording's state at the tick showing, with the balls recorded
+    /// then where they were.
+    pub fn frame(&self) -> Snapshot {
+        let tick = self.ticks[self.index()];
+        let at = |samples: &[(f64, f64)]| {
+            let i = samples.binary_search_by(|&(t, _)| t.total_cmp(&tick)).ok()?;
+            Some(samples[i].1)
+        };
+        // Histories only have balls still there at the end, so each is in
+        // the final state
+        let balls = self
+            .tracks
+            .iter()
+            .filter_map(|(&id, track)| {
+                let ball = self.state.balls.iter().find(|ball| ball.id == id)?;
+                Some(BallState {
+                    x: at(&track.x)?,
+                    y: at(&track.y)?,
+                    vx: at(&track.vx)?,
+                    vy: at(&track.vy)?,
+                    ..*ball
+                })
+            })
+            .collect();
+        Snapshot { tick_count: tick as u64, paused: !self.playing, balls, ..self.state.clone() }
+    }
+
+    /// Moves playback on by `elapsed` of wall-clock time, stopping at the
+    /// end.
+    pub fn advance(&mut self, elapsed: Duration) {
+        if !self.playing {
+            return;
+        }
+        self.tick += elapsed.as_secs_f64() * 1000.0 / self.tick_ms * self.speed;
+        if self.tick >= self.last() {
+            self.tick = self.last();
+            self.playing = false;
+        }
+    }
+
+    /// Plays from where it is, or from the start once it's at the end.
+    pub fn toggle(&mut self) {
+        if !self.playing && self.tick >= self.last() {
+            self.tick = self.first();
+        }
+        self.playing = !self.playing;
+    }
+
+    /// Pauses on the recorded tick `by` ticks on from the one showing.
+    pub fn step(&mut self, by: isize) {
+        self.playing = false;
+        let index = (self.index() as isize + by).clamp(0, self.ticks.len() as isize - 1);
+        self.tick = self.ticks[index as usize];
+    }
+
+    /// Jumps to `progress` of the way through, from 0 to 1.
+    pub fn seek(&mut self, progress: f64) {
+        self.tick = self.first() + (self.last() - self.first()) * progress.clamp(0.0, 1.0);
+    }
+
+    /// Doubles or halves the speed for `faster` or not.
+    pub fn change_speed(&mut self, faster: bool) {
+        let speed = if faster { self.speed * 2.0 } else { self.speed / 2.0 };
+        self.speed = speed.clamp(SPEEDS.0, SPEEDS.1);
+    }
+}
+
+/// The timeline's box along the bottom of the arena, when there's room for
+/// it.
+pub fn timeline_area(arena: Rect) -> Option<Rect> {
+    (arena.width >= 20 && arena.height >= 8).then(|| Rect::new(arena.x, arena.bottom() - 4, arena.width, 4))
+}
+
+/// The row of the timeline that's drawn as a track, and clicked to seek.
+pub fn track_area(timeline: Rect) -> Rect {
+    Rect::new(timeline.x + 1, timeline.y + 1, timeline.width - 2, 1)
+}
+
+fn invalid(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn replay() -> Replay {
+        let ball = BallState { id: 3, x: 0.0, y: 0.0, vx: 0.0, vy: 0.0, radius: 0.5, mass: 1.0, color: None, glyph: None, body: None };
+        let state = Snapshot { tick_count: 30, balls: vec![ball], ..Snapshot::default() };
+        let points = |scale: f64| (10..=30).step_by(10).map(|tick| (tick as f64, tick as f64 * scale)).collect::<Vec<_>>();
+        let document = json!({
+            "version": 1,
+            "tick_ms": 10.0,
+            "state": state,
+            "histories": { "3": { "x": points(1.0), "y": points(2.0), "vx": points(0.0), "vy": points(0.0) } },
+        });
+        Replay::read(document.to_string().as_bytes(), Format::Json, "run.json".to_string()).unwrap()
+    }
+
+    #[test]
+    fn plays_steps_and_seeks_through_the_recording() {
+        let mut replay = replay();
+        assert_eq!(replay.ticks, [10.0, 20.0, 30.0]);
+        let frame = replay.frame();
+        assert_eq!((frame.tick_count, frame.balls[0].x, frame.balls[0].y), (10, 10.0, 20.0));
+
+        // Ten ticks of 10 ms take 0.1 s at 1x
+        replay.toggle();
+        replay.advance(Duration::from_millis(150));
+        assert_eq!(replay.frame().tick_count, 20);
+        replay.change_speed(true);
+        replay.advance(Duration::from_millis(100));
+        assert_eq!((replay.tick, replay.playing), (30.0, false));
+
+        replay.step(-1);
+        assert_eq!(replay.frame().balls[0].x, 20.0);
+        replay.seek(0.0);
+        assert_eq!(replay.progress(), 0.0);
+        replay.step(-1);
+        assert_eq!(replay.tick, 10.0);
+        assert!(Replay::read("{}".as_bytes(), Format::Csv, String::new()).is_err());
+    }
+}
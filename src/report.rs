@@ -0,0 +1,283 @@
+//! Run reports for write-ups: exporting to `.md` or `.tex` gives a document
+//! with the run's parameters and seed, its collision counts and summary
+//! statistics, a table of the balls, and charts of their histories. A
+//! Markdown report links SVG charts written next to it (`run.x.svg` and so
+//! on); a LaTeX one draws them itself with pgfplots.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use rust_tui_ball_bounce::components::{with_ticks, History, Samples};
+
+use crate::export::Recording;
+
+// Which samples a chart plots
+type Values = fn(&History) -> &Samples;
+
+// A row of the report's tables, as label and value
+type Fact = (&'static str, String);
+
+/// The charted quantities: what the file is called, the chart's title and
+/// which samples it plots.
+pub const CHARTS: [(&str, &str, Values); 4] = [
+    ("x", "X position", |history| &history.x),
+    ("y", "Y position", |history| &history.y),
+    ("vx", "X velocity", |history| &history.vx),
+    ("vy", "Y velocity", |history| &history.vy),
+];
+
+// Points per ball per chart; longer histories are thinned to this
+const MAX_POINTS: usize = 400;
+
+// Line colors by ball id for balls without their own, in the default
+// theme's order, darkened to read on white
+const COLORS: [&str; 8] = ["#c8a000", "#2e9e2e", "#d03030", "#3060d0", "#b040b0", "#20a0b0", "#ff6060", "#60d060"];
+
+/// `run.md` and `x` → `run.x.svg`.
+pub fn chart_path(path: &Path, name: &str) -> PathBuf {
+    path.with_extension(format!("{}.svg", name))
+}
+
+/// Each ball's speed over the recording, on average and at its highest.
+fn speeds(history: &History) -> (f64, f64) {
+    let speeds: Vec<f64> = history.vx.iter().zip(history.vy.iter()).map(|(vx, vy)| vx.hypot(*vy)).collect();
+    let mean = speeds.iter().sum::<f64>() / speeds.len().max(1) as f64;
+    (mean, speeds.iter().copied().fold(0.0, f64::max))
+}
+
+/// The report's rows of parameters and of results.
+fn facts(recording: &Recording) -> (Vec<Fact>, Vec<Fact>) {
+    let snapshot = recording.snapshot;
+    let tick_ms = recording.tick_rate.as_secs_f64() * 1000.0;
+    let parameters = vec![
+        ("Scenario", snapshot.scenario_name.clone().unwrap_or_else(|| "sandbox".to_string())),
+        ("Seed", snapshot.seed.to_string()),
+        ("Arena", format!("{} × {} cells", snapshot.area_width, snapshot.area_height / snapshot.aspect)),
+        ("Aspect", snapshot.aspect.to_string()),
+        // A sum of no gravity forces is -0
+        ("Gravity", format!("{} cells/tick²", snapshot.gravity + 0.0)),
+        ("Speed", format!("{}x", snapshot.speed_multiplier)),
+        ("Tick", format!("{} ms", tick_ms)),
+        ("Balls", snapshot.balls.len().to_string()),
+    ];
+    let (kinetic, potential) = snapshot
+        .balls
+        .iter()
+        .map(|ball| snapshot.energy(ball))
+        .fold((0.0, 0.0), |(k, p), (kinetic, potential)| (k + kinetic, p + potential));
+    let (px, py) = snapshot.balls.iter().fold((0.0, 0.0), |(px, py), ball| (px + ball.mass * ball.vx, py + ball.mass * ball.vy));
+    let results = vec![
+        ("Ticks", format!("{} ({:.2} s)", snapshot.tick_count, snapshot.tick_count as f64 * tick_ms / 1000.0)),
+        ("Ticks charted", recording.ticks.len().to_string()),
+        ("Collisions", snapshot.collisions.to_string()),
+        ("Wall bounces", snapshot.wall_bounces.to_string()),
+        ("Kinetic energy", format!("{:.4}", kinetic)),
+        ("Potential energy", format!("{:.4}", potential)),
+        ("Momentum", format!("({:.4}, {:.4})", px, py)),
+    ];
+    (parameters, results)
+}
+
+/// One row per ball: id, mass, radius, where it ended and how fast it went.
+fn ball_rows(recording: &Recording) -> Vec<[String; 7]> {
+    recording
+        .snapshot
+        .balls
+        .iter()
+        .map(|ball| {
+            let history = recording.histories.iter().find(|(id, _)| *id == ball.id).map(|&(_, history)| history);
+            let (mean, top) = history.map_or((0.0, 0.0), speeds);
+            [
+                ball.id.to_string(),
+                ball.mass.to_string(),
+                ball.radius.to_string(),
+                format!("{:.2}", ball.x),
+                format!("{:.2}", ball.y),
+                format!("{:.3}", mean),
+                format!("{:.3}", top),
+            ]
+        })
+        .collect()
+}
+
+const BALL_HEADINGS: [&str; 7] = ["Ball", "Mass", "Radius", "x", "y", "Mean speed", "Top speed"];
+
+/// `values` at their ticks, thinned to [`MAX_POINTS`].
+fn points(recording: &Recording, values: &Samples) -> Vec<(f64, f64)> {
+    let step = values.len().div_ceil(MAX_POINTS).max(1);
+    with_ticks(recording.ticks, values).step_by(step).collect()
+}
+
+/// The Markdown report, linking the charts by the file names
+/// [`chart_path`] gives for `path`.
+pub fn markdown(recording: &Recording, path: &Path) -> String {
+    let (parameters, results) = facts(recording);
+    let mut out = String::new();
+    let title = recording.snapshot.scenario_name.as_deref().unwrap_or("sandbox");
+    let _ = writeln!(out, "# Run report: {}\n", title);
+    for (heading, rows) in [("Parameters", parameters), ("Results", results)] {
+        let _ = writeln!(out, "## {}\n\n| | |\n|---|---|", heading);
+        for (label, value) in rows {
+            let _ = writeln!(out, "| {} | {} |", label, value.replace('|', "\\|"));
+        }
+        out.push('\n');
+    }
+    let _ = writeln!(out, "## Balls\n\n| {} |\n|{}", BALL_HEADINGS.join(" | "), "---|".repeat(BALL_HEADINGS.len()));
+    for row in ball_rows(recording) {
+        let _ = writeln!(out, "| {} |", row.join(" | "));
+    }
+    out.push_str("\n## Charts\n");
+    for (name, title, _) in CHARTS {
+        let file = chart_path(path, name);
+        let file = file.file_name().map_or(String::new(), |file| file.to_string_lossy().into_owned());
+        let _ = write!(out, "\n![{}]({})\n", title, file);
+    }
+    out
+}
+
+/// A chart of `values` for every ball, as an SVG document.
+pub fn svg(recording: &Recording, title: &str, values: Values) -> String {
+    let (width, height, left, right, top, bottom) = (640.0, 260.0, 60.0, 20.0, 30.0, 30.0);
+    let series: Vec<(usize, Vec<(f64, f64)>)> =
+        recording.histories.iter().map(|&(id, history)| (id, points(recording, values(history)))).collect();
+    let all = series.iter().flat_map(|(_, points)| points.iter());
+    let (t_min, t_max, v_min, v_max) = all.fold((f64::MAX, f64::MIN, f64::MAX, f64::MIN), |(t0, t1, v0, v1), &(t, v)| {
+        (t0.min(t), t1.max(t), v0.min(v), v1.max(v))
+    });
+    let (t_min, t_max) = if t_min < t_max { (t_min, t_max) } else { (0.0, 1.0) };
+    let (v_min, v_max) = if v_min < v_max { (v_min, v_max) } else { (v_min.min(0.0) - 1.0, v_max.max(0.0) + 1.0) };
+    let x = |t: f64| left + (t - t_min) / (t_max - t_min) * (width - left - right);
+    let y = |v: f64| height - bottom - (v - v_min) / (v_max - v_min) * (height - top - bottom);
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="12">"#, width, height);
+    let _ = writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    let _ = writeln!(out, r#"<text x="{}" y="20" font-size="14">{}</text>"#, left, title);
+    let _ = writeln!(
+        out,
+        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#888"/>"##,
+        left,
+        top,
+        width - left - right,
+        height - top - bottom
+    );
+    let labels = [
+        (left - 6.0, top + 4.0, "end", format!("{:.2}", v_max)),
+        (left - 6.0, height - bottom, "end", format!("{:.2}", v_min)),
+        (left, height - bottom + 16.0, "start", format!("{}", t_min)),
+        (width - right, height - bottom + 16.0, "end", format!("{}", t_max)),
+    ];
+    for (lx, ly, anchor, text) in labels {
+        let _ = writeln!(out, r#"<text x="{}" y="{}" text-anchor="{}">{}</text>"#, lx, ly, anchor, text);
+    }
+    for (id, points) in &series {
+        let line: Vec<String> = points.iter().map(|&(t, v)| format!("{:.1},{:.1}", x(t), y(v))).collect();
+        let own = recording.snapshot.balls.iter().find(|ball| ball.id == *id).and_then(|ball| ball.color);
+        let color = own.map_or_else(|| COLORS[id % COLORS.len()].to_string(), |color| color.to_string());
+        let _ = writeln!(
+            out,
+            r#"<polyline fill="none" stroke="{}" stroke-width="1.5" points="{}"/>"#,
+            color,
+            line.join(" ")
+        );
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// The LaTeX report, a document of its own with pgfplots charts.
+pub fn latex(recording: &Recording) -> String {
+    let (parameters, results) = facts(recording);
+    let mut out = String::new();
+    out.push_str("\\documentclass{article}\n\\usepackage[utf8]{inputenc}\n\\usepackage{pgfplots}\n\\pgfplotsset{compat=1.16}\n");
+    let title = recording.snapshot.scenario_name.as_deref().unwrap_or("sandbox");
+    let _ = writeln!(out, "\\title{{Run report: {}}}\n\\date{{}}\n\\begin{{document}}\n\\maketitle", escape(title));
+    for (heading, rows) in [("Parameters", parameters), ("Results", results)] {
+        let _ = writeln!(out, "\n\\section*{{{}}}\n\\begin{{tabular}}{{ll}}", heading);
+        for (label, value) in rows {
+            let value = escape(&value).replace('×', "$\\times$").replace('²', "$^2$");
+            let _ = writeln!(out, "{} & {} \\\\", label, value);
+        }
+        out.push_str("\\end{tabular}\n");
+    }
+    let _ = writeln!(out, "\n\\section*{{Balls}}\n\\begin{{tabular}}{{{}}}", "r".repeat(BALL_HEADINGS.len()));
+    let _ = writeln!(out, "{} \\\\\n\\hline", BALL_HEADINGS.join(" & "));
+    for row in ball_rows(recording) {
+        let _ = writeln!(out, "{} \\\\", row.join(" & "));
+    }
+    out.push_str("\\end{tabular}\n\n\\section*{Charts}\n");
+    for (_, title, values) in CHARTS {
+        let _ = writeln!(out, "\n\\begin{{tikzpicture}}\n\\begin{{axis}}[title={{{}}}, xlabel={{Tick}}, width=\\textwidth, height=6cm]", title);
+        for &(id, history) in &recording.histories {
+            let coordinates: Vec<String> = points(recording, values(history)).iter().map(|(t, v)| format!("({},{:.4})", t, v)).collect();
+            let _ = writeln!(out, "\\addplot[no marks] coordinates {{{}}};", coordinates.join(" "));
+            let _ = writeln!(out, "\\addlegendentry{{Ball {}}}", id);
+        }
+        out.push_str("\\end{axis}\n\\end{tikzpicture}\n");
+    }
+    out.push_str("\n\\end{document}\n");
+    out
+}
+
+/// `text` with LaTeX's special characters escaped.
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rust_tui_ball_bounce::{BallState, Snapshot};
+
+    use super::*;
+
+    #[test]
+    fn reports_hold_the_facts_and_charts() {
+        let history = History { x: vec![2.0, 2.5].into(), y: vec![3.0, 3.0].into(), vx: vec![0.5, 0.5].into(), vy: vec![0.0, 0.0].into() };
+        let ball = BallState { id: 3, x: 2.5, y: 3.0, vx: 0.5, vy: 0.0, radius: 0.5, mass: 2.0, color: None, glyph: None, body: None };
+        let snapshot = Snapshot {
+            tick_count: 2,
+            seed: 7,
+            collisions: 4,
+            scenario_name: Some("my_gas".to_string()),
+            balls: vec![ball],
+            ..Snapshot::default()
+        };
+        let recording = Recording {
+            snapshot: &snapshot,
+            ticks: &[1.0, 2.0],
+            histories: vec![(3, &history)],
+            tick_rate: Duration::from_millis(20),
+        };
+
+        let markdown = markdown(&recording, Path::new("out/run.md"));
+        assert!(markdown.starts_with("# Run report: my_gas\n"));
+        assert!(markdown.contains("| Seed | 7 |"));
+        assert!(markdown.contains("| Collisions | 4 |"));
+        assert!(markdown.contains("| 3 | 2 | 0.5 | 2.50 | 3.00 | 0.500 | 0.500 |"));
+        assert!(markdown.contains("![X velocity](run.vx.svg)"));
+        assert_eq!(chart_path(Path::new("out/run.md"), "vx"), Path::new("out/run.vx.svg"));
+
+        let svg = svg(&recording, "X position", |history| &history.x);
+        assert!(svg.contains("<polyline"));
+
+        let latex = latex(&recording);
+        assert!(latex.contains("Run report: my\\_gas"));
+        assert!(latex.contains("coordinates {(1,2.0000) (2,2.5000)}"));
+        assert!(latex.trim_end().ends_with("\\end{document}"));
+    }
+}
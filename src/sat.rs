@@ -0,0 +1,122 @@
+//! Contact between convex outlines by the separating axis theorem: two
+//! convex shapes overlap exactly when their shadows overlap on every axis
+//! that could separate them, which for polygons are their edges' normals
+//! and for a circle against a polygon also the line from the circle's
+//! center to the polygon's nearest corner. The axis they overlap least on
+//! is the way to push them apart.
+
+/// A ball's outline in arena units: a circle, or a convex polygon's corners
+/// in order round it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Outline {
+    Circle { x: f64, y: f64, radius: f64 },
+    Polygon(Vec<(f64, f64)>),
+}
+
+impl Outline {
+    fn center(&self) -> (f64, f64) {
+        match self {
+            Outline::Circle { x, y, .. } => (*x, *y),
+            Outline::Polygon(corners) => {
+                let n = corners.len() as f64;
+                let (sx, sy) = corners.iter().fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+                (sx / n, sy / n)
+            }
+        }
+    }
+
+    // The span of the outline's shadow on the unit axis `(nx, ny)`
+    fn project(&self, nx: f64, ny: f64) -> (f64, f64) {
+        match self {
+            Outline::Circle { x, y, radius } => {
+                let at = x * nx + y * ny;
+                (at - radius, at + radius)
+            }
+            Outline::Polygon(corners) => corners
+                .iter()
+                .map(|&(x, y)| x * nx + y * ny)
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), at| (lo.min(at), hi.max(at))),
+        }
+    }
+
+    // The axes this outline could be separated from `other` along, not yet
+    // of unit length
+    fn axes(&self, other: &Outline, axes: &mut Vec<(f64, f64)>) {
+        match self {
+            Outline::Polygon(corners) => {
+                for (i, &(x, y)) in corners.iter().enumerate() {
+                    let (next_x, next_y) = corners[(i + 1) % corners.len()];
+                    axes.push((next_y - y, x - next_x));
+                }
+            }
+            Outline::Circle { x, y, .. } => {
+                let nearest = match other {
+                    Outline::Polygon(corners) => corners
+                        .iter()
+                        .copied()
+                        .min_by(|a, b| (a.0 - x).hypot(a.1 - y).total_cmp(&(b.0 - x).hypot(b.1 - y))),
+                    Outline::Circle { x, y, .. } => Some((*x, *y)),
+                };
+                if let Some((to_x, to_y)) = nearest {
+                    axes.push((to_x - x, to_y - y));
+                }
+            }
+        }
+    }
+}
+
+/// Where `a` and `b` overlap, the unit normal pointing from `a` toward `b`
+/// and how far they'd have to move apart along it to only touch.
+pub(crate) fn contact(a: &Outline, b: &Outline) -> Option<(f64, f64, f64)> {
+    let mut axes = Vec::new();
+    a.axes(b, &mut axes);
+    b.axes(a, &mut axes);
+    let mut best: Option<(f64, f64, f64)> = None;
+    for (ax, ay) in axes {
+        let length = ax.hypot(ay);
+        if length == 0.0 {
+            continue;
+        }
+        let (nx, ny) = (ax / length, ay / length);
+        let (a_lo, a_hi) = a.project(nx, ny);
+        let (b_lo, b_hi) = b.project(nx, ny);
+        let overlap = a_hi.min(b_hi) - a_lo.max(b_lo);
+        if overlap <= 0.0 {
+            return None;
+        }
+        if best.is_none_or(|(_, _, depth)| overlap < depth) {
+            best = Some((nx, ny, overlap));
+        }
+    }
+    let (nx, ny, depth) = best?;
+    let ((ax, ay), (bx, by)) = (a.center(), b.center());
+    if (bx - ax) * nx + (by - ay) * ny < 0.0 {
+        Some((-nx, -ny, depth))
+    } else {
+        Some((nx, ny, depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64, half: f64) -> Outline {
+        Outline::Polygon(vec![(x - half, y - half), (x + half, y - half), (x + half, y + half), (x - half, y + half)])
+    }
+
+    #[test]
+    fn corners_miss_circles_their_bounding_circles_would_hit() {
+        // Level with the square's side, the circle is pushed straight out
+        let (nx, ny, depth) = contact(&square(0.0, 0.0, 1.0), &Outline::Circle { x: 1.5, y: 0.0, radius: 1.0 }).unwrap();
+        assert_eq!((nx, ny), (1.0, 0.0));
+        assert!((depth - 0.5).abs() < 1e-12);
+        // Just past a corner, where only the circle round the square reaches
+        assert!(2.08f64.hypot(1.2) < 1.0 + 2.0f64.sqrt());
+        assert_eq!(contact(&square(0.0, 0.0, 1.0), &Outline::Circle { x: 2.08, y: 1.2, radius: 1.0 }), None);
+        let triangle = Outline::Polygon(vec![(0.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]);
+        let (nx, ny, _) = contact(&square(0.0, 1.5, 1.0), &triangle).unwrap();
+        assert_eq!((nx, ny), (0.0, -1.0));
+        assert_eq!(contact(&square(3.0, 0.0, 1.0), &triangle), None);
+    }
+}
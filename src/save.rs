@@ -0,0 +1,245 @@
+//! The versioned, serializable form of a [`Simulation`], and the schema
+//! version shared by everything this crate and its app write to disk or send
+//! elsewhere: saved states, exports and the config file.
+//!
+//! Every format carries a `version`. Data from an older version is brought up
+//! to date as it's read, so files keep loading as the formats grow; data
+//! from a newer version is refused rather than misread.
+
+use std::any::Any;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::components::{BallId, Charge, Glyph, Growth, Rgb, Shape, Spin, Ttl};
+use crate::force::{Attractor, Boids, Drag, Friction, Gravity, Wind};
+use crate::scenario::{BallSpec, Forces, Obstacle, Pockets, Thermostat};
+use crate::Simulation;
+
+/// The schema version written today. Bump it when a format changes in a way
+/// `#[serde(default)]` can't absorb, and add the upgrade step to
+/// [`SavedSimulation::upgrade`] (or the format's own equivalent).
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A `version` field, [`SCHEMA_VERSION`] unless read from older data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SchemaVersion(pub u32);
+
+impl Default for SchemaVersion {
+    fn default() -> Self {
+        SchemaVersion(SCHEMA_VERSION)
+    }
+}
+
+impl SchemaVersion {
+    /// Fails for versions newer than this build understands.
+    pub fn check(self) -> Result<(), SchemaError> {
+        if self.0 > SCHEMA_VERSION {
+            return Err(SchemaError::TooNew(self.0));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SchemaError {
+    #[error("schema version {0} is newer than this build supports ({SCHEMA_VERSION}); upgrade to read it")]
+    TooNew(u32),
+}
+
+/// Everything needed to pick a simulation up where it left off, apart from
+/// ball histories and the events not yet drained.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedSimulation {
+    pub version: SchemaVersion,
+    pub tick_count: u64,
+    pub paused: bool,
+    pub collisions: u64,
+    pub wall_bounces: u64,
+    pub ball_counter: usize,
+    pub area_width: f64,
+    pub area_height: f64,
+    pub speed_multiplier: f64,
+    pub ball_radius: f64,
+    pub aspect: f64,
+    pub scenario_name: Option<String>,
+    #[serde(default)]
+    pub gas: bool,
+    #[serde(default)]
+    pub count_hits: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_clicks: Option<u64>,
+    pub seed: u64,
+    // Only the built-in kinds; custom forces can't be saved
+    pub forces: Forces,
+    pub obstacles: Vec<Obstacle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pockets: Option<Pockets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thermostat: Option<Thermostat>,
+    // Ordered by id
+    pub balls: Vec<SavedBall>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedBall {
+    pub id: usize,
+    pub x: f64,
+    pub y: f64,
+    pub vx: f64,
+    pub vy: f64,
+    pub radius: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charge: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mass: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub growth: Option<Growth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<Shape>,
+    // In degrees, as in scenarios
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spin: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Rgb>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<Glyph>,
+}
+
+impl SavedSimulation {
+    /// Brings data from an older schema version up to date.
+    pub fn upgrade(mut self) -> Result<SavedSimulation, SchemaError> {
+        self.version.check()?;
+        // Version 1 is the first; steps from each older version go here,
+        // oldest first, e.g. `if self.version.0 < 2 { ... }`
+        self.version = SchemaVersion::default();
+        Ok(self)
+    }
+}
+
+impl Simulation {
+    /// The simulation as data, to serialize. Forces are combined into one
+    /// [`Forces`] table, so they come back in its order.
+    pub fn save(&self) -> SavedSimulation {
+        let mut forces = Forces { gravity_direction: self.gravity_direction, ..Forces::default() };
+        for force in &self.forces {
+            let force = &**force as &dyn Any;
+            if let Some(Gravity { g, .. }) = force.downcast_ref() {
+                forces.gravity += g;
+            } else if let Some(Drag(k)) = force.downcast_ref() {
+                forces.drag += k;
+            } else if let Some(Friction(f)) = force.downcast_ref() {
+                forces.friction += f;
+            } else if let Some(&wind) = force.downcast_ref::<Wind>() {
+                forces.wind = Some(wind);
+            } else if let Some(&attractor) = force.downcast_ref::<Attractor>() {
+                forces.attractors.push(attractor);
+            } else if let Some(&boids) = force.downcast_ref::<Boids>() {
+                forces.boids = Some(boids);
+            }
+        }
+        let balls = self
+            .balls
+            .iter()
+            .map(|ball| {
+                let (charge, ttl, growth, spin) = self
+                    .world
+                    .query::<(&BallId, Option<&Charge>, Option<&Ttl>, Option<&Growth>, Option<&Spin>)>()
+                    .iter()
+                    .find(|(id, _, _, _, _)| id.0 == ball.id)
+                    .map_or((None, None, None, None), |(_, charge, ttl, growth, spin)| {
+                        (charge.map(|c| c.0), ttl.map(|t| t.0), growth.copied(), spin.map(|s| s.0.to_degrees()))
+                    });
+                SavedBall {
+                    id: ball.id,
+                    x: ball.x,
+                    y: ball.y,
+                    vx: ball.vx,
+                    vy: ball.vy,
+                    radius: ball.radius,
+                    charge,
+                    mass: (ball.mass != 1.0).then_some(ball.mass),
+                    ttl,
+                    growth,
+                    shape: ball.body.map(|body| body.shape),
+                    angle: ball.body.map(|body| body.angle.to_degrees()),
+                    spin,
+                    color: ball.color,
+                    glyph: ball.glyph,
+                }
+            })
+            .collect();
+        SavedSimulation {
+            version: SchemaVersion::default(),
+            tick_count: self.tick_count,
+            paused: self.paused,
+            collisions: self.collisions,
+            wall_bounces: self.wall_bounces,
+            ball_counter: self.ball_counter,
+            area_width: self.area_width,
+            area_height: self.area_height,
+            speed_multiplier: self.speed_multiplier,
+            ball_radius: self.ball_radius,
+            aspect: self.aspect,
+            scenario_name: self.scenario_name.clone(),
+            gas: self.gas,
+            count_hits: self.count_hits,
+            final_clicks: self.final_clicks,
+            seed: self.seed,
+            forces,
+            obstacles: self.obstacles.clone(),
+            pockets: self.pockets,
+            thermostat: self.thermostat,
+            balls,
+        }
+    }
+
+    /// A simulation from saved data, upgraded first if it's from an older
+    /// version. Balls keep their ids.
+    pub fn restore(saved: SavedSimulation) -> Result<Simulation, SchemaError> {
+        let saved = saved.upgrade()?;
+        let mut sim = Simulation::new(saved.area_width, saved.area_height);
+        sim.tick_count = saved.tick_count;
+        sim.paused = saved.paused;
+        sim.collisions = saved.collisions;
+        sim.wall_bounces = saved.wall_bounces;
+        sim.speed_multiplier = saved.speed_multiplier;
+        sim.ball_radius = saved.ball_radius;
+        sim.aspect = saved.aspect;
+        sim.scenario_name = saved.scenario_name;
+        sim.gas = saved.gas;
+        sim.count_hits = saved.count_hits;
+        sim.final_clicks = saved.final_clicks;
+        sim.seed = saved.seed;
+        sim.forces = saved.forces.build();
+        sim.gravity_direction = saved.forces.gravity_direction;
+        sim.obstacles = saved.obstacles;
+        sim.pockets = saved.pockets;
+        sim.thermostat = saved.thermostat;
+        for ball in saved.balls {
+            // `add_ball` takes the next id from the counter
+            sim.ball_counter = ball.id;
+            sim.add_ball(BallSpec {
+                radius: Some(ball.radius),
+                charge: ball.charge,
+                mass: ball.mass,
+                ttl: ball.ttl,
+                growth: ball.growth,
+                shape: ball.shape,
+                angle: ball.angle,
+                spin: ball.spin,
+                color: ball.color,
+                glyph: ball.glyph,
+                ..BallSpec::new(ball.x, ball.y, ball.vx, ball.vy)
+            });
+        }
+        sim.ball_counter = saved.ball_counter;
+        sim.drain_events();
+        Ok(sim)
+    }
+}
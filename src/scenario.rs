@@ -0,0 +1,473 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::components::{Glyph, Growth, Rgb, Shape};
+use crate::force::{Attractor, Boids, Direction, Drag, Force, Friction, Gravity, Wind};
+use crate::rng::Rng;
+
+/// A scenario file: initial balls, forces, obstacles and parameters.
+/// Positions and sizes are in arena cells; velocities in cells per tick.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub parameters: Parameters,
+    #[serde(default)]
+    pub forces: Forces,
+    #[serde(default)]
+    pub balls: Vec<BallSpec>,
+    #[serde(default)]
+    pub groups: Vec<BallGroup>,
+    #[serde(default)]
+    pub obstacles: Vec<Obstacle>,
+    pub pockets: Option<Pockets>,
+    pub thermostat: Option<Thermostat>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub speed: Option<f64>,
+    pub ball_radius: Option<f64>,
+    // A gas of like balls, whose collision statistics are worth comparing
+    // with kinetic theory's
+    #[serde(default)]
+    pub gas: bool,
+    // Show how many times balls have hit each other and the walls, for
+    // demos where the count is the point, until balls in a row along x are
+    // moving apart for good
+    #[serde(default)]
+    pub count_hits: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Forces {
+    // Acceleration toward `gravity_direction`, cells per tick²
+    pub gravity: f64,
+    // "down" unless set to "up", "left", "right" or "center"
+    pub gravity_direction: Direction,
+    // Fraction of velocity lost per tick
+    pub drag: f64,
+    // Speed lost per tick, cells per tick²
+    pub friction: f64,
+    pub wind: Option<Wind>,
+    pub attractors: Vec<Attractor>,
+    // Flocking, with `[forces.boids]` on its own taking the default weights
+    pub boids: Option<Boids>,
+}
+
+impl Forces {
+    /// The forces this table describes, in the order they apply each tick.
+    pub fn build(&self) -> Vec<Box<dyn Force>> {
+        let mut forces: Vec<Box<dyn Force>> = Vec::new();
+        if self.gravity != 0.0 {
+            // The simulation puts the center where it belongs as it steps
+            forces.push(Box::new(Gravity { g: self.gravity, direction: self.gravity_direction, center: (0.0, 0.0) }));
+        }
+        if let Some(wind) = self.wind {
+            forces.push(Box::new(wind));
+        }
+        for &attractor in &self.attractors {
+            forces.push(Box::new(attractor));
+        }
+        if let Some(boids) = self.boids {
+            forces.push(Box::new(boids));
+        }
+        if self.drag != 0.0 {
+            forces.push(Box::new(Drag(self.drag)));
+        }
+        if self.friction != 0.0 {
+            forces.push(Box::new(Friction(self.friction)));
+        }
+        forces
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BallSpec {
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub vx: f64,
+    #[serde(default)]
+    pub vy: f64,
+    pub radius: Option<f64>,
+    pub charge: Option<f64>,
+    // 1 when unset
+    pub mass: Option<f64>,
+    // Ticks before the ball disappears
+    pub ttl: Option<u64>,
+    // Inflating or deflating as it goes
+    pub growth: Option<Growth>,
+    // A square or triangle rather than a circle, turned `angle` degrees
+    // clockwise and turning `spin` degrees a tick
+    pub shape: Option<Shape>,
+    pub angle: Option<f64>,
+    pub spin: Option<f64>,
+    pub color: Option<Rgb>,
+    pub glyph: Option<Glyph>,
+}
+
+impl BallSpec {
+    /// A ball at `(x, y)` moving at `(vx, vy)`, with the simulation's default
+    /// radius, color, glyph and mass and no charge, lifetime, growth or
+    /// shape.
+    pub fn new(x: f64, y: f64, vx: f64, vy: f64) -> BallSpec {
+        BallSpec {
+            x,
+            y,
+            vx,
+            vy,
+            radius: None,
+            charge: None,
+            mass: None,
+            ttl: None,
+            growth: None,
+            shape: None,
+            angle: None,
+            spin: None,
+            color: None,
+            glyph: None,
+        }
+    }
+}
+
+impl BallSpec {
+    /// Checks the ball is one a scenario file could hold: finite numbers, a
+    /// radius and growth within the scenario limits, a positive mass, and an
+    /// angle or spin only with a shape. Balls from elsewhere, such as
+    /// imported files and scripts, go through the same checks.
+    pub fn validate(&self) -> Result<(), String> {
+        if ![self.x, self.y, self.vx, self.vy].iter().chain(&self.charge).all(|value| value.is_finite()) {
+            return Err("expected finite numbers".to_string());
+        }
+        if let Some(radius) = self.radius {
+            check_radius(radius)?;
+        }
+        if let Some(growth) = &self.growth {
+            check_growth(growth)?;
+        }
+        check_turns(self.shape, [self.angle, self.spin])?;
+        match self.mass {
+            Some(mass) if !(mass > 0.0 && mass.is_finite()) => Err(format!("mass {} must be more than 0", mass)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn check_radius(radius: f64) -> Result<(), String> {
+    if (0.1..=5.0).contains(&radius) {
+        Ok(())
+    } else {
+        Err(format!("radius {} is outside the range 0.1-5.0", radius))
+    }
+}
+
+fn check_growth(growth: &Growth) -> Result<(), String> {
+    if growth.rate.is_finite() && 0.1 <= growth.min && growth.min <= growth.max && growth.max <= 5.0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "growth of {} between {} and {} needs a rate and 0.1 <= min <= max <= 5",
+            growth.rate, growth.min, growth.max
+        ))
+    }
+}
+
+fn check_turns(shape: Option<Shape>, turns: [Option<f64>; 2]) -> Result<(), String> {
+    match turns.into_iter().flatten().find(|turn| shape.is_none() || !turn.is_finite()) {
+        Some(turn) => Err(format!("angle or spin {} needs a shape and a finite number of degrees", turn)),
+        None => Ok(()),
+    }
+}
+
+/// `count` balls at random positions with random headings at a fixed speed.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BallGroup {
+    pub count: usize,
+    pub speed: f64,
+    pub radius: Option<f64>,
+    pub growth: Option<Growth>,
+    // Each turned at random
+    pub shape: Option<Shape>,
+    pub spin: Option<f64>,
+    // [x0, y0, x1, y1]; the whole arena when omitted
+    pub region: Option<[f64; 4]>,
+}
+
+/// Axis-aligned block of wall cells.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Obstacle {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Six pockets that take any ball whose center comes within `radius` of
+/// one: a pocket in each corner and one halfway along the top and bottom
+/// walls, wherever those are as the arena is resized.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pockets {
+    pub radius: f64,
+}
+
+impl Pockets {
+    /// The pocket centers for balls kept within `[0, right] x [0, floor]`.
+    pub fn centers(&self, right: f64, floor: f64) -> [(f64, f64); 6] {
+        let middle = right / 2.0;
+        [(0.0, 0.0), (middle, 0.0), (right, 0.0), (0.0, floor), (middle, floor), (right, floor)]
+    }
+}
+
+/// Walls with a temperature: a ball leaving one goes at a speed drawn from
+/// the wall's thermal distribution rather than the one it came in at, so a
+/// hot floor heats a gas and a cold ceiling cools it. A temperature is the
+/// mean kinetic energy it gives a ball, half its mass times the mean square
+/// speed; walls left unset reflect balls as usual.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thermostat {
+    pub top: Option<f64>,
+    pub bottom: Option<f64>,
+    pub left: Option<f64>,
+    pub right: Option<f64>,
+}
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("head-on", include_str!("../scenarios/head-on.toml")),
+    ("gas", include_str!("../scenarios/gas.toml")),
+    ("gravity-cascade", include_str!("../scenarios/gravity-cascade.toml")),
+    ("newtons-cradle", include_str!("../scenarios/newtons-cradle.toml")),
+    ("velocity-exchange", include_str!("../scenarios/velocity-exchange.toml")),
+    ("projectile", include_str!("../scenarios/projectile.toml")),
+    ("pi-1", include_str!("../scenarios/pi-1.toml")),
+    ("pi-100", include_str!("../scenarios/pi-100.toml")),
+    ("pi-10000", include_str!("../scenarios/pi-10000.toml")),
+    ("flock", include_str!("../scenarios/flock.toml")),
+    ("convection", include_str!("../scenarios/convection.toml")),
+    ("billiards", include_str!("../scenarios/billiards.toml")),
+    ("dodge", include_str!("../scenarios/dodge.toml")),
+    ("targets", include_str!("../scenarios/targets.toml")),
+];
+
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error(transparent)]
+    Parse(#[from] toml::de::Error),
+    #[error("{0}")]
+    Invalid(String),
+    // The underlying error is part of the message rather than a `source`, so
+    // it shows up wherever the error is printed
+    #[error("{}: {error}", path.display())]
+    Read { path: PathBuf, error: io::Error },
+    #[error("{}: {error}", path.display())]
+    File { path: PathBuf, error: Box<ScenarioError> },
+    #[error("unknown scenario \"{name}\" (built-ins: {})", builtin_names().join(", "))]
+    Unknown { name: String },
+}
+
+#[derive(Clone, Debug)]
+pub enum Source {
+    Builtin(&'static str),
+    File(PathBuf),
+}
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub scenario: Scenario,
+    pub source: Source,
+}
+
+impl Scenario {
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let scenario: Scenario = toml::from_str(text)?;
+        scenario.validate().map_err(ScenarioError::Invalid)?;
+        Ok(scenario)
+    }
+
+    pub fn load(path: &Path) -> Result<Scenario, ScenarioError> {
+        let text = fs::read_to_string(path).map_err(|error| ScenarioError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Scenario::parse(&text).map_err(|error| ScenarioError::File {
+            path: path.to_path_buf(),
+            error: Box::new(error),
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(speed) = self.parameters.speed {
+            if !(0.25..=5.0).contains(&speed) {
+                return Err(format!("parameters.speed = {} is outside the range 0.25-5.0", speed));
+            }
+        }
+        if let Some(radius) = self.parameters.ball_radius {
+            check_radius(radius)?;
+        }
+        for ball in &self.balls {
+            ball.validate()?;
+        }
+        for group in &self.groups {
+            if let Some(radius) = group.radius {
+                check_radius(radius)?;
+            }
+            if let Some(growth) = &group.growth {
+                check_growth(growth)?;
+            }
+            check_turns(group.shape, [None, group.spin])?;
+        }
+        if !(0.0..1.0).contains(&self.forces.drag) {
+            return Err(format!("forces.drag = {} must be in [0, 1)", self.forces.drag));
+        }
+        if !(self.forces.friction >= 0.0 && self.forces.friction.is_finite()) {
+            return Err(format!("forces.friction = {} must be 0 or more", self.forces.friction));
+        }
+        if let Some(wind) = &self.forces.wind {
+            if !(0.0..1.0).contains(&wind.strength) {
+                return Err(format!("forces.wind.strength = {} must be in [0, 1)", wind.strength));
+            }
+        }
+        if let Some(boids) = &self.forces.boids {
+            if let Some(weight) = [boids.separation, boids.alignment, boids.cohesion].into_iter().find(|&w| !(w >= 0.0 && w.is_finite())) {
+                return Err(format!("forces.boids weight {} must be 0 or more", weight));
+            }
+            if !(1.0..=20.0).contains(&boids.radius) {
+                return Err(format!("forces.boids.radius = {} is outside the range 1-20", boids.radius));
+            }
+            if !(0.0 <= boids.min_speed && boids.min_speed <= boids.max_speed && boids.max_speed.is_finite()) {
+                return Err(format!(
+                    "forces.boids speeds {}-{} must be 0 or more, with min_speed up to max_speed",
+                    boids.min_speed, boids.max_speed
+                ));
+            }
+        }
+        if self.obstacles.iter().any(|o| o.width <= 0.0 || o.height <= 0.0) {
+            return Err("obstacles need a positive width and height".to_string());
+        }
+        if let Some(pockets) = &self.pockets {
+            if !(0.5..=5.0).contains(&pockets.radius) {
+                return Err(format!("pockets.radius = {} is outside the range 0.5-5.0", pockets.radius));
+            }
+        }
+        if let Some(thermostat) = &self.thermostat {
+            let walls = [("top", thermostat.top), ("bottom", thermostat.bottom), ("left", thermostat.left), ("right", thermostat.right)];
+            for (wall, temperature) in walls {
+                if let Some(temperature) = temperature.filter(|t| !(0.0..=1.0).contains(t)) {
+                    return Err(format!("thermostat.{} = {} is outside the range 0-1", wall, temperature));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Expands explicit balls and random groups into concrete specs for an
+    /// arena of the given size.
+    pub fn ball_specs(&self, width: f64, height: f64) -> Vec<BallSpec> {
+        let mut specs = self.balls.clone();
+        specs.extend(expand_groups(&self.groups, self.seed, width, height));
+        specs
+    }
+}
+
+/// Concrete balls for random `groups` in an arena of the given size. The
+/// same seed always gives the same balls.
+pub fn expand_groups(groups: &[BallGroup], seed: u64, width: f64, height: f64) -> Vec<BallSpec> {
+    let mut rng = Rng::new(seed);
+    let mut specs = Vec::new();
+    for group in groups {
+        let [x0, y0, x1, y1] = group.region.unwrap_or([0.0, 0.0, width - 1.0, height - 1.0]);
+        for _ in 0..group.count {
+            let angle = rng.range(0.0, std::f64::consts::TAU);
+            // Drawn only for shapes, so groups of round balls come out as they always have
+            let turned = group.shape.map(|_| rng.range(0.0, 360.0));
+            specs.push(BallSpec {
+                x: rng.range(x0, x1),
+                y: rng.range(y0, y1),
+                vx: group.speed * angle.cos(),
+                vy: group.speed * angle.sin(),
+                radius: group.radius,
+                charge: None,
+                mass: None,
+                ttl: None,
+                growth: group.growth,
+                shape: group.shape,
+                angle: turned,
+                spin: group.spin,
+                color: None,
+                glyph: None,
+            });
+        }
+    }
+    specs
+}
+
+pub fn builtin(slug: &str) -> Option<Scenario> {
+    BUILTIN
+        .iter()
+        .find(|(name, _)| *name == slug)
+        .map(|(_, text)| Scenario::parse(text).expect("built-in scenarios are valid"))
+}
+
+/// Resolves `--scenario`: a built-in name, or otherwise a path to a file.
+pub fn resolve(name_or_path: &str) -> Result<Scenario, ScenarioError> {
+    match builtin(name_or_path) {
+        Some(scenario) => Ok(scenario),
+        None if Path::new(name_or_path).exists() => Scenario::load(Path::new(name_or_path)),
+        None => Err(ScenarioError::Unknown {
+            name: name_or_path.to_string(),
+        }),
+    }
+}
+
+/// The built-in presets' names, in the order the picker lists them.
+pub fn builtin_names() -> Vec<&'static str> {
+    BUILTIN.iter().map(|(name, _)| *name).collect()
+}
+
+/// Built-in presets followed by any `*.toml` files in `dir`. Files that fail
+/// to parse are returned as errors alongside the entries that loaded.
+pub fn discover(dir: Option<&Path>) -> (Vec<Entry>, Vec<ScenarioError>) {
+    let mut entries: Vec<Entry> = BUILTIN
+        .iter()
+        .map(|&(slug, text)| Entry {
+            scenario: Scenario::parse(text).expect("built-in scenarios are valid"),
+            source: Source::Builtin(slug),
+        })
+        .collect();
+    let mut errors = Vec::new();
+
+    let Some(files) = dir.and_then(|dir| fs::read_dir(dir).ok()) else {
+        return (entries, errors);
+    };
+    let mut paths: Vec<PathBuf> = files
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        match Scenario::load(&path) {
+            Ok(scenario) => entries.push(Entry {
+                scenario,
+                source: Source::File(path),
+            }),
+            Err(err) => errors.push(err),
+        }
+    }
+    (entries, errors)
+}
@@ -0,0 +1,102 @@
+//! High scores, kept in `scores.toml` next to the config file: each game's
+//! best few results, and how many runs it has seen with their total, for an
+//! average.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+// Results kept per game
+const KEPT: usize = 5;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scores {
+    // Shots to clear the table
+    pub billiards: Board,
+    // Seconds survived
+    pub dodge: Board,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Board {
+    // Best first
+    pub best: Vec<f64>,
+    pub runs: u64,
+    pub total: f64,
+}
+
+impl Board {
+    /// Counts a run's `result`, returning where it placed in [`best`](Self::best)
+    /// if it made it. Lower results are better if `lower_wins`, and a tie
+    /// places after the results already there.
+    pub fn record(&mut self, result: f64, lower_wins: bool) -> Option<usize> {
+        self.runs += 1;
+        self.total += result;
+        let place = self.best.partition_point(|&best| if lower_wins { best <= result } else { best >= result });
+        if place >= KEPT {
+            return None;
+        }
+        self.best.insert(place, result);
+        self.best.truncate(KEPT);
+        Some(place)
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        (self.runs > 0).then(|| self.total / self.runs as f64)
+    }
+}
+
+/// Reads the scores file. A missing file yields no scores yet.
+pub fn load(path: &Path) -> Result<Scores, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Scores::default()),
+        Err(err) => Err(format!("{}: {}", path.display(), err)),
+    }
+}
+
+/// Writes the scores file, creating its directory if needed.
+pub fn save(path: &Path, scores: &Scores) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = toml::to_string_pretty(scores).map_err(io::Error::other)?;
+    fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boards_keep_the_best_few() {
+        let mut board = Board::default();
+        for result in [9.0, 20.5, 3.0, 20.5, 14.0, 1.0] {
+            board.record(result, false);
+        }
+        assert_eq!(board.best, [20.5, 20.5, 14.0, 9.0, 3.0]);
+        assert_eq!(board.record(12.0, false), Some(3));
+        assert_eq!(board.record(0.5, false), None);
+        assert_eq!((board.runs, board.average()), (8, Some(80.5 / 8.0)));
+
+        let mut board = Board::default();
+        assert_eq!(board.record(30.0, true), Some(0));
+        assert_eq!(board.record(22.0, true), Some(0));
+        assert_eq!(board.record(22.0, true), Some(1));
+        assert_eq!(board.best, [22.0, 22.0, 30.0]);
+    }
+
+    #[test]
+    fn scores_read_back_from_toml() {
+        let mut scores = Scores::default();
+        scores.dodge.record(14.8, false);
+        let text = toml::to_string_pretty(&scores).unwrap();
+        assert_eq!(toml::from_str::<Scores>(&text).unwrap(), scores);
+        // Games added later start empty
+        assert_eq!(toml::from_str::<Scores>("").unwrap(), Scores::default());
+    }
+}
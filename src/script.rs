@@ -0,0 +1,391 @@
+//! `--script PATH` and the palette's `eval`: [Rhai] scripts that drive the
+//! simulation. A script's top level runs once when it's loaded; the hooks it
+//! defines then run for every update:
+//!
+//! - `fn on_tick(tick)` after each tick
+//! - `fn on_collision(a, b, impulse)` for each collision between two balls
+//!
+//! `attach(id, "name")` gives one ball a behavior of its own: the script's
+//! `fn name(ball, tick)` then runs after each tick with that ball's state,
+//! until `detach(id)` or the ball is gone.
+//!
+//! Scripts see the state as of the latest update, and their changes reach
+//! the simulation in the order they were made.
+//!
+//! [Rhai]: https://rhai.rs
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::rc::Rc;
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT, INT};
+use rust_tui_ball_bounce::force::{Drag, Wind};
+use rust_tui_ball_bounce::{BallSpec, BallState, Command, SimEvent, Simulation, Snapshot};
+
+use crate::app::App;
+use crate::sim_thread::Update;
+
+// Operations one run may take, so a runaway loop can't freeze the app
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+type Edit = Box<dyn FnOnce(&mut Simulation) + Send>;
+
+/// A change a script asked for, sent on once it returns.
+enum Action {
+    Command(Command),
+    Edit(Edit),
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Snapshot,
+    actions: Vec<Action>,
+    // The function each ball with a behavior runs, by its id
+    behaviors: BTreeMap<usize, String>,
+    // The last line the script printed
+    printed: Option<String>,
+}
+
+/// The engine with the simulation API registered, and what scripts have
+/// defined so far.
+pub struct Script {
+    engine: Engine,
+    // Functions from the loaded file and from `eval`, without statements
+    functions: AST,
+    scope: Scope<'static>,
+    shared: Rc<RefCell<Shared>>,
+    // Cleared when a hook fails, so the error isn't repeated every tick
+    hooks: bool,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        let shared = Rc::new(RefCell::new(Shared::default()));
+        Script {
+            engine: engine(&shared),
+            functions: AST::empty(),
+            scope: Scope::new(),
+            shared,
+            hooks: true,
+        }
+    }
+}
+
+impl Script {
+    fn has_fn(&self, name: &str) -> bool {
+        self.hooks && self.functions.iter_functions().any(|f| f.name == name)
+    }
+
+    /// Runs `ast` against `state`, keeping the functions it defines.
+    fn run(&mut self, ast: AST, state: &Snapshot) -> Result<Dynamic, String> {
+        self.shared.borrow_mut().state = state.clone();
+        let ast = self.functions.merge(&ast);
+        let value = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut self.scope, &ast)
+            .map_err(|err| err.to_string())?;
+        self.functions = ast.clone_functions_only();
+        Ok(value)
+    }
+
+    fn call(&mut self, name: &str, args: impl rhai::FuncArgs) -> Result<(), String> {
+        self.engine
+            .call_fn::<Dynamic>(&mut self.scope, &self.functions, name, args)
+            .map(|_| ())
+            .map_err(|err| format!("{}: {}", name, err))
+    }
+}
+
+impl App {
+    /// Runs the script at `path` and keeps its hooks, after any loaded before.
+    pub fn load_script(&mut self, path: &Path) -> Result<(), String> {
+        let ast = self
+            .script
+            .engine
+            .compile_file(path.into())
+            .map_err(|err| err.to_string())?;
+        let result = self.script.run(ast, &self.state);
+        self.send_script_actions();
+        result.map(|_| ())
+    }
+
+    /// Runs `code` alongside the loaded script, returning what it evaluates
+    /// to unless that's nothing.
+    pub fn eval_script(&mut self, code: &str) -> Result<Option<String>, String> {
+        let ast = self
+            .script
+            .engine
+            .compile_with_scope(&self.script.scope, code)
+            .map_err(|err| err.to_string())?;
+        // A new hook gets a chance even if an old one failed
+        self.script.hooks = true;
+        let result = self.script.run(ast, &self.state);
+        self.send_script_actions();
+        let value = result?;
+        Ok((!value.is_unit()).then(|| value.to_string()))
+    }
+
+    /// Calls the hooks the script defines for `update`. The first error is
+    /// shown as a notice and stops the hooks until the next `eval`.
+    pub fn run_script_hooks(&mut self, update: &Update) {
+        let ticked = update.snapshot.tick_count > self.state.tick_count;
+        let on_tick = ticked && self.script.has_fn("on_tick");
+        let on_collision = self.script.has_fn("on_collision");
+        let behaviors = {
+            let mut shared = self.script.shared.borrow_mut();
+            // Ids start again from 0 after a reset, and a gone ball's
+            // behavior shouldn't pass to a new one
+            if update.events.iter().any(|event| matches!(event, SimEvent::Reset)) {
+                shared.behaviors.clear();
+            }
+            let balls = &update.snapshot.balls;
+            shared.behaviors.retain(|id, _| balls.iter().any(|ball| ball.id == *id));
+            ticked && self.script.hooks && !shared.behaviors.is_empty()
+        };
+        if !on_tick && !on_collision && !behaviors {
+            return;
+        }
+        self.script.shared.borrow_mut().state = update.snapshot.clone();
+        let tick = update.snapshot.tick_count as INT;
+        let mut result = Ok(());
+        if on_tick {
+            result = self.script.call("on_tick", (tick,));
+        }
+        if behaviors {
+            let attached = self.script.shared.borrow().behaviors.clone();
+            for (id, name) in attached {
+                if let Some(ball) = update.snapshot.balls.iter().find(|ball| ball.id == id) {
+                    let ball = Dynamic::from_map(ball_map(ball));
+                    result = result.and_then(|()| self.script.call(&name, (ball, tick)));
+                }
+            }
+        }
+        for event in &update.events {
+            if let (true, SimEvent::Collision { a, b, impulse, .. }) = (on_collision, event) {
+                result = result.and_then(|()| {
+                    self.script
+                        .call("on_collision", (*a as INT, *b as INT, *impulse as FLOAT))
+                });
+            }
+        }
+        if let Err(err) = result {
+            self.script.hooks = false;
+            self.notice = Some(format!("Script stopped: {}", err));
+        }
+        self.send_script_actions();
+    }
+
+    fn send_script_actions(&mut self) {
+        let mut shared = self.script.shared.borrow_mut();
+        for action in shared.actions.drain(..) {
+            match action {
+                Action::Command(command) => self.sim.command(command),
+                Action::Edit(edit) => self.sim.edit(edit),
+            }
+        }
+        if let Some(printed) = shared.printed.take() {
+            self.notice = Some(printed);
+        }
+    }
+}
+
+/// Accepts an integer wherever a number is expected, as scripts write both.
+fn number(value: &Dynamic) -> Result<f64, Box<EvalAltResult>> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|i| i as f64))
+        .map_err(|type_name| format!("expected a number, got {}", type_name).into())
+}
+
+/// A ball as scripts see it: a map of its `id`, `x`, `y`, `vx`, `vy` and
+/// `radius`.
+fn ball_map(ball: &BallState) -> Map {
+    let mut map = Map::new();
+    map.insert("id".into(), Dynamic::from_int(ball.id as INT));
+    for (key, value) in [
+        ("x", ball.x),
+        ("y", ball.y),
+        ("vx", ball.vx),
+        ("vy", ball.vy),
+        ("radius", ball.radius),
+    ] {
+        map.insert(key.into(), Dynamic::from_float(value));
+    }
+    map
+}
+
+fn engine(shared: &Rc<RefCell<Shared>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let out = Rc::clone(shared);
+    engine.on_print(move |line| out.borrow_mut().printed = Some(line.to_string()));
+
+    let command = |command: fn() -> Command| {
+        let shared = Rc::clone(shared);
+        move || shared.borrow_mut().actions.push(Action::Command(command()))
+    };
+    engine.register_fn("pause", command(|| Command::SetPaused(true)));
+    engine.register_fn("resume", command(|| Command::SetPaused(false)));
+    engine.register_fn("remove", command(|| Command::RemoveBall));
+    engine.register_fn("clear", command(|| Command::Reset));
+
+    let setter = |command: fn(f64) -> Command| {
+        let shared = Rc::clone(shared);
+        move |value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+            let value = number(&value)?;
+            shared
+                .borrow_mut()
+                .actions
+                .push(Action::Command(command(value)));
+            Ok(())
+        }
+    };
+    engine.register_fn("speed", setter(Command::SetSpeed));
+    engine.register_fn("gravity", setter(Command::SetGravity));
+
+    let edit = |shared: &Rc<RefCell<Shared>>| {
+        let shared = Rc::clone(shared);
+        move |edit: Edit| shared.borrow_mut().actions.push(Action::Edit(edit))
+    };
+    let push = edit(shared);
+    engine.register_fn(
+        "add_ball",
+        move |x: Dynamic, y: Dynamic, vx: Dynamic, vy: Dynamic| {
+            let spec = BallSpec::new(number(&x)?, number(&y)?, number(&vx)?, number(&vy)?);
+            push(Box::new(move |sim| {
+                sim.add_ball(spec);
+            }));
+            Ok::<_, Box<EvalAltResult>>(())
+        },
+    );
+    let push = edit(shared);
+    engine.register_fn(
+        "add_ball",
+        move |x: Dynamic,
+              y: Dynamic,
+              vx: Dynamic,
+              vy: Dynamic,
+              radius: Dynamic|
+              -> Result<(), Box<EvalAltResult>> {
+            let spec = BallSpec {
+                radius: Some(number(&radius)?),
+                ..BallSpec::new(number(&x)?, number(&y)?, number(&vx)?, number(&vy)?)
+            };
+            spec.validate()?;
+            push(Box::new(move |sim| {
+                sim.add_ball(spec);
+            }));
+            Ok(())
+        },
+    );
+    let push = edit(shared);
+    engine.register_fn("kick", move |id: INT, dvx: Dynamic, dvy: Dynamic| {
+        let (dvx, dvy) = (number(&dvx)?, number(&dvy)?);
+        push(Box::new(move |sim| {
+            sim.push_ball(id as usize, dvx, dvy);
+        }));
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let push = edit(shared);
+    engine.register_fn("drag", move |k: Dynamic| {
+        let drag = Drag(number(&k)?);
+        push(Box::new(move |sim| sim.add_force(drag)));
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let push = edit(shared);
+    engine.register_fn(
+        "wind",
+        move |vx: Dynamic, vy: Dynamic, strength: Dynamic| {
+            let wind = Wind {
+                vx: number(&vx)?,
+                vy: number(&vy)?,
+                strength: number(&strength)?,
+            };
+            push(Box::new(move |sim| sim.add_force(wind)));
+            Ok::<_, Box<EvalAltResult>>(())
+        },
+    );
+
+    let state = Rc::clone(shared);
+    engine.register_fn("tick", move || state.borrow().state.tick_count as INT);
+    let state = Rc::clone(shared);
+    engine.register_fn("arena", move || {
+        let state = &state.borrow().state;
+        let mut arena = Map::new();
+        arena.insert("width".into(), Dynamic::from_float(state.area_width));
+        arena.insert("height".into(), Dynamic::from_float(state.area_height));
+        arena
+    });
+    let state = Rc::clone(shared);
+    engine.register_fn("balls", move || -> Array {
+        let balls = &state.borrow().state.balls;
+        balls.iter().map(|ball| Dynamic::from_map(ball_map(ball))).collect()
+    });
+
+    let behaviors = Rc::clone(shared);
+    engine.register_fn("attach", move |id: INT, name: &str| -> Result<(), Box<EvalAltResult>> {
+        let mut shared = behaviors.borrow_mut();
+        if !shared.state.balls.iter().any(|ball| ball.id as INT == id) {
+            return Err(format!("there's no ball {}", id).into());
+        }
+        shared.behaviors.insert(id as usize, name.to_string());
+        Ok(())
+    });
+    let behaviors = Rc::clone(shared);
+    engine.register_fn("detach", move |id: INT| {
+        behaviors.borrow_mut().behaviors.remove(&(id as usize)).is_some()
+    });
+    engine
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions(script: &mut Script, code: &str) -> Vec<String> {
+        let ast = script.engine.compile(code).unwrap();
+        let _ = script.run(ast, &Snapshot::default()).unwrap();
+        let shared = &mut script.shared.borrow_mut();
+        shared
+            .actions
+            .drain(..)
+            .map(|action| match action {
+                Action::Command(command) => format!("{:?}", command),
+                Action::Edit(_) => "edit".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn calls_queue_actions_in_order_and_functions_are_kept() {
+        let mut script = Script::default();
+        let queued = actions(
+            &mut script,
+            "fn fast() { speed(2) } pause(); add_ball(1, 2.5, 0, 0); fast();",
+        );
+        assert_eq!(queued, ["SetPaused(true)", "edit", "SetSpeed(2.0)"]);
+        assert_eq!(actions(&mut script, "fast()"), ["SetSpeed(2.0)"]);
+        assert!(script.has_fn("fast"));
+        let ast = script.engine.compile("add_ball(1, 2, 0, 0, 9)").unwrap();
+        assert!(script.run(ast, &Snapshot::default()).is_err());
+    }
+
+    #[test]
+    fn behaviors_are_attached_only_to_balls_that_exist() {
+        let mut script = Script::default();
+        let ball = BallState { id: 3, x: 1.0, y: 2.0, vx: 0.5, vy: 0.0, radius: 0.5, mass: 1.0, color: None, glyph: None, body: None };
+        let state = Snapshot { balls: vec![ball], ..Snapshot::default() };
+        let code = "fn home(ball, tick) { kick(ball.id, -ball.vx, tick) } attach(3, \"home\");";
+        let _ = script.run(script.engine.compile(code).unwrap(), &state).unwrap();
+        assert_eq!(script.shared.borrow().behaviors.get(&3).map(String::as_str), Some("home"));
+        assert!(script.run(script.engine.compile("attach(4, \"home\")").unwrap(), &state).is_err());
+
+        let ball = Dynamic::from_map(ball_map(&state.balls[0]));
+        script.call("home", (ball, 7 as INT)).unwrap();
+        assert_eq!(script.shared.borrow_mut().actions.drain(..).count(), 1);
+        let detached = script.run(script.engine.compile("detach(3)").unwrap(), &state).unwrap();
+        assert!(detached.as_bool().unwrap() && script.shared.borrow().behaviors.is_empty());
+    }
+}
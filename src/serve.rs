@@ -0,0 +1,65 @@
+//! `--serve`: streams the simulation to WebSocket clients as one JSON
+//! [`Snapshot`] per tick, for browser views and other live consumers.
+
+use std::io;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rust_tui_ball_bounce::{SimulationObserver, Snapshot};
+use tungstenite::Message;
+
+// Ticks queued per client before a slow one starts missing them
+const CLIENT_BACKLOG: usize = 64;
+
+type Clients = Arc<Mutex<Vec<SyncSender<Arc<str>>>>>;
+
+/// Accepts clients on a thread of its own and gives each a thread that
+/// writes to it, so a slow client never holds up the app.
+pub struct WebSocketServer {
+    clients: Clients,
+}
+
+impl WebSocketServer {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<WebSocketServer> {
+        let listener = TcpListener::bind(addr)?;
+        let clients = Clients::default();
+        let accepted = Arc::clone(&clients);
+        // Never joined: it waits in `accept` until the process exits
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (tx, rx) = mpsc::sync_channel(CLIENT_BACKLOG);
+                accepted.lock().unwrap().push(tx);
+                thread::spawn(move || serve_client(stream, rx));
+            }
+        });
+        Ok(WebSocketServer { clients })
+    }
+}
+
+impl SimulationObserver for WebSocketServer {
+    fn on_tick(&mut self, snapshot: &Snapshot) {
+        let mut clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+        let Ok(json) = serde_json::to_string(snapshot) else {
+            return;
+        };
+        let json: Arc<str> = json.into();
+        clients.retain(|tx| !matches!(tx.try_send(Arc::clone(&json)), Err(TrySendError::Disconnected(_))));
+    }
+}
+
+/// Completes the handshake, then sends ticks until the client goes away.
+fn serve_client(stream: TcpStream, ticks: Receiver<Arc<str>>) {
+    let Ok(mut socket) = tungstenite::accept(stream) else {
+        return;
+    };
+    for json in ticks {
+        if socket.send(Message::Text(json.to_string())).is_err() {
+            return;
+        }
+    }
+}
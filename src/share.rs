@@ -0,0 +1,317 @@
+//! `--share ADDR` and `join ADDR`: one app hosts the simulation and other
+//! terminals join it over TCP. Everyone sees the same arena, and each guest
+//! gets a color of its own to spawn, fling and remove balls in.
+//!
+//! Both ways the protocol is one JSON object per line. The host sends a
+//! guest [`HostMessage::Welcome`] with its color, then a
+//! [`HostMessage::State`] every tick; guests send [`GuestMessage`]s.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Modifier, Style},
+    text::Span,
+};
+use rust_tui_ball_bounce::components::Rgb;
+use rust_tui_ball_bounce::rng::Rng;
+use rust_tui_ball_bounce::{BallSpec, Simulation, SimulationObserver, Snapshot};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self as async_mpsc, UnboundedReceiver, UnboundedSender};
+
+use crate::app::App;
+use crate::theme::Theme;
+use crate::viewer::{self, Remote};
+
+// Ticks queued per guest before a slow one starts missing them
+const GUEST_BACKLOG: usize = 64;
+// Handed out in turn; guests after the eighth share colors, and balls
+const GUEST_COLORS: [Rgb; 8] = [
+    Rgb(0xff, 0x55, 0x55),
+    Rgb(0x55, 0xaa, 0xff),
+    Rgb(0x55, 0xdd, 0x55),
+    Rgb(0xff, 0xcc, 0x33),
+    Rgb(0xcc, 0x66, 0xff),
+    Rgb(0x33, 0xdd, 0xdd),
+    Rgb(0xff, 0x88, 0x33),
+    Rgb(0xff, 0x77, 0xcc),
+];
+const MAX_GUEST_BALLS: usize = 20;
+// The fastest a guest can spawn a ball, and the most one fling adds
+const MAX_GUEST_SPEED: f64 = 3.0;
+const SPAWN_SPEED: f64 = 0.5;
+const FLING: f64 = 0.5;
+
+/// What the host sends, generic so the state can be sent borrowed.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum HostMessage<S = Snapshot> {
+    Welcome { color: Rgb },
+    State(S),
+}
+
+/// What a guest asks of the host. Only balls in the guest's color are
+/// flung or removed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GuestMessage {
+    Spawn { x: f64, y: f64, vx: f64, vy: f64 },
+    Fling { dvx: f64, dvy: f64 },
+    // The guest's newest ball
+    Remove,
+}
+
+/// A message from a guest, waiting for the app to act on it.
+pub struct GuestRequest {
+    color: Rgb,
+    message: GuestMessage,
+}
+
+type Guests = Arc<Mutex<Vec<SyncSender<Arc<str>>>>>;
+
+/// Accepts guests on a thread of its own and gives each a thread that
+/// writes to it and one that reads from it.
+pub struct ShareServer {
+    guests: Guests,
+    rx: UnboundedReceiver<GuestRequest>,
+    // Also keeps `recv` pending rather than ending while nobody is listening
+    tx: UnboundedSender<GuestRequest>,
+}
+
+impl ShareServer {
+    /// A server nobody can join, for when `--share` isn't given.
+    pub fn disabled() -> ShareServer {
+        let (tx, rx) = async_mpsc::unbounded_channel();
+        ShareServer { guests: Guests::default(), rx, tx }
+    }
+
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<ShareServer> {
+        let listener = TcpListener::bind(addr)?;
+        let server = ShareServer::disabled();
+        let guests = Arc::clone(&server.guests);
+        let requests = server.tx.clone();
+        // Never joined: it waits in `accept` until the process exits
+        thread::spawn(move || {
+            for (n, stream) in listener.incoming().flatten().enumerate() {
+                let color = GUEST_COLORS[n % GUEST_COLORS.len()];
+                let Ok(reader) = stream.try_clone() else {
+                    continue;
+                };
+                let (tx, rx) = mpsc::sync_channel(GUEST_BACKLOG);
+                guests.lock().unwrap().push(tx);
+                thread::spawn(move || write_to_guest(stream, color, rx));
+                let requests = requests.clone();
+                thread::spawn(move || read_from_guest(BufReader::new(reader), color, requests));
+            }
+        });
+        Ok(server)
+    }
+
+    /// An observer that sends every tick to the guests.
+    pub fn broadcaster(&self) -> Broadcaster {
+        Broadcaster { guests: Arc::clone(&self.guests) }
+    }
+
+    pub async fn recv(&mut self) -> Option<GuestRequest> {
+        self.rx.recv().await
+    }
+}
+
+pub struct Broadcaster {
+    guests: Guests,
+}
+
+impl SimulationObserver for Broadcaster {
+    fn on_tick(&mut self, snapshot: &Snapshot) {
+        let mut guests = self.guests.lock().unwrap();
+        if guests.is_empty() {
+            return;
+        }
+        let Ok(json) = serde_json::to_string(&HostMessage::State(snapshot)) else {
+            return;
+        };
+        let json: Arc<str> = json.into();
+        guests.retain(|tx| !matches!(tx.try_send(Arc::clone(&json)), Err(TrySendError::Disconnected(_))));
+    }
+}
+
+/// Welcomes the guest, then sends ticks until it goes away.
+fn write_to_guest(mut stream: TcpStream, color: Rgb, ticks: Receiver<Arc<str>>) {
+    let Ok(welcome) = serde_json::to_string(&HostMessage::<Snapshot>::Welcome { color }) else {
+        return;
+    };
+    if writeln!(stream, "{}", welcome).is_err() {
+        return;
+    }
+    for json in ticks {
+        if writeln!(stream, "{}", json).is_err() {
+            return;
+        }
+    }
+}
+
+/// Passes the guest's messages to the app, skipping any it can't read.
+fn read_from_guest(reader: impl BufRead, color: Rgb, requests: UnboundedSender<GuestRequest>) {
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let Ok(message) = serde_json::from_str(&line) else {
+            continue;
+        };
+        if requests.send(GuestRequest { color, message }).is_err() {
+            return;
+        }
+    }
+}
+
+impl App {
+    pub fn handle_guest(&mut self, GuestRequest { color, message }: GuestRequest) {
+        self.sim.edit(move |sim| apply(sim, color, message));
+    }
+}
+
+/// Carries out a guest's message, within the limits on guests.
+fn apply(sim: &mut Simulation, color: Rgb, message: GuestMessage) {
+    let own: Vec<usize> = sim.state().iter().filter(|ball| ball.color == Some(color)).map(|ball| ball.id).collect();
+    match message {
+        GuestMessage::Spawn { x, y, vx, vy } => {
+            let inside = (0.0..sim.area_width).contains(&x) && (0.0..sim.area_height).contains(&y);
+            if own.len() < MAX_GUEST_BALLS && inside && vx.is_finite() && vy.is_finite() {
+                let (vx, vy) = limit(vx, vy);
+                sim.add_ball(BallSpec { color: Some(color), ..BallSpec::new(x, y, vx, vy) });
+            }
+        }
+        GuestMessage::Fling { dvx, dvy } if dvx.is_finite() && dvy.is_finite() => {
+            let (dvx, dvy) = limit(dvx, dvy);
+            for id in own {
+                sim.push_ball(id, dvx, dvy);
+            }
+        }
+        GuestMessage::Fling { .. } => {}
+        GuestMessage::Remove => {
+            if let Some(&id) = own.last() {
+                sim.remove_ball_by_id(id);
+            }
+        }
+    }
+}
+
+/// `(vx, vy)` scaled down to at most [`MAX_GUEST_SPEED`].
+fn limit(vx: f64, vy: f64) -> (f64, f64) {
+    let speed = vx.hypot(vy);
+    if speed <= MAX_GUEST_SPEED {
+        return (vx, vy);
+    }
+    (vx / speed * MAX_GUEST_SPEED, vy / speed * MAX_GUEST_SPEED)
+}
+
+/// A connection to a host, and the arena as of its latest message.
+pub struct Guest {
+    addr: String,
+    stream: TcpStream,
+    rx: UnboundedReceiver<HostMessage>,
+    color: Option<Rgb>,
+    state: Snapshot,
+    rng: Rng,
+}
+
+impl Guest {
+    pub fn connect(addr: &str) -> io::Result<Guest> {
+        let stream = TcpStream::connect(addr)?;
+        let rx = viewer::read_lines(BufReader::new(stream.try_clone()?));
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |t| t.as_nanos() as u64);
+        Ok(Guest { addr: addr.to_string(), stream, rx, color: None, state: Snapshot::default(), rng: Rng::new(seed) })
+    }
+
+    /// A ball somewhere in the arena, heading off in a random direction.
+    fn random_spawn(&mut self) -> GuestMessage {
+        let angle = self.rng.range(0.0, std::f64::consts::TAU);
+        GuestMessage::Spawn {
+            x: self.rng.range(1.0, (self.state.area_width - 1.0).max(1.0)),
+            y: self.rng.range(1.0, (self.state.area_height - 1.0).max(1.0)),
+            vx: SPAWN_SPEED * angle.cos(),
+            vy: SPAWN_SPEED * angle.sin(),
+        }
+    }
+
+    fn send(&mut self, message: &GuestMessage) -> io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        writeln!(self.stream, "{}", json)
+    }
+}
+
+impl Remote for Guest {
+    type Message = HostMessage;
+
+    fn messages(&mut self) -> &mut UnboundedReceiver<HostMessage> {
+        &mut self.rx
+    }
+
+    fn receive(&mut self, message: HostMessage) {
+        match message {
+            HostMessage::Welcome { color } => self.color = Some(color),
+            HostMessage::State(state) => self.state = state,
+        }
+    }
+
+    fn state(&self) -> Option<&Snapshot> {
+        Some(&self.state)
+    }
+
+    fn title(&self, _theme: &Theme, ascii: bool) -> Vec<Span<'static>> {
+        let mut title = vec![Span::raw(format!(" Shared arena on {} ", self.addr))];
+        if let Some(color) = self.color {
+            let mine = self.state.balls.iter().filter(|ball| ball.color == Some(color)).count();
+            title.push(Span::styled(if ascii { "o" } else { "●" }, Style::default().fg(color.into()).add_modifier(Modifier::BOLD)));
+            title.push(Span::raw(format!(" {} yours ", mine)));
+        }
+        title
+    }
+
+    fn keys(&self) -> &'static str {
+        " a spawn · arrows fling · x remove · q leave "
+    }
+
+    fn key(&mut self, code: KeyCode) -> Result<()> {
+        let message = match code {
+            KeyCode::Char('a') | KeyCode::Char(' ') => self.random_spawn(),
+            KeyCode::Char('x') => GuestMessage::Remove,
+            KeyCode::Left => GuestMessage::Fling { dvx: -FLING, dvy: 0.0 },
+            KeyCode::Right => GuestMessage::Fling { dvx: FLING, dvy: 0.0 },
+            KeyCode::Up => GuestMessage::Fling { dvx: 0.0, dvy: -FLING },
+            KeyCode::Down => GuestMessage::Fling { dvx: 0.0, dvy: FLING },
+            _ => return Ok(()),
+        };
+        self.send(&message).context("lost the connection to the host")
+    }
+
+    fn gone(&self) -> String {
+        format!("{} closed the connection", self.addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guests_only_move_their_own_balls() {
+        let (red, blue) = (GUEST_COLORS[0], GUEST_COLORS[1]);
+        let mut sim = Simulation::new(40.0, 20.0);
+        sim.add_ball(BallSpec::new(20.0, 10.0, 0.0, 0.0));
+        apply(&mut sim, red, GuestMessage::Spawn { x: 5.0, y: 5.0, vx: 30.0, vy: 0.0 });
+        apply(&mut sim, blue, GuestMessage::Spawn { x: 30.0, y: 5.0, vx: 0.0, vy: 0.0 });
+        apply(&mut sim, blue, GuestMessage::Spawn { x: 50.0, y: 5.0, vx: 0.0, vy: 0.0 });
+        apply(&mut sim, red, GuestMessage::Fling { dvx: 0.0, dvy: 1.0 });
+        apply(&mut sim, blue, GuestMessage::Remove);
+
+        let velocities: Vec<_> = sim.state().iter().map(|ball| (ball.vx, ball.vy, ball.color)).collect();
+        // The spawn outside the arena was dropped, and red's speed capped
+        assert_eq!(velocities, [(0.0, 0.0, None), (MAX_GUEST_SPEED, 1.0, Some(red))]);
+    }
+}
@@ -0,0 +1,312 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use rust_tui_ball_bounce::clock::{Clock, StepTimes, SystemClock, TickTimer};
+use rust_tui_ball_bounce::{BallState, Command, SimEvent, Simulation, Snapshot};
+use tokio::sync::mpsc::{self as async_mpsc, UnboundedReceiver, UnboundedSender};
+
+use crate::twin;
+
+type Edit = Box<dyn FnOnce(&mut Simulation) + Send>;
+type Read = Box<dyn FnOnce(&Simulation) + Send>;
+/// Makes a simulation to compare with from the one running.
+pub type Build = Box<dyn FnOnce(&Simulation) -> Option<Simulation> + Send>;
+
+enum Message {
+    Command(Command),
+    // Anything commands don't cover, like applying a profile or resizing
+    Edit(Edit),
+    // A look that changes nothing, so sends no update
+    Read(Read),
+    TickRate(Duration),
+    // Resizes the arena of every simulation running
+    Resize(f64, f64, bool),
+    // Starts a twin nudged by epsilon, or stops it
+    Twin(Option<f64>),
+    // Starts a simulation to compare with, built from this one, or stops it
+    Compare(Option<Build>),
+    Shutdown,
+}
+
+/// What the simulation looked like after a tick or a change, plus the events
+/// that led there.
+pub struct Update {
+    pub snapshot: Snapshot,
+    pub events: Vec<SimEvent>,
+    // How long each phase of the tick took, for updates that come from one
+    pub step_times: Option<StepTimes>,
+    // The twin's balls while a chaos experiment runs
+    pub twin: Option<Vec<BallState>>,
+    // The other simulation's state while a comparison runs
+    pub other: Option<Snapshot>,
+}
+
+/// Runs the simulation on its own thread so a slow physics step never stalls
+/// drawing or input. Changes go in as messages; an [`Update`] comes back after
+/// every tick and every change.
+pub struct SimThread {
+    tx: Sender<Message>,
+    rx: UnboundedReceiver<Update>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SimThread {
+    pub fn spawn(mut sim: Simulation, tick_rate: Duration) -> SimThread {
+        let (tx, inbox) = mpsc::channel();
+        let (outbox, rx) = async_mpsc::unbounded_channel();
+        // The UI builds its chart history from the updates instead
+        sim.max_history = 0;
+        let handle = thread::spawn(move || run(sim, tick_rate, inbox, outbox));
+        SimThread {
+            tx,
+            rx,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn command(&self, command: Command) {
+        // Only fails once the thread is gone, and then there's nothing to update
+        let _ = self.tx.send(Message::Command(command));
+    }
+
+    pub fn edit(&self, edit: impl FnOnce(&mut Simulation) + Send + 'static) {
+        let _ = self.tx.send(Message::Edit(Box::new(edit)));
+    }
+
+    pub fn read(&self, read: impl FnOnce(&Simulation) + Send + 'static) {
+        let _ = self.tx.send(Message::Read(Box::new(read)));
+    }
+
+    pub fn set_tick_rate(&self, tick_rate: Duration) {
+        let _ = self.tx.send(Message::TickRate(tick_rate));
+    }
+
+    /// Resizes the arena like [`Simulation::resize`], for the twin and the
+    /// simulation compared with too, which an edit would stop.
+    pub fn resize(&self, width: f64, height: f64, rescale: bool) {
+        let _ = self.tx.send(Message::Resize(width, height, rescale));
+    }
+
+    /// Starts a [twin](crate::twin) with its first ball `epsilon` to the
+    /// right, or with `None` stops the one running.
+    pub fn twin(&self, epsilon: Option<f64>) {
+        let _ = self.tx.send(Message::Twin(epsilon));
+    }
+
+    /// Starts a [comparison](crate::compare) with the simulation `build`
+    /// makes, or with `None` stops the one running.
+    pub fn compare(&self, build: Option<Build>) {
+        let _ = self.tx.send(Message::Compare(build));
+    }
+
+    /// Waits for the next update; `None` once the thread has stopped.
+    pub async fn recv(&mut self) -> Option<Update> {
+        self.rx.recv().await
+    }
+}
+
+impl Drop for SimThread {
+    fn drop(&mut self) {
+        let _ = self.tx.send(Message::Shutdown);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run(sim: Simulation, tick_rate: Duration, inbox: Receiver<Message>, outbox: UnboundedSender<Update>) {
+    let mut runner = Runner {
+        sim,
+        twin: None,
+        other: None,
+        timer: TickTimer::new(SystemClock::new(), tick_rate),
+    };
+    loop {
+        let update = match inbox.recv_timeout(runner.timer.remaining()) {
+            Ok(Message::Shutdown) | Err(RecvTimeoutError::Disconnected) => return,
+            Ok(message) => runner.handle(message),
+            Err(RecvTimeoutError::Timeout) => runner.poll(),
+        };
+        if let Some(update) = update {
+            if outbox.send(update).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// The simulation thread's loop body, apart from the waiting, so it can be
+/// driven by a [`ManualClock`](rust_tui_ball_bounce::clock::ManualClock).
+struct Runner<C> {
+    sim: Simulation,
+    twin: Option<Simulation>,
+    other: Option<Other>,
+    timer: TickTimer<C>,
+}
+
+/// The simulation compared with, and the tick count of the one it follows
+/// when it last caught up, so it keeps step across resets.
+struct Other {
+    sim: Simulation,
+    seen: u64,
+}
+
+impl<C: Clock> Runner<C> {
+    /// Applies a message, returning the update to send if it changed anything.
+    fn handle(&mut self, message: Message) -> Option<Update> {
+        match message {
+            Message::Command(command) => {
+                // Only time passing keeps the twin comparable
+                let timing = matches!(
+                    command,
+                    Command::TogglePause
+                        | Command::SetPaused(_)
+                        | Command::SpeedUp
+                        | Command::SpeedDown
+                        | Command::SetSpeed(_)
+                        | Command::Step
+                );
+                if !timing {
+                    self.twin = None;
+                }
+                self.sim.apply(command);
+            }
+            Message::Edit(edit) => {
+                self.twin = None;
+                edit(&mut self.sim);
+            }
+            Message::Read(read) => {
+                read(&self.sim);
+                return None;
+            }
+            Message::Resize(width, height, rescale) => {
+                let others = self.twin.iter_mut().chain(self.other.as_mut().map(|other| &mut other.sim));
+                for sim in std::iter::once(&mut self.sim).chain(others) {
+                    sim.resize(width, height, rescale);
+                }
+            }
+            Message::Twin(epsilon) => self.twin = epsilon.and_then(|epsilon| twin::nudged(&self.sim, epsilon)),
+            Message::Compare(build) => {
+                let seen = self.sim.tick_count;
+                self.other = build.and_then(|build| build(&self.sim)).map(|sim| Other { sim, seen });
+            }
+            Message::TickRate(rate) => {
+                self.timer.set_rate(rate);
+                return None;
+            }
+            Message::Shutdown => return None,
+        }
+        Some(self.update(None))
+    }
+
+    /// Ticks if a tick is due and the simulation isn't paused.
+    fn poll(&mut self) -> Option<Update> {
+        if !self.timer.poll() || self.sim.paused {
+            return None;
+        }
+        let times = self.sim.step_timed(self.sim.speed_multiplier, self.timer.clock());
+        Some(self.update(Some(times)))
+    }
+
+    fn update(&mut self, step_times: Option<StepTimes>) -> Update {
+        if let Some(twin) = &mut self.twin {
+            while twin.tick_count < self.sim.tick_count {
+                twin.step(self.sim.speed_multiplier);
+            }
+            twin.drain_events();
+        }
+        if let Some(other) = &mut self.other {
+            for _ in other.seen..self.sim.tick_count {
+                other.sim.step(self.sim.speed_multiplier);
+            }
+            other.seen = self.sim.tick_count;
+            other.sim.drain_events();
+        }
+        Update {
+            snapshot: self.sim.snapshot(),
+            events: self.sim.drain_events(),
+            step_times,
+            twin: self.twin.as_ref().map(|twin| twin.state().to_vec()),
+            other: self.other.as_ref().map(|other| other.sim.snapshot()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::clock::ManualClock;
+
+    use super::*;
+
+    const RATE: Duration = Duration::from_millis(16);
+
+    fn runner() -> (Runner<ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        let mut sim = Simulation::new(80.0, 20.0);
+        sim.add_ball(sim.next_ball_spec());
+        sim.drain_events();
+        let timer = TickTimer::new(clock.clone(), RATE);
+        (Runner { sim, twin: None, other: None, timer }, clock)
+    }
+
+    #[test]
+    fn ticks_follow_the_clock() {
+        let (mut runner, clock) = runner();
+        assert!(runner.poll().is_none());
+        clock.advance(RATE);
+        assert_eq!(runner.poll().map(|u| u.snapshot.tick_count), Some(1));
+        assert!(runner.poll().is_none());
+        runner.handle(Message::TickRate(RATE * 2));
+        clock.advance(RATE);
+        assert!(runner.poll().is_none());
+        clock.advance(RATE);
+        assert_eq!(runner.poll().map(|u| u.snapshot.tick_count), Some(2));
+    }
+
+    #[test]
+    fn paused_runs_only_step() {
+        let (mut runner, clock) = runner();
+        let update = runner.handle(Message::Command(Command::SetPaused(true))).unwrap();
+        assert_eq!(update.events, [SimEvent::Paused(true)]);
+        clock.advance(RATE * 10);
+        assert!(runner.poll().is_none());
+        let update = runner.handle(Message::Command(Command::Step)).unwrap();
+        assert_eq!(update.snapshot.tick_count, 1);
+        assert!(update.snapshot.paused);
+    }
+
+    #[test]
+    fn the_twin_keeps_step_until_the_simulation_changes() {
+        let (mut runner, clock) = runner();
+        let update = runner.handle(Message::Twin(Some(0.5))).unwrap();
+        assert_eq!(update.twin.unwrap()[0].x, update.snapshot.balls[0].x + 0.5);
+        clock.advance(RATE);
+        let update = runner.poll().unwrap();
+        assert_eq!(runner.twin.as_ref().map(|twin| twin.tick_count), Some(1));
+        assert!(update.twin.is_some());
+        let update = runner.handle(Message::Command(Command::SetSpeed(2.0))).unwrap();
+        assert!(update.twin.is_some());
+        let update = runner.handle(Message::Command(Command::AddBall)).unwrap();
+        assert!(update.twin.is_none());
+    }
+
+    #[test]
+    fn the_other_simulation_ticks_and_resizes_alongside() {
+        let (mut runner, clock) = runner();
+        let build: Build = Box::new(|sim: &Simulation| Simulation::restore(sim.save()).ok());
+        runner.handle(Message::Compare(Some(build)));
+        clock.advance(RATE);
+        runner.poll();
+        runner.handle(Message::Command(Command::AddBall));
+        let update = runner.handle(Message::Resize(40.0, 10.0, false)).unwrap();
+        let other = update.other.unwrap();
+        assert_eq!((other.tick_count, other.area_width, other.balls.len()), (1, 40.0, 1));
+        assert_eq!(update.snapshot.balls.len(), 2);
+        // Through a reset too
+        runner.handle(Message::Command(Command::Reset));
+        runner.handle(Message::Command(Command::Step));
+        assert_eq!(runner.other.as_ref().map(|other| other.sim.tick_count), Some(2));
+        assert!(runner.handle(Message::Compare(None)).unwrap().other.is_none());
+    }
+}
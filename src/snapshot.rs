@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::force::{Boids, Direction};
+use crate::scenario::{Obstacle, Pockets};
+use crate::BallState;
+
+/// An owned copy of the simulation's visible state, cheap enough to take
+/// every tick and hand to another thread.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub tick_count: u64,
+    pub paused: bool,
+    pub collisions: u64,
+    pub wall_bounces: u64,
+    pub speed_multiplier: f64,
+    pub area_width: f64,
+    pub area_height: f64,
+    pub aspect: f64,
+    pub scenario_name: Option<String>,
+    // Missing from instances older than gas statistics
+    #[serde(default)]
+    pub gas: bool,
+    // Missing from instances older than hit counts
+    #[serde(default)]
+    pub count_hits: bool,
+    // What `count_hits` shows; see `Simulation::clicks`. Missing from
+    // instances older than the count stopping
+    #[serde(default)]
+    pub clicks: u64,
+    pub seed: u64,
+    pub obstacles: Vec<Obstacle>,
+    // Missing from instances older than energy bars
+    #[serde(default)]
+    pub gravity: f64,
+    // Missing from instances older than gravity directions
+    #[serde(default)]
+    pub gravity_direction: Direction,
+    // Missing from instances older than flocking
+    #[serde(default)]
+    pub boids: Option<Boids>,
+    // Missing from instances older than pockets
+    #[serde(default)]
+    pub pockets: Option<Pockets>,
+    // Ordered by id
+    pub balls: Vec<BallState>,
+    // The force field on a grid, while `Simulation::field_spacing` asks for
+    // it and some force has one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field: Vec<FieldSample>,
+}
+
+impl Snapshot {
+    /// `ball`'s kinetic and potential energy, taking its potential as its
+    /// height under [`Snapshot::gravity`] above the wall gravity pulls
+    /// toward, or above the middle of the arena.
+    pub fn energy(&self, ball: &BallState) -> (f64, f64) {
+        let kinetic = 0.5 * ball.mass * (ball.vx * ball.vx + ball.vy * ball.vy);
+        let (right, floor) = (self.area_width - 1.0, self.area_height - self.aspect);
+        let height = match self.gravity_direction {
+            Direction::Down => floor - ball.y,
+            Direction::Up => ball.y,
+            Direction::Left => ball.x,
+            Direction::Right => right - ball.x,
+            Direction::Center => (ball.x - right / 2.0).hypot(ball.y - floor / 2.0),
+        };
+        let potential = ball.mass * self.gravity * height.max(0.0);
+        (kinetic, potential)
+    }
+}
+
+/// The net acceleration a ball at rest at `(x, y)` would get.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldSample {
+    pub x: f64,
+    pub y: f64,
+    pub ax: f64,
+    pub ay: f64,
+}
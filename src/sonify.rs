@@ -0,0 +1,173 @@
+//! `--midi` and `--osc`: plays collisions as notes so the simulation can
+//! drive a synth. Harder hits play higher and louder, and each ball plays on
+//! a channel of its own, wrapping after 16.
+
+use std::io::{self, Write};
+use std::net::{ToSocketAddrs, UdpSocket};
+
+use rust_tui_ball_bounce::{SimEvent, SimulationObserver, Snapshot};
+
+// The impulse that plays the highest, loudest note; harder hits are clamped
+const FULL_SCALE_IMPULSE: f64 = 3.0;
+const LOWEST_PITCH: u8 = 36;
+const PITCH_RANGE: u8 = 60;
+const QUIETEST_VELOCITY: u8 = 40;
+// How long a note is held before its note-off, in ticks
+const NOTE_TICKS: u32 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    pub channel: u8,
+    pub pitch: u8,
+    pub velocity: u8,
+}
+
+impl Note {
+    /// The note for ball `a` hitting another with `impulse`, on `a`'s channel.
+    fn for_collision(a: usize, impulse: f64) -> Note {
+        let strength = (impulse / FULL_SCALE_IMPULSE).clamp(0.0, 1.0);
+        Note {
+            channel: (a % 16) as u8,
+            pitch: LOWEST_PITCH + (strength * PITCH_RANGE as f64).round() as u8,
+            velocity: QUIETEST_VELOCITY + (strength * (127 - QUIETEST_VELOCITY) as f64).round() as u8,
+        }
+    }
+}
+
+/// Somewhere notes can be sent.
+pub trait NoteOutput {
+    fn note_on(&mut self, note: Note) -> io::Result<()>;
+
+    /// Ends a note started by `note_on`. Outputs without held notes can
+    /// leave this alone.
+    fn note_off(&mut self, _note: Note) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Raw MIDI bytes written to a device or pipe, such as `/dev/snd/midiC1D0`
+/// or a FIFO read by a soft synth.
+pub struct Midi<W>(pub W);
+
+impl<W: Write> NoteOutput for Midi<W> {
+    fn note_on(&mut self, note: Note) -> io::Result<()> {
+        self.0.write_all(&[0x90 | note.channel, note.pitch, note.velocity])?;
+        self.0.flush()
+    }
+
+    fn note_off(&mut self, note: Note) -> io::Result<()> {
+        self.0.write_all(&[0x80 | note.channel, note.pitch, 0])?;
+        self.0.flush()
+    }
+}
+
+/// OSC over UDP: each note is a `/ballbounce/note` message with the
+/// channel, pitch and velocity as int32 arguments.
+pub struct Osc {
+    socket: UdpSocket,
+}
+
+impl Osc {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Osc> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(addr)?;
+        Ok(Osc { socket })
+    }
+}
+
+impl NoteOutput for Osc {
+    fn note_on(&mut self, note: Note) -> io::Result<()> {
+        let args = [note.channel, note.pitch, note.velocity].map(i32::from);
+        // Nobody listening is not an error worth stopping for
+        let _ = self.socket.send(&osc_message("/ballbounce/note", &args));
+        Ok(())
+    }
+}
+
+/// Encodes an OSC message whose arguments are all int32s.
+fn osc_message(address: &str, args: &[i32]) -> Vec<u8> {
+    fn push_padded(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(s.as_bytes());
+        // At least one NUL, then up to a multiple of four bytes
+        out.resize((out.len() / 4 + 1) * 4, 0);
+    }
+    let mut out = Vec::new();
+    push_padded(&mut out, address);
+    push_padded(&mut out, &format!(",{}", "i".repeat(args.len())));
+    for arg in args {
+        out.extend_from_slice(&arg.to_be_bytes());
+    }
+    out
+}
+
+/// Plays a note on `out` for every collision, holding each for a few ticks.
+pub struct Sonifier<O: NoteOutput> {
+    out: O,
+    // Notes playing, with the ticks they have left
+    held: Vec<(Note, u32)>,
+}
+
+impl<O: NoteOutput> Sonifier<O> {
+    pub fn new(out: O) -> Self {
+        Sonifier { out, held: Vec::new() }
+    }
+}
+
+impl<O: NoteOutput> SimulationObserver for Sonifier<O> {
+    fn on_tick(&mut self, _snapshot: &Snapshot) {
+        let out = &mut self.out;
+        self.held.retain_mut(|(note, ticks)| {
+            *ticks -= 1;
+            if *ticks == 0 {
+                let _ = out.note_off(*note);
+            }
+            *ticks > 0
+        });
+    }
+
+    fn on_event(&mut self, event: &SimEvent) {
+        if let SimEvent::Collision { a, impulse, .. } = *event {
+            let note = Note::for_collision(a, impulse);
+            // A device that went away just means silence
+            if self.out.note_on(note).is_ok() {
+                self.held.push((note, NOTE_TICKS));
+            }
+        }
+    }
+}
+
+impl<O: NoteOutput> Drop for Sonifier<O> {
+    fn drop(&mut self) {
+        for (note, _) in self.held.drain(..) {
+            let _ = self.out.note_off(note);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collisions_play_held_notes() {
+        let mut sonifier = Sonifier::new(Midi(Vec::new()));
+        let collision = SimEvent::Collision { a: 17, b: 20, x: 0.0, y: 0.0, impulse: 1.5, before: [(0.0, 0.0); 2], after: [(0.0, 0.0); 2] };
+        sonifier.on_event(&collision);
+        for _ in 0..NOTE_TICKS {
+            assert_eq!(sonifier.out.0.len(), 3);
+            sonifier.on_tick(&Snapshot::default());
+        }
+        // Ball 17 plays on channel 1, halfway up the range
+        assert_eq!(sonifier.out.0, [0x91, 66, 84, 0x81, 66, 0]);
+    }
+
+    #[test]
+    fn osc_messages_are_padded_to_four_bytes() {
+        let message = osc_message("/ballbounce/note", &[1, 66, 84]);
+        let mut expected = b"/ballbounce/note\0\0\0\0,iii\0\0\0\0".to_vec();
+        for arg in [1, 66, 84] {
+            expected.extend_from_slice(&[0, 0, 0, arg]);
+        }
+        assert_eq!(message, expected);
+    }
+}
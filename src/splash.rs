@@ -0,0 +1,82 @@
+//! The title shown at start-up: the name in large block letters with a few
+//! balls dropping through it under gravity. It gives way to the menu or the
+//! simulation after [`DURATION`], or at once on any key. `display.splash =
+//! false` turns it off.
+
+use std::time::Duration;
+
+use rust_tui_ball_bounce::force::Gravity;
+use rust_tui_ball_bounce::{BallSpec, Simulation};
+
+use crate::mode::Mode;
+
+/// How long the splash shows unless a key skips it.
+pub const DURATION: Duration = Duration::from_millis(2500);
+
+/// The title, one string per row, `#` for a filled cell.
+pub const TITLE: [&str; 5] = [
+    "####   ###  #     #       ####   ###  #   # #   #  #### #####",
+    "#   # #   # #     #       #   # #   # #   # ##  # #     #    ",
+    "####  ##### #     #       ####  #   # #   # # # # #     #### ",
+    "#   # #   # #     #       #   # #   # #   # #  ## #     #    ",
+    "####  #   # ##### #####   ####   ###   ###  #   #  #### #####",
+];
+
+// A simulation tick's length, as the balls' speeds assume
+const TICK: Duration = Duration::from_millis(16);
+
+pub struct Splash {
+    // The balls, in a simulation the size of the screen
+    pub sim: Simulation,
+    pub shown: Duration,
+    // The mode to go on to
+    pub then: Box<Mode>,
+}
+
+impl Splash {
+    /// A splash for a `width` x `height` screen, with no balls for
+    /// `reduced_motion`.
+    pub fn new(width: u16, height: u16, reduced_motion: bool, then: Mode) -> Splash {
+        let (width, height) = (width.max(1) as f64, height.max(1) as f64);
+        let mut sim = Simulation::new(width, height);
+        sim.add_force(Gravity::down(0.02));
+        if !reduced_motion {
+            // Spread along the top, thrown a little sideways either way
+            for i in 0..6 {
+                let x = width * (i as f64 + 0.5) / 6.0;
+                let vx = if i % 2 == 0 { 0.3 } else { -0.3 };
+                sim.add_ball(BallSpec::new(x, 1.0 + (i % 3) as f64, vx, 0.0));
+            }
+        }
+        Splash { sim, shown: Duration::ZERO, then: Box::new(then) }
+    }
+
+    /// Moves the balls on by `elapsed`.
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.shown += elapsed;
+        self.sim.step(elapsed.as_secs_f64() / TICK.as_secs_f64());
+    }
+
+    /// Whether the splash has had its time.
+    pub fn over(&self) -> bool {
+        self.shown >= DURATION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balls_fall_until_the_time_is_up() {
+        let mut splash = Splash::new(80, 24, false, Mode::Running);
+        let y = splash.sim.state()[0].y;
+        splash.advance(Duration::from_millis(500));
+        assert!(splash.sim.state()[0].y > y && !splash.over());
+        splash.advance(DURATION);
+        assert!(splash.over());
+        assert!(matches!(*splash.then, Mode::Running));
+        assert!(TITLE.iter().all(|row| row.len() == TITLE[0].len()));
+        assert!(Splash::new(80, 24, true, Mode::Running).sim.state().is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+//! Parameter sweeps. `sweep gravity 0 0.1` reruns the simulation as it
+//! stands ten times over, each with gravity set to the next of ten values
+//! from 0 to 0.1, and charts what each run ended up with against the value
+//! it ran with. The runs are headless and go on a thread of their own, so
+//! the app keeps going and the chart fills in as they finish.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use rust_tui_ball_bounce::save::SavedSimulation;
+use rust_tui_ball_bounce::Simulation;
+
+use crate::sim_thread::SimThread;
+use crate::units::Quantity;
+
+/// How many values a sweep runs.
+pub const RUNS: usize = 10;
+
+/// How long each run goes on for, in ticks.
+pub const TICKS: u64 = 1000;
+
+// Balls slower than this, in cells per tick, count as at rest
+const REST_SPEED: f64 = 0.01;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    Gravity,
+    Drag,
+    Friction,
+    Radius,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    // Ticks until every ball is at rest for good
+    Settle,
+    // Kinetic and potential, at the end
+    Energy,
+    // Between balls, over the run
+    Collisions,
+}
+
+impl Parameter {
+    pub const NAMES: [&'static str; 4] = ["gravity", "drag", "friction", "radius"];
+
+    pub fn by_name(name: &str) -> Option<Parameter> {
+        let all = [Parameter::Gravity, Parameter::Drag, Parameter::Friction, Parameter::Radius];
+        Some(all[Self::NAMES.iter().position(|&known| known == name)?])
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    /// What the values are, for those that have SI units.
+    pub fn quantity(self) -> Option<Quantity> {
+        match self {
+            Parameter::Gravity | Parameter::Friction => Some(Quantity::Acceleration),
+            Parameter::Radius => Some(Quantity::Length),
+            Parameter::Drag => None,
+        }
+    }
+
+    /// Sets the parameter to `value` in `saved`.
+    pub fn set(self, saved: &mut SavedSimulation, value: f64) {
+        match self {
+            Parameter::Gravity => saved.forces.gravity = value,
+            Parameter::Drag => saved.forces.drag = value,
+            Parameter::Friction => saved.forces.friction = value,
+            Parameter::Radius => saved.balls.iter_mut().for_each(|ball| ball.radius = value),
+        }
+    }
+}
+
+impl Metric {
+    pub const NAMES: [&'static str; 3] = ["settle", "energy", "collisions"];
+
+    pub fn by_name(name: &str) -> Option<Metric> {
+        let all = [Metric::Settle, Metric::Energy, Metric::Collisions];
+        Some(all[Self::NAMES.iter().position(|&known| known == name)?])
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    pub fn quantity(self) -> Option<Quantity> {
+        match self {
+            Metric::Settle => Some(Quantity::Time),
+            Metric::Energy => Some(Quantity::Energy),
+            Metric::Collisions => None,
+        }
+    }
+}
+
+/// What to sweep, from the palette. Values are in simulation units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plan {
+    pub parameter: Parameter,
+    pub from: f64,
+    pub to: f64,
+    pub metric: Metric,
+}
+
+impl Plan {
+    /// The values run, evenly spaced from `from` to `to`.
+    pub fn values(self) -> impl Iterator<Item = f64> {
+        (0..RUNS).map(move |i| self.from + (self.to - self.from) * i as f64 / (RUNS - 1) as f64)
+    }
+}
+
+/// A sweep under way or done, and the runs finished so far.
+pub struct Sweep {
+    pub plan: Plan,
+    // Each value and what its run measured, if it could: a run that never
+    // settles has no time to settle
+    pub results: Vec<(f64, Option<f64>)>,
+    rx: Receiver<(f64, Option<f64>)>,
+}
+
+impl Sweep {
+    /// Starts sweeping from the simulation's current state.
+    pub fn start(sim: &SimThread, plan: Plan) -> Sweep {
+        let (tx, rx) = mpsc::channel();
+        sim.read(move |sim| {
+            let saved = sim.save();
+            thread::spawn(move || run_all(saved, plan, tx));
+        });
+        Sweep { plan, results: Vec::new(), rx }
+    }
+
+    /// Takes in the runs finished since the last call, returning whether
+    /// there were any.
+    pub fn poll(&mut self) -> bool {
+        let before = self.results.len();
+        self.results.extend(self.rx.try_iter());
+        self.results.len() > before
+    }
+
+    pub fn done(&self) -> bool {
+        self.results.len() == RUNS
+    }
+}
+
+// Stops early once the sweep is dropped
+fn run_all(saved: SavedSimulation, plan: Plan, tx: Sender<(f64, Option<f64>)>) {
+    for value in plan.values() {
+        let mut saved = saved.clone();
+        plan.parameter.set(&mut saved, value);
+        if tx.send((value, run(saved, plan.metric))).is_err() {
+            return;
+        }
+    }
+}
+
+/// Runs `saved` for [`TICKS`] and measures `metric`.
+pub fn run(saved: SavedSimulation, metric: Metric) -> Option<f64> {
+    let mut sim = Simulation::restore(saved).ok()?;
+    sim.max_history = 0;
+    sim.paused = false;
+    let (start, collisions) = (sim.tick_count, sim.collisions);
+    // The tick after the last one with a ball still moving
+    let mut settled = start;
+    for _ in 0..TICKS {
+        sim.tick();
+        sim.drain_events();
+        if sim.state().iter().any(|ball| ball.vx.hypot(ball.vy) >= REST_SPEED) {
+            settled = sim.tick_count + 1;
+        }
+    }
+    match metric {
+        Metric::Settle => (settled <= sim.tick_count).then(|| (settled - start) as f64),
+        Metric::Energy => {
+            let snapshot = sim.snapshot();
+            Some(snapshot.balls.iter().map(|ball| snapshot.energy(ball)).map(|(kinetic, potential)| kinetic + potential).sum())
+        }
+        Metric::Collisions => Some((sim.collisions - collisions) as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::BallSpec;
+
+    use super::*;
+
+    #[test]
+    fn more_friction_settles_sooner() {
+        let mut sim = Simulation::new(80.0, 20.0);
+        sim.add_ball(BallSpec::new(10.0, 10.0, 0.8, 0.0));
+        let saved = sim.save();
+        let plan = Plan { parameter: Parameter::Friction, from: 0.0, to: 0.01, metric: Metric::Settle };
+        let results: Vec<_> = plan
+            .values()
+            .map(|value| {
+                let mut saved = saved.clone();
+                plan.parameter.set(&mut saved, value);
+                run(saved, plan.metric)
+            })
+            .collect();
+        assert_eq!(results.len(), RUNS);
+        // Without friction it bounces for ever
+        assert_eq!(results[0], None);
+        let times: Vec<f64> = results[1..].iter().map(|time| time.unwrap()).collect();
+        assert!(times.windows(2).all(|pair| pair[1] <= pair[0]), "{:?}", times);
+        // Speed lost at 0.01 per tick runs out in about 80 ticks
+        assert!((times[8] - 80.0).abs() < 2.0, "{:?}", times);
+    }
+}
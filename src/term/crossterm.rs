@@ -0,0 +1,80 @@
+use std::io::{self, IsTerminal};
+
+use ::crossterm::{
+    cursor::{self, MoveTo, Show},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, EventStream},
+    execute,
+    style::Print,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use futures::StreamExt;
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use super::{output, Input, InputStream, Output, Term};
+
+pub struct CrosstermTerm {
+    terminal: Terminal<CrosstermBackend<Output>>,
+}
+
+impl CrosstermTerm {
+    pub fn new() -> io::Result<CrosstermTerm> {
+        enable_raw_mode()?;
+        let mut out = output();
+        execute!(out, EnterAlternateScreen, EnableMouseCapture)?;
+        Ok(CrosstermTerm {
+            terminal: Terminal::new(CrosstermBackend::new(out))?,
+        })
+    }
+}
+
+impl Term for CrosstermTerm {
+    type Backend = CrosstermBackend<Output>;
+
+    fn terminal(&mut self) -> &mut Terminal<Self::Backend> {
+        &mut self.terminal
+    }
+
+    fn read(&mut self) -> io::Result<Input> {
+        event::read().map(to_input)
+    }
+
+    fn input(&mut self) -> InputStream {
+        EventStream::new().map(|event| event.map(to_input)).boxed()
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        disable_raw_mode()?;
+        execute!(
+            self.terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
+        self.terminal.show_cursor()
+    }
+
+    fn reset() {
+        let _ = disable_raw_mode();
+        let _ = execute!(output(), LeaveAlternateScreen, DisableMouseCapture, Show);
+    }
+
+    // Only crossterm reads input on demand, so the answer to the position
+    // query isn't taken for a key press
+    fn measure(&mut self, text: &str) -> Option<u16> {
+        // The query and its answer go through stdout, which `--pipe` keeps
+        // for data
+        if !io::stdout().is_terminal() {
+            return None;
+        }
+        let backend = self.terminal.backend_mut();
+        execute!(backend, MoveTo(0, 0), Print(text)).ok()?;
+        cursor::position().ok().map(|(x, _)| x)
+    }
+}
+
+fn to_input(event: Event) -> Input {
+    match event {
+        Event::Key(key) => Input::Key(key.code),
+        Event::Mouse(mouse) => Input::Mouse(mouse),
+        _ => Input::Other,
+    }
+}
@@ -0,0 +1,205 @@
+//! The terminal the UI draws to and reads keys from. Crossterm is the
+//! default; build with the `termion` or `termwiz` feature to use one of those
+//! instead where crossterm misbehaves. Every backend reports keys as crossterm
+//! [`KeyCode`]s, so the keymap and input handling work the same on all of them.
+//! Mouse input, used for aiming in games, only comes through crossterm.
+
+use std::io::{self, Stderr, Stdout, Write};
+use std::ops::{Deref, DerefMut};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ::crossterm::event::{KeyCode, MouseEvent};
+use futures::stream::BoxStream;
+use ratatui::{backend::Backend, Terminal};
+
+#[cfg(not(any(feature = "termion", feature = "termwiz")))]
+mod crossterm;
+#[cfg(all(feature = "termion", not(feature = "termwiz")))]
+mod termion;
+#[cfg(feature = "termwiz")]
+mod termwiz;
+
+#[cfg(not(any(feature = "termion", feature = "termwiz")))]
+pub use self::crossterm::CrosstermTerm as SystemTerm;
+#[cfg(all(feature = "termion", not(feature = "termwiz")))]
+pub use self::termion::TermionTerm as SystemTerm;
+#[cfg(feature = "termwiz")]
+pub use self::termwiz::TermwizTerm as SystemTerm;
+
+pub enum Input {
+    Key(KeyCode),
+    // Only crossterm turns on mouse reporting
+    #[cfg_attr(any(feature = "termion", feature = "termwiz"), allow(dead_code))]
+    Mouse(MouseEvent),
+    // Resize, focus and anything else the UI doesn't act on
+    Other,
+}
+
+pub type InputStream = BoxStream<'static, io::Result<Input>>;
+
+/// A terminal in raw mode on the alternate screen. Wrap it in a
+/// [`TermGuard`] so it's handed back to the shell however the app exits.
+pub trait Term {
+    type Backend: Backend;
+
+    fn terminal(&mut self) -> &mut Terminal<Self::Backend>;
+
+    /// Blocks until the next input event. Not for use inside the async event
+    /// loop, which reads from [`input`](Term::input) instead.
+    fn read(&mut self) -> io::Result<Input>;
+
+    /// Input events as a stream. Take it once, after any blocking reads.
+    fn input(&mut self) -> InputStream;
+
+    /// Leaves raw mode and the alternate screen and shows the cursor.
+    fn restore(&mut self) -> io::Result<()>;
+
+    /// As much of [`restore`](Term::restore) as can be done without the
+    /// terminal itself, for the panic hook. Errors are ignored.
+    fn reset();
+
+    /// How many columns printing `text` moves the cursor, as the terminal
+    /// reports it, or `None` if it can't be asked. Leaves `text` on screen.
+    fn measure(&mut self, _text: &str) -> Option<u16> {
+        None
+    }
+}
+
+// Set by `--pipe`, which keeps stdout for data
+static DRAW_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Moves the UI to stderr, for every terminal created from now on.
+pub fn draw_to_stderr() {
+    DRAW_TO_STDERR.store(true, Ordering::SeqCst);
+}
+
+/// Stdout, or stderr after [`draw_to_stderr`]: where the UI and anything
+/// meant for the terminal, like the bell, should be written.
+pub fn output() -> Output {
+    if DRAW_TO_STDERR.load(Ordering::SeqCst) {
+        Output::Stderr(io::stderr())
+    } else {
+        Output::Stdout(io::stdout())
+    }
+}
+
+pub enum Output {
+    Stdout(Stdout),
+    Stderr(Stderr),
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(out) => out.write(buf),
+            Output::Stderr(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(out) => out.flush(),
+            Output::Stderr(out) => out.flush(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl std::os::fd::AsFd for Output {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        match self {
+            Output::Stdout(out) => out.as_fd(),
+            Output::Stderr(out) => out.as_fd(),
+        }
+    }
+}
+
+// Set while a guarded terminal is in raw mode. The guard and the panic hook
+// each restore only if the other hasn't: leaving the alternate screen twice
+// moves the cursor back over whatever was printed in between
+static NEEDS_RESTORE: AtomicBool = AtomicBool::new(false);
+
+/// Restores the terminal when dropped, including while unwinding from a panic.
+pub struct TermGuard<T: Term>(T);
+
+impl<T: Term> TermGuard<T> {
+    pub fn new(term: T) -> TermGuard<T> {
+        NEEDS_RESTORE.store(true, Ordering::SeqCst);
+        TermGuard(term)
+    }
+}
+
+impl<T: Term> Deref for TermGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Term> DerefMut for TermGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Term> Drop for TermGuard<T> {
+    fn drop(&mut self) {
+        if NEEDS_RESTORE.swap(false, Ordering::SeqCst) {
+            let _ = self.0.restore();
+        }
+    }
+}
+
+/// Resets the terminal before a panic message is printed, so it lands on the
+/// normal screen instead of vanishing with the alternate one.
+pub fn install_panic_hook() {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if NEEDS_RESTORE.swap(false, Ordering::SeqCst) {
+            SystemTerm::reset();
+        }
+        default(info);
+    }));
+}
+
+/// Reads input on a thread of its own, for backends with only blocking reads.
+#[cfg(any(feature = "termion", feature = "termwiz"))]
+struct InputThread {
+    rx: Option<tokio::sync::mpsc::UnboundedReceiver<io::Result<Input>>>,
+}
+
+#[cfg(any(feature = "termion", feature = "termwiz"))]
+impl InputThread {
+    /// `read` blocks for the next event and returns `None` once input ends.
+    fn spawn(mut read: impl FnMut() -> Option<io::Result<Input>> + Send + 'static) -> InputThread {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        // Never joined: it sits in a blocking read until the process exits
+        std::thread::spawn(move || {
+            while let Some(input) = read() {
+                if tx.send(input).is_err() {
+                    return;
+                }
+            }
+        });
+        InputThread { rx: Some(rx) }
+    }
+
+    fn read(&mut self) -> io::Result<Input> {
+        match self.rx.as_mut().and_then(|rx| rx.blocking_recv()) {
+            Some(input) => input,
+            None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "terminal input closed")),
+        }
+    }
+
+    fn stream(&mut self) -> InputStream {
+        use futures::StreamExt;
+
+        let Some(rx) = self.rx.take() else {
+            return futures::stream::empty().boxed();
+        };
+        futures::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|input| (input, rx)) })
+            .boxed()
+    }
+}
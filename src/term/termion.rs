@@ -0,0 +1,102 @@
+use std::io::{self, Write};
+use std::sync::{Mutex, PoisonError};
+
+use crossterm::event::KeyCode;
+use ratatui::backend::TermionBackend;
+use ratatui::termion::{
+    cursor,
+    event::{Event, Key},
+    input::TermRead,
+    raw::{IntoRawMode, RawTerminal},
+    screen::{ToAlternateScreen, ToMainScreen},
+};
+use ratatui::Terminal;
+
+use super::{output, Input, InputStream, InputThread, Output, Term};
+
+// Held here rather than wrapping the writer so the panic hook can end raw
+// mode too; dropping it restores the settings from before
+static RAW_MODE: Mutex<Option<RawTerminal<Output>>> = Mutex::new(None);
+
+pub struct TermionTerm {
+    terminal: Terminal<TermionBackend<Output>>,
+    input: InputThread,
+}
+
+impl TermionTerm {
+    pub fn new() -> io::Result<TermionTerm> {
+        *RAW_MODE.lock().unwrap_or_else(PoisonError::into_inner) = Some(output().into_raw_mode()?);
+        let mut out = output();
+        write!(out, "{}", ToAlternateScreen)?;
+        // The tty rather than stdin, which may be carrying piped commands
+        let mut events = ratatui::termion::get_tty()?.events();
+        Ok(TermionTerm {
+            terminal: Terminal::new(TermionBackend::new(out))?,
+            input: InputThread::spawn(move || events.next().map(|event| event.map(to_input))),
+        })
+    }
+}
+
+impl Term for TermionTerm {
+    type Backend = TermionBackend<Output>;
+
+    fn terminal(&mut self) -> &mut Terminal<Self::Backend> {
+        &mut self.terminal
+    }
+
+    fn read(&mut self) -> io::Result<Input> {
+        self.input.read()
+    }
+
+    fn input(&mut self) -> InputStream {
+        self.input.stream()
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        self.terminal.show_cursor()?;
+        let backend = self.terminal.backend_mut();
+        write!(backend, "{}", ToMainScreen)?;
+        backend.flush()?;
+        end_raw_mode();
+        Ok(())
+    }
+
+    fn reset() {
+        let mut out = output();
+        let _ = write!(out, "{}{}", ToMainScreen, cursor::Show);
+        let _ = out.flush();
+        end_raw_mode();
+    }
+}
+
+fn end_raw_mode() {
+    RAW_MODE.lock().unwrap_or_else(PoisonError::into_inner).take();
+}
+
+fn to_input(event: Event) -> Input {
+    let Event::Key(key) = event else {
+        return Input::Other;
+    };
+    let code = match key {
+        // termion reports Enter and Tab as the characters they send
+        Key::Char('\n') => KeyCode::Enter,
+        Key::Char('\t') => KeyCode::Tab,
+        Key::Char(c) => KeyCode::Char(c),
+        Key::Esc => KeyCode::Esc,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Delete => KeyCode::Delete,
+        Key::Insert => KeyCode::Insert,
+        Key::BackTab => KeyCode::BackTab,
+        Key::Left => KeyCode::Left,
+        Key::Right => KeyCode::Right,
+        Key::Up => KeyCode::Up,
+        Key::Down => KeyCode::Down,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        Key::F(n) => KeyCode::F(n),
+        _ => return Input::Other,
+    };
+    Input::Key(code)
+}
@@ -0,0 +1,122 @@
+use std::io::{self, Write};
+use std::sync::{Mutex, PoisonError};
+
+use crossterm::event::KeyCode;
+use ratatui::backend::TermwizBackend;
+use ratatui::termwiz::{
+    caps::Capabilities,
+    input::{InputEvent, KeyCode as TermwizKey, Modifiers},
+    terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal as _},
+};
+use ratatui::Terminal;
+
+use super::{output, Input, InputStream, InputThread, Term};
+
+// A handle opened before raw mode, for the panic hook: dropping it puts back
+// the settings it saw when opened
+static COOKED: Mutex<Option<SystemTerminal>> = Mutex::new(None);
+
+pub struct TermwizTerm {
+    terminal: Terminal<TermwizBackend>,
+    input: InputThread,
+}
+
+impl TermwizTerm {
+    pub fn new() -> io::Result<TermwizTerm> {
+        *COOKED.lock().unwrap_or_else(PoisonError::into_inner) = Some(open()?);
+        let mut output = open()?;
+        output.set_raw_mode().map_err(io::Error::other)?;
+        // Entered by hand: after `enter_alternate_screen` the terminal leaves
+        // it again when dropped, which after the guard or panic hook already
+        // has would move the cursor back over anything printed since
+        write_escape(ENTER_ALTERNATE_SCREEN)?;
+        let backend =
+            TermwizBackend::with_buffered_terminal(BufferedTerminal::new(output).map_err(io::Error::other)?);
+        // The backend owns its terminal for drawing, so input is read through
+        // a second handle to the same tty on the input thread
+        let mut tty = open()?;
+        let input = InputThread::spawn(move || match tty.poll_input(None) {
+            Ok(Some(event)) => Some(Ok(to_input(event))),
+            Ok(None) => Some(Ok(Input::Other)),
+            Err(err) => Some(Err(io::Error::other(err))),
+        });
+        Ok(TermwizTerm {
+            terminal: Terminal::new(backend)?,
+            input,
+        })
+    }
+}
+
+impl Term for TermwizTerm {
+    type Backend = TermwizBackend;
+
+    fn terminal(&mut self) -> &mut Terminal<Self::Backend> {
+        &mut self.terminal
+    }
+
+    fn read(&mut self) -> io::Result<Input> {
+        self.input.read()
+    }
+
+    fn input(&mut self) -> InputStream {
+        self.input.stream()
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        self.terminal.show_cursor()?;
+        write_escape(LEAVE_ALTERNATE_SCREEN)?;
+        let tty = self.terminal.backend_mut().buffered_terminal_mut().terminal();
+        tty.set_cooked_mode().map_err(io::Error::other)?;
+        COOKED.lock().unwrap_or_else(PoisonError::into_inner).take();
+        Ok(())
+    }
+
+    fn reset() {
+        let _ = write_escape(LEAVE_ALTERNATE_SCREEN);
+        let _ = write_escape(SHOW_CURSOR);
+        COOKED.lock().unwrap_or_else(PoisonError::into_inner).take();
+    }
+}
+
+fn open() -> io::Result<SystemTerminal> {
+    Capabilities::new_from_env()
+        .and_then(SystemTerminal::new)
+        .map_err(io::Error::other)
+}
+
+const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h";
+const LEAVE_ALTERNATE_SCREEN: &str = "\x1b[?1049l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+fn write_escape(sequence: &str) -> io::Result<()> {
+    let mut out = output();
+    out.write_all(sequence.as_bytes())?;
+    out.flush()
+}
+
+fn to_input(event: InputEvent) -> Input {
+    let InputEvent::Key(key) = event else {
+        return Input::Other;
+    };
+    let code = match key.key {
+        TermwizKey::Char(c) => KeyCode::Char(c),
+        TermwizKey::Enter => KeyCode::Enter,
+        TermwizKey::Escape => KeyCode::Esc,
+        TermwizKey::Tab if key.modifiers.contains(Modifiers::SHIFT) => KeyCode::BackTab,
+        TermwizKey::Tab => KeyCode::Tab,
+        TermwizKey::Backspace => KeyCode::Backspace,
+        TermwizKey::Delete => KeyCode::Delete,
+        TermwizKey::Insert => KeyCode::Insert,
+        TermwizKey::LeftArrow => KeyCode::Left,
+        TermwizKey::RightArrow => KeyCode::Right,
+        TermwizKey::UpArrow => KeyCode::Up,
+        TermwizKey::DownArrow => KeyCode::Down,
+        TermwizKey::Home => KeyCode::Home,
+        TermwizKey::End => KeyCode::End,
+        TermwizKey::PageUp => KeyCode::PageUp,
+        TermwizKey::PageDown => KeyCode::PageDown,
+        TermwizKey::Function(n) => KeyCode::F(n),
+        _ => return Input::Other,
+    };
+    Input::Key(code)
+}
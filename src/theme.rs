@@ -0,0 +1,385 @@
+use ratatui::style::Color;
+use rust_tui_ball_bounce::widget::BALL_COLORS;
+
+pub struct Theme {
+    pub name: &'static str,
+    pub arena_border: Color,
+    pub status_border: Color,
+    pub x_border: Color,
+    pub y_border: Color,
+    pub vx_border: Color,
+    pub vy_border: Color,
+    pub axis: Color,
+    pub label: Color,
+    pub muted: Color,
+    pub running: Color,
+    pub paused: Color,
+    pub ball_count: Color,
+    pub speed: Color,
+    pub notice: Color,
+    pub obstacle: Color,
+    // Key hint colors in the controls list
+    pub hint: Color,
+    pub hint_add: Color,
+    pub hint_remove: Color,
+    pub hint_up: Color,
+    pub hint_down: Color,
+    pub hint_alt: Color,
+    // Layout editor overlay
+    pub selected: Color,
+    pub picked: Color,
+    pub balls: &'static [Color],
+    // Drawn without color, as `--no-color` draws any theme
+    pub monochrome: bool,
+}
+
+impl Theme {
+    pub fn ball_color(&self, color_idx: usize) -> Color {
+        self.balls[color_idx % self.balls.len()]
+    }
+}
+
+pub static DEFAULT: Theme = Theme {
+    name: "default",
+    arena_border: Color::Cyan,
+    status_border: Color::Yellow,
+    x_border: Color::Cyan,
+    y_border: Color::Magenta,
+    vx_border: Color::LightCyan,
+    vy_border: Color::LightMagenta,
+    axis: Color::Gray,
+    label: Color::White,
+    muted: Color::DarkGray,
+    running: Color::Green,
+    paused: Color::Red,
+    ball_count: Color::Cyan,
+    speed: Color::LightYellow,
+    notice: Color::LightYellow,
+    obstacle: Color::Gray,
+    hint: Color::Yellow,
+    hint_add: Color::Green,
+    hint_remove: Color::Red,
+    hint_up: Color::LightGreen,
+    hint_down: Color::LightRed,
+    hint_alt: Color::Cyan,
+    selected: Color::White,
+    picked: Color::LightYellow,
+    balls: BALL_COLORS,
+    monochrome: false,
+};
+
+pub static OCEAN: Theme = Theme {
+    name: "ocean",
+    arena_border: Color::Blue,
+    status_border: Color::LightBlue,
+    x_border: Color::Blue,
+    y_border: Color::Cyan,
+    vx_border: Color::LightBlue,
+    vy_border: Color::LightCyan,
+    axis: Color::Gray,
+    label: Color::White,
+    muted: Color::DarkGray,
+    running: Color::LightCyan,
+    paused: Color::LightMagenta,
+    ball_count: Color::LightBlue,
+    speed: Color::LightCyan,
+    notice: Color::LightCyan,
+    obstacle: Color::Gray,
+    hint: Color::LightBlue,
+    hint_add: Color::Cyan,
+    hint_remove: Color::LightMagenta,
+    hint_up: Color::LightCyan,
+    hint_down: Color::Magenta,
+    hint_alt: Color::Blue,
+    selected: Color::White,
+    picked: Color::LightCyan,
+    balls: &[
+        Color::LightCyan,
+        Color::LightBlue,
+        Color::White,
+        Color::Cyan,
+        Color::LightGreen,
+        Color::Blue,
+        Color::LightMagenta,
+        Color::Green,
+    ],
+    monochrome: false,
+};
+
+pub static EMBER: Theme = Theme {
+    name: "ember",
+    arena_border: Color::Red,
+    status_border: Color::LightRed,
+    x_border: Color::Red,
+    y_border: Color::Yellow,
+    vx_border: Color::LightRed,
+    vy_border: Color::LightYellow,
+    axis: Color::Gray,
+    label: Color::White,
+    muted: Color::DarkGray,
+    running: Color::LightYellow,
+    paused: Color::Red,
+    ball_count: Color::LightRed,
+    speed: Color::Yellow,
+    notice: Color::LightYellow,
+    obstacle: Color::Gray,
+    hint: Color::LightRed,
+    hint_add: Color::Yellow,
+    hint_remove: Color::Red,
+    hint_up: Color::LightYellow,
+    hint_down: Color::Magenta,
+    hint_alt: Color::LightRed,
+    selected: Color::White,
+    picked: Color::Yellow,
+    balls: &[
+        Color::LightYellow,
+        Color::LightRed,
+        Color::Yellow,
+        Color::Red,
+        Color::White,
+        Color::Magenta,
+        Color::LightMagenta,
+        Color::Rgb(255, 140, 0),
+    ],
+    monochrome: false,
+};
+
+/// Only the brightest colors, and plain white where others use dark gray,
+/// for low vision.
+pub static HIGH_CONTRAST: Theme = Theme {
+    name: "high-contrast",
+    arena_border: Color::White,
+    status_border: Color::LightYellow,
+    x_border: Color::White,
+    y_border: Color::White,
+    vx_border: Color::White,
+    vy_border: Color::White,
+    axis: Color::White,
+    label: Color::White,
+    muted: Color::Gray,
+    running: Color::LightGreen,
+    paused: Color::LightRed,
+    ball_count: Color::LightYellow,
+    speed: Color::LightYellow,
+    notice: Color::LightYellow,
+    obstacle: Color::White,
+    hint: Color::LightYellow,
+    hint_add: Color::LightGreen,
+    hint_remove: Color::LightRed,
+    hint_up: Color::LightCyan,
+    hint_down: Color::LightMagenta,
+    hint_alt: Color::White,
+    selected: Color::LightYellow,
+    picked: Color::LightCyan,
+    balls: &[
+        Color::White,
+        Color::LightYellow,
+        Color::LightCyan,
+        Color::LightGreen,
+        Color::LightMagenta,
+        Color::LightRed,
+    ],
+    monochrome: false,
+};
+
+/// No color at all, for terminals whose palette can't be trusted. White
+/// here only marks what's drawn bold, and dark gray what's drawn dim; see
+/// `ui::draw`.
+pub static MONOCHROME: Theme = Theme {
+    name: "monochrome",
+    arena_border: Color::Reset,
+    status_border: Color::Reset,
+    x_border: Color::Reset,
+    y_border: Color::Reset,
+    vx_border: Color::Reset,
+    vy_border: Color::Reset,
+    axis: Color::Reset,
+    label: Color::White,
+    muted: Color::DarkGray,
+    running: Color::Reset,
+    paused: Color::White,
+    ball_count: Color::Reset,
+    speed: Color::Reset,
+    notice: Color::White,
+    obstacle: Color::Reset,
+    hint: Color::Reset,
+    hint_add: Color::Reset,
+    hint_remove: Color::Reset,
+    hint_up: Color::Reset,
+    hint_down: Color::Reset,
+    hint_alt: Color::Reset,
+    selected: Color::White,
+    picked: Color::White,
+    balls: &[Color::Reset],
+    monochrome: true,
+};
+
+/// For red-green color blindness with weak green: the Okabe-Ito colors,
+/// with blue and orange where other themes use green and red.
+pub static DEUTERANOPIA: Theme = Theme {
+    name: "deuteranopia",
+    arena_border: Color::Rgb(0x56, 0xb4, 0xe9),
+    status_border: Color::Rgb(0xe6, 0x9f, 0x00),
+    x_border: Color::Rgb(0x56, 0xb4, 0xe9),
+    y_border: Color::Rgb(0xcc, 0x79, 0xa7),
+    vx_border: Color::Rgb(0x56, 0xb4, 0xe9),
+    vy_border: Color::Rgb(0xcc, 0x79, 0xa7),
+    axis: Color::Gray,
+    label: Color::White,
+    muted: Color::DarkGray,
+    running: Color::Rgb(0x56, 0xb4, 0xe9),
+    paused: Color::Rgb(0xe6, 0x9f, 0x00),
+    ball_count: Color::Rgb(0x56, 0xb4, 0xe9),
+    speed: Color::Rgb(0xf0, 0xe4, 0x42),
+    notice: Color::Rgb(0xf0, 0xe4, 0x42),
+    obstacle: Color::Gray,
+    hint: Color::Rgb(0xf0, 0xe4, 0x42),
+    hint_add: Color::Rgb(0x56, 0xb4, 0xe9),
+    hint_remove: Color::Rgb(0xe6, 0x9f, 0x00),
+    hint_up: Color::Rgb(0x56, 0xb4, 0xe9),
+    hint_down: Color::Rgb(0xd5, 0x5e, 0x00),
+    hint_alt: Color::Rgb(0xcc, 0x79, 0xa7),
+    selected: Color::White,
+    picked: Color::Rgb(0xf0, 0xe4, 0x42),
+    balls: &[
+        Color::Rgb(0xe6, 0x9f, 0x00),
+        Color::Rgb(0x56, 0xb4, 0xe9),
+        Color::Rgb(0xf0, 0xe4, 0x42),
+        Color::Rgb(0x00, 0x72, 0xb2),
+        Color::Rgb(0xd5, 0x5e, 0x00),
+        Color::Rgb(0xcc, 0x79, 0xa7),
+        Color::Rgb(0x00, 0x9e, 0x73),
+        Color::White,
+    ],
+    monochrome: false,
+};
+
+/// For red-green color blindness with weak red, to which reds look dark:
+/// blues, magenta and bright oranges, none of them told apart by red alone.
+pub static PROTANOPIA: Theme = Theme {
+    name: "protanopia",
+    arena_border: Color::Rgb(0x64, 0x8f, 0xff),
+    status_border: Color::Rgb(0xff, 0xb0, 0x00),
+    x_border: Color::Rgb(0x64, 0x8f, 0xff),
+    y_border: Color::Rgb(0xdc, 0x26, 0x7f),
+    vx_border: Color::Rgb(0x64, 0x8f, 0xff),
+    vy_border: Color::Rgb(0xdc, 0x26, 0x7f),
+    axis: Color::Gray,
+    label: Color::White,
+    muted: Color::DarkGray,
+    running: Color::Rgb(0x64, 0x8f, 0xff),
+    paused: Color::Rgb(0xff, 0xb0, 0x00),
+    ball_count: Color::Rgb(0x64, 0x8f, 0xff),
+    speed: Color::Rgb(0xff, 0xb0, 0x00),
+    notice: Color::Rgb(0xff, 0xb0, 0x00),
+    obstacle: Color::Gray,
+    hint: Color::Rgb(0xff, 0xb0, 0x00),
+    hint_add: Color::Rgb(0x64, 0x8f, 0xff),
+    hint_remove: Color::Rgb(0xfe, 0x61, 0x00),
+    hint_up: Color::Rgb(0x64, 0x8f, 0xff),
+    hint_down: Color::Rgb(0xfe, 0x61, 0x00),
+    hint_alt: Color::Rgb(0x78, 0x5e, 0xf0),
+    selected: Color::White,
+    picked: Color::Rgb(0xff, 0xb0, 0x00),
+    balls: &[
+        Color::Rgb(0xff, 0xb0, 0x00),
+        Color::Rgb(0x64, 0x8f, 0xff),
+        Color::Rgb(0xdc, 0x26, 0x7f),
+        Color::White,
+        Color::Rgb(0x78, 0x5e, 0xf0),
+        Color::Rgb(0xfe, 0x61, 0x00),
+        Color::Rgb(0x56, 0xb4, 0xe9),
+        Color::Gray,
+    ],
+    monochrome: false,
+};
+
+/// For blue-yellow color blindness: reds, teals and pinks, with no blue
+/// against green or yellow against violet.
+pub static TRITANOPIA: Theme = Theme {
+    name: "tritanopia",
+    arena_border: Color::Rgb(0x00, 0xa0, 0xa0),
+    status_border: Color::Rgb(0xff, 0x7a, 0xb6),
+    x_border: Color::Rgb(0x00, 0xa0, 0xa0),
+    y_border: Color::Rgb(0xff, 0x7a, 0xb6),
+    vx_border: Color::Rgb(0x00, 0xa0, 0xa0),
+    vy_border: Color::Rgb(0xff, 0x7a, 0xb6),
+    axis: Color::Gray,
+    label: Color::White,
+    muted: Color::DarkGray,
+    running: Color::Rgb(0x00, 0xc0, 0xc0),
+    paused: Color::Rgb(0xe8, 0x30, 0x30),
+    ball_count: Color::Rgb(0x00, 0xc0, 0xc0),
+    speed: Color::Rgb(0xff, 0x7a, 0xb6),
+    notice: Color::Rgb(0xff, 0x7a, 0xb6),
+    obstacle: Color::Gray,
+    hint: Color::Rgb(0xff, 0x7a, 0xb6),
+    hint_add: Color::Rgb(0x00, 0xc0, 0xc0),
+    hint_remove: Color::Rgb(0xe8, 0x30, 0x30),
+    hint_up: Color::Rgb(0x00, 0xc0, 0xc0),
+    hint_down: Color::Rgb(0xe8, 0x30, 0x30),
+    hint_alt: Color::White,
+    selected: Color::White,
+    picked: Color::Rgb(0xff, 0x7a, 0xb6),
+    balls: &[
+        Color::Rgb(0xe8, 0x30, 0x30),
+        Color::Rgb(0x00, 0xc0, 0xc0),
+        Color::Rgb(0xff, 0x7a, 0xb6),
+        Color::White,
+        Color::Rgb(0x8c, 0x00, 0x00),
+        Color::Rgb(0x00, 0x70, 0x70),
+        Color::Gray,
+        Color::Rgb(0xc0, 0x40, 0x90),
+    ],
+    monochrome: false,
+};
+
+pub static THEMES: &[&Theme] = &[
+    &DEFAULT,
+    &OCEAN,
+    &EMBER,
+    &HIGH_CONTRAST,
+    &MONOCHROME,
+    &DEUTERANOPIA,
+    &PROTANOPIA,
+    &TRITANOPIA,
+];
+
+pub const THEME_NAMES: [&str; 8] =
+    ["default", "ocean", "ember", "high-contrast", "monochrome", "deuteranopia", "protanopia", "tritanopia"];
+
+pub fn by_name(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().copied().find(|theme| theme.name == name)
+}
+
+/// What the arena's empty space is filled with, in the theme's colors and
+/// kept dim so balls stand out against it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backdrop {
+    None,
+    // Scattered dim dots, a few of them twinkling as the simulation runs
+    Stars,
+    // The arena's border color, faint at the top and deepening downwards;
+    // shows only on truecolor terminals
+    Gradient,
+}
+
+/// Values `display.background` takes.
+pub const BACKDROP_NAMES: [&str; 3] = ["none", "stars", "gradient"];
+
+impl Backdrop {
+    pub const ALL: [Backdrop; 3] = [Backdrop::None, Backdrop::Stars, Backdrop::Gradient];
+
+    pub fn by_name(name: &str) -> Option<Backdrop> {
+        Some(Backdrop::ALL[BACKDROP_NAMES.iter().position(|&known| known == name)?])
+    }
+
+    pub fn name(self) -> &'static str {
+        BACKDROP_NAMES[self as usize]
+    }
+
+    /// The backdrop `step` places along from this one, wrapping around.
+    pub fn cycle(self, step: isize) -> Backdrop {
+        Backdrop::ALL[(self as isize + step).rem_euclid(Backdrop::ALL.len() as isize) as usize]
+    }
+}
@@ -0,0 +1,94 @@
+//! The chaos experiment. `twin` starts a copy of the simulation from the
+//! same state, but with the first ball moved a tiny `epsilon` to the right,
+//! and the simulation thread steps the two in lockstep. The arena draws the
+//! twin's balls as ghosts under the real ones, and a chart over the arena
+//! follows how far apart the two have drifted, on a log scale: with balls
+//! colliding the gap grows exponentially until it's as big as the arena.
+//!
+//! The twin is built from a save, so custom forces aren't copied. Pausing,
+//! stepping and the speed apply to both; any other change ends the
+//! experiment, as the two would no longer differ by just `epsilon`.
+
+use rust_tui_ball_bounce::{BallState, Simulation};
+
+/// How far the twin's first ball is moved when `twin` doesn't say.
+pub const DEFAULT_EPSILON: f64 = 1e-6;
+
+// Samples charted; older ones are dropped
+const MAX_SAMPLES: usize = 4000;
+
+/// The twin as last reported, and how the gap has grown.
+#[derive(Debug)]
+pub struct Twin {
+    pub epsilon: f64,
+    pub balls: Vec<BallState>,
+    // Tick and the gap's base-10 logarithm
+    pub divergence: Vec<(f64, f64)>,
+}
+
+impl Twin {
+    pub fn new(epsilon: f64) -> Twin {
+        Twin { epsilon, balls: Vec::new(), divergence: Vec::new() }
+    }
+
+    /// Takes in the twin's balls at `tick`, beside the simulation's.
+    pub fn record(&mut self, tick: u64, balls: &[BallState], twin: Vec<BallState>) {
+        let gap = divergence(balls, &twin).max(f64::MIN_POSITIVE);
+        if self.divergence.last().is_some_and(|&(last, _)| last == tick as f64) {
+            self.divergence.pop();
+        }
+        if self.divergence.len() == MAX_SAMPLES {
+            self.divergence.remove(0);
+        }
+        self.divergence.push((tick as f64, gap.log10()));
+        self.balls = twin;
+    }
+
+    /// The latest gap, once there is one.
+    pub fn gap(&self) -> Option<f64> {
+        self.divergence.last().map(|&(_, log)| 10f64.powf(log))
+    }
+}
+
+/// `sim`'s twin: the same state with the lowest-id ball `epsilon` further
+/// right. `None` without balls.
+pub fn nudged(sim: &Simulation, epsilon: f64) -> Option<Simulation> {
+    let mut saved = sim.save();
+    saved.balls.first_mut()?.x += epsilon;
+    let mut twin = Simulation::restore(saved).ok()?;
+    twin.max_history = 0;
+    Some(twin)
+}
+
+/// How far apart two sets of balls are: the distance between their
+/// positions taken together, each ball against the one with its id.
+pub fn divergence(a: &[BallState], b: &[BallState]) -> f64 {
+    a.iter()
+        .filter_map(|ball| {
+            let other = b.iter().find(|other| other.id == ball.id)?;
+            Some((ball.x - other.x).powi(2) + (ball.y - other.y).powi(2))
+        })
+        .sum::<f64>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_tiny_nudge_grows() {
+        let mut sim = Simulation::new(40.0, 20.0);
+        for _ in 0..6 {
+            sim.add_ball(sim.next_ball_spec());
+        }
+        let mut twin = nudged(&sim, 1e-9).unwrap();
+        assert!((divergence(sim.state(), twin.state()) - 1e-9).abs() < 1e-12);
+        for _ in 0..2000 {
+            sim.step(1.0);
+            twin.step(1.0);
+        }
+        assert!(divergence(sim.state(), twin.state()) > 1e-3);
+        assert!(nudged(&Simulation::new(40.0, 20.0), 1e-9).is_none());
+    }
+}
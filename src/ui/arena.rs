@@ -0,0 +1,243 @@
+use std::f64::consts::FRAC_PI_4;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::Widget,
+};
+use rust_tui_ball_bounce::widget::BallArenaWidget;
+
+use crate::app::App;
+use crate::caps::ColorDepth;
+use crate::clip;
+use crate::compare::Comparison;
+use crate::game;
+use crate::lag::TickMeter;
+use crate::mode::Mode;
+use crate::paint::{Paint, ASCII_SHADES, SHADES};
+use crate::theme::Backdrop;
+use crate::twin::Twin;
+use crate::ui::{pane_block, ASCII_BALL_CHARS, BALL_CHARS};
+
+/// Columns and rows between the force field's arrows.
+pub const FIELD_SPACING: (f64, f64) = (8.0, 4.0);
+
+// Pointing right and on round clockwise, as y grows downward
+const ARROWS: [&str; 8] = ["→", "↘", "↓", "↙", "←", "↖", "↑", "↗"];
+const ASCII_ARROWS: [&str; 8] = [">", "\\", "v", "/", "<", "\\", "^", "/"];
+
+/// The arena pane's area for the simulation, and for the one compared with
+/// in the right half while a comparison runs.
+pub fn split(area: Rect, comparing: bool) -> (Rect, Option<Rect>) {
+    if !comparing {
+        return (area, None);
+    }
+    let left = area.width / 2;
+    (Rect { width: left, ..area }, Some(Rect { x: area.x + left, width: area.width - left, ..area }))
+}
+
+pub fn draw(buf: &mut Buffer, app: &App, area: Rect) {
+    let (area, right) = split(area, app.comparison.is_some());
+    if let (Some(comparison), Some(right)) = (&app.comparison, right) {
+        draw_other(buf, app, comparison, right);
+    }
+    // A replay shows its recording in place of the simulation
+    let frame;
+    let state = match &app.mode {
+        Mode::Replay(replay) => {
+            frame = replay.frame();
+            &frame
+        }
+        _ => &app.state,
+    };
+    let mut block = pane_block(
+        app,
+        (app.strings.arena)(state.balls.len()),
+        app.theme.arena_border,
+    );
+    if app.lag.lagging(app.tick_rate) {
+        let style = Style::default().fg(app.theme.notice).add_modifier(Modifier::BOLD);
+        block = block.title(Line::styled(app.strings.lagging, style).right_aligned());
+    }
+    if let Some(rate) = app.tick_meter.as_ref().and_then(TickMeter::rate) {
+        let style = Style::default().fg(app.theme.muted);
+        block = block.title(Line::styled(format!(" {} ticks/s ", rate), style).right_aligned());
+    }
+    let inner = block.inner(area);
+
+    let (glyphs, wall) = if app.ascii { (ASCII_BALL_CHARS, "#") } else { (BALL_CHARS, "█") };
+    let arena = BallArenaWidget::from_snapshot(state)
+        .block(block)
+        .glyphs(glyphs)
+        .colors(app.theme.balls)
+        .obstacles(wall, Style::default().fg(app.theme.obstacle))
+        .pockets(if app.ascii { ":" } else { "░" }, Style::default().fg(app.theme.muted))
+        .ascii(app.ascii);
+    arena.render(area, buf);
+
+    // Painted by the simulation, not the recording
+    if let (Some(paint), false) = (&app.paint, matches!(app.mode, Mode::Replay(_))) {
+        draw_paint(buf, app, paint, inner);
+    }
+    if let Some(twin) = &app.twin {
+        draw_ghosts(buf, app, twin, inner);
+    }
+    if app.show_field {
+        draw_field(buf, app, inner);
+    }
+
+    let aim = if app.ascii { "." } else { "·" };
+    for (x, y) in game::aim_line(app) {
+        let (x, y) = (x.round(), y.round());
+        if x >= 0.0 && y >= 0.0 && x < inner.width as f64 && y < inner.height as f64 {
+            let (x, y) = (inner.x + x as u16, inner.y + y as u16);
+            // Over empty cloth only, so no ball is hidden
+            if buf[(x, y)].symbol() == " " {
+                buf.set_string(x, y, aim, Style::default().fg(app.theme.selected));
+            }
+        }
+    }
+
+    if let Some(((x, y), glyph)) = game::marker(app) {
+        let (x, y) = (x.round(), y.round());
+        if x >= 0.0 && y >= 0.0 && x < inner.width as f64 && y < inner.height as f64 {
+            let style = Style::default().fg(app.theme.notice).add_modifier(Modifier::BOLD);
+            buf.set_string(inner.x + x as u16, inner.y + y as u16, glyph, style);
+        }
+    }
+
+    if let Mode::ObstacleEdit(edit) = &app.mode {
+        if edit.x < inner.width && edit.y < inner.height {
+            let cursor = Style::default().fg(app.theme.selected).add_modifier(Modifier::REVERSED);
+            buf[(inner.x + edit.x, inner.y + edit.y)].set_style(cursor);
+        }
+    }
+    draw_backdrop(buf, app, inner, state.tick_count);
+}
+
+/// The stains, in the cells the balls and walls left empty.
+fn draw_paint(buf: &mut Buffer, app: &App, paint: &Paint, inner: Rect) {
+    let shades = if app.ascii { ASCII_SHADES } else { SHADES };
+    for ((x, y), stain) in paint.stains() {
+        if x >= inner.width || y >= inner.height {
+            continue;
+        }
+        let cell = &mut buf[(inner.x + x, inner.y + y)];
+        if cell.symbol() == " " {
+            let color = stain.color.map_or(app.theme.ball_color(stain.id), Color::from);
+            cell.set_symbol(shades[stain.shade()]).set_fg(color);
+        }
+    }
+}
+
+/// The backdrop, in cells everything else left empty; a gradient also
+/// tints the background behind the balls.
+fn draw_backdrop(buf: &mut Buffer, app: &App, inner: Rect, tick: u64) {
+    match app.backdrop {
+        Backdrop::None => {}
+        Backdrop::Stars => {
+            let (star, bright) = if app.ascii { (".", "+") } else { ("·", "+") };
+            let dim = Style::default().fg(app.theme.muted).add_modifier(Modifier::DIM);
+            for (x, y) in cells(inner) {
+                let hash = scatter(x - inner.x, y - inner.y);
+                if !hash.is_multiple_of(29) || buf[(x, y)].symbol() != " " {
+                    continue;
+                }
+                // One star in four flares for a moment every few seconds
+                let flaring = !app.reduced_motion && hash >> 8 & 3 == 0 && (tick / 30 + (hash >> 16) as u64).is_multiple_of(8);
+                let (symbol, style) = if flaring { (bright, Style::default().fg(app.theme.muted)) } else { (star, dim) };
+                buf.set_string(x, y, symbol, style);
+            }
+        }
+        // Lesser depths would round it to blocks of black
+        Backdrop::Gradient if app.colors < ColorDepth::TrueColor => {}
+        Backdrop::Gradient => {
+            let [r, g, b] = clip::rgb(app.theme.arena_border);
+            for (x, y) in cells(inner) {
+                let depth = 0.04 + 0.12 * (y - inner.y) as f64 / inner.height.max(2).saturating_sub(1) as f64;
+                let shade = |channel: u8| (channel as f64 * depth).round() as u8;
+                buf[(x, y)].set_bg(Color::Rgb(shade(r), shade(g), shade(b)));
+            }
+        }
+    }
+}
+
+fn cells(area: Rect) -> impl Iterator<Item = (u16, u16)> {
+    (area.top()..area.bottom()).flat_map(move |y| (area.left()..area.right()).map(move |x| (x, y)))
+}
+
+/// A well-mixed number for the cell at `(x, y)`, so stars are scattered
+/// the same way every frame.
+fn scatter(x: u16, y: u16) -> u32 {
+    let mut hash = (x as u32).wrapping_mul(0x9e37_79b9) ^ (y as u32).wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^ hash >> 12
+}
+
+/// The simulation compared with, titled with what it differs by.
+fn draw_other(buf: &mut Buffer, app: &App, comparison: &Comparison, area: Rect) {
+    let Some(state) = &comparison.state else {
+        return;
+    };
+    let block = pane_block(app, (app.strings.compared)(&comparison.label(), state.balls.len()), app.theme.picked);
+    let inner = block.inner(area);
+    let (glyphs, wall) = if app.ascii { (ASCII_BALL_CHARS, "#") } else { (BALL_CHARS, "█") };
+    BallArenaWidget::from_snapshot(state)
+        .block(block)
+        .glyphs(glyphs)
+        .colors(app.theme.balls)
+        .obstacles(wall, Style::default().fg(app.theme.obstacle))
+        .pockets(if app.ascii { ":" } else { "░" }, Style::default().fg(app.theme.muted))
+        .ascii(app.ascii)
+        .render(area, buf);
+    draw_backdrop(buf, app, inner, state.tick_count);
+}
+
+/// The chaos experiment twin's balls, dim, wherever the arena's own leave
+/// room for them.
+fn draw_ghosts(buf: &mut Buffer, app: &App, twin: &Twin, inner: Rect) {
+    for ball in &twin.balls {
+        let (x, y) = (ball.x.round(), (ball.y / app.state.aspect).round());
+        if x < 0.0 || y < 0.0 || x >= inner.width as f64 || y >= inner.height as f64 {
+            continue;
+        }
+        let cell = &mut buf[(inner.x + x as u16, inner.y + y as u16)];
+        if cell.symbol() == " " {
+            cell.set_symbol(app.ball_glyph(ball.id));
+            cell.set_style(Style::default().fg(app.theme.muted));
+        }
+    }
+}
+
+/// An arrow at each point the field was sampled at, pointing the way the
+/// net force pushes: dim where it's weak next to the strongest, bold where
+/// it's close. Balls and walls are drawn over.
+fn draw_field(buf: &mut Buffer, app: &App, inner: Rect) {
+    let field = &app.state.field;
+    let strongest = field.iter().map(|sample| sample.ax.hypot(sample.ay)).fold(0.0, f64::max);
+    let arrows = if app.ascii { ASCII_ARROWS } else { ARROWS };
+    for sample in field {
+        let strength = sample.ax.hypot(sample.ay) / strongest;
+        let (x, y) = (sample.x.floor(), (sample.y / app.state.aspect).floor());
+        if strength <= 0.0 || x < 0.0 || y < 0.0 || x >= inner.width as f64 || y >= inner.height as f64 {
+            continue;
+        }
+        let cell = &mut buf[(inner.x + x as u16, inner.y + y as u16)];
+        if cell.symbol() != " " {
+            continue;
+        }
+        let octant = (sample.ay.atan2(sample.ax) / FRAC_PI_4).round() as i32;
+        let modifier = if strength < 1.0 / 3.0 {
+            Modifier::DIM
+        } else if strength > 2.0 / 3.0 {
+            Modifier::BOLD
+        } else {
+            Modifier::empty()
+        };
+        cell.set_symbol(arrows[octant.rem_euclid(8) as usize]);
+        cell.set_style(Style::default().fg(app.theme.axis).add_modifier(modifier));
+    }
+}
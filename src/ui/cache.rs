@@ -0,0 +1,158 @@
+//! Panes are redrawn only when something they show has changed: charts when
+//! the history does, so not at all while paused, and the controls when their
+//! text would differ. Otherwise the cells drawn last time are copied back in.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::mem;
+
+use ratatui::{buffer::Buffer, layout::Rect};
+
+use crate::app::App;
+use crate::game::Game;
+use crate::lag::TickMeter;
+use crate::layout::{Pane, Section};
+use crate::mode::Mode;
+use crate::paint::Paint;
+use crate::ui::status;
+
+/// Each pane's cells as last drawn, and the key they were drawn for.
+#[derive(Default)]
+pub struct PaneCache {
+    panes: HashMap<Pane, (u64, Buffer)>,
+}
+
+impl PaneCache {
+    /// Forgets every pane, for changes no key covers, like a new theme.
+    pub fn clear(&mut self) {
+        self.panes.clear();
+    }
+
+    /// Copies `pane`'s cached cells into `buf` if they were drawn at `area`
+    /// for `key`, and otherwise draws them afresh with `draw`. Panes without
+    /// a key are always drawn.
+    pub fn draw(&mut self, pane: Pane, area: Rect, key: Option<u64>, buf: &mut Buffer, draw: impl FnOnce(&mut Buffer)) {
+        if let Some((cached_key, cells)) = self.panes.get(&pane) {
+            if key == Some(*cached_key) && cells.area == area {
+                copy(cells, buf);
+                return;
+            }
+        }
+        // Reuses the old pane's cells rather than allocating
+        let mut cells = self.panes.remove(&pane).map_or_else(|| Buffer::empty(area), |(_, cells)| cells);
+        cells.resize(area);
+        cells.reset();
+        draw(&mut cells);
+        copy(&cells, buf);
+        if let Some(key) = key {
+            self.panes.insert(pane, (key, cells));
+        }
+    }
+}
+
+fn copy(from: &Buffer, to: &mut Buffer) {
+    let area = from.area.intersection(to.area);
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            to[(x, y)] = from[(x, y)].clone();
+        }
+    }
+}
+
+/// What `pane` is drawn from, hashed; `None` if it can't be cached.
+pub fn key(app: &App, pane: Pane) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    match pane {
+        Pane::Arena => {
+            // The wall cursor and replays move without the state changing
+            if let Mode::ObstacleEdit(_) | Mode::Replay(_) = app.mode {
+                return None;
+            }
+            let rate = app.tick_meter.as_ref().and_then(TickMeter::rate);
+            (app.updates, app.lag.lagging(app.tick_rate), rate, app.show_field, app.backdrop, app.twin.is_some(), app.comparison.is_some()).hash(&mut hasher);
+            // Cleared or switched between updates
+            app.paint.as_ref().map(Paint::len).hash(&mut hasher);
+            if let Some(Game::Billiards(billiards)) = &app.game {
+                (billiards.angle.to_bits(), billiards.power.to_bits()).hash(&mut hasher);
+            }
+        }
+        Pane::Status => {
+            let state = &app.state;
+            // A few balls are listed, with their positions as shown and
+            // energy bars, from the picked one's group and marking it while
+            // a pane is focused
+            let cursor = app.ball_cursor.min(state.balls.len().saturating_sub(1));
+            (app.focus, cursor).hash(&mut hasher);
+            for (_, ball) in status::listed(app) {
+                (ball.id, ball.color, ball.glyph, status::position(app, ball.x), status::position(app, ball.y)).hash(&mut hasher);
+            }
+            status::energy_bars(app).hash(&mut hasher);
+            status::gas_lines(app).hash(&mut hasher);
+            status::collision_lines(app).hash(&mut hasher);
+            app.status_panel.contains(&Section::Rate).then(|| status::hit_rate(app)).hash(&mut hasher);
+            (state.paused, state.balls.len(), state.speed_multiplier.to_bits()).hash(&mut hasher);
+            state.count_hits.then_some(state.clicks).hash(&mut hasher);
+            (&state.scenario_name, &app.profile, &app.notice, mem::discriminant(&app.mode)).hash(&mut hasher);
+            if let Some(Game::Billiards(billiards)) = &app.game {
+                let aim = (billiards.angle.to_degrees().round() as i64, (billiards.power * 100.0).round() as i64);
+                (billiards.potted, billiards.shots, aim, billiards.ready(state)).hash(&mut hasher);
+            }
+            if let Some(board) = app.game.as_ref().and_then(|game| game.board(&app.scores)) {
+                board.best.first().map(|best| best.to_bits()).hash(&mut hasher);
+            }
+            if let Some(Game::Targets(targets)) = &app.game {
+                (targets.scores, targets.winner, targets.level).hash(&mut hasher);
+            }
+            if let Some(Game::Dodge(dodge)) = &app.game {
+                // The time is shown to a tenth of a second
+                let tenths = (dodge.survived as f64 * app.tick_rate.as_secs_f64() * 10.0) as u64;
+                (tenths, dodge.over).hash(&mut hasher);
+                dodge.effects().map(|(power_up, _)| power_up).collect::<Vec<_>>().hash(&mut hasher);
+            }
+        }
+        Pane::Quiver => app.updates.hash(&mut hasher),
+        Pane::XPosition | Pane::YPosition | Pane::XVelocity | Pane::YVelocity => {
+            let arena = (app.state.area_width.to_bits(), app.state.area_height.to_bits());
+            (app.history_version, arena, app.lag.chart_step()).hash(&mut hasher);
+            // Each series is drawn in its ball's color, with its glyph in the legend
+            app.state.balls.iter().map(|ball| (ball.color, ball.glyph)).collect::<Vec<_>>().hash(&mut hasher);
+            // Collisions are marked for the picked ball
+            app.picked_ball().map(|ball| ball.id).hash(&mut hasher);
+            if app.focus == Some(pane) {
+                let cursor = app.ball_cursor.min(app.state.balls.len().saturating_sub(1));
+                (app.crosshair.to_bits(), app.state.balls.get(cursor).map(|ball| ball.id)).hash(&mut hasher);
+            }
+        }
+    }
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Style;
+
+    use super::*;
+
+    #[test]
+    fn panes_are_drawn_again_only_for_a_new_key_or_area() {
+        let mut cache = PaneCache::default();
+        let area = Rect::new(2, 1, 4, 2);
+        let mut draws = 0;
+        let mut frame = |cache: &mut PaneCache, key, area| {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 10, 5));
+            cache.draw(Pane::Status, area, key, &mut buf, |cells| {
+                draws += 1;
+                cells.set_string(area.x, area.y, "ab", Style::default());
+            });
+            buf
+        };
+        let first = frame(&mut cache, Some(1), area);
+        assert_eq!(first[(2, 1)].symbol(), "a");
+        assert_eq!(frame(&mut cache, Some(1), area), first);
+        frame(&mut cache, Some(2), area);
+        frame(&mut cache, Some(2), Rect::new(0, 0, 4, 2));
+        frame(&mut cache, None, area);
+        frame(&mut cache, None, area);
+        assert_eq!(draws, 5);
+    }
+}
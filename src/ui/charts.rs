@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_6, PI};
+use std::mem;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Margin, Rect},
+    style::Style,
+    symbols,
+    text::{Line, Span},
+    widgets::canvas::{Canvas, Line as CanvasLine, Points},
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Widget},
+};
+
+use rust_tui_ball_bounce::components::{with_ticks, History, Samples};
+
+use crate::app::App;
+use crate::caps::Glyphs;
+use crate::compare::Comparison;
+use crate::layout::Pane;
+use crate::markers::Marker;
+use crate::profiler::Phase;
+use crate::twin::Twin;
+use crate::ui::pane_block;
+use crate::units::{self, Quantity};
+
+pub fn draw_x_graph(buf: &mut Buffer, app: &App, area: Rect) {
+    let x_max = app.state.area_width.max(app.chart_data.bounds(Pane::XPosition).1).max(1.0);
+
+    let mut datasets = datasets(app, app.chart_data.series(Pane::XPosition));
+    let crosshair = crosshair(app, Pane::XPosition, [0.0, x_max]);
+    let block = with_crosshair(app, &crosshair, &mut datasets, pane_block(app, app.strings.x_position, app.theme.x_border));
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(time_axis(app))
+        .y_axis(
+            Axis::default()
+                .title(units::titled(app.units, Quantity::Length, "X"))
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([0.0, x_max])
+                .labels(vec![
+                    Span::raw("0"),
+                    Span::raw(units::show(app.units, Quantity::Length, x_max, 0)),
+                ]),
+        );
+
+    chart.render(area, buf);
+    draw_markers(buf, app, area);
+}
+
+pub fn draw_y_graph(buf: &mut Buffer, app: &App, area: Rect) {
+    let y_max = app.state.area_height.max(app.chart_data.bounds(Pane::YPosition).1).max(1.0);
+
+    let mut datasets = datasets(app, app.chart_data.series(Pane::YPosition));
+    let crosshair = crosshair(app, Pane::YPosition, [0.0, y_max]);
+    let block = with_crosshair(app, &crosshair, &mut datasets, pane_block(app, app.strings.y_position, app.theme.y_border));
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(time_axis(app))
+        .y_axis(
+            Axis::default()
+                .title(units::titled(app.units, Quantity::Length, "Y"))
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([0.0, y_max])
+                .labels(vec![
+                    Span::raw("0"),
+                    Span::raw(units::show(app.units, Quantity::Length, y_max, 0)),
+                ]),
+        );
+
+    chart.render(area, buf);
+    draw_markers(buf, app, area);
+}
+
+pub fn draw_vx_graph(buf: &mut Buffer, app: &App, area: Rect) {
+    let (v_min, v_max) = app.chart_data.bounds(Pane::XVelocity);
+
+    let mut datasets = datasets(app, app.chart_data.series(Pane::XVelocity));
+    let crosshair = crosshair(app, Pane::XVelocity, [v_min, v_max]);
+    let block = with_crosshair(app, &crosshair, &mut datasets, pane_block(app, app.strings.x_velocity, app.theme.vx_border));
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(time_axis(app))
+        .y_axis(
+            Axis::default()
+                .title(units::titled(app.units, Quantity::Speed, "Vx"))
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([v_min, v_max])
+                .labels(vec![
+                    Span::raw(units::show(app.units, Quantity::Speed, v_min, 1)),
+                    Span::raw("0"),
+                    Span::raw(units::show(app.units, Quantity::Speed, v_max, 1)),
+                ]),
+        );
+
+    chart.render(area, buf);
+    draw_markers(buf, app, area);
+}
+
+pub fn draw_vy_graph(buf: &mut Buffer, app: &App, area: Rect) {
+    let (v_min, v_max) = app.chart_data.bounds(Pane::YVelocity);
+
+    let mut datasets = datasets(app, app.chart_data.series(Pane::YVelocity));
+    let crosshair = crosshair(app, Pane::YVelocity, [v_min, v_max]);
+    let block = with_crosshair(app, &crosshair, &mut datasets, pane_block(app, app.strings.y_velocity, app.theme.vy_border));
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(time_axis(app))
+        .y_axis(
+            Axis::default()
+                .title(units::titled(app.units, Quantity::Speed, "Vy"))
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([v_min, v_max])
+                .labels(vec![
+                    Span::raw(units::show(app.units, Quantity::Speed, v_min, 1)),
+                    Span::raw("0"),
+                    Span::raw(units::show(app.units, Quantity::Speed, v_max, 1)),
+                ]),
+        );
+
+    chart.render(area, buf);
+    draw_markers(buf, app, area);
+}
+
+/// Every ball as an arrow from where it is along its velocity, in its color,
+/// scaled so the fastest reaches a third of the way across the arena. The
+/// title reads that fastest speed.
+pub fn draw_quiver(buf: &mut Buffer, app: &App, area: Rect) {
+    let (width, height) = (app.state.area_width.max(1.0), app.state.area_height.max(1.0));
+    let balls = &app.state.balls;
+    let fastest = balls.iter().map(|ball| ball.vx.hypot(ball.vy)).fold(0.0, f64::max);
+    let scale = if fastest > 0.0 { width.min(height) / 3.0 / fastest } else { 0.0 };
+
+    let mut block = pane_block(app, app.strings.quiver, app.theme.arena_border);
+    if !balls.is_empty() {
+        let readout = format!(" max {} ", units::show_with_symbol(app.units, Quantity::Speed, fastest, 2));
+        block = block.title(Line::from(readout).right_aligned());
+    }
+    Canvas::default()
+        .block(block)
+        .marker(chart_marker(app))
+        .x_bounds([0.0, width])
+        .y_bounds([0.0, height])
+        .paint(|ctx| {
+            for ball in balls {
+                let color = app.ball_color(ball.id);
+                // The canvas counts y up from the floor
+                let (x, y) = (ball.x, height - ball.y);
+                let (dx, dy) = (ball.vx * scale, -ball.vy * scale);
+                if dx == 0.0 && dy == 0.0 {
+                    ctx.draw(&Points { coords: &[(x, y)], color });
+                    continue;
+                }
+                let (tip_x, tip_y) = (x + dx, y + dy);
+                ctx.draw(&CanvasLine { x1: x, y1: y, x2: tip_x, y2: tip_y, color });
+                // The head's two barbs, back from the tip either side
+                let (length, angle) = (dx.hypot(dy) * 0.3, dy.atan2(dx));
+                for side in [-1.0, 1.0] {
+                    let barb = angle + PI + side * FRAC_PI_6;
+                    let (x2, y2) = (tip_x + length * barb.cos(), tip_y + length * barb.sin());
+                    ctx.draw(&CanvasLine { x1: tip_x, y1: tip_y, x2, y2, color });
+                }
+            }
+        })
+        .render(area, buf);
+}
+
+/// How far the chaos experiment's twin has drifted from the simulation over
+/// time, on a log scale, with the latest gap in the title.
+pub fn draw_divergence(buf: &mut Buffer, app: &App, twin: &Twin, area: Rect) {
+    let points = &twin.divergence;
+    let t_min = points.first().map_or(0.0, |&(t, _)| t);
+    let t_max = points.last().map_or(0.0, |&(t, _)| t).max(t_min + 1.0);
+    let low = points.iter().map(|&(_, log)| log).fold(f64::MAX, f64::min).floor();
+    let high = points.iter().map(|&(_, log)| log).fold(f64::MIN, f64::max).ceil();
+    let (low, high) = if low < high { (low, high) } else { (low.min(0.0) - 1.0, high.max(0.0) + 1.0) };
+
+    let mut block = pane_block(app, app.strings.divergence, app.theme.status_border);
+    if let Some(gap) = twin.gap() {
+        // From the nudge to now
+        let scale = app.units.map_or(1.0, |units| units.scale(Quantity::Length));
+        let unit = app.units.map_or(String::new(), |_| format!(" {}", Quantity::Length.symbol()));
+        let arrow = if app.ascii { "->" } else { "→" };
+        let readout = format!(" {:.0e} {} {:.1e}{} ", twin.epsilon * scale, arrow, gap * scale, unit);
+        block = block.title(Line::from(readout).right_aligned());
+    }
+    let dataset = Dataset::default()
+        .graph_type(GraphType::Line)
+        .marker(chart_marker(app))
+        .style(Style::default().fg(app.theme.notice))
+        .data(points);
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([t_min, t_max])
+                .labels(vec![
+                    Span::raw(units::show(app.units, Quantity::Time, t_min, 0)),
+                    Span::raw(units::show(app.units, Quantity::Time, t_max, 0)),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([low, high])
+                .labels(vec![Span::raw(format!("1e{}", low)), Span::raw(format!("1e{}", high))]),
+        );
+    chart.render(area, buf);
+}
+
+/// The comparison's metric over time for both simulations, with the
+/// latest values in the title.
+pub fn draw_comparison(buf: &mut Buffer, app: &App, comparison: &Comparison, area: Rect) {
+    let [mine, theirs] = &comparison.series;
+    let points = || mine.iter().chain(theirs);
+    let t_min = points().map(|&(t, _)| t).reduce(f64::min).unwrap_or(0.0);
+    let t_max = points().map(|&(t, _)| t).fold(t_min + 1.0, f64::max);
+    let low = points().map(|&(_, value)| value).fold(0.0, f64::min);
+    let high = points().map(|&(_, value)| value).fold(0.0, f64::max);
+    let high = if high > low { high * 1.1 } else { low + 1.0 };
+    let quantity = comparison.metric.quantity();
+    let show = |value: f64| match quantity {
+        Some(quantity) => units::show(app.units, quantity, value, 2),
+        None => format!("{:.0}", value),
+    };
+
+    let mut block = pane_block(app, (app.strings.comparison)(comparison.metric.name()), app.theme.status_border);
+    if let (Some(&(_, a)), Some(&(_, b))) = (mine.last(), theirs.last()) {
+        block = block.title(Line::from(format!(" {} / {} ", show(a), show(b))).right_aligned());
+    }
+    let datasets = vec![
+        Dataset::default()
+            .graph_type(GraphType::Line)
+            .marker(chart_marker(app))
+            .style(Style::default().fg(app.theme.arena_border))
+            .data(mine),
+        Dataset::default()
+            .graph_type(GraphType::Line)
+            .marker(chart_marker(app))
+            .style(Style::default().fg(app.theme.picked))
+            .data(theirs),
+    ];
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([t_min, t_max])
+                .labels(vec![
+                    Span::raw(units::show(app.units, Quantity::Time, t_min, 0)),
+                    Span::raw(units::show(app.units, Quantity::Time, t_max, 0)),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([low, high])
+                .labels(vec![Span::raw(show(low)), Span::raw(show(high))]),
+        );
+    chart.render(area, buf);
+}
+
+/// The time axis the charts share, from the oldest tick charted to the
+/// newest.
+fn time_axis(app: &App) -> Axis<'static> {
+    let (t_min, t_max) = app.chart_data.time_bounds;
+    Axis::default()
+        .title(units::titled(app.units, Quantity::Time, app.strings.time))
+        .style(Style::default().fg(app.theme.axis))
+        .bounds([t_min, t_max])
+        .labels(vec![
+            Span::raw(units::show(app.units, Quantity::Time, t_min, 0)),
+            Span::raw(units::show(app.units, Quantity::Time, t_max, 0)),
+        ])
+}
+
+/// Puts `app.markers` on the time axis of the chart just drawn at `area`,
+/// the picked ball's collisions among them.
+fn draw_markers(buf: &mut Buffer, app: &App, area: Rect) {
+    let inner = area.inner(Margin::new(1, 1));
+    // The axis is the row above the time labels, starting at its corner
+    let Some(y) = inner.bottom().checked_sub(2).filter(|&y| y > inner.top()) else {
+        return;
+    };
+    let Some(left) = (inner.left()..inner.right()).find(|&x| buf[(x, y)].symbol() == symbols::line::BOTTOM_LEFT) else {
+        return;
+    };
+    let width = inner.right() - left - 1;
+    let (t_min, t_max) = app.chart_data.time_bounds;
+    if width == 0 || t_min >= t_max {
+        return;
+    }
+    for (t, marker) in app.markers.shown(app.picked_ball().map(|ball| ball.id)) {
+        if !(t_min..=t_max).contains(&t) {
+            continue;
+        }
+        let x = left + 1 + ((t - t_min) / (t_max - t_min) * (width - 1) as f64).round() as u16;
+        let color = match marker {
+            Marker::Collision { .. } => app.theme.picked,
+            Marker::Spawn { id } => app.ball_color(id),
+            Marker::SpeedChange => app.theme.notice,
+        };
+        buf[(x, y)].set_symbol(marker.symbol(app.ascii)).set_fg(color);
+    }
+}
+
+// A ball id and the points to chart for it
+type Series = (usize, Vec<(f64, f64)>);
+
+/// Chart points and bounds as last prepared from the history. They only
+/// change with the history and its thinning, so they are kept while paused
+/// and when it's just the window or the theme that changed.
+#[derive(Default)]
+pub struct ChartData {
+    // History version and chart step they were prepared for
+    key: Option<(u64, usize)>,
+    time_bounds: (f64, f64),
+    // Points per chart pane and their value bounds; for position charts,
+    // just the highest value
+    panes: HashMap<Pane, (Vec<Series>, (f64, f64))>,
+}
+
+impl ChartData {
+    fn series(&self, pane: Pane) -> &[Series] {
+        self.panes.get(&pane).map_or(&[], |(series, _)| series)
+    }
+
+    fn bounds(&self, pane: Pane) -> (f64, f64) {
+        self.panes.get(&pane).map_or((-1.0, 1.0), |&(_, bounds)| bounds)
+    }
+}
+
+/// Brings `app.chart_data` up to date for the chart panes in `panes`, if
+/// the history has changed since it was prepared.
+pub fn prepare(app: &mut App, panes: impl IntoIterator<Item = Pane>) {
+    let mut data = mem::take(&mut app.chart_data);
+    let key = (app.history_version, app.lag.chart_step());
+    if data.key != Some(key) {
+        data.key = Some(key);
+        data.time_bounds = global_time_bounds(app);
+        data.panes.clear();
+    }
+    for pane in panes {
+        // Position charts are bounded by the arena, stretched only to fit
+        // history recorded before it shrank
+        let (kind, bounded): (fn(&History) -> &Samples, _) = match pane {
+            Pane::XPosition => (|history| &history.x, false),
+            Pane::YPosition => (|history| &history.y, false),
+            Pane::XVelocity => (|history| &history.vx, true),
+            Pane::YVelocity => (|history| &history.vy, true),
+            Pane::Arena | Pane::Status | Pane::Quiver => continue,
+        };
+        data.panes.entry(pane).or_insert_with(|| {
+            app.profiler.time(Phase::ChartPrep, || {
+                let bounds = if bounded { value_bounds(app, kind) } else { (0.0, highest(app, kind)) };
+                (series(app, kind), bounds)
+            })
+        });
+    }
+    app.chart_data = data;
+}
+
+/// One kind of sample for each ball, paired with its ticks and thinned out
+/// while drawing is lagging.
+fn series(app: &App, kind: fn(&History) -> &Samples) -> Vec<Series> {
+    let step = app.lag.chart_step();
+    app.history
+        .iter()
+        .map(|(&id, history)| {
+            let points = with_ticks(&app.history_ticks, kind(history)).step_by(step).collect();
+            (id, points)
+        })
+        .collect()
+}
+
+fn datasets<'a>(app: &App, series: &'a [Series]) -> Vec<Dataset<'a>> {
+    series
+        .iter()
+        .enumerate()
+        .map(|(i, (id, points))| {
+            Dataset::default()
+                .name(format!("{} B{}", app.ball_glyph(*id), i + 1))
+                .marker(chart_marker(app))
+                .style(Style::default().fg(app.ball_color(*id)))
+                .data(points)
+        })
+        .collect()
+}
+
+// The line marking a time in a focused chart, and the picked ball's value
+// there
+struct Crosshair {
+    line: [(f64, f64); 2],
+    readout: String,
+}
+
+/// The crosshair for `pane` if it has the focus, `app.crosshair` ticks back
+/// from the newest and spanning `y_bounds`.
+fn crosshair(app: &App, pane: Pane, [y_min, y_max]: [f64; 2]) -> Option<Crosshair> {
+    if app.focus != Some(pane) {
+        return None;
+    }
+    let (t_min, t_max) = app.chart_data.time_bounds;
+    let t = (t_max - app.crosshair).max(t_min);
+    let ball = app.picked_ball();
+    let quantity = if matches!(pane, Pane::XPosition | Pane::YPosition) { Quantity::Length } else { Quantity::Speed };
+    let time = units::show_with_symbol(app.units, Quantity::Time, t, 0);
+    let readout = ball
+        .and_then(|ball| {
+            let (_, points) = app.chart_data.series(pane).iter().find(|(id, _)| *id == ball.id)?;
+            let &(_, value) = points.iter().take_while(|&&(at, _)| at <= t).last()?;
+            let value = units::show_with_symbol(app.units, quantity, value, 1);
+            Some(format!(" {} t {}: {} ", app.ball_glyph(ball.id), time, value))
+        })
+        .unwrap_or_else(|| format!(" t {} ", time));
+    Some(Crosshair { line: [(t, y_min), (t, y_max)], readout })
+}
+
+fn with_crosshair<'a>(
+    app: &App,
+    crosshair: &'a Option<Crosshair>,
+    datasets: &mut Vec<Dataset<'a>>,
+    block: Block<'a>,
+) -> Block<'a> {
+    let Some(crosshair) = crosshair else {
+        return block;
+    };
+    datasets.push(
+        Dataset::default()
+            .graph_type(GraphType::Line)
+            .marker(chart_marker(app))
+            .style(Style::default().fg(app.theme.selected))
+            .data(&crosshair.line),
+    );
+    block.title(Line::from(crosshair.readout.as_str()).right_aligned())
+}
+
+pub fn chart_marker(app: &App) -> symbols::Marker {
+    match app.glyphs {
+        _ if app.ascii => symbols::Marker::Dot,
+        Glyphs::HalfBlock => symbols::Marker::HalfBlock,
+        _ => symbols::Marker::Braille,
+    }
+}
+
+fn value_bounds(app: &App, kind: fn(&History) -> &Samples) -> (f64, f64) {
+    let mut v_min = f64::MAX;
+    let mut v_max = f64::MIN;
+
+    for history in app.history.values() {
+        for &v in kind(history) {
+            if v < v_min { v_min = v; }
+            if v > v_max { v_max = v; }
+        }
+    }
+
+    if v_min >= v_max {
+        (-1.0, 1.0)
+    } else {
+        // Add a small margin
+        let margin = (v_max - v_min) * 0.1;
+        (v_min - margin, v_max + margin)
+    }
+}
+
+fn highest(app: &App, kind: fn(&History) -> &Samples) -> f64 {
+    app.history.values().flat_map(|history| kind(history).iter().copied()).fold(0.0, f64::max)
+}
+
+fn global_time_bounds(app: &App) -> (f64, f64) {
+    // From the start of the longest history to the latest tick
+    let longest = app.history.values().map(|history| history.x.len()).max().unwrap_or(0);
+    let ticks = &app.history_ticks[app.history_ticks.len().saturating_sub(longest)..];
+    let t_min = ticks.first().copied().unwrap_or(f64::MAX);
+    let t_max = ticks.last().copied().unwrap_or(f64::MIN);
+
+    if t_min >= t_max {
+        (0.0, 1.0)
+    } else {
+        (t_min, t_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_tui_ball_bounce::Simulation;
+
+    use super::*;
+    use crate::app::AppBuilder;
+
+    #[test]
+    fn points_are_prepared_again_only_when_the_history_changes() {
+        let mut app = AppBuilder::new(Simulation::new(80.0, 20.0)).build();
+        let record = |app: &mut App, t: f64, vx: f64| {
+            app.history_ticks.push(t, 10);
+            app.history.entry(0).or_default().record((0.0, 0.0), (vx, 0.0), 10);
+        };
+        record(&mut app, 1.0, 0.5);
+        app.history_version += 1;
+        prepare(&mut app, [Pane::XVelocity]);
+        assert_eq!(app.chart_data.series(Pane::XVelocity)[0].1, [(1.0, 0.5)]);
+
+        // Unchanged as far as the version says, as when only the window is
+        record(&mut app, 2.0, 0.7);
+        prepare(&mut app, [Pane::XVelocity, Pane::Arena]);
+        assert_eq!(app.chart_data.series(Pane::XVelocity)[0].1.len(), 1);
+
+        app.history_version += 1;
+        prepare(&mut app, [Pane::XVelocity]);
+        assert_eq!(app.chart_data.series(Pane::XVelocity)[0].1, [(1.0, 0.5), (2.0, 0.7)]);
+        assert_eq!(app.chart_data.time_bounds, (1.0, 2.0));
+    }
+}
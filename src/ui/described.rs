@@ -0,0 +1,29 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::app::App;
+use crate::keys::Action;
+
+/// The [described view](crate::describe): the latest description as plain
+/// lines, with no borders or symbols for a screen reader to read out.
+pub fn draw(buf: &mut Buffer, app: &App, area: Rect) {
+    let mut lines: Vec<Line> = app.description.lines.iter().map(|line| Line::raw(line.as_str())).collect();
+    lines.push(Line::raw(""));
+    if let Some(notice) = &app.notice {
+        lines.push(Line::raw(notice.as_str()));
+    }
+    lines.push(Line::raw(format!(
+        "Press {} for the panes, or {} for help.",
+        app.keymap.label(Action::Described, app.ascii),
+        app.keymap.label(Action::Help, app.ascii)
+    )));
+    Paragraph::new(lines)
+        .style(Style::default().fg(app.theme.label))
+        .wrap(Wrap { trim: false })
+        .render(area, buf);
+}
@@ -0,0 +1,764 @@
+mod arena;
+mod cache;
+mod charts;
+mod described;
+mod overlay;
+mod status;
+
+use std::mem;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::Line,
+    widgets::{Block, Borders},
+};
+
+use crate::app::App;
+use crate::caps::ColorDepth;
+use crate::layout::Pane;
+use crate::mode::Mode;
+
+pub use arena::FIELD_SPACING;
+pub use cache::PaneCache;
+pub use charts::ChartData;
+pub use rust_tui_ball_bounce::widget::{ASCII_BALL_CHARS, BALL_CHARS};
+
+/// The smallest terminal the panes are drawn in; below it they'd overlap
+/// and the popups wouldn't fit, so a message asks for more room instead.
+pub const MIN_SIZE: (u16, u16) = (40, 12);
+
+/// Terminals narrower or shorter than this get the compact layout: the
+/// arena, one chart and a one-line status bar rather than the six panes.
+pub const COMPACT_BELOW: (u16, u16) = (100, 30);
+
+pub const ASCII_BORDER: symbols::border::Set = symbols::border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+pub fn draw(f: &mut ratatui::Frame, app: &mut App) {
+    // The described view is only text, which wraps to any size. The arena
+    // keeps its last size meanwhile, and takes the new one once there's room.
+    let screen = f.area();
+    if !app.described && (screen.width < MIN_SIZE.0 || screen.height < MIN_SIZE.1) {
+        overlay::draw_too_small(f, app);
+        return;
+    }
+    // The layout editor always shows the grid it edits
+    app.compact = !matches!(app.mode, Mode::LayoutEdit(_))
+        && (screen.width < COMPACT_BELOW.0 || screen.height < COMPACT_BELOW.1);
+    let (slots, bar) = if app.compact {
+        let (slots, bar) = compact_split(app, screen);
+        (slots.to_vec(), Some(bar))
+    } else {
+        (app.layout.split(screen).to_vec(), None)
+    };
+
+    for &(pane, area) in &slots {
+        if pane == Pane::Arena {
+            // The first size is the one the balls were placed for, so only
+            // later ones are resizes to rescale for
+            let resized = app.arena_area != Rect::default();
+            let (area, _) = arena::split(area, app.comparison.is_some());
+            app.arena_area = area.inner(Margin::new(1, 1));
+            let inner_width = if area.width > 2 { area.width - 2 } else { 1 };
+            let inner_height = if area.height > 2 { area.height - 2 } else { 1 };
+            let width = inner_width as f64;
+            let height = inner_height as f64 * app.state.aspect;
+            if (width, height) != (app.state.area_width, app.state.area_height) {
+                app.state.area_width = width;
+                app.state.area_height = height;
+                let rescale = resized && app.rescale_on_resize;
+                app.sim.resize(width, height, rescale);
+            }
+        }
+    }
+
+    if app.described {
+        // The arena keeps the size its pane would have, so the simulation
+        // doesn't change under the text
+        let area = f.area();
+        described::draw(f.buffer_mut(), app, area);
+    } else {
+        charts::prepare(app, slots.iter().map(|&(pane, _)| pane));
+        let mut cache = mem::take(&mut app.pane_cache);
+        for &(pane, area) in &slots {
+            let app = &*app;
+            cache.draw(pane, area, cache::key(app, pane), f.buffer_mut(), |buf| match pane {
+                Pane::Arena => arena::draw(buf, app, area),
+                Pane::Status => status::draw(buf, app, area),
+                Pane::XPosition => charts::draw_x_graph(buf, app, area),
+                Pane::YPosition => charts::draw_y_graph(buf, app, area),
+                Pane::XVelocity => charts::draw_vx_graph(buf, app, area),
+                Pane::YVelocity => charts::draw_vy_graph(buf, app, area),
+                Pane::Quiver => charts::draw_quiver(buf, app, area),
+            });
+        }
+        app.pane_cache = cache;
+        if let Some(bar) = bar {
+            status::draw_bar(f.buffer_mut(), app, bar);
+        }
+        if let Some(&(_, area)) = slots.iter().find(|&&(pane, _)| Some(pane) == app.focus) {
+            highlight_border(f.buffer_mut(), area, Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD));
+        }
+    }
+
+    if let Some(game) = app.game.as_ref().filter(|game| game.over() && app.mode.is_base()) {
+        overlay::draw_scores(f, app, game);
+    }
+    if let Some(twin) = app.twin.as_ref().filter(|_| !app.described) {
+        overlay::draw_twin(f, app, twin);
+    }
+    if let Some(comparison) = app.comparison.as_ref().filter(|_| !app.described) {
+        overlay::draw_comparison(f, app, comparison);
+    }
+    if app.show_zoom && !app.described {
+        overlay::draw_zoom(f, app);
+    }
+    if let Some((name, description)) = app.caption().filter(|_| !app.described && app.mode.is_base()) {
+        overlay::draw_caption(f, app, name, description);
+    }
+    if app.show_profiler {
+        overlay::draw_profiler(f, app);
+    }
+
+    match &app.mode {
+        Mode::Running | Mode::Paused | Mode::ObstacleEdit(_) => {}
+        Mode::Help => overlay::draw_help(f, app),
+        Mode::Sweep => overlay::draw_sweep(f, app),
+        Mode::Menu(picker) => overlay::draw_menu(f, app, picker),
+        Mode::CommandPalette(palette) => overlay::draw_palette(f, app, palette),
+        Mode::Settings(picker) => overlay::draw_settings(f, app, picker),
+        Mode::ScenarioPicker(picker) => overlay::draw_scenario_picker(f, app, picker),
+        Mode::ProfilePicker(picker) => overlay::draw_profile_picker(f, app, picker),
+        Mode::ThemePicker(themes) => overlay::draw_theme_picker(f, app, themes),
+        Mode::LayoutEdit(edit) => overlay::draw_layout_edit(f, app, edit, &slots),
+        Mode::Restyle(restyle) => overlay::draw_restyle(f, app, restyle),
+        Mode::Flock(flock) => overlay::draw_flock(f, app, flock),
+        Mode::Splash(splash) => overlay::draw_splash(f, app, splash),
+        Mode::Replay(replay) if !app.described => overlay::draw_timeline(f, app, replay),
+        Mode::Replay(_) => {}
+    }
+
+    if !app.color || app.theme.monochrome {
+        // Keep bold/reversed modifiers so emphasis survives without color,
+        // and stand in for the colors that mark the brightest and dimmest
+        // text, and a ball's own color
+        for cell in f.buffer_mut().content.iter_mut() {
+            match cell.fg {
+                Color::White | Color::Rgb(..) => cell.modifier.insert(Modifier::BOLD),
+                Color::DarkGray => cell.modifier.insert(Modifier::DIM),
+                _ => {}
+            }
+            cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        }
+    } else if app.colors < ColorDepth::TrueColor {
+        // Ball colors can be any RGB; the terminal gets the nearest it has
+        for cell in f.buffer_mut().content.iter_mut() {
+            cell.fg = app.colors.fit(cell.fg);
+            cell.bg = app.colors.fit(cell.bg);
+        }
+    }
+}
+
+/// The arena over one chart, with the status bar along the bottom. The chart
+/// is the focused one, so Tab pages through them, or else the first in the
+/// layout.
+fn compact_split(app: &App, area: Rect) -> ([(Pane, Rect); 2], Rect) {
+    let [panes, bar] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+    let [arena, chart] = Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(panes);
+    let pane = app.compact_chart();
+    ([(Pane::Arena, arena), (pane, chart)], bar)
+}
+
+/// Restyles the border around `area`, title and all, to mark the focused
+/// pane.
+fn highlight_border(buf: &mut Buffer, area: Rect, style: Style) {
+    let area = area.intersection(buf.area);
+    if area.is_empty() {
+        return;
+    }
+    let (right, bottom) = (area.right() - 1, area.bottom() - 1);
+    for x in area.left()..area.right() {
+        buf[(x, area.top())].set_style(style);
+        buf[(x, bottom)].set_style(style);
+    }
+    for y in area.top()..area.bottom() {
+        buf[(area.left(), y)].set_style(style);
+        buf[(right, y)].set_style(style);
+    }
+}
+
+pub fn pane_block<'a>(app: &App, title: impl Into<Line<'a>>, color: Color) -> Block<'a> {
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+    if app.ascii {
+        block.border_set(ASCII_BORDER)
+    } else {
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use insta::assert_snapshot;
+    use ratatui::buffer::Cell;
+    use ratatui::style::{Color, Modifier};
+    use ratatui::{backend::TestBackend, Terminal};
+    use rust_tui_ball_bounce::components::Glyph;
+    use rust_tui_ball_bounce::force::{Attractor, Boids, Direction};
+    use rust_tui_ball_bounce::scenario::Obstacle;
+    use rust_tui_ball_bounce::{scenario, BallSpec, Command, Simulation, SimulationBuilder};
+
+    use super::{draw, status, FIELD_SPACING, MIN_SIZE};
+    use crate::app::{sim_builder, App, AppBuilder};
+    use crate::caps::ColorDepth;
+    use crate::compare::{self, Change, Comparison};
+    use crate::config::Config;
+    use crate::export::Format;
+    use crate::input::{handle_key, handle_mouse};
+    use crate::layout::{LayoutEdit, Pane};
+    use crate::mode::{Mode, BALL_PALETTE};
+    use crate::paint::Paint;
+    use crate::replay::{self, Replay};
+    use crate::sim_thread::Update;
+    use crate::splash::Splash;
+    use crate::sweep::{Metric, Parameter, Plan, Sweep};
+    use crate::theme::THEMES;
+    use crate::twin::{self, Twin};
+
+    const SIZE: (u16, u16) = (120, 40);
+
+    fn render(app: &mut App, width: u16, height: u16) -> TestBackend {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        terminal.backend().clone()
+    }
+
+    /// An app showing `ticks` of a simulation run here rather than on its
+    /// thread, so every frame comes out the same.
+    fn app_after(config: &Config, ticks: usize, size: (u16, u16)) -> App {
+        app_running(sim_builder(config), config, ticks, size)
+    }
+
+    fn app_running(builder: SimulationBuilder, config: &Config, ticks: usize, (width, height): (u16, u16)) -> App {
+        let mut sim = builder.build();
+        let mut app = AppBuilder::new(Simulation::new(80.0, 20.0)).config(config).build();
+        app.state = sim.snapshot();
+        // Fit the arena to the terminal before anything moves
+        render(&mut app, width, height);
+        sim.area_width = app.state.area_width;
+        sim.area_height = app.state.area_height;
+        for _ in 0..ticks {
+            sim.tick();
+            let events = sim.drain_events();
+            app.receive(Update { snapshot: sim.snapshot(), events, step_times: None, twin: None, other: None });
+        }
+        app
+    }
+
+    fn config_with_balls(balls: usize) -> Config {
+        let mut config = Config::default();
+        config.simulation.balls = balls;
+        // Rather than whatever the environment's locale is
+        config.display.language = "en".to_string();
+        config
+    }
+
+    #[test]
+    fn default_layout() {
+        let mut app = app_after(&config_with_balls(3), 120, SIZE);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn ascii_paused() {
+        let mut config = config_with_balls(6);
+        config.display.ascii = true;
+        let mut app = app_after(&config, 60, SIZE);
+        app.state.paused = true;
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn spanish() {
+        let mut config = config_with_balls(3);
+        config.display.language = "es".to_string();
+        let mut app = app_after(&config, 60, SIZE);
+        app.state.paused = true;
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn layout_edit() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        app.mode = Mode::LayoutEdit(LayoutEdit::new());
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn quiver_in_place_of_a_chart() {
+        let mut app = app_after(&config_with_balls(3), 30, SIZE);
+        // The bottom right slot, holding Y Velocity, cycles past the charts
+        // already shown
+        for code in [KeyCode::Char('L'), KeyCode::Down, KeyCode::Down, KeyCode::Right, KeyCode::Char('c'), KeyCode::Esc] {
+            handle_key(&mut app, code);
+        }
+        assert_eq!(app.layout.panes[5], Pane::Quiver);
+        app.layout.cycle_chart(5);
+        assert_eq!(app.layout.panes[5], Pane::YVelocity);
+        app.layout.cycle_chart(0);
+        assert_eq!(app.layout.panes[0], Pane::Arena);
+        app.layout.cycle_chart(5);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn scenario_picker() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        app.mode = app.scenario_picker();
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn demo_caption() {
+        let config = config_with_balls(0);
+        let pi = scenario::builtin("pi-100").unwrap();
+        let mut app = app_running(sim_builder(&config).scenario(&pi), &config, 400, SIZE);
+        assert_eq!(app.state.clicks, 31);
+        // Loading it from the picker puts its description over the arena
+        app.mode = app.scenario_picker();
+        for _ in 0..7 {
+            handle_key(&mut app, KeyCode::Down);
+        }
+        handle_key(&mut app, KeyCode::Enter);
+        assert_eq!(app.caption(), Some((pi.name.as_str(), pi.description.as_str())));
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+        // for a few seconds
+        app.state.tick_count = 1000;
+        assert_eq!(app.caption(), None);
+    }
+
+    #[test]
+    fn profile_picker() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        app.mode = app.profile_picker();
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn menu() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        app.mode = app.menu();
+        handle_key(&mut app, KeyCode::Down);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn help() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        app.mode = Mode::Help;
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn command_palette() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        for c in ":s".chars() {
+            handle_key(&mut app, KeyCode::Char(c));
+        }
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn settings() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        for code in [KeyCode::Char('o'), KeyCode::Right] {
+            handle_key(&mut app, code);
+        }
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn theme_picker_previews_until_esc() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        for code in [KeyCode::Char('t'), KeyCode::Down] {
+            handle_key(&mut app, code);
+        }
+        assert_eq!(app.theme.name, THEMES[1].name);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+        handle_key(&mut app, KeyCode::Esc);
+        assert_eq!(app.theme.name, THEMES[0].name);
+        assert!(app.mode.is_base());
+    }
+
+    #[test]
+    fn paint_stains_the_balls_paths() {
+        let mut config = config_with_balls(2);
+        config.display.paint = true;
+        let mut app = app_after(&config, 30, SIZE);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+        handle_key(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.paint.as_ref().map(Paint::len), Some(0));
+        handle_key(&mut app, KeyCode::Char('b'));
+        assert!(app.paint.is_none());
+    }
+
+    #[test]
+    fn flock_weights_are_sent_as_they_change() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        for code in [KeyCode::Char('f'), KeyCode::Right, KeyCode::Down, KeyCode::Right, KeyCode::Right] {
+            handle_key(&mut app, code);
+        }
+        let sent = |app: &App| match app.commands.last() {
+            Some(&Command::SetBoids(boids)) => boids,
+            _ => panic!("no boids sent"),
+        };
+        assert_eq!(sent(&app), Some(Boids { separation: 1.5, ..Boids::default() }));
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+        handle_key(&mut app, KeyCode::Up);
+        handle_key(&mut app, KeyCode::Left);
+        assert_eq!(sent(&app), None);
+        handle_key(&mut app, KeyCode::Esc);
+        assert!(app.mode.is_base());
+    }
+
+    #[test]
+    fn gravity_turns_round_the_walls_and_the_center() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        handle_key(&mut app, KeyCode::Char('g'));
+        assert!(matches!(app.commands.last(), Some(&Command::SetGravityDirection(Direction::Left))));
+        assert!(app.notice.as_deref().is_some_and(|notice| notice.contains("set gravity")));
+        // As the simulation would send back, with some gravity set
+        (app.state.gravity, app.state.gravity_direction) = (0.05, Direction::Right);
+        handle_key(&mut app, KeyCode::Char('g'));
+        assert!(matches!(app.commands.last(), Some(&Command::SetGravityDirection(Direction::Center))));
+        assert_eq!(app.notice.as_deref(), Some("Gravity: center"));
+    }
+
+    #[test]
+    fn restyle_picker() {
+        let mut app = app_after(&config_with_balls(3), 30, SIZE);
+        // Enter on the controls panel's second ball, then the first color and
+        // the last glyph
+        for code in [KeyCode::Tab, KeyCode::Tab, KeyCode::Down, KeyCode::Enter, KeyCode::Right, KeyCode::Down, KeyCode::Left] {
+            handle_key(&mut app, code);
+        }
+        let Mode::Restyle(restyle) = &app.mode else {
+            panic!("not restyling");
+        };
+        assert_eq!((restyle.id, restyle.color, restyle.glyph), (app.state.balls[1].id, Some(BALL_PALETTE[0].1), Some(Glyph(7))));
+        // As the simulation would send back
+        (app.state.balls[1].color, app.state.balls[1].glyph) = (restyle.color, restyle.glyph);
+        assert_eq!(app.ball_glyph(app.state.balls[1].id), "★");
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn starfield_backdrop() {
+        let mut config = config_with_balls(3);
+        config.display.background = "stars".to_string();
+        let mut app = app_after(&config, 30, SIZE);
+        let backend = render(&mut app, SIZE.0, SIZE.1);
+        let arena = app.arena_area;
+        let stars = (arena.top()..arena.bottom())
+            .flat_map(|y| (arena.left()..arena.right()).map(move |x| (x, y)))
+            .filter(|&(x, y)| backend.buffer()[(x, y)].symbol() == "·")
+            .count();
+        assert!(stars > 10, "{}", stars);
+        assert_snapshot!(backend);
+
+        // The gradient deepens downwards, behind the balls too
+        app.backdrop = app.backdrop.cycle(1);
+        app.colors = ColorDepth::TrueColor;
+        let backend = render(&mut app, SIZE.0, SIZE.1);
+        let bg = |y: u16| backend.buffer()[(arena.x, y)].bg;
+        assert!(matches!((bg(arena.top()), bg(arena.bottom() - 1)), (Color::Rgb(_, top, _), Color::Rgb(_, bottom, _)) if top < bottom));
+        let ball = &app.state.balls[0];
+        let (x, y) = (arena.x + ball.x.round() as u16, arena.y + (ball.y / app.state.aspect).round() as u16);
+        assert!(matches!(backend.buffer()[(x, y)].bg, Color::Rgb(..)));
+    }
+
+    #[test]
+    fn splash() {
+        let mut app = app_after(&config_with_balls(3), 0, SIZE);
+        let mut splash = Splash::new(SIZE.0, SIZE.1, false, Mode::Paused);
+        // A second or so in, frame by frame
+        for _ in 0..70 {
+            splash.advance(Duration::from_millis(16));
+        }
+        app.mode = Mode::Splash(Box::new(splash));
+        let backend = render(&mut app, SIZE.0, SIZE.1);
+        let version = format!("v{}", env!("CARGO_PKG_VERSION"));
+        assert_snapshot!(backend.to_string().replace(&version, "v0.0.0"));
+
+        // Any key goes on to what comes next
+        handle_key(&mut app, KeyCode::Char('q'));
+        assert!(matches!(app.mode, Mode::Paused));
+    }
+
+    #[test]
+    fn focused_chart() {
+        let mut app = app_after(&config_with_balls(3), 120, SIZE);
+        // Past the arena and the controls to the X chart, then back a while
+        // and onto the second ball
+        for code in [KeyCode::Tab, KeyCode::Tab, KeyCode::Tab, KeyCode::Left, KeyCode::Left, KeyCode::Down] {
+            handle_key(&mut app, code);
+        }
+        assert_eq!(app.focus, Some(Pane::XPosition));
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn force_field() {
+        let mut app = app_after(&config_with_balls(3), 60, SIZE);
+        handle_key(&mut app, KeyCode::Char('v'));
+        assert!(app.show_field);
+        // The field a simulation on its thread would send back
+        let (width, height) = (app.state.area_width, app.state.area_height);
+        let mut sim = Simulation::new(width, height);
+        sim.add_force(Attractor { x: width / 2.0, y: height / 2.0, strength: 40.0 });
+        sim.field_spacing = Some((FIELD_SPACING.0, FIELD_SPACING.1 * app.state.aspect));
+        app.state.field = sim.snapshot().field;
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn zoom_inset() {
+        let mut app = app_after(&config_with_balls(3), 60, SIZE);
+        handle_key(&mut app, KeyCode::Char('z'));
+        // The second ball about to hit the first, by a wall
+        app.ball_cursor = 1;
+        let (x, y) = (app.state.area_width / 2.0, app.state.area_height / 2.0);
+        (app.state.balls[0].x, app.state.balls[0].y) = (x - 0.6, y);
+        (app.state.balls[1].x, app.state.balls[1].y) = (x + 0.6, y);
+        app.state.obstacles = vec![Obstacle { x: x + 2.0, y: y - 2.0, width: 1.0, height: 4.0 }];
+        let backend = render(&mut app, SIZE.0, SIZE.1);
+        let glyph = app.ball_glyph(app.state.balls[1].id);
+        let arena = app.arena_area;
+        let inset = (arena.y..arena.y + 12).flat_map(|y| (arena.right() - 28..arena.right()).map(move |x| (x, y)));
+        let cells = inset.filter(|&(x, y)| backend.buffer()[(x, y)].symbol() == glyph).count();
+        assert!(cells > 4, "{}", cells);
+        assert_snapshot!(backend);
+    }
+
+    #[test]
+    fn si_units() {
+        let mut config = config_with_balls(3);
+        config.simulation.meters_per_cell = 0.1;
+        let mut app = app_after(&config, 120, SIZE);
+        for code in [KeyCode::Tab, KeyCode::Tab, KeyCode::Tab] {
+            handle_key(&mut app, code);
+        }
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn gas_statistics() {
+        let config = config_with_balls(0);
+        let gas = sim_builder(&config).scenario(&scenario::builtin("gas").unwrap());
+        let mut app = app_running(gas, &config, 200, SIZE);
+        let readouts = app.gas_stats.readouts(&app.state);
+        // Near theory, if a little longer for the contacts steps miss
+        let ratio = readouts.free_path.measured.unwrap() / readouts.free_path.theory.unwrap();
+        assert!((0.8..1.6).contains(&ratio), "{}", ratio);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn collision_readout() {
+        let config = config_with_balls(0);
+        let head_on = sim_builder(&config).scenario(&scenario::builtin("head-on").unwrap());
+        let mut app = app_running(head_on, &config, 200, SIZE);
+        assert!(app.state.collisions > 0);
+        // Only shown while stepping
+        assert!(status::collision_lines(&app).is_empty());
+        app.state.paused = true;
+        let lines = status::collision_lines(&app);
+        assert_eq!(lines[0], ("Hit:".to_string(), "1 & 2".to_string()));
+        assert_eq!(lines[1], ("px:".to_string(), "0.000 → 0.000".to_string()));
+        assert_eq!(lines[3].0, "KE:");
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn twin_divergence() {
+        let config = config_with_balls(5);
+        let mut app = app_after(&config, 0, SIZE);
+        let mut sim = sim_builder(&config).build();
+        (sim.area_width, sim.area_height) = (app.state.area_width, app.state.area_height);
+        let mut twin = twin::nudged(&sim, 1e-6).unwrap();
+        app.twin = Some(Twin::new(1e-6));
+        for _ in 0..400 {
+            sim.tick();
+            twin.tick();
+            let events = sim.drain_events();
+            app.receive(Update { snapshot: sim.snapshot(), events, step_times: None, twin: Some(twin.state().to_vec()), other: None });
+        }
+        let gap = app.twin.as_ref().and_then(Twin::gap).unwrap();
+        assert!(gap > 1e-6, "{}", gap);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn side_by_side_comparison() {
+        let config = config_with_balls(4);
+        let mut app = app_after(&config, 0, SIZE);
+        app.comparison = Some(Comparison::new(Change::Set(Parameter::Gravity, 0.05), compare::Metric::Energy));
+        // The arena halves, for both simulations
+        render(&mut app, SIZE.0, SIZE.1);
+        let mut sim = sim_builder(&config).build();
+        (sim.area_width, sim.area_height) = (app.state.area_width, app.state.area_height);
+        assert!(sim.area_width < 60.0);
+        let mut other = compare::with_parameter(&sim, Parameter::Gravity, 0.05).unwrap();
+        for _ in 0..200 {
+            sim.tick();
+            other.step(1.0);
+            let events = sim.drain_events();
+            app.receive(Update { snapshot: sim.snapshot(), events, step_times: None, twin: None, other: Some(other.snapshot()) });
+        }
+        let [mine, theirs] = &app.comparison.as_ref().unwrap().series;
+        assert_eq!(mine.len(), 200);
+        assert_ne!(mine.last(), theirs.last());
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn parameter_sweep() {
+        let mut sim = Simulation::new(80.0, 20.0);
+        sim.add_ball(BallSpec::new(10.0, 10.0, 0.8, 0.0));
+        let mut app = AppBuilder::new(sim).config(&config_with_balls(0)).build();
+        let plan = Plan { parameter: Parameter::Friction, from: 0.0, to: 0.01, metric: Metric::Settle };
+        app.sweep = Some(Sweep::start(&app.sim, plan));
+        app.mode = Mode::Sweep;
+        let sweep = app.sweep.as_mut().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while !sweep.done() && Instant::now() < deadline {
+            sweep.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(sweep.done());
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn replay_timeline() {
+        let mut app = app_after(&config_with_balls(4), 120, SIZE);
+        let path = std::env::temp_dir().join(format!("ball-bounce-replay-{}.json", std::process::id()));
+        app.export(Some(path.clone())).unwrap();
+        let json = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let replay = Replay::read(&json[..], Format::Json, "run.json".to_string()).unwrap();
+        app.mode = app.replay(replay);
+        let tick = |app: &App| match &app.mode {
+            Mode::Replay(replay) => replay.frame().tick_count,
+            _ => unreachable!(),
+        };
+        assert_eq!(tick(&app), 1);
+        handle_key(&mut app, KeyCode::End);
+        handle_key(&mut app, KeyCode::Left);
+        assert_eq!(tick(&app), 119);
+
+        // A click halfway along the track goes halfway through
+        let track = replay::track_area(replay::timeline_area(app.arena_area).unwrap());
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: track.x + (track.width - 1) / 2,
+            row: track.y,
+            modifiers: KeyModifiers::NONE,
+        };
+        handle_mouse(&mut app, click);
+        assert!((59..=61).contains(&tick(&app)), "{}", tick(&app));
+        // The charts show the recording until the replay is left
+        assert_eq!(app.history_ticks.len(), 120);
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+        handle_key(&mut app, KeyCode::Esc);
+        assert!(app.history_ticks.is_empty());
+    }
+
+    #[test]
+    fn energy_bars() {
+        let mut app = app_after(&config_with_balls(3), 60, SIZE);
+        // Under gravity the same balls have potential energy too, more the
+        // higher they are
+        app.state.gravity = 0.05;
+        let backend = render(&mut app, SIZE.0, SIZE.1);
+        let potential = |cell: &Cell| cell.symbol() == "█" && cell.fg == app.theme.y_border;
+        assert!(backend.buffer().content.iter().any(potential));
+        assert_snapshot!(backend);
+    }
+
+    #[test]
+    fn status_panel_sections_in_config_order() {
+        let mut config = config_with_balls(0);
+        config.display.status_panel = ["energy", "rate", "speed"].map(String::from).to_vec();
+        let head_on = sim_builder(&config).scenario(&scenario::builtin("head-on").unwrap());
+        let mut app = app_running(head_on, &config, 200, SIZE);
+        assert_eq!(status::hit_rate(&app), Some("0.6".to_string()));
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn compact_layout() {
+        let mut app = app_after(&config_with_balls(3), 60, (80, 24));
+        // The arena, then each chart in turn, skipping the controls panel
+        for _ in 0..3 {
+            handle_key(&mut app, KeyCode::Tab);
+        }
+        assert_eq!(app.focus, Some(Pane::YPosition));
+        assert_snapshot!(render(&mut app, 80, 24));
+    }
+
+    #[test]
+    fn obstacle_edit() {
+        let mut app = app_after(&config_with_balls(2), 30, SIZE);
+        handle_key(&mut app, KeyCode::Char('w'));
+        for code in [KeyCode::Enter, KeyCode::Right, KeyCode::Enter, KeyCode::Down] {
+            handle_key(&mut app, code);
+        }
+        assert_snapshot!(render(&mut app, SIZE.0, SIZE.1));
+    }
+
+    #[test]
+    fn small_terminal() {
+        let mut app = app_after(&config_with_balls(4), 60, MIN_SIZE);
+        assert_snapshot!(render(&mut app, MIN_SIZE.0, MIN_SIZE.1));
+    }
+
+    #[test]
+    fn too_small_terminal() {
+        let mut app = app_after(&config_with_balls(4), 60, SIZE);
+        let arena = (app.state.area_width, app.state.area_height);
+        assert_snapshot!(render(&mut app, MIN_SIZE.0 - 1, MIN_SIZE.1 - 1));
+        assert_eq!((app.state.area_width, app.state.area_height), arena);
+    }
+
+    #[test]
+    fn monochrome_marks_with_modifiers_instead() {
+        let mut config = config_with_balls(2);
+        config.display.theme = "monochrome".to_string();
+        let mut app = app_after(&config, 30, SIZE);
+        app.mode = app.menu();
+        let backend = render(&mut app, SIZE.0, SIZE.1);
+        let cells = &backend.buffer().content;
+        assert!(cells.iter().all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+        // The highlighted entry, and the muted text
+        assert!(cells.iter().any(|cell| cell.symbol() == "▶" && cell.modifier.contains(Modifier::REVERSED | Modifier::BOLD)));
+        assert!(cells.iter().any(|cell| cell.modifier.contains(Modifier::DIM)));
+    }
+}
@@ -0,0 +1,659 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Axis, Chart, Clear, Dataset, GraphType, List, ListItem, ListState, Paragraph},
+};
+use rust_tui_ball_bounce::scenario::{self, Source};
+use rust_tui_ball_bounce::widget::BallArenaWidget;
+use rust_tui_ball_bounce::BallState;
+
+use crate::app::App;
+use crate::compare::Comparison;
+use crate::game::Game;
+use crate::keys::Action;
+use crate::layout::{LayoutEdit, Pane};
+use crate::mode::{Flock, Look, MenuEntry, Palette, Restyle, Rule, Setting, ThemePicker};
+use crate::palette;
+use crate::picker::Picker;
+use crate::profiler::Phase;
+use crate::replay::{self, Replay};
+use crate::splash::{self, Splash};
+use crate::sweep::{Metric, RUNS};
+use crate::twin::Twin;
+use crate::ui::{charts, pane_block, ASCII_BALL_CHARS, BALL_CHARS, MIN_SIZE};
+use crate::units::{self, Quantity};
+
+/// How many times the zoom inset magnifies.
+pub const ZOOM: u16 = 3;
+
+pub fn draw_layout_edit(
+    f: &mut ratatui::Frame,
+    app: &App,
+    edit: &LayoutEdit,
+    slots: &[(Pane, Rect)],
+) {
+    let (swap_mark, cursor_mark) = if app.ascii { ("<>", ">") } else { ("⇄", "▶") };
+
+    if let Some(picked) = edit.picked {
+        let (pane, area) = slots[picked];
+        let block = pane_block(app, format!(" {} {} ", swap_mark, pane.title()), app.theme.picked)
+            .border_style(Style::default().fg(app.theme.picked).add_modifier(Modifier::BOLD));
+        f.render_widget(block, area);
+    }
+
+    let (pane, area) = slots[edit.cursor];
+    let block = pane_block(app, format!(" {} {} ", cursor_mark, pane.title()), app.theme.selected)
+        .border_style(Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD));
+    let block = if app.ascii {
+        block
+    } else {
+        block.border_type(ratatui::widgets::BorderType::Thick)
+    };
+    f.render_widget(block, area);
+}
+
+pub fn draw_scenario_picker(f: &mut ratatui::Frame, app: &App, picker: &Picker<scenario::Entry>) {
+    let items = picker
+        .entries
+        .iter()
+        .map(|entry| {
+            let origin = match &entry.source {
+                Source::Builtin(slug) => format!("  ({})", slug),
+                Source::File(path) => format!("  ({})", path.file_name().unwrap_or_default().to_string_lossy()),
+            };
+            Line::from(vec![
+                Span::styled(entry.scenario.name.clone(), Style::default().fg(app.theme.label)),
+                Span::styled(origin, Style::default().fg(app.theme.muted)),
+            ])
+        })
+        .collect();
+    let description = picker.current().map_or("", |entry| entry.scenario.description.as_str());
+    draw_picker_popup(f, app, " Load Scenario  [Enter] load  [Esc] cancel ", items, picker.selected, description);
+}
+
+pub fn draw_menu(f: &mut ratatui::Frame, app: &App, picker: &Picker<MenuEntry>) {
+    let items = picker
+        .entries
+        .iter()
+        .map(|entry| {
+            let (name, kind) = match entry {
+                MenuEntry::Sandbox => ("Sandbox", "  (free play)"),
+                MenuEntry::Game(game) => (game.title(), "  (game)"),
+                MenuEntry::Demo(entry) => (entry.scenario.name.as_str(), "  (demo)"),
+            };
+            Line::from(vec![
+                Span::styled(name.to_string(), Style::default().fg(app.theme.label)),
+                Span::styled(kind, Style::default().fg(app.theme.muted)),
+            ])
+        })
+        .collect();
+    let description = match picker.current() {
+        Some(MenuEntry::Sandbox) => "Add and remove balls, draw walls and change the physics as you like, starting from your config.",
+        Some(MenuEntry::Game(game)) => game.description(),
+        Some(MenuEntry::Demo(entry)) => entry.scenario.description.as_str(),
+        None => "",
+    };
+    draw_picker_popup(f, app, " Ball Bounce  [Enter] start  [Esc] close ", items, picker.selected, description);
+}
+
+pub fn draw_profile_picker(f: &mut ratatui::Frame, app: &App, picker: &Picker<Option<String>>) {
+    let items = picker
+        .entries
+        .iter()
+        .map(|name| {
+            let active = if *name == app.profile { "  (active)" } else { "" };
+            Line::from(vec![
+                Span::styled(name.as_deref().unwrap_or("(none)").to_string(), Style::default().fg(app.theme.label)),
+                Span::styled(active, Style::default().fg(app.theme.muted)),
+            ])
+        })
+        .collect();
+    let description = match picker.current() {
+        Some(None) => "Config file settings without a profile.",
+        _ => "Restarts the simulation with this profile's theme, physics defaults, and layout.",
+    };
+    draw_picker_popup(f, app, " Profile  [Enter] apply  [Esc] cancel ", items, picker.selected, description);
+}
+
+pub fn draw_theme_picker(f: &mut ratatui::Frame, app: &App, themes: &ThemePicker) {
+    let glyph = if app.ascii { ASCII_BALL_CHARS[0] } else { BALL_CHARS[0] };
+    let items = themes
+        .picker
+        .entries
+        .iter()
+        .map(|theme| {
+            // A swatch of the theme's ball colors
+            let mut spans = vec![Span::styled(format!("{:<14}", theme.name), Style::default().fg(app.theme.label))];
+            spans.extend(theme.balls.iter().map(|&color| Span::styled(format!(" {}", glyph), Style::default().fg(color))));
+            Line::from(spans)
+        })
+        .collect();
+    let description = format!(
+        "Shown as you move. Enter keeps it and saves it to the config file; Esc goes back to {}.",
+        themes.original.name
+    );
+    draw_picker_popup(f, app, " Theme  [Enter] keep  [Esc] cancel ", items, themes.picker.selected, &description);
+}
+
+pub fn draw_help(f: &mut ratatui::Frame, app: &App) {
+    let key = Style::default().fg(app.theme.label);
+    let muted = Style::default().fg(app.theme.muted);
+    let strings = app.strings;
+    let mut lines = vec![Line::from(Span::styled(strings.keys, key.add_modifier(Modifier::BOLD)))];
+    for &(action, description) in strings.help {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<14}", app.keymap.label(action, app.ascii)), key),
+            Span::styled(description, muted),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(strings.commands, key.add_modifier(Modifier::BOLD))));
+    for (usage, description) in palette::COMMANDS {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<22}", usage), key),
+            Span::styled(*description, muted),
+        ]));
+    }
+
+    let area = popup_area(f.area(), 64, lines.len() as u16 + 2);
+    f.render_widget(Clear, area);
+    let block = pane_block(app, strings.help_title, app.theme.status_border);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// The last parameter sweep's metric against the values it ran, over the
+/// middle of the screen, filling in as runs finish.
+pub fn draw_sweep(f: &mut ratatui::Frame, app: &App) {
+    let Some(sweep) = &app.sweep else {
+        return;
+    };
+    let plan = sweep.plan;
+    let points: Vec<(f64, f64)> = sweep.results.iter().filter_map(|&(value, metric)| Some((value, metric?))).collect();
+    let (low, high) = if plan.from <= plan.to { (plan.from, plan.to) } else { (plan.to, plan.from) };
+    let high = if low < high { high } else { low + 1.0 };
+    let top = points.iter().map(|&(_, metric)| metric).fold(0.0, f64::max);
+    let top = if top > 0.0 { top * 1.1 } else { 1.0 };
+    let (parameter, metric) = (plan.parameter.quantity(), plan.metric.quantity());
+    let show = |quantity: Option<Quantity>, value: f64, decimals| match quantity {
+        Some(quantity) => units::show(app.units, quantity, value, decimals),
+        None => format!("{:.*}", decimals, value),
+    };
+    let metric_decimals = if plan.metric == Metric::Energy { 2 } else { 0 };
+
+    let title = (app.strings.sweep)(plan.metric.name(), plan.parameter.name());
+    let mut block = pane_block(app, title, app.theme.status_border);
+    if !sweep.done() {
+        let progress = format!(" {}/{} ", sweep.results.len(), RUNS);
+        block = block.title(Line::styled(progress, Style::default().fg(app.theme.notice)).right_aligned());
+    }
+    let unsettled = sweep.results.len() - points.len();
+    if unsettled > 0 {
+        let note = format!(" {} ", (app.strings.unsettled)(unsettled));
+        block = block.title_bottom(Line::styled(note, Style::default().fg(app.theme.muted)));
+    }
+    let dataset = Dataset::default()
+        .graph_type(GraphType::Line)
+        .marker(charts::chart_marker(app))
+        .style(Style::default().fg(app.theme.notice))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(
+            Axis::default()
+                .title(match parameter {
+                    Some(quantity) => units::titled(app.units, quantity, plan.parameter.name()),
+                    None => plan.parameter.name().to_string(),
+                })
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([low, high])
+                .labels(vec![Span::raw(show(parameter, low, 3)), Span::raw(show(parameter, high, 3))]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(app.theme.axis))
+                .bounds([0.0, top])
+                .labels(vec![Span::raw("0"), Span::raw(show(metric, top, metric_decimals))]),
+        );
+
+    let area = popup_area(f.area(), 64, 18);
+    f.render_widget(Clear, area);
+    f.render_widget(chart, area);
+}
+
+/// The finished game's best results, with this run's place marked, over
+/// the middle of the screen. Nothing for a game that keeps none.
+pub fn draw_scores(f: &mut ratatui::Frame, app: &App, game: &Game) {
+    let Some(board) = game.board(&app.scores) else {
+        return;
+    };
+    let mut lines: Vec<Line> = board
+        .best
+        .iter()
+        .enumerate()
+        .map(|(place, &result)| {
+            let this_run = app.placed == Some(place);
+            let style = if this_run {
+                Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.label)
+            };
+            let mark = if !this_run { "" } else if app.ascii { "  <- this run" } else { "  ◀ this run" };
+            Line::from(vec![
+                Span::styled(format!("  {}. {:>9}", place + 1, game.score_text(result)), style),
+                Span::styled(mark, style),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    let average = board.average().map_or_else(String::new, |average| format!(", average {}", game.score_text(average)));
+    let runs = match (game, board.runs) {
+        (Game::Billiards(_), 1) => "table cleared",
+        (Game::Billiards(_), _) => "tables cleared",
+        (_, 1) => "run",
+        _ => "runs",
+    };
+    lines.push(Line::from(Span::styled(
+        format!("  {} {}{}", board.runs, runs, average),
+        Style::default().fg(app.theme.muted),
+    )));
+
+    let next = match game {
+        Game::Dodge(_) | Game::Targets(_) => "[Enter] play again".to_string(),
+        Game::Billiards(_) => format!("[{}] menu", app.keymap.label(Action::Menu, app.ascii)),
+    };
+    let area = popup_area(f.area(), 44, lines.len() as u16 + 2);
+    f.render_widget(Clear, area);
+    let block = pane_block(app, format!(" {} High Scores  {} ", game.title(), next), app.theme.status_border);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Each phase's average time per frame in the top right corner, above a
+/// bar of how much of the frame budget they fill between them.
+pub fn draw_profiler(f: &mut ratatui::Frame, app: &App) {
+    let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+    let budget = ms(app.frame_rate);
+    let times = Phase::ALL.map(|phase| app.profiler.average(phase));
+    let total: Duration = times.iter().sum();
+    let (swatch, fill, empty) = if app.ascii { ("#", "#", ".") } else { ("■", "█", "·") };
+
+    let screen = f.area();
+    let width = screen.width.min(32);
+    let height = screen.height.min(Phase::ALL.len() as u16 + 4);
+    let area = Rect::new(screen.right() - width, screen.y, width, height);
+    let bar_width = width.saturating_sub(2) as usize;
+
+    // Stacked in phase order; past the budget the bar is simply full
+    let mut bar = Vec::new();
+    let mut elapsed = 0.0;
+    let mut filled = 0;
+    for (i, &time) in times.iter().enumerate() {
+        elapsed += ms(time);
+        let end = ((elapsed / budget * bar_width as f64).round() as usize).min(bar_width);
+        bar.push(Span::styled(fill.repeat(end - filled), Style::default().fg(app.theme.ball_color(i))));
+        filled = end;
+    }
+    bar.push(Span::styled(empty.repeat(bar_width - filled), Style::default().fg(app.theme.muted)));
+
+    let mut lines = vec![Line::from(bar)];
+    for (i, (phase, &time)) in Phase::ALL.iter().zip(&times).enumerate() {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", swatch), Style::default().fg(app.theme.ball_color(i))),
+            Span::styled(format!("{:<11}{:>6.2} ms", phase.label(), ms(time)), Style::default().fg(app.theme.label)),
+        ]));
+    }
+    let over = if ms(total) > budget { app.theme.notice } else { app.theme.muted };
+    lines.push(Line::from(Span::styled(
+        format!("  {:<11}{:>6.2} / {:.1} ms", "frame", ms(total), budget),
+        Style::default().fg(over),
+    )));
+
+    f.render_widget(Clear, area);
+    let block = pane_block(app, " Frame Timings ", app.theme.status_border);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// The chaos experiment's divergence chart, in the arena's bottom right
+/// corner.
+pub fn draw_twin(f: &mut ratatui::Frame, app: &App, twin: &Twin) {
+    let arena = app.arena_area;
+    let (width, height) = (arena.width.min(40), arena.height.min(10));
+    if width < 20 || height < 6 {
+        return;
+    }
+    let area = Rect::new(arena.right() - width, arena.bottom() - height, width, height);
+    f.render_widget(Clear, area);
+    charts::draw_divergence(f.buffer_mut(), app, twin, area);
+}
+
+/// The comparison's chart, in the arena's bottom left corner, clear of the
+/// twin's.
+pub fn draw_comparison(f: &mut ratatui::Frame, app: &App, comparison: &Comparison) {
+    let arena = app.arena_area;
+    let (width, height) = (arena.width.min(40), arena.height.min(10));
+    if width < 20 || height < 6 {
+        return;
+    }
+    let area = Rect::new(arena.x, arena.bottom() - height, width, height);
+    f.render_widget(Clear, area);
+    charts::draw_comparison(f.buffer_mut(), app, comparison, area);
+}
+
+/// The picked ball and what's around it, magnified [`ZOOM`] times, in the
+/// arena's top right corner. Balls fill every cell their disc covers, in
+/// their own glyph, so it's clear whose is whose where they touch.
+pub fn draw_zoom(f: &mut ratatui::Frame, app: &App) {
+    let Some(ball) = app.picked_ball() else {
+        return;
+    };
+    let arena = app.arena_area;
+    let (width, height) = ((arena.width / 3).min(32), (arena.height / 2).min(12));
+    if width < 12 || height < 6 {
+        return;
+    }
+    let area = Rect::new(arena.right() - width, arena.y, width, height);
+    f.render_widget(Clear, area);
+    let color = Style::default().fg(app.ball_color(ball.id));
+    let title = Line::from(vec![Span::raw((app.strings.zoom)(ZOOM)), Span::styled(format!("{} ", app.ball_glyph(ball.id)), color)]);
+    let block = pane_block(app, title, app.theme.arena_border);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let state = &app.state;
+    let zoom = ZOOM as f64;
+    let row = state.aspect;
+    let (wall, outside) = if app.ascii { ("#", ":") } else { ("█", "░") };
+    let buf = f.buffer_mut();
+    for y in 0..inner.height {
+        for x in 0..inner.width {
+            // The middle of the cell in arena units, where a ball's own
+            // cell is centered on it
+            let at_x = ball.x + (x as f64 + 0.5 - inner.width as f64 / 2.0) / zoom;
+            let at_y = ball.y + (y as f64 + 0.5 - inner.height as f64 / 2.0) / zoom * row;
+            // At least a cell across, however small
+            let covers = |other: &&BallState| {
+                let radius = other.radius.max(0.5 / zoom);
+                match other.body {
+                    // Inside when on the inner side of every edge, going round clockwise
+                    Some(body) => {
+                        let corners = body.shape.corners(other.x, other.y / row, radius, body.angle);
+                        corners.iter().zip(corners.iter().cycle().skip(1)).all(|(&(x0, y0), &(x1, y1))| {
+                            (x1 - x0) * (at_y / row - y0) - (y1 - y0) * (at_x - x0) >= 0.0
+                        })
+                    }
+                    None => (at_x - other.x).powi(2) + ((at_y - other.y) / row).powi(2) <= radius * radius,
+                }
+            };
+            let (symbol, style) = if let Some(other) = state.balls.iter().find(covers) {
+                let style = Style::default().fg(app.ball_color(other.id));
+                let style = if other.id == ball.id { style.add_modifier(Modifier::BOLD) } else { style };
+                (app.ball_glyph(other.id), style)
+            } else if at_x < -0.5 || at_y < -row / 2.0 || at_x > state.area_width - 0.5 || at_y > state.area_height - row / 2.0 {
+                (outside, Style::default().fg(app.theme.muted))
+            } else if state.obstacles.iter().any(|o| {
+                at_x + 0.5 > o.x && at_x + 0.5 < o.x + o.width && at_y + row / 2.0 > o.y && at_y + row / 2.0 < o.y + o.height
+            }) {
+                (wall, Style::default().fg(app.theme.obstacle))
+            } else {
+                continue;
+            };
+            buf.set_string(inner.x + x, inner.y + y, symbol, style);
+        }
+    }
+}
+
+/// A replay's timeline, in a box along the bottom of the arena: a track
+/// marked with how far in it is, then whether it's playing, how fast, and
+/// the time and tick it's at.
+pub fn draw_timeline(f: &mut ratatui::Frame, app: &App, replay: &Replay) {
+    let Some(area) = replay::timeline_area(app.arena_area) else {
+        return;
+    };
+    f.render_widget(Clear, area);
+    f.render_widget(pane_block(app, (app.strings.replay)(&replay.name), app.theme.status_border), area);
+
+    let track = replay::track_area(area);
+    let (played, cursor, rest) = if app.ascii { ("=", "O", "-") } else { ("━", "●", "─") };
+    let at = (replay.progress() * track.width.saturating_sub(1) as f64).round() as usize;
+    let line = Line::from(vec![
+        Span::styled(played.repeat(at), Style::default().fg(app.theme.selected)),
+        Span::styled(cursor, Style::default().fg(app.theme.notice).add_modifier(Modifier::BOLD)),
+        Span::styled(rest.repeat((track.width as usize).saturating_sub(at + 1)), Style::default().fg(app.theme.muted)),
+    ]);
+    f.render_widget(Paragraph::new(line), track);
+
+    let mark = match (replay.playing, app.ascii) {
+        (true, false) => "▶",
+        (true, true) => ">",
+        (false, false) => "⏸",
+        (false, true) => "||",
+    };
+    let seconds = |tick: f64| (tick - replay.first()) * replay.tick_ms / 1000.0;
+    let tick = replay.ticks[replay.index()];
+    let status = format!(
+        "{} {}x  {:.1} / {:.1} s  #{}",
+        mark,
+        replay.speed,
+        seconds(tick),
+        seconds(replay.last()),
+        tick
+    );
+    let row = Rect::new(track.x, track.y + 1, track.width, 1);
+    f.render_widget(Paragraph::new(Line::styled(status, Style::default().fg(app.theme.label))), row);
+}
+
+/// A newly loaded scenario's description in a box along the top of the
+/// arena, titled with its name.
+pub fn draw_caption(f: &mut ratatui::Frame, app: &App, name: &str, description: &str) {
+    let arena = app.arena_area;
+    let width = arena.width.min(64);
+    if width < 16 {
+        return;
+    }
+    let lines: Vec<Line> = wrap(description, width as usize - 4)
+        .into_iter()
+        .map(|line| Line::styled(format!(" {}", line), Style::default().fg(app.theme.label)))
+        .collect();
+    let height = (lines.len() as u16 + 2).min(arena.height);
+    let area = Rect::new(arena.x + (arena.width - width) / 2, arena.y, width, height);
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(pane_block(app, format!(" {} ", name), app.theme.status_border)), area);
+}
+
+/// `text` broken between words into lines of at most `width` characters,
+/// except for words longer than that.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+pub fn draw_palette(f: &mut ratatui::Frame, app: &App, palette: &Palette) {
+    let cursor = if app.ascii { "_" } else { "▏" };
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(":", Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD)),
+            Span::styled(palette.input.clone(), Style::default().fg(app.theme.label)),
+            Span::styled(cursor, Style::default().fg(app.theme.selected)),
+        ]),
+        Line::from(""),
+    ];
+    match &palette.error {
+        Some(error) => lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(app.theme.notice)))),
+        None => {
+            for (usage, description) in palette::matches(&palette.input) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<22}", usage), Style::default().fg(app.theme.label)),
+                    Span::styled(*description, Style::default().fg(app.theme.muted)),
+                ]));
+            }
+        }
+    }
+
+    let area = popup_area(f.area(), 64, lines.len() as u16 + 2);
+    f.render_widget(Clear, area);
+    let block = pane_block(app, " Command  [Enter] run  [Tab] complete  [Esc] cancel ", app.theme.status_border);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub fn draw_settings(f: &mut ratatui::Frame, app: &App, picker: &Picker<Setting>) {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let items = picker
+        .entries
+        .iter()
+        .map(|&setting| {
+            let value = match setting {
+                Setting::Theme => app.theme.name,
+                Setting::Background => app.backdrop.name(),
+                Setting::Ascii => on_off(app.ascii),
+                Setting::Color => on_off(app.color),
+            };
+            let value = if app.ascii { format!("< {} >", value) } else { format!("◀ {} ▶", value) };
+            Line::from(vec![
+                Span::styled(format!("{:<14}", setting.label()), Style::default().fg(app.theme.label)),
+                Span::styled(value, Style::default().fg(app.theme.muted)),
+            ])
+        })
+        .collect();
+    let description = "Changes last until you quit; set them in the [display] table of the config file to keep them.";
+    draw_picker_popup(f, app, " Settings  [←/→] change  [Esc] close ", items, picker.selected, description);
+}
+
+pub fn draw_flock(f: &mut ratatui::Frame, app: &App, flock: &Flock) {
+    let items = flock
+        .rows
+        .entries
+        .iter()
+        .map(|&rule| {
+            let value = match rule {
+                Rule::Flocking if flock.on => "on".to_string(),
+                Rule::Flocking => "off".to_string(),
+                Rule::Separation => format!("{:.2}", flock.boids.separation),
+                Rule::Alignment => format!("{:.2}", flock.boids.alignment),
+                Rule::Cohesion => format!("{:.2}", flock.boids.cohesion),
+            };
+            let value = if app.ascii { format!("< {} >", value) } else { format!("◀ {} ▶", value) };
+            Line::from(vec![
+                Span::styled(format!("{:<14}", rule.label()), Style::default().fg(app.theme.label)),
+                Span::styled(value, Style::default().fg(app.theme.muted)),
+            ])
+        })
+        .collect();
+    let description = "Separation keeps neighbors apart, alignment turns each ball to their heading and cohesion draws it to their center. A weight of 0 turns its rule off.";
+    draw_picker_popup(f, app, " Flocking  [←/→] change  [Esc] close ", items, flock.rows.selected, description);
+}
+
+pub fn draw_restyle(f: &mut ratatui::Frame, app: &App, restyle: &Restyle) {
+    let glyphs = if app.ascii { ASCII_BALL_CHARS } else { BALL_CHARS };
+    let color = restyle.color.map_or(app.theme.ball_color(restyle.id), Color::from);
+    let glyph = glyphs[restyle.glyph.map_or(restyle.id, |glyph| glyph.0) % glyphs.len()];
+    let items = restyle
+        .rows
+        .entries
+        .iter()
+        .map(|&look| {
+            let (label, value) = match look {
+                Look::Color => ("Color", restyle.color_name().unwrap_or("theme").to_string()),
+                Look::Glyph if restyle.glyph.is_none() => ("Glyph", format!("{} auto", glyph)),
+                Look::Glyph => ("Glyph", glyph.to_string()),
+            };
+            let value = if app.ascii { format!("< {} >", value) } else { format!("◀ {} ▶", value) };
+            Line::from(vec![
+                Span::styled(format!("{:<14}", label), Style::default().fg(app.theme.label)),
+                Span::styled(value, Style::default().fg(color)),
+            ])
+        })
+        .collect();
+    let title = format!(" Ball {}  [←/→] change  [Esc] close ", glyph);
+    let description = "Saves and exports keep the ball's color and glyph; theme and auto go back to the defaults.";
+    draw_picker_popup(f, app, &title, items, restyle.rows.selected, description);
+}
+
+/// The title over the whole screen, with the splash's balls in front of it.
+pub fn draw_splash(f: &mut ratatui::Frame, app: &App, splash: &Splash) {
+    let screen = f.area();
+    f.render_widget(Clear, screen);
+    let width = splash::TITLE[0].len() as u16;
+    let fill = if app.ascii { "#" } else { "█" };
+    let mut lines: Vec<Line> = if screen.width >= width {
+        let style = Style::default().fg(app.theme.arena_border).add_modifier(Modifier::BOLD);
+        splash::TITLE.iter().map(|row| Line::styled(row.replace('#', fill), style)).collect()
+    } else {
+        vec![Line::styled("BALL BOUNCE", Style::default().fg(app.theme.arena_border).add_modifier(Modifier::BOLD))]
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::styled(format!("v{}", env!("CARGO_PKG_VERSION")), Style::default().fg(app.theme.muted)));
+    let area = popup_area(screen, width.max(screen.width), lines.len() as u16);
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+
+    let glyphs = if app.ascii { ASCII_BALL_CHARS } else { BALL_CHARS };
+    f.render_widget(BallArenaWidget::new(&splash.sim).glyphs(glyphs).colors(app.theme.balls), screen);
+}
+
+/// In place of everything else while the terminal is under [`MIN_SIZE`].
+pub fn draw_too_small(f: &mut ratatui::Frame, app: &App) {
+    let screen = f.area();
+    let lines = vec![
+        Line::styled(
+            format!("{} {}x{}", app.strings.resize, MIN_SIZE.0, MIN_SIZE.1),
+            Style::default().fg(app.theme.notice).add_modifier(Modifier::BOLD),
+        ),
+        Line::styled(format!("({} {}x{})", app.strings.now, screen.width, screen.height), Style::default().fg(app.theme.muted)),
+    ];
+    let area = popup_area(screen, screen.width, lines.len() as u16);
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+}
+
+/// A `width` x `height` rectangle centered on `screen`, shrunk to fit.
+fn popup_area(screen: Rect, width: u16, height: u16) -> Rect {
+    let width = screen.width.min(width);
+    let height = screen.height.min(height);
+    Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn draw_picker_popup(
+    f: &mut ratatui::Frame,
+    app: &App,
+    title: &str,
+    items: Vec<Line>,
+    selected: usize,
+    description: &str,
+) {
+    let area = popup_area(f.area(), 64, items.len() as u16 + 8);
+    f.render_widget(Clear, area);
+
+    let block = pane_block(app, title, app.theme.status_border);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let list_height = inner.height.saturating_sub(3);
+    let list_area = Rect::new(inner.x, inner.y, inner.width, list_height);
+    let desc_area = Rect::new(inner.x, inner.y + list_height, inner.width, inner.height - list_height);
+
+    let list = List::new(items.into_iter().map(ListItem::new))
+        .highlight_style(Style::default().fg(app.theme.selected).add_modifier(Modifier::REVERSED))
+        .highlight_symbol(if app.ascii { "> " } else { "▶ " });
+    let mut state = ListState::default().with_selected(Some(selected));
+    f.render_stateful_widget(list, list_area, &mut state);
+
+    let description = Paragraph::new(description)
+        .style(Style::default().fg(app.theme.muted))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(description, desc_area);
+}
+
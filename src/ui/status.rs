@@ -0,0 +1,456 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use rust_tui_ball_bounce::BallState;
+
+use crate::app::App;
+use crate::game::{Game, PLAYERS, WINNING_SCORE};
+use crate::gas::Stat;
+use crate::keys::Action;
+use crate::layout::{Pane, Section};
+use crate::mode::Mode;
+use crate::ui::pane_block;
+use crate::units::{self, Quantity};
+
+/// The compact layout's one line in place of the controls panel: whether
+/// it's running, the ball count and speed, any notice, and the help key at
+/// the far end if there's room.
+pub fn draw_bar(buf: &mut Buffer, app: &App, area: Rect) {
+    let theme = app.theme;
+    let strings = app.strings;
+    let mut spans = vec![
+        Span::raw(" "),
+        running(app),
+        Span::styled(format!("  {} {}", strings.balls, app.state.balls.len()), Style::default().fg(theme.ball_count)),
+        Span::styled(format!("  {} {:.2}x", strings.speed, app.state.speed_multiplier), Style::default().fg(theme.speed)),
+    ];
+    if let Some(notice) = &app.notice {
+        spans.push(Span::styled(format!("  {}", notice), Style::default().fg(theme.notice)));
+    }
+    let line = Line::from(spans);
+    let help = format!("[{}] {} ", app.keymap.label(Action::Help, app.ascii), strings.hint(Action::Help));
+    let fits = line.width() + help.chars().count() < area.width as usize;
+    line.render(area, buf);
+    if fits {
+        Line::styled(help, Style::default().fg(theme.hint_alt)).right_aligned().render(area, buf);
+    }
+}
+
+// Where the energy bars start in the ball list, at the least, and how many
+// cells they take. Coordinates in meters can push them further right.
+const ENERGY_COLUMN: usize = 16;
+const ENERGY_WIDTH: usize = 10;
+
+/// The balls the controls panel lists, with their indices: up to four,
+/// from the picked one's group of four.
+pub fn listed(app: &App) -> impl Iterator<Item = (usize, &BallState)> {
+    let balls = &app.state.balls;
+    let cursor = app.ball_cursor.min(balls.len().saturating_sub(1));
+    balls.iter().enumerate().skip(cursor / 4 * 4).take(4)
+}
+
+/// While a gas runs, its collision statistics as measured and, in
+/// brackets, as kinetic theory expects them, each under its label.
+pub fn gas_lines(app: &App) -> Vec<(&'static str, String)> {
+    if !app.state.gas {
+        return Vec::new();
+    }
+    let strings = app.strings;
+    let readouts = app.gas_stats.readouts(&app.state);
+    let stat = |stat: Stat, quantity: Quantity, decimals| {
+        let show = |value: Option<f64>| value.map_or("-".to_string(), |value| units::show(app.units, quantity, value, decimals));
+        let symbol = if app.units.is_some() { format!(" {}", quantity.symbol()) } else { String::new() };
+        format!("{} ({}){}", show(stat.measured), show(stat.theory), symbol)
+    };
+    vec![
+        (strings.gas, strings.seen_theory.to_string()),
+        (strings.free_path, stat(readouts.free_path, Quantity::Length, 1)),
+        (strings.between, stat(readouts.between, Quantity::Time, 1)),
+        (strings.frequency, stat(readouts.frequency, Quantity::Frequency, 3)),
+    ]
+}
+
+/// While paused, the latest collision's balls and then the pair's momentum,
+/// by component, and kinetic energy, each as before → after, under its label.
+pub fn collision_lines(app: &App) -> Vec<(String, String)> {
+    let Some(exchange) = app.last_collision.filter(|_| app.state.paused) else {
+        return Vec::new();
+    };
+    let balls = format!("{} & {}", exchange.a + 1, exchange.b + 1);
+    let hit = match app.units {
+        Some(_) => format!("{} ({}, {})", balls, Quantity::Momentum.symbol(), Quantity::Energy.symbol()),
+        None => balls,
+    };
+    let (before, after) = (exchange.before(), exchange.after());
+    let arrow = if app.ascii { "->" } else { "→" };
+    let change = |quantity, before, after| {
+        format!("{} {} {}", units::show(app.units, quantity, before, 3), arrow, units::show(app.units, quantity, after, 3))
+    };
+    vec![
+        (app.strings.hit.to_string(), hit),
+        ("px:".to_string(), change(Quantity::Momentum, before.momentum.0, after.momentum.0)),
+        ("py:".to_string(), change(Quantity::Momentum, before.momentum.1, after.momentum.1)),
+        (format!("{}:", app.strings.kinetic), change(Quantity::Energy, before.energy, after.energy)),
+    ]
+}
+
+/// A listed ball's coordinate, as the controls panel shows it.
+pub fn position(app: &App, value: f64) -> String {
+    units::show(app.units, Quantity::Length, value, 0)
+}
+
+/// The cells of each listed ball's energy bar that are kinetic and then
+/// potential. They're scaled to the most energy any ball has, so a ball
+/// trading one for the other keeps its bar's length.
+pub fn energy_bars(app: &App) -> Vec<(usize, usize)> {
+    let state = &app.state;
+    let most = state.balls.iter().map(|ball| state.energy(ball)).fold(0.0, |most, (ke, pe)| f64::max(most, ke + pe));
+    let cells = |energy: f64| if most > 0.0 { ((energy / most * ENERGY_WIDTH as f64).round() as usize).min(ENERGY_WIDTH) } else { 0 };
+    listed(app)
+        .map(|(_, ball)| {
+            let (ke, pe) = state.energy(ball);
+            let ke_cells = cells(ke);
+            (ke_cells, cells(ke + pe).max(ke_cells) - ke_cells)
+        })
+        .collect()
+}
+
+fn running(app: &App) -> Span<'static> {
+    let strings = app.strings;
+    let status = match (app.state.paused, app.ascii) {
+        (true, false) => format!("⏸  {}", strings.paused),
+        (false, false) => format!("▶  {}", strings.running),
+        (true, true) => format!("|| {}", strings.paused),
+        (false, true) => format!(">  {}", strings.running),
+    };
+    let color = if app.state.paused { app.theme.paused } else { app.theme.running };
+    Span::styled(status, Style::default().fg(color).add_modifier(Modifier::BOLD))
+}
+
+pub fn draw(buf: &mut Buffer, app: &App, area: Rect) {
+    let theme = app.theme;
+    let strings = app.strings;
+
+    let mut text = vec![Line::from("")];
+    let mut listed_balls = false;
+    for &section in &app.status_panel {
+        match section {
+            Section::Status => text.push(Line::from(vec![label(app, strings.status), running(app)])),
+            Section::Counts => text.push(Line::from(vec![
+                label(app, strings.balls),
+                Span::styled(format!("{}", app.state.balls.len()), Style::default().fg(theme.ball_count).add_modifier(Modifier::BOLD)),
+            ])),
+            Section::Speed => text.push(Line::from(vec![
+                label(app, strings.speed),
+                Span::styled(format!("{:.2}x", app.state.speed_multiplier), Style::default().fg(theme.speed).add_modifier(Modifier::BOLD)),
+            ])),
+            Section::Scenario => scenario_lines(app, &mut text),
+            Section::Collisions => {
+                if app.state.count_hits {
+                    let hits = app.state.clicks;
+                    let highlight = Style::default().fg(theme.ball_count).add_modifier(Modifier::BOLD);
+                    text.push(Line::from(vec![label(app, strings.clicks), Span::styled(hits.to_string(), highlight)]));
+                }
+                for (name, value) in gas_lines(app) {
+                    text.push(Line::from(vec![label(app, name), Span::styled(value, Style::default().fg(theme.muted))]));
+                }
+                for (name, value) in collision_lines(app) {
+                    text.push(Line::from(vec![label(app, &name), Span::styled(value, Style::default().fg(theme.muted))]));
+                }
+            }
+            Section::Game => game_lines(app, &mut text),
+            // Either puts the balls in one list, with whichever of their
+            // positions and energy bars the panel shows
+            Section::Balls | Section::Energy if !listed_balls => {
+                listed_balls = true;
+                ball_lines(app, area, &mut text);
+            }
+            Section::Balls | Section::Energy => {}
+            Section::Rate => {
+                if let Some(rate) = hit_rate(app) {
+                    text.push(Line::from(vec![label(app, strings.hit_rate), Span::styled(rate, Style::default().fg(theme.muted))]));
+                }
+            }
+            Section::Hints => hint_lines(app, &mut text),
+        }
+    }
+
+    if let Some(notice) = &app.notice {
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            format!("  {}", notice),
+            Style::default().fg(theme.notice),
+        )));
+    }
+
+    let paragraph = Paragraph::new(text).block(pane_block(app, strings.controls, theme.status_border));
+
+    paragraph.render(area, buf);
+}
+
+// Labels line up in a column of ten
+fn label(app: &App, text: &str) -> Span<'static> {
+    Span::styled(format!("  {:<8}", text), Style::default().fg(app.theme.label).add_modifier(Modifier::BOLD))
+}
+
+fn scenario_lines(app: &App, text: &mut Vec<Line>) {
+    let theme = app.theme;
+    let strings = app.strings;
+    if let Some(name) = &app.profile {
+        text.push(Line::from(vec![
+            label(app, strings.profile),
+            Span::styled(format!(" {}", name), Style::default().fg(theme.muted)),
+        ]));
+    }
+    if let Some(name) = &app.state.scenario_name {
+        text.push(Line::from(vec![
+            label(app, strings.scene),
+            Span::styled(name.clone(), Style::default().fg(theme.muted)),
+        ]));
+    }
+}
+
+/// Collisions per second of the charted history, if it spans any time.
+pub fn hit_rate(app: &App) -> Option<String> {
+    let (&first, &last) = (app.history_ticks.first()?, app.history_ticks.last()?);
+    let seconds = (last - first) * app.tick_rate.as_secs_f64();
+    (seconds > 0.0).then(|| format!("{:.1}", app.markers.collisions() as f64 / seconds))
+}
+
+fn game_lines(app: &App, text: &mut Vec<Line>) {
+    let theme = app.theme;
+    let strings = app.strings;
+    let highlight = Style::default().fg(theme.ball_count).add_modifier(Modifier::BOLD);
+    if let Some(Game::Billiards(billiards)) = &app.game {
+        let score = if billiards.cleared(&app.state) {
+            format!(" cleared in {} shots", billiards.shots)
+        } else {
+            format!(" {} potted, {} shots", billiards.potted, billiards.shots)
+        };
+        let aim = if billiards.ready(&app.state) {
+            let degrees = if app.ascii { " deg" } else { "°" };
+            format!(" {:.0}{} at {:.0}%", billiards.angle.to_degrees(), degrees, billiards.power * 100.0)
+        } else {
+            " wait for the balls to stop".to_string()
+        };
+        text.push(Line::from(vec![label(app, strings.game), Span::styled("Billiards", highlight)]));
+        text.push(Line::from(vec![label(app, strings.score), Span::styled(score, highlight)]));
+        text.push(Line::from(vec![label(app, strings.aim), Span::styled(aim, Style::default().fg(theme.muted))]));
+    }
+    if let Some(Game::Dodge(dodge)) = &app.game {
+        let seconds = dodge.survived as f64 * app.tick_rate.as_secs_f64();
+        let survived = if dodge.over { format!(" {:.1}s, caught", seconds) } else { format!(" {:.1}s", seconds) };
+        text.push(Line::from(vec![label(app, strings.game), Span::styled("Dodge", highlight)]));
+        text.push(Line::from(vec![label(app, strings.survived), Span::styled(survived, highlight)]));
+        let effects: Vec<_> = dodge
+            .effects()
+            .map(|(power_up, left)| format!("{} {:.0}s", power_up.name(), (left as f64 * app.tick_rate.as_secs_f64()).ceil()))
+            .collect();
+        let effects = if effects.is_empty() {
+            Span::styled(" none", Style::default().fg(theme.muted))
+        } else {
+            Span::styled(format!(" {}", effects.join(", ")), Style::default().fg(theme.notice))
+        };
+        text.push(Line::from(vec![label(app, strings.power), effects]));
+    }
+    if let Some(Game::Targets(targets)) = &app.game {
+        let mut score = vec![label(app, strings.score)];
+        for (&(color, name), points) in PLAYERS.iter().zip(targets.scores) {
+            // With the ball's shape, for anyone who can't tell the colors apart
+            let glyph = app.state.balls.iter().find(|ball| ball.color == Some(color)).map_or("", |ball| app.ball_glyph(ball.id));
+            score.push(Span::styled(format!(" {}{} {}", glyph, name, points), Style::default().fg(color.into()).add_modifier(Modifier::BOLD)));
+        }
+        let goal = match targets.winner {
+            Some(winner) => format!(" {} wins", PLAYERS[winner].1),
+            None => format!(" first to {}", WINNING_SCORE),
+        };
+        text.push(Line::from(vec![label(app, strings.game), Span::styled("Targets", highlight)]));
+        text.push(Line::from(vec![label(app, strings.level), Span::styled(format!(" {}", targets.level), highlight)]));
+        text.push(Line::from(score));
+        text.push(Line::from(vec![label(app, strings.goal), Span::styled(goal, Style::default().fg(theme.muted))]));
+    }
+    if let Some((game, board)) = app.game.as_ref().and_then(|game| Some((game, game.board(&app.scores)?))) {
+        let best = board.best.first().map_or("none yet".to_string(), |&best| game.score_text(best));
+        text.push(Line::from(vec![label(app, strings.best), Span::styled(best, Style::default().fg(theme.muted))]));
+    }
+}
+
+/// Up to four balls, each with its position if the panel shows the balls
+/// and its energy bar if it shows energy and is wide enough.
+fn ball_lines(app: &App, area: Rect, text: &mut Vec<Line>) {
+    let theme = app.theme;
+    let strings = app.strings;
+    let balls = &app.state.balls;
+    let positions = app.status_panel.contains(&Section::Balls);
+    let (swatch, fill, empty) = if app.ascii { ("#", "#", ".") } else { ("■", "█", "·") };
+    let (kinetic, potential) = (Style::default().fg(theme.vx_border), Style::default().fg(theme.y_border));
+    let coordinates = |ball: &BallState| format!("x:{} y:{}", position(app, ball.x), position(app, ball.y));
+    let column = if positions {
+        listed(app).map(|(_, ball)| coordinates(ball).chars().count() + 5).fold(ENERGY_COLUMN, usize::max)
+    } else {
+        5
+    };
+    let energies = app.status_panel.contains(&Section::Energy) && area.width as usize >= 2 + column + ENERGY_WIDTH && !balls.is_empty();
+    // Above the positions, what they're in, if not cells
+    let unit = match app.units {
+        Some(_) if positions && !balls.is_empty() => format!("    x, y ({})", Quantity::Length.symbol()),
+        _ => String::new(),
+    };
+    let mut legend = vec![Span::styled(format!("{:<1$}", unit, column), Style::default().fg(theme.muted))];
+    if energies {
+        legend.push(Span::styled(format!("{} {}", swatch, strings.kinetic), kinetic));
+        legend.push(Span::styled(format!(" {} {}", swatch, strings.potential), potential));
+    }
+    text.push(Line::from(legend));
+
+    // Marking the picked ball while a pane that picks balls has the focus
+    let cursor = app.ball_cursor.min(balls.len().saturating_sub(1));
+    let picking = app.focus.is_some_and(|pane| pane != Pane::Arena);
+    for ((i, ball), (ke_cells, pe_cells)) in listed(app).zip(energy_bars(app)) {
+        let picked = if picking && i == cursor { Modifier::REVERSED } else { Modifier::empty() };
+        let glyph = Style::default().fg(app.ball_color(ball.id));
+        let mut line = vec![Span::styled(format!("  {} ", app.ball_glyph(ball.id)), glyph)];
+        if positions {
+            line.push(Span::styled(coordinates(ball), Style::default().fg(theme.muted).add_modifier(picked)));
+        } else {
+            line[0] = line[0].clone().patch_style(Style::default().add_modifier(picked));
+        }
+        if energies {
+            let used = line.iter().map(Span::width).sum::<usize>();
+            line.push(Span::raw(" ".repeat(column - used)));
+            line.push(Span::styled(fill.repeat(ke_cells), kinetic));
+            line.push(Span::styled(fill.repeat(pe_cells), potential));
+            line.push(Span::styled(empty.repeat(ENERGY_WIDTH - ke_cells - pe_cells), Style::default().fg(theme.muted)));
+        }
+        text.push(Line::from(line));
+    }
+    if balls.len() > 4 {
+        text.push(Line::from(Span::styled(
+            format!("  {}", (strings.more)(balls.len() - 4)),
+            Style::default().fg(theme.muted),
+        )));
+    }
+}
+
+/// The keys for the current mode, the focused pane and the game, under a
+/// rule.
+fn hint_lines(app: &App, text: &mut Vec<Line>) {
+    let theme = app.theme;
+    let strings = app.strings;
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled(
+        if app.ascii { "  ------------------------" } else { "  ────────────────────────" },
+        Style::default().fg(theme.muted),
+    )));
+    text.push(Line::from(""));
+
+    let arrows = if app.ascii { "[Arrows]" } else { "[←↑↓→]" };
+    let controls: Vec<(&str, &str, Color)> = match &app.mode {
+        Mode::LayoutEdit(_) => vec![
+            (arrows, strings.select_pane, theme.hint),
+            ("[Enter]", strings.pick_swap, theme.hint_add),
+            ("[[/]]", strings.width, theme.hint_up),
+            ("[{/}]", strings.height, theme.hint_up),
+            ("[C]", strings.change_pane, theme.hint_alt),
+            ("[0]", strings.reset_layout, theme.hint_remove),
+            ("[L/Esc]", strings.save_exit, theme.hint),
+        ],
+        Mode::ObstacleEdit(_) => vec![
+            (arrows, strings.move_cursor, theme.hint),
+            ("[Enter]", strings.toggle_wall, theme.hint_add),
+            ("[C]", strings.clear_walls, theme.hint_remove),
+            ("[W/Esc]", strings.done, theme.hint),
+        ],
+        Mode::Replay(_) => vec![
+            ("[Space]", strings.play_pause, theme.hint_add),
+            (if app.ascii { "[Left/Right]" } else { "[←/→]" }, strings.step_tick, theme.hint),
+            (if app.ascii { "[Up/Down]" } else { "[↑/↓]" }, strings.replay_speed, theme.hint_up),
+            ("[Home/End]", strings.start_end, theme.hint),
+            ("[Click]", strings.seek, theme.hint_alt),
+            ("[Esc]", strings.done, theme.hint),
+        ],
+        _ => Vec::new(),
+    };
+    let hint = |keys: &str, description: &str, color: Color| {
+        Line::from(Span::styled(format!("  {:<10} {}", keys, description), Style::default().fg(color)))
+    };
+    for &(keys, description, color) in &controls {
+        text.push(hint(keys, description, color));
+    }
+    let (left_right, up_down) = if app.ascii { ("[Left/Right]", "[Up/Down]") } else { ("[←/→]", "[↑/↓]") };
+    let focused: Vec<(&str, &str, Color)> = match app.focus {
+        None => Vec::new(),
+        Some(Pane::Arena | Pane::Quiver) => vec![("[Esc]", strings.unfocus, theme.hint)],
+        Some(Pane::Status) => vec![
+            (up_down, strings.pick_ball, theme.hint_up),
+            ("[Enter]", strings.restyle, theme.hint_alt),
+            ("[Esc]", strings.unfocus, theme.hint),
+        ],
+        Some(_) => vec![
+            (left_right, strings.crosshair, theme.hint_up),
+            (up_down, strings.pick_ball, theme.hint_up),
+            ("[Esc]", strings.unfocus, theme.hint),
+        ],
+    };
+    for (keys, description, color) in focused {
+        text.push(hint(keys, description, color));
+    }
+    if controls.is_empty() {
+        let mut bindings = vec![
+            (Action::Pause, theme.hint),
+            (Action::AddBall, theme.hint_add),
+            (Action::RemoveBall, theme.hint_remove),
+            (Action::SpeedUp, theme.hint_up),
+            (Action::SpeedDown, theme.hint_down),
+            (Action::Scenarios, theme.hint_alt),
+            (Action::Menu, theme.hint_alt),
+            (Action::Profiles, theme.hint_alt),
+            (Action::EditLayout, theme.hint_alt),
+            (Action::FocusNext, theme.hint_alt),
+            (Action::Help, theme.hint_alt),
+            (Action::Quit, theme.hint),
+        ];
+        if app.state.paused {
+            bindings.insert(1, (Action::Step, theme.hint));
+        }
+        if let Some(game) = &app.game {
+            let keys: Vec<(&str, &str, Color)> = match game {
+                Game::Billiards(_) if app.ascii => vec![
+                    (arrows, strings.aim_power, theme.hint_up),
+                    ("[Enter]", strings.shoot, theme.hint_add),
+                    ("[Drag]", strings.aim_shoot, theme.hint_alt),
+                ],
+                Game::Billiards(_) => vec![
+                    ("[←/→]", strings.aim_hint, theme.hint_up),
+                    ("[↑/↓]", strings.power_hint, theme.hint_up),
+                    ("[Enter]", strings.shoot, theme.hint_add),
+                    ("[Drag]", strings.aim_shoot, theme.hint_alt),
+                ],
+                Game::Dodge(dodge) => vec![
+                    (arrows, strings.move_ball, theme.hint_up),
+                    ("[Enter]", if dodge.over { strings.play_again } else { strings.stop }, theme.hint_add),
+                ],
+                Game::Targets(targets) => {
+                    let mut keys = vec![("[WASD]", strings.move_orange, theme.hint_up), (arrows, strings.move_blue, theme.hint_up)];
+                    if targets.winner.is_some() {
+                        keys.push(("[Enter]", strings.next_level, theme.hint_add));
+                    }
+                    keys
+                }
+            };
+            for (keys, description, color) in keys {
+                text.push(hint(keys, description, color));
+            }
+            bindings.retain(|(action, _)| matches!(action, Action::Pause | Action::Step | Action::Menu | Action::Help | Action::Quit));
+        }
+        for (action, color) in bindings {
+            let keys = format!("[{}]", app.keymap.label(action, app.ascii));
+            text.push(hint(&keys, strings.hint(action), color));
+        }
+    }
+}
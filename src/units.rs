@@ -0,0 +1,114 @@
+//! Readouts in meters and seconds. The simulation counts in cells and ticks:
+//! a unit of length is a column's width, rows being `aspect` units tall, and
+//! velocities are per tick at 1x. With `simulation.meters_per_cell` set, the
+//! controls panel, chart axes and described view convert to SI units, time
+//! counting ticks at the configured `tick_ms` as exports do, and
+//! `set gravity` takes m/s². Masses are in kg.
+
+use std::time::Duration;
+
+/// How many meters a cell is and seconds a tick takes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    pub meters_per_cell: f64,
+    pub seconds_per_tick: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantity {
+    Length,
+    Speed,
+    Time,
+    Frequency,
+    Acceleration,
+    Momentum,
+    Energy,
+}
+
+impl Quantity {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Quantity::Length => "m",
+            Quantity::Speed => "m/s",
+            Quantity::Time => "s",
+            Quantity::Frequency => "Hz",
+            Quantity::Acceleration => "m/s²",
+            Quantity::Momentum => "kg·m/s",
+            Quantity::Energy => "J",
+        }
+    }
+}
+
+impl Units {
+    /// Units of `meters_per_cell` at ticks of `tick_rate`; `None` for 0,
+    /// which keeps cells and ticks.
+    pub fn new(meters_per_cell: f64, tick_rate: Duration) -> Option<Units> {
+        (meters_per_cell > 0.0).then_some(Units { meters_per_cell, seconds_per_tick: tick_rate.as_secs_f64() })
+    }
+
+    /// SI units per simulation unit of `quantity`.
+    pub fn scale(self, quantity: Quantity) -> f64 {
+        match quantity {
+            Quantity::Length => self.meters_per_cell,
+            Quantity::Speed => self.meters_per_cell / self.seconds_per_tick,
+            Quantity::Time => self.seconds_per_tick,
+            Quantity::Frequency => 1.0 / self.seconds_per_tick,
+            Quantity::Acceleration => self.meters_per_cell / (self.seconds_per_tick * self.seconds_per_tick),
+            // Masses are the same in either
+            Quantity::Momentum => self.scale(Quantity::Speed),
+            Quantity::Energy => self.scale(Quantity::Speed).powi(2),
+        }
+    }
+
+    /// `value` in SI units as the simulation counts it.
+    pub fn to_sim(self, quantity: Quantity, value: f64) -> f64 {
+        value / self.scale(quantity)
+    }
+}
+
+/// `value`, in simulation units, as a number to show: with `decimals` as it
+/// is, or converted to three significant figures.
+pub fn show(units: Option<Units>, quantity: Quantity, value: f64, decimals: usize) -> String {
+    let Some(units) = units else {
+        return format!("{:.*}", decimals, value);
+    };
+    let value = value * units.scale(quantity);
+    let magnitude = if value == 0.0 { 0 } else { value.abs().log10().floor() as i32 };
+    format!("{:.*}", (2 - magnitude).clamp(0, 3) as usize, value)
+}
+
+/// [`show`], followed by the unit's symbol when there is one.
+pub fn show_with_symbol(units: Option<Units>, quantity: Quantity, value: f64, decimals: usize) -> String {
+    match units {
+        Some(_) => format!("{} {}", show(units, quantity, value, decimals), quantity.symbol()),
+        None => show(units, quantity, value, decimals),
+    }
+}
+
+/// `title` with the unit of `quantity` after it, when there is one.
+pub fn titled(units: Option<Units>, quantity: Quantity, title: &str) -> String {
+    match units {
+        Some(_) => format!("{} ({})", title, quantity.symbol()),
+        None => title.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_convert_to_three_significant_figures() {
+        let units = Units::new(0.1, Duration::from_millis(16));
+        assert_eq!(Units::new(0.0, Duration::from_millis(16)), None);
+        assert_eq!(show(None, Quantity::Length, 23.4, 0), "23");
+        assert_eq!(show(units, Quantity::Length, 23.4, 0), "2.34");
+        assert_eq!(show(units, Quantity::Time, 60.0, 0), "0.960");
+        assert_eq!(show_with_symbol(units, Quantity::Speed, -0.6, 1), "-3.75 m/s");
+        assert_eq!(show(units, Quantity::Length, 0.0, 0), "0.00");
+        assert_eq!(titled(units, Quantity::Length, "X"), "X (m)");
+        // Earth's gravity comes out a fairly gentle pull per tick
+        let g = units.unwrap().to_sim(Quantity::Acceleration, 9.81);
+        assert!((g - 0.0251).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,130 @@
+//! The full-screen arena that `join` and `attach` show for a simulation
+//! running elsewhere: one loop draws frames, reads keys and takes in what
+//! the other end sends, and each [`Remote`] supplies its title, its keys and
+//! what to make of the messages.
+
+use std::io::BufRead;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use crossterm::event::KeyCode;
+use futures::StreamExt;
+use ratatui::{
+    layout::Alignment,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders},
+};
+use rust_tui_ball_bounce::widget::BallArenaWidget;
+use rust_tui_ball_bounce::Snapshot;
+use serde::de::DeserializeOwned;
+use tokio::sync::mpsc::{self as async_mpsc, UnboundedReceiver};
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::config::Config;
+use crate::term::{Input, Term};
+use crate::theme::{self, Theme};
+use crate::ui::{ASCII_BALL_CHARS, ASCII_BORDER, BALL_CHARS};
+
+/// The other end of a connection, as a viewer sees it.
+pub trait Remote {
+    type Message;
+
+    /// Where the messages come in, ending when the connection does.
+    fn messages(&mut self) -> &mut UnboundedReceiver<Self::Message>;
+
+    fn receive(&mut self, message: Self::Message);
+
+    /// The arena to draw, once there is one.
+    fn state(&self) -> Option<&Snapshot>;
+
+    /// The title along the top.
+    fn title(&self, theme: &Theme, ascii: bool) -> Vec<Span<'static>>;
+
+    /// The keys, shown along the bottom.
+    fn keys(&self) -> &'static str;
+
+    /// Handles a key other than `q` or Esc, which leave.
+    fn key(&mut self, _code: KeyCode) -> Result<()> {
+        Ok(())
+    }
+
+    /// Why the viewer stopped when the connection ended.
+    fn gone(&self) -> String;
+}
+
+/// Reads one JSON value per line from `reader` on a thread of its own,
+/// until the connection ends or sends a line that isn't one.
+pub fn read_lines<T: DeserializeOwned + Send + 'static>(reader: impl BufRead + Send + 'static) -> UnboundedReceiver<T> {
+    let (tx, rx) = async_mpsc::unbounded_channel();
+    // Ends with the connection; the viewer notices when `rx` does
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(message) = line.map_err(|_| ()).and_then(|line| serde_json::from_str(&line).map_err(|_| ())) else {
+                return;
+            };
+            if tx.send(message).is_err() {
+                return;
+            }
+        }
+    });
+    rx
+}
+
+/// Shows `remote` until `q` or Esc, or until the connection ends.
+pub fn run(mut remote: impl Remote, term: &mut impl Term, config: &Config) -> Result<()> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()?
+        .block_on(event_loop(&mut remote, term, config))
+}
+
+async fn event_loop(remote: &mut impl Remote, term: &mut impl Term, config: &Config) -> Result<()> {
+    let theme = theme::by_name(&config.display.theme).unwrap_or(&theme::DEFAULT);
+    let mut input = term.input();
+    let mut frames = time::interval(Duration::from_secs(1) / config.display.fps);
+    frames.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    loop {
+        tokio::select! {
+            _ = frames.tick() => {
+                term.terminal().draw(|f| draw(f, remote, theme, config.display.ascii))?;
+            }
+            event = input.next() => match event {
+                Some(Ok(Input::Key(KeyCode::Char('q') | KeyCode::Esc))) => return Ok(()),
+                Some(Ok(Input::Key(code))) => remote.key(code)?,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err).context("could not read terminal input"),
+                None => return Ok(()),
+            },
+            message = remote.messages().recv() => match message {
+                Some(message) => remote.receive(message),
+                None => return Err(anyhow!(remote.gone())),
+            },
+        }
+    }
+}
+
+fn draw(f: &mut ratatui::Frame, remote: &impl Remote, theme: &Theme, ascii: bool) {
+    let keys = Line::from(Span::styled(remote.keys(), Style::default().fg(theme.muted))).alignment(Alignment::Right);
+    let mut block = Block::default()
+        .title(Line::from(remote.title(theme, ascii)))
+        .title_bottom(keys)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.arena_border));
+    if ascii {
+        block = block.border_set(ASCII_BORDER);
+    }
+    let Some(state) = remote.state() else {
+        f.render_widget(block, f.area());
+        return;
+    };
+    let glyphs = if ascii { ASCII_BALL_CHARS } else { BALL_CHARS };
+    let arena = BallArenaWidget::from_snapshot(state)
+        .block(block)
+        .glyphs(glyphs)
+        .colors(theme.balls)
+        .obstacles(if ascii { "#" } else { "█" }, Style::default().fg(theme.obstacle))
+        .ascii(ascii);
+    f.render_widget(arena, f.area());
+}
@@ -0,0 +1,220 @@
+//! A ratatui widget that draws the arena, for embedding the simulation in
+//! other terminal apps.
+//!
+//! ```
+//! use ratatui::{buffer::Buffer, layout::Rect, widgets::{Block, Widget}};
+//! use rust_tui_ball_bounce::{widget::BallArenaWidget, Simulation};
+//!
+//! let mut sim = Simulation::new(40.0, 10.0);
+//! sim.add_ball(sim.next_ball_spec());
+//! let area = Rect::new(0, 0, 42, 12);
+//! let mut buf = Buffer::empty(area);
+//! BallArenaWidget::new(&sim).block(Block::bordered()).render(area, &mut buf);
+//! ```
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Widget},
+};
+
+use crate::components::{Glyph, Rgb};
+use crate::scenario::{Obstacle, Pockets};
+use crate::{BallState, Simulation, Snapshot};
+
+pub const BALL_CHARS: &[&str] = &Glyph::SHAPES;
+pub const ASCII_BALL_CHARS: &[&str] = &Glyph::ASCII;
+pub const BALL_COLORS: &[Color] = &[
+    Color::Yellow,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+];
+
+// The radius from which squares and triangles are drawn in outline
+const OUTLINED: f64 = 1.5;
+
+impl From<Rgb> for Color {
+    fn from(Rgb(r, g, b): Rgb) -> Color {
+        Color::Rgb(r, g, b)
+    }
+}
+
+/// Draws balls, obstacles and pockets one arena cell per terminal cell, with rows
+/// `aspect` units tall. Balls outside the area are left out, so size the
+/// simulation to the area (minus any block borders) to see all of them.
+pub struct BallArenaWidget<'a> {
+    balls: &'a [BallState],
+    obstacles: &'a [Obstacle],
+    // With the right wall and floor they're placed against
+    pockets: Option<(Pockets, f64, f64)>,
+    aspect: f64,
+    block: Option<Block<'a>>,
+    glyphs: &'a [&'a str],
+    colors: &'a [Color],
+    wall: &'a str,
+    obstacle_style: Style,
+    pocket: &'a str,
+    pocket_style: Style,
+    ascii: bool,
+}
+
+impl<'a> BallArenaWidget<'a> {
+    pub fn new(sim: &'a Simulation) -> Self {
+        let pockets = sim.pockets.map(|pockets| (pockets, sim.area_width - 1.0, sim.area_height - sim.aspect));
+        Self::with_parts(sim.state(), &sim.obstacles, pockets, sim.aspect)
+    }
+
+    /// Draws a [`Snapshot`], such as one sent from a simulation thread.
+    pub fn from_snapshot(snapshot: &'a Snapshot) -> Self {
+        let floor = snapshot.area_height - snapshot.aspect;
+        let pockets = snapshot.pockets.map(|pockets| (pockets, snapshot.area_width - 1.0, floor));
+        Self::with_parts(&snapshot.balls, &snapshot.obstacles, pockets, snapshot.aspect)
+    }
+
+    fn with_parts(
+        balls: &'a [BallState],
+        obstacles: &'a [Obstacle],
+        pockets: Option<(Pockets, f64, f64)>,
+        aspect: f64,
+    ) -> Self {
+        BallArenaWidget {
+            balls,
+            obstacles,
+            pockets,
+            aspect,
+            block: None,
+            glyphs: BALL_CHARS,
+            colors: BALL_COLORS,
+            wall: "█",
+            obstacle_style: Style::default().fg(Color::Gray),
+            pocket: "░",
+            pocket_style: Style::default().fg(Color::DarkGray),
+            ascii: false,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Symbols for balls, picked by ball id.
+    pub fn glyphs(mut self, glyphs: &'a [&'a str]) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Colors for balls, picked by ball id. Balls with an [`Rgb`] color of
+    /// their own keep it.
+    pub fn colors(mut self, colors: &'a [Color]) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// The symbol obstacles are filled with, and its style.
+    pub fn obstacles(mut self, wall: &'a str, style: Style) -> Self {
+        self.wall = wall;
+        self.obstacle_style = style;
+        self
+    }
+
+    /// The symbol pockets are filled with, and its style.
+    pub fn pockets(mut self, pocket: &'a str, style: Style) -> Self {
+        self.pocket = pocket;
+        self.pocket_style = style;
+        self
+    }
+
+    /// Whether squares and triangles are drawn in ASCII.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+}
+
+impl Widget for BallArenaWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let inner = match &self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        for obstacle in self.obstacles {
+            let x0 = obstacle.x.round().max(0.0) as u16;
+            let y0 = (obstacle.y / self.aspect).round().max(0.0) as u16;
+            let x1 = ((obstacle.x + obstacle.width).round().max(0.0) as u16).min(inner.width);
+            let y1 = ((obstacle.y + obstacle.height) / self.aspect).round().max(0.0) as u16;
+            let y1 = y1.max(y0 + 1).min(inner.height);
+            for y in y0..y1 {
+                if x0 < x1 {
+                    let cells = self.wall.repeat((x1 - x0) as usize);
+                    buf.set_string(inner.x + x0, inner.y + y, cells, self.obstacle_style);
+                }
+            }
+        }
+
+        if let Some((pockets, right, floor)) = self.pockets {
+            for (x, y) in pockets.centers(right, floor) {
+                // Every cell whose middle is inside the pocket, and the one
+                // at its center however small it is
+                let r = pockets.radius;
+                let rows = ((y - r) / self.aspect).floor().max(0.0) as u16..=((y + r) / self.aspect).ceil() as u16;
+                for row in rows.filter(|&row| row < inner.height) {
+                    for column in ((x - r).floor().max(0.0) as u16..=(x + r).ceil() as u16).filter(|&c| c < inner.width) {
+                        let (cx, cy) = (column as f64, row as f64 * self.aspect);
+                        let center = column == x.round() as u16 && row == (y / self.aspect).round() as u16;
+                        if center || (cx - x).hypot(cy - y) < r {
+                            buf.set_string(inner.x + column, inner.y + row, self.pocket, self.pocket_style);
+                        }
+                    }
+                }
+            }
+        }
+
+        for ball in self.balls {
+            let bx = ball.x.round() as u16;
+            let by = (ball.y / self.aspect).round() as u16;
+            let color = ball.color.map_or(self.colors[ball.id % self.colors.len()], Color::from);
+            let style = Style::default()
+                .fg(color)
+                .add_modifier(Modifier::BOLD);
+
+            // Shapes big enough to span a few cells are outlined, under the
+            // symbol at their center
+            if let Some(body) = ball.body.filter(|_| ball.radius >= OUTLINED) {
+                let corners = body.shape.corners(ball.x, ball.y / self.aspect, ball.radius, body.angle);
+                let edge = if self.ascii { "*" } else { "•" };
+                for (i, &(x0, y0)) in corners.iter().enumerate() {
+                    let (x1, y1) = corners[(i + 1) % corners.len()];
+                    // Half a cell at a time, so no cell along the edge is skipped
+                    let steps = ((x1 - x0).hypot(y1 - y0) * 2.0).ceil().max(1.0) as usize;
+                    for step in 0..=steps {
+                        let t = step as f64 / steps as f64;
+                        let (x, y) = ((x0 + (x1 - x0) * t).round(), (y0 + (y1 - y0) * t).round());
+                        if x >= 0.0 && y >= 0.0 && x < inner.width as f64 && y < inner.height as f64 {
+                            buf.set_string(inner.x + x as u16, inner.y + y as u16, edge, style);
+                        }
+                    }
+                }
+            }
+
+            if bx < inner.width && by < inner.height {
+                let glyph = match ball.body {
+                    Some(body) => body.shape.glyph(body.angle, self.ascii),
+                    None => self.glyphs[ball.glyph.map_or(ball.id, |glyph| glyph.0) % self.glyphs.len()],
+                };
+                buf.set_string(inner.x + bx, inner.y + by, glyph, style);
+            }
+        }
+    }
+}
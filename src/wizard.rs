@@ -0,0 +1,220 @@
+use std::io;
+use std::path::Path;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::config::Config;
+use crate::term::{Input, Term};
+use crate::theme::THEMES;
+use crate::ui::{ASCII_BALL_CHARS, ASCII_BORDER, BALL_CHARS};
+
+const MAX_BALLS: usize = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Theme,
+    Glyphs,
+    Aspect,
+    Balls,
+}
+
+const STEPS: [Step; 4] = [Step::Theme, Step::Glyphs, Step::Aspect, Step::Balls];
+
+struct Wizard {
+    step: usize,
+    theme_idx: usize,
+    ascii: bool,
+    aspect_correction: bool,
+    balls: usize,
+}
+
+impl Wizard {
+    fn from_config(config: &Config) -> Self {
+        Wizard {
+            step: 0,
+            theme_idx: THEMES
+                .iter()
+                .position(|t| t.name == config.display.theme)
+                .unwrap_or(0),
+            ascii: config.display.ascii,
+            aspect_correction: config.display.aspect_correction,
+            balls: config.simulation.balls.clamp(1, MAX_BALLS),
+        }
+    }
+
+    fn adjust(&mut self, delta: isize) {
+        match STEPS[self.step] {
+            Step::Theme => {
+                let last = THEMES.len() as isize - 1;
+                self.theme_idx = (self.theme_idx as isize + delta).clamp(0, last) as usize;
+            }
+            Step::Glyphs => self.ascii = delta > 0,
+            Step::Aspect => self.aspect_correction = delta > 0,
+            Step::Balls => {
+                self.balls = (self.balls as isize + delta).clamp(1, MAX_BALLS as isize) as usize;
+            }
+        }
+    }
+
+    fn apply(&self, config: &mut Config) {
+        config.display.theme = THEMES[self.theme_idx].name.to_string();
+        config.display.ascii = self.ascii;
+        config.display.aspect_correction = self.aspect_correction;
+        config.simulation.balls = self.balls;
+    }
+}
+
+/// Whether to show the wizard: only when no config file exists yet.
+pub fn is_first_run(config_path: Option<&Path>) -> bool {
+    config_path.is_some_and(|path| !path.exists())
+}
+
+/// Asks for a few display defaults and returns `config` updated with the
+/// answers. Esc skips the remaining questions and keeps what's been chosen.
+/// Returns `None` if the user quits with `q`.
+pub fn run(term: &mut impl Term, mut config: Config) -> io::Result<Option<Config>> {
+    let mut wizard = Wizard::from_config(&config);
+
+    loop {
+        term.terminal().draw(|f| draw(f, &wizard))?;
+
+        let Input::Key(code) = term.read()? else {
+            continue;
+        };
+        match code {
+            KeyCode::Up | KeyCode::Left | KeyCode::Char('k') | KeyCode::Char('-') => wizard.adjust(-1),
+            KeyCode::Down | KeyCode::Right | KeyCode::Char('j') | KeyCode::Char('+') => wizard.adjust(1),
+            KeyCode::Backspace => wizard.step = wizard.step.saturating_sub(1),
+            KeyCode::Enter if wizard.step + 1 < STEPS.len() => wizard.step += 1,
+            KeyCode::Enter | KeyCode::Esc => {
+                wizard.apply(&mut config);
+                return Ok(Some(config));
+            }
+            KeyCode::Char('q') => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn draw(f: &mut ratatui::Frame, wizard: &Wizard) {
+    let theme = THEMES[wizard.theme_idx];
+    let screen = f.area();
+    let width = screen.width.min(64);
+    let height = screen.height.min(20);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(Clear, area);
+
+    let label = Style::default().fg(theme.label).add_modifier(Modifier::BOLD);
+    let muted = Style::default().fg(theme.muted);
+    let marker = if wizard.ascii { "> " } else { "▶ " };
+    let option = |selected: bool, text: String| {
+        if selected {
+            Line::from(Span::styled(
+                format!("  {}{}", marker, text),
+                Style::default().fg(theme.selected).add_modifier(Modifier::BOLD),
+            ))
+        } else {
+            Line::from(Span::styled(format!("    {}", text), muted))
+        }
+    };
+
+    let step = STEPS[wizard.step];
+    let mut text = vec![
+        Line::from(Span::styled(
+            "  No config file found, so let's pick a few defaults.",
+            muted,
+        )),
+        Line::from(""),
+    ];
+
+    match step {
+        Step::Theme => {
+            text.push(Line::from(Span::styled("  Color theme", label)));
+            text.push(Line::from(""));
+            for (i, t) in THEMES.iter().enumerate() {
+                text.push(option(i == wizard.theme_idx, t.name.to_string()));
+            }
+            text.push(Line::from(""));
+            let glyphs = if wizard.ascii { ASCII_BALL_CHARS } else { BALL_CHARS };
+            let mut preview = vec![Span::raw("    ")];
+            for (i, glyph) in glyphs.iter().enumerate() {
+                preview.push(Span::styled(
+                    format!("{} ", glyph),
+                    Style::default().fg(theme.ball_color(i)).add_modifier(Modifier::BOLD),
+                ));
+            }
+            text.push(Line::from(preview));
+        }
+        Step::Glyphs => {
+            text.push(Line::from(Span::styled("  Symbols", label)));
+            text.push(Line::from(""));
+            text.push(option(!wizard.ascii, format!("Unicode   {}", BALL_CHARS.join(" "))));
+            text.push(option(wizard.ascii, format!("ASCII     {}", ASCII_BALL_CHARS.join(" "))));
+            text.push(Line::from(""));
+            text.push(Line::from(Span::styled(
+                "  Pick ASCII if the Unicode row shows boxes or question marks.",
+                muted,
+            )));
+        }
+        Step::Aspect => {
+            text.push(Line::from(Span::styled("  Aspect correction", label)));
+            text.push(Line::from(""));
+            text.push(option(!wizard.aspect_correction, "Off".to_string()));
+            text.push(option(wizard.aspect_correction, "On".to_string()));
+            text.push(Line::from(""));
+            text.push(Line::from(Span::styled(
+                "  Terminal cells are about twice as tall as they are wide. With \
+                 correction on, each row counts as two units so balls move and \
+                 collide as they would on a square grid.",
+                muted,
+            )));
+        }
+        Step::Balls => {
+            text.push(Line::from(Span::styled("  Balls at startup", label)));
+            text.push(Line::from(""));
+            let (left, right) = if wizard.ascii { ("<", ">") } else { ("◀", "▶") };
+            text.push(Line::from(Span::styled(
+                format!("    {} {} {}", left, wizard.balls, right),
+                Style::default().fg(theme.selected).add_modifier(Modifier::BOLD),
+            )));
+        }
+    }
+
+    let footer = Line::from(Span::styled(
+        if wizard.ascii {
+            "  [Arrows] choose  [Enter] next  [Backspace] back  [Esc] finish"
+        } else {
+            "  [↑↓] choose  [Enter] next  [Backspace] back  [Esc] finish"
+        },
+        Style::default().fg(theme.hint),
+    ));
+
+    let block = Block::default()
+        .title(format!(" Setup ({}/{}) ", wizard.step + 1, STEPS.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.status_border));
+    let block = if wizard.ascii { block.border_set(ASCII_BORDER) } else { block };
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    f.render_widget(
+        Paragraph::new(text).wrap(Wrap { trim: false }),
+        Rect::new(inner.x, inner.y, inner.width, inner.height.saturating_sub(1)),
+    );
+    if inner.height > 0 {
+        f.render_widget(
+            Paragraph::new(footer),
+            Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1),
+        );
+    }
+}
@@ -0,0 +1,572 @@
+use std::any::Any;
+
+use proptest::prelude::*;
+use rust_tui_ball_bounce::components::{self, Glyph, Growth, Rgb, Shape};
+use rust_tui_ball_bounce::force::{Boids, Direction, Drag, Gravity, Wind};
+use rust_tui_ball_bounce::save::{SchemaError, SchemaVersion, SCHEMA_VERSION};
+use rust_tui_ball_bounce::{
+    scenario, BallSpec, BallState, SimEvent, Simulation, SimulationBuilder, SimulationObserver,
+};
+
+fn run(balls: &[(f64, f64, f64, f64)], steps: usize) -> Simulation {
+    let mut sim = Simulation::new(40.0, 20.0);
+    sim.add_force(Gravity::down(0.05));
+    sim.add_force(Drag(0.001));
+    for &(x, y, vx, vy) in balls {
+        sim.spawn(x, y, vx, vy, 0.75);
+    }
+    for _ in 0..steps {
+        sim.step(1.0);
+    }
+    sim
+}
+
+#[test]
+fn tick_is_a_step_at_the_current_speed() {
+    let mut ticked = Simulation::new(80.0, 20.0);
+    let mut stepped = Simulation::new(80.0, 20.0);
+    for sim in [&mut ticked, &mut stepped] {
+        sim.speed_multiplier = 1.5;
+        sim.add_ball(sim.next_ball_spec());
+        sim.add_ball(sim.next_ball_spec());
+    }
+    for _ in 0..50 {
+        ticked.tick();
+        stepped.step(1.5);
+    }
+    assert_eq!(ticked.state(), stepped.state());
+}
+
+#[test]
+fn step_advances_while_paused() {
+    let mut sim = Simulation::new(80.0, 20.0);
+    sim.add_ball(sim.next_ball_spec());
+    sim.set_paused(true);
+    let before = sim.state().to_vec();
+    sim.tick();
+    assert_eq!(sim.state(), before);
+    sim.step(1.0);
+    assert_eq!(sim.tick_count, 1);
+    assert_ne!(sim.state(), before);
+}
+
+#[test]
+fn balls_from_specs_keep_their_settings() {
+    let mut sim = Simulation::new(80.0, 20.0);
+    sim.max_history = 10;
+    let spec = BallSpec {
+        radius: Some(2.0),
+        ttl: Some(5),
+        ..BallSpec::new(10.0, 5.0, 1.0, 0.0)
+    };
+    let id = sim.add_ball(spec);
+    assert_eq!(sim.state()[0].radius, 2.0);
+    for _ in 0..4 {
+        sim.step(1.0);
+    }
+    assert_eq!(sim.state()[0].x, 14.0);
+    let histories: Vec<_> = sim.histories().map(|(id, h)| (id, h.x.len())).collect();
+    assert_eq!(histories, [(id, 4)]);
+    // The ttl runs out on the fifth step
+    sim.step(1.0);
+    assert!(sim.state().is_empty());
+}
+
+#[test]
+fn histories_keep_the_latest_samples() {
+    let mut sim = Simulation::new(80.0, 20.0);
+    sim.max_history = 3;
+    sim.add_ball(BallSpec::new(10.0, 5.0, 1.0, 0.0));
+    for _ in 0..10 {
+        sim.step(1.0);
+    }
+    assert_eq!(sim.history_ticks(), [8.0, 9.0, 10.0]);
+    let (_, history) = sim.histories().next().unwrap();
+    assert_eq!(&*history.x, [18.0, 19.0, 20.0]);
+    drop(history);
+    // Lowering the limit drops the extra samples on the next tick
+    sim.max_history = 1;
+    sim.step(1.0);
+    assert_eq!(sim.history_ticks(), [11.0]);
+    let (_, history) = sim.histories().next().unwrap();
+    assert_eq!(&*history.vx, [1.0]);
+    let text = toml::to_string(&*history).unwrap();
+    assert!(text.contains("x = [21.0]"), "{}", text);
+}
+
+#[test]
+fn late_balls_line_up_with_the_latest_ticks() {
+    let mut sim = Simulation::new(80.0, 20.0);
+    sim.add_ball(BallSpec::new(10.0, 5.0, 1.0, 0.0));
+    sim.step(1.0);
+    sim.step(1.0);
+    sim.add_ball(BallSpec::new(40.0, 5.0, 0.0, 1.0));
+    sim.step(1.0);
+    let ticks = sim.history_ticks().to_vec();
+    let points: Vec<Vec<(f64, f64)>> = sim
+        .histories()
+        .map(|(_, history)| components::with_ticks(&ticks, &history.y).collect())
+        .collect();
+    assert_eq!(points, [vec![(1.0, 5.0), (2.0, 5.0), (3.0, 5.0)], vec![(3.0, 6.0)]]);
+}
+
+#[test]
+fn building_from_a_scenario_matches_loading_it() {
+    let scenario = scenario::builtin("gas").unwrap();
+    let built = SimulationBuilder::new().arena(80.0, 20.0).scenario(&scenario).build();
+    let mut loaded = Simulation::new(80.0, 20.0);
+    loaded.load_scenario(&scenario);
+    assert_eq!(built.state(), loaded.state());
+    assert_eq!(built.scenario_name.as_deref(), Some(scenario.name.as_str()));
+    assert!(built.gas && loaded.gas);
+    loaded.reset();
+    assert!(!loaded.snapshot().gas);
+}
+
+#[test]
+fn setting_gravity_replaces_the_old_one() {
+    let mut sim = run(&[], 0);
+    sim.set_gravity(0.2);
+    assert_eq!(sim.forces.len(), 2);
+    let first: &dyn Any = &*sim.forces[0];
+    // Centered in the 40 x 20 arena, for when it's turned that way
+    assert_eq!(first.downcast_ref::<Gravity>(), Some(&Gravity { center: (19.5, 9.5), ..Gravity::down(0.2) }));
+
+    sim.set_gravity(0.0);
+    assert_eq!(sim.forces.len(), 1);
+    let only: &dyn Any = &*sim.forces[0];
+    assert!(only.is::<Drag>());
+}
+
+#[test]
+fn boids_fall_into_line() {
+    // Two neighbors heading apart at right angles, and a loner out of reach
+    let mut sim = Simulation::new(80.0, 40.0);
+    sim.spawn(10.0, 10.0, 0.5, 0.0, 0.5);
+    sim.spawn(12.0, 10.0, 0.0, 0.5, 0.5);
+    sim.spawn(60.0, 30.0, -0.5, 0.0, 0.5);
+    let boids = Boids { separation: 0.0, cohesion: 0.0, ..Boids::default() };
+    sim.set_boids(Some(boids));
+    assert_eq!(sim.snapshot().boids, Some(boids));
+    let heading = |sim: &Simulation, id: usize| sim.state()[id].vy.atan2(sim.state()[id].vx);
+    for _ in 0..40 {
+        sim.step(1.0);
+    }
+    assert!((heading(&sim, 0) - heading(&sim, 1)).abs() < 0.2);
+    assert_eq!((sim.state()[2].vx, sim.state()[2].vy), (-0.5, 0.0));
+
+    sim.set_boids(None);
+    assert_eq!(sim.boids(), None);
+    assert!(sim.forces.is_empty());
+}
+
+#[test]
+fn a_restored_flock_keeps_flocking() {
+    let mut sim = SimulationBuilder::new().scenario(&scenario::builtin("flock").unwrap()).arena(80.0, 40.0).build();
+    let mut restored = Simulation::restore(sim.save()).unwrap();
+    assert_eq!(restored.boids(), sim.boids());
+    assert!(restored.boids().is_some());
+    // Steered alike, where a flock without its rules would drift apart
+    let mut unsteered = Simulation::restore(sim.save()).unwrap();
+    unsteered.set_boids(None);
+    for _ in 0..50 {
+        sim.step(1.0);
+        restored.step(1.0);
+        unsteered.step(1.0);
+    }
+    assert_eq!(restored.state(), sim.state());
+    assert_ne!(unsteered.state(), sim.state());
+}
+
+#[test]
+fn thermostat_walls_heat_and_cool_the_gas() {
+    let mut sim = SimulationBuilder::new().scenario(&scenario::builtin("convection").unwrap()).arena(80.0, 40.0).build();
+    // Mean kinetic energy in the bottom and top halves, over a stretch
+    let (mut hot, mut cold) = ((0.0, 0.0), (0.0, 0.0));
+    for tick in 0..3000 {
+        sim.step(1.0);
+        if tick < 1000 {
+            continue;
+        }
+        for ball in sim.state() {
+            let half = if ball.y > 20.0 { &mut hot } else { &mut cold };
+            half.0 += 0.5 * ball.mass * (ball.vx * ball.vx + ball.vy * ball.vy);
+            half.1 += 1.0;
+        }
+    }
+    let (hot, cold) = (hot.0 / hot.1, cold.0 / cold.1);
+    assert!(hot > cold && hot < 0.4 && cold > 0.02);
+    assert_eq!(Simulation::restore(sim.save()).unwrap().thermostat, sim.thermostat);
+}
+
+#[test]
+fn gravity_pulls_whichever_way_it_is_turned() {
+    let mut sim = Simulation::new(41.0, 21.0);
+    sim.set_gravity_direction(Direction::Left);
+    sim.set_gravity(0.1);
+    sim.spawn(20.0, 5.0, 0.0, 0.0, 0.5);
+    sim.spawn(30.0, 15.0, 0.0, 0.0, 0.5);
+    sim.step(1.0);
+    assert_eq!((sim.state()[0].vx, sim.state()[0].vy), (-0.1, 0.0));
+
+    // Toward (20, 10), the middle of where centers can go
+    sim.set_gravity_direction(Direction::Center);
+    sim.step(1.0);
+    assert!((sim.state()[0].vx + 0.1).abs() < 0.01 && sim.state()[0].vy > 0.09);
+    assert!(sim.state()[1].vx < -0.1 && sim.state()[1].vy < 0.0);
+    assert_eq!(sim.snapshot().gravity_direction, Direction::Center);
+
+    // Kept through having no gravity, and saving
+    sim.set_gravity(0.0);
+    sim.set_gravity(0.2);
+    let restored = Simulation::restore(sim.save()).unwrap();
+    assert_eq!(restored.gravity_direction, Direction::Center);
+    let gravity = restored.forces[0].as_ref() as &dyn Any;
+    assert_eq!(gravity.downcast_ref::<Gravity>().map(|g| (g.g, g.direction)), Some((0.2, Direction::Center)));
+    assert_eq!(Direction::Center.next(), Direction::Down);
+}
+
+#[test]
+fn growing_balls_gain_mass_and_push_their_neighbors_apart() {
+    let mut sim = Simulation::new(80.0, 20.0);
+    let growth = Growth { rate: 0.1, min: 0.5, max: 2.0 };
+    for x in [20.0, 24.0] {
+        sim.add_ball(BallSpec { radius: Some(0.5), growth: Some(growth), ..BallSpec::new(x, 10.0, 0.0, 0.0) });
+    }
+    sim.add_ball(BallSpec {
+        radius: Some(1.0),
+        mass: Some(2.0),
+        growth: Some(Growth { rate: -0.05, ..Growth::default() }),
+        ..BallSpec::new(60.0, 10.0, 0.0, 0.0)
+    });
+    for _ in 0..30 {
+        sim.step(1.0);
+    }
+    let balls = sim.state();
+    assert_eq!((balls[0].radius, balls[1].radius), (2.0, 2.0));
+    assert!((balls[0].mass - 16.0).abs() < 1e-9);
+    assert!(balls[1].x - balls[0].x >= 4.0 - 1e-9);
+    // Down to the default least radius, at a hundredth of its mass
+    assert!((balls[2].radius - 0.1).abs() < 1e-9 && (balls[2].mass - 0.02).abs() < 1e-9);
+
+    let restored = Simulation::restore(sim.save()).unwrap();
+    assert_eq!(restored.save().balls[0].growth, Some(growth));
+
+    // Limits a scenario would turn down are held to the ones it allows
+    let mut sim = Simulation::new(80.0, 20.0);
+    let shrink = Growth { rate: -0.1, min: 0.0, max: 5.0 };
+    sim.add_ball(BallSpec { radius: Some(0.5), growth: Some(shrink), ..BallSpec::new(20.0, 10.0, 0.2, 0.0) });
+    // Starting past its largest, it's put there rather than snapping to it a tick later
+    sim.add_ball(BallSpec { radius: Some(4.0), growth: Some(growth), ..BallSpec::new(60.0, 10.0, 0.0, 0.0) });
+    assert_eq!(sim.state()[1].radius, 2.0);
+    for _ in 0..20 {
+        sim.step(1.0);
+    }
+    let balls = sim.state();
+    assert!((balls[0].radius - 0.1).abs() < 1e-9 && (balls[0].mass - 0.04).abs() < 1e-9 && balls[0].x.is_finite());
+    assert_eq!((balls[1].radius, balls[1].mass), (2.0, 1.0));
+    assert_eq!(sim.save().balls[0].growth, Some(Growth { rate: -0.1, min: 0.1, max: 5.0 }));
+}
+
+#[test]
+fn squares_and_triangles_collide_by_their_sides_and_corners() {
+    let mut sim = Simulation::new(80.0, 20.0);
+    // A square with its sides at 1.4 of its center against the left wall
+    let square = |x, vx| BallSpec { radius: Some(2.0), shape: Some(Shape::Square), ..BallSpec::new(x, 10.0, vx, 0.0) };
+    sim.add_ball(square(5.0, -0.5));
+    let mut leftmost = f64::INFINITY;
+    for _ in 0..20 {
+        sim.step(1.0);
+        leftmost = leftmost.min(sim.state()[0].x);
+    }
+    assert!(sim.state()[0].vx > 0.0);
+    assert!((leftmost - 2.0f64.sqrt()).abs() < 1e-9);
+
+    // Turned onto its corner, a ball coming at one of its sides gets
+    // within its corners' reach before they meet
+    let mut sim = Simulation::new(80.0, 20.0);
+    sim.add_ball(BallSpec { angle: Some(45.0), ..square(40.0, 0.0) });
+    sim.add_ball(BallSpec { radius: Some(0.5), ..BallSpec::new(44.0, 14.0, -0.1, -0.1) });
+    let mut closest = f64::INFINITY;
+    for _ in 0..40 {
+        sim.step(1.0);
+        let balls = sim.state();
+        closest = closest.min((balls[1].x - balls[0].x).hypot(balls[1].y - balls[0].y));
+    }
+    assert!((2.0f64.sqrt() + 0.5..2.5).contains(&closest), "{}", closest);
+    let balls = sim.state();
+    assert!(balls[0].vx < 0.0 && balls[0].vy < 0.0 && balls[1].vx.abs() < 1e-9);
+
+    // Spinning a quarter turn in ten ticks, from pointing up to pointing right
+    let mut sim = Simulation::new(80.0, 20.0);
+    sim.add_ball(BallSpec { shape: Some(Shape::Triangle), spin: Some(9.0), ..BallSpec::new(40.0, 10.0, 0.0, 0.0) });
+    for _ in 0..10 {
+        sim.step(1.0);
+    }
+    let body = sim.state()[0].body.unwrap();
+    assert!((body.angle - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    assert_eq!(body.shape.glyph(body.angle, false), "▶");
+    let saved = Simulation::restore(sim.save()).unwrap().save();
+    assert_eq!((saved.balls[0].shape, saved.balls[0].spin), (Some(Shape::Triangle), Some(9.0)));
+    assert!(scenario::Scenario::parse("[[balls]]\nx = 1.0\ny = 1.0\nspin = 5.0\n").is_err());
+}
+
+#[test]
+fn pushing_a_ball_changes_its_velocity() {
+    let mut sim = Simulation::new(40.0, 20.0);
+    sim.spawn(10.0, 5.0, 0.5, 0.0, 0.75);
+    assert!(sim.push_ball(0, 0.25, -1.0));
+    assert!(!sim.push_ball(7, 1.0, 1.0));
+    assert_eq!((sim.state()[0].vx, sim.state()[0].vy), (0.75, -1.0));
+    sim.step(1.0);
+    assert_eq!((sim.state()[0].x, sim.state()[0].y), (10.75, 4.0));
+}
+
+#[test]
+fn resized_balls_collide_at_their_new_size() {
+    let mut sim = Simulation::new(40.0, 20.0);
+    // Closing in too slowly to meet at this size
+    sim.spawn(10.0, 5.0, 0.1, 0.0, 0.5);
+    sim.spawn(12.0, 5.0, 0.0, 0.0, 0.5);
+    sim.step(1.0);
+    assert_eq!(sim.snapshot().collisions, 0);
+    assert!(sim.resize_ball(0, 1.75));
+    assert!(!sim.resize_ball(7, 1.0));
+    assert_eq!(sim.state()[0].radius, 1.75);
+    sim.step(1.0);
+    assert_eq!(sim.snapshot().collisions, 1);
+}
+
+#[test]
+fn restyled_balls_keep_their_look_through_a_save() {
+    let mut sim = Simulation::new(40.0, 20.0);
+    sim.add_ball(BallSpec { color: Some(Rgb(255, 136, 0)), ..BallSpec::new(10.0, 5.0, 0.5, 0.0) });
+    assert!(sim.restyle_ball(0, None, Some(Glyph(7))));
+    assert!(!sim.restyle_ball(7, None, None));
+    sim.step(1.0);
+    assert_eq!((sim.state()[0].color, sim.state()[0].glyph), (None, Some(Glyph(7))));
+
+    let text = toml::to_string(&sim.save()).unwrap();
+    assert!(text.contains("glyph = \"★\""));
+    let restored = Simulation::restore(toml::from_str(&text).unwrap()).unwrap();
+    assert_eq!(restored.state()[0].glyph, Some(Glyph(7)));
+    // The ASCII stand-in names the same shape
+    assert_eq!("x".parse(), Ok(Glyph(7)));
+    assert!("?".parse::<Glyph>().is_err());
+}
+
+#[test]
+fn shrinking_the_arena_brings_balls_back_inside() {
+    let mut sim = Simulation::new(41.0, 21.0);
+    sim.spawn(30.0, 5.0, 0.0, 0.0, 0.5);
+    sim.spawn(10.0, 18.0, 0.0, 0.0, 0.5);
+    sim.resize(21.0, 11.0, false);
+    let places: Vec<_> = sim.state().iter().map(|ball| (ball.x, ball.y)).collect();
+    assert_eq!(places, [(20.0, 5.0), (10.0, 10.0)]);
+
+    let mut sim = Simulation::new(41.0, 21.0);
+    sim.spawn(30.0, 5.0, 0.0, 0.0, 0.5);
+    sim.resize(21.0, 11.0, true);
+    assert_eq!((sim.state()[0].x, sim.state()[0].y), (15.0, 2.5));
+    // The world moved too, not just the cached state
+    sim.step(1.0);
+    assert_eq!((sim.state()[0].x, sim.state()[0].y), (15.0, 2.5));
+}
+
+#[test]
+fn the_field_is_sampled_only_when_asked() {
+    let mut sim = Simulation::new(40.0, 20.0);
+    sim.add_force(Gravity::down(0.05));
+    sim.add_force(Drag(0.01));
+    assert!(sim.snapshot().field.is_empty());
+
+    sim.field_spacing = Some((10.0, 10.0));
+    let field = sim.snapshot().field;
+    assert_eq!(field.len(), 8);
+    assert_eq!((field[0].x, field[0].y), (5.0, 5.0));
+    assert!(field.iter().all(|sample| (sample.ax, sample.ay) == (0.0, 0.05)));
+
+    // A positive charge pushes away from itself
+    sim.set_gravity(0.0);
+    sim.add_ball(BallSpec { charge: Some(4.0), ..BallSpec::new(20.0, 10.0, 0.0, 0.0) });
+    let (ax, ay) = sim.field_at(24.0, 10.0);
+    assert_eq!((ax, ay), (0.25, 0.0));
+
+    // Drag alone has no field to draw
+    sim.reset();
+    sim.add_force(Drag(0.01));
+    sim.field_spacing = Some((10.0, 10.0));
+    assert!(sim.snapshot().field.is_empty());
+}
+
+#[test]
+fn a_rising_ball_trades_kinetic_for_potential_energy() {
+    let mut sim = Simulation::new(40.0, 20.0);
+    sim.set_gravity(0.05);
+    let floor = sim.area_height - sim.aspect;
+    sim.add_ball(BallSpec::new(20.0, floor, 0.0, -1.0));
+    let snapshot = sim.snapshot();
+    assert_eq!(snapshot.gravity, 0.05);
+    let (kinetic, potential) = snapshot.energy(&snapshot.balls[0]);
+    assert_eq!((kinetic, potential), (0.5, 0.0));
+
+    for _ in 0..5 {
+        sim.tick();
+    }
+    let snapshot = sim.snapshot();
+    let (risen_kinetic, risen_potential) = snapshot.energy(&snapshot.balls[0]);
+    assert!(risen_kinetic < kinetic && risen_potential > 0.0);
+    assert!((risen_kinetic + risen_potential - kinetic).abs() < 0.05);
+}
+
+#[test]
+fn collisions_carry_the_velocities_either_side() {
+    let mut sim = Simulation::new(40.0, 20.0);
+    // Meeting off-center, so both components change
+    sim.spawn(10.0, 5.0, 1.0, 0.0, 1.0);
+    sim.spawn(12.5, 6.0, -0.5, 0.25, 1.0);
+    sim.step(1.0);
+    let events = sim.drain_events();
+    let Some(&SimEvent::Collision { before, after, .. }) = events.iter().find(|event| matches!(event, SimEvent::Collision { .. })) else {
+        panic!("no collision in {:?}", events);
+    };
+    assert_eq!(before, [(1.0, 0.0), (-0.5, 0.25)]);
+    assert_ne!(before, after);
+    let momentum = |[(vxa, vya), (vxb, vyb)]: [(f64, f64); 2]| (vxa + vxb, vya + vyb);
+    let energy = |[(vxa, vya), (vxb, vyb)]: [(f64, f64); 2]| vxa.hypot(vya).powi(2) + vxb.hypot(vyb).powi(2);
+    let (before_momentum, after_momentum) = (momentum(before), momentum(after));
+    assert!((before_momentum.0 - after_momentum.0).abs() < 1e-12 && (before_momentum.1 - after_momentum.1).abs() < 1e-12);
+    assert!((energy(before) - energy(after)).abs() < 1e-12);
+    // And the balls leave with the velocities after
+    let velocities: Vec<_> = sim.state().iter().map(|ball| (ball.vx, ball.vy)).collect();
+    assert_eq!(velocities, after);
+}
+
+#[test]
+fn colliding_blocks_count_digits_of_pi() {
+    for (slug, digits) in [("pi-1", 3), ("pi-100", 31), ("pi-10000", 314)] {
+        let scenario = scenario::builtin(slug).unwrap();
+        let mut sim = SimulationBuilder::new().arena(80.0, 20.0).aspect(2.0).scenario(&scenario).build();
+        assert!(sim.snapshot().count_hits);
+        // Until the heavy ball heads off faster than the light one can follow
+        let parted = |sim: &Simulation| {
+            let [light, heavy] = sim.state() else { unreachable!() };
+            heavy.vx > 0.0 && (0.0..=heavy.vx).contains(&light.vx)
+        };
+        for _ in 0..5000 {
+            if parted(&sim) {
+                break;
+            }
+            sim.tick();
+        }
+        assert!(parted(&sim), "{}", slug);
+        assert_eq!(sim.collisions + sim.wall_bounces, digits, "{}", slug);
+    }
+}
+
+#[test]
+fn saved_simulations_pick_up_where_they_left_off() {
+    // Forces in the order a `Forces` table builds them
+    let mut sim = Simulation::new(40.0, 20.0);
+    sim.add_force(Gravity::down(0.05));
+    sim.add_force(Wind { vx: 0.2, vy: 0.0, strength: 0.01 });
+    sim.add_force(Drag(0.001));
+    for (x, y) in [(5.0, 5.0), (15.0, 8.0), (25.0, 10.0)] {
+        sim.add_ball(BallSpec::new(x, y, 0.6, -0.3));
+    }
+    sim.remove_ball();
+    sim.add_ball(BallSpec {
+        charge: Some(1.5),
+        mass: Some(4.0),
+        ttl: Some(500),
+        color: Some(Rgb(255, 136, 0)),
+        ..BallSpec::new(30.0, 4.0, 0.0, 0.5)
+    });
+    for _ in 0..50 {
+        sim.step(1.0);
+    }
+
+    let text = toml::to_string(&sim.save()).unwrap();
+    assert!(text.starts_with(&format!("version = {}\n", SCHEMA_VERSION)));
+    let mut restored = Simulation::restore(toml::from_str(&text).unwrap()).unwrap();
+    assert_eq!(restored.save(), sim.save());
+    for _ in 0..50 {
+        sim.step(1.0);
+        restored.step(1.0);
+    }
+    assert_eq!(restored.state(), sim.state());
+    assert_eq!(restored.state()[2].color, Some(Rgb(255, 136, 0)));
+    assert_eq!(restored.state()[2].mass, 4.0);
+    assert_eq!(restored.ball_counter, sim.ball_counter);
+}
+
+#[test]
+fn newer_schema_versions_are_refused() {
+    let mut saved = run(&[], 0).save();
+    saved.version = SchemaVersion(SCHEMA_VERSION + 1);
+    assert_eq!(Simulation::restore(saved).err(), Some(SchemaError::TooNew(SCHEMA_VERSION + 1)));
+}
+
+#[derive(Default)]
+struct Counts {
+    spawns: usize,
+    collisions: usize,
+    removes: usize,
+}
+
+impl SimulationObserver for Counts {
+    fn on_spawn(&mut self, _id: usize) {
+        self.spawns += 1;
+    }
+
+    fn on_collision(&mut self, _a: usize, _b: usize, _x: f64, _y: f64) {
+        self.collisions += 1;
+    }
+
+    fn on_remove(&mut self, _id: usize) {
+        self.removes += 1;
+    }
+}
+
+#[test]
+fn observers_hear_about_events() {
+    let mut sim = Simulation::new(80.0, 20.0);
+    let mut counts = Counts::default();
+    sim.load_scenario(&scenario::builtin("head-on").unwrap());
+    for _ in 0..200 {
+        for event in sim.drain_events() {
+            counts.on_event(&event);
+        }
+        sim.step(1.0);
+    }
+    sim.remove_ball();
+    for event in sim.drain_events() {
+        counts.on_event(&event);
+    }
+    assert_eq!(counts.spawns, 2);
+    assert_eq!(counts.collisions as u64, sim.collisions);
+    assert!(counts.collisions > 0);
+    assert_eq!(counts.removes, 1);
+}
+
+fn arb_balls() -> impl Strategy<Value = Vec<(f64, f64, f64, f64)>> {
+    prop::collection::vec((0.0..39.0, 0.0..19.0, -3.0..3.0, -3.0..3.0), 1..12)
+}
+
+proptest! {
+    #[test]
+    fn same_inputs_give_the_same_run(balls in arb_balls()) {
+        let (first, second) = (run(&balls, 200), run(&balls, 200));
+        prop_assert_eq!(first.state(), second.state());
+    }
+
+    #[test]
+    fn balls_stay_in_bounds(balls in arb_balls(), steps in 1..300usize) {
+        let sim = run(&balls, steps);
+        let floor = sim.area_height - sim.aspect;
+        for &BallState { x, y, .. } in sim.state() {
+            prop_assert!((0.0..=sim.area_width - 1.0).contains(&x), "x = {}", x);
+            prop_assert!((0.0..=floor).contains(&y), "y = {}", y);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! The ball arena in a browser: the simulation core and the arena widget
+//! compiled to WebAssembly, drawn into an xterm.js terminal by `www/main.js`.
+//!
+//! JavaScript owns the clock and the terminal. It calls [`Demo::tick`] on a
+//! timer, writes whatever [`Demo::frame`] returns, and passes key presses to
+//! [`Demo::key`]; nothing here touches the time or any native backend.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::{Block, Borders, Widget};
+use rust_tui_ball_bounce::ansi::encode;
+use rust_tui_ball_bounce::widget::BallArenaWidget;
+use rust_tui_ball_bounce::{Command, Simulation, SimulationBuilder};
+use wasm_bindgen::prelude::*;
+
+/// Rows are twice as tall as columns are wide in most terminal fonts.
+const ASPECT: f64 = 2.0;
+const START_BALLS: usize = 3;
+
+#[wasm_bindgen]
+pub struct Demo {
+    sim: Simulation,
+    area: Rect,
+    // The last frame written, so the next only sends what changed
+    previous: Option<Buffer>,
+}
+
+#[wasm_bindgen]
+impl Demo {
+    /// A simulation filling a `cols` by `rows` terminal. `seed` places the
+    /// balls; pass something random for a different start each load.
+    #[wasm_bindgen(constructor)]
+    pub fn new(cols: u16, rows: u16, seed: u32) -> Demo {
+        let area = Rect::new(0, 0, cols, rows);
+        let (width, height) = arena_size(area);
+        let sim = SimulationBuilder::new()
+            .arena(width, height)
+            .aspect(ASPECT)
+            .seed(seed as u64)
+            .auto_balls(START_BALLS)
+            .build();
+        Demo { sim, area, previous: None }
+    }
+
+    /// Follows the terminal to a new size. The next frame redraws everything.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.area = Rect::new(0, 0, cols, rows);
+        let (width, height) = arena_size(self.area);
+        self.sim.area_width = width;
+        self.sim.area_height = height;
+        self.previous = None;
+    }
+
+    pub fn tick(&mut self) {
+        self.sim.tick();
+        self.sim.drain_events();
+    }
+
+    /// Handles a `KeyboardEvent.key` with the app's default bindings,
+    /// returning whether it was one of them.
+    pub fn key(&mut self, key: &str) -> bool {
+        let command = match key {
+            " " | "p" => Command::TogglePause,
+            "+" | "=" | "a" => Command::AddBall,
+            "-" | "_" | "r" => Command::RemoveBall,
+            "ArrowUp" => Command::SpeedUp,
+            "ArrowDown" => Command::SpeedDown,
+            "." => Command::Step,
+            _ => return false,
+        };
+        self.sim.apply(command);
+        self.sim.drain_events();
+        true
+    }
+
+    /// Escape sequences that bring the terminal up to date: the whole screen
+    /// after a resize or on the first call, otherwise just the changed cells.
+    pub fn frame(&mut self) -> String {
+        let mut buffer = Buffer::empty(self.area);
+        BallArenaWidget::new(&self.sim).block(self.block()).render(self.area, &mut buffer);
+        let output = match &self.previous {
+            Some(previous) => encode(previous, &buffer),
+            None => format!("\x1b[?25l\x1b[2J{}", encode(&Buffer::empty(self.area), &buffer)),
+        };
+        self.previous = Some(buffer);
+        output
+    }
+}
+
+impl Demo {
+    fn block(&self) -> Block<'static> {
+        let state = if self.sim.paused { "  PAUSED" } else { "" };
+        let title = format!(
+            " Balls: {}  Speed: x{:.1}{} ",
+            self.sim.state().len(),
+            self.sim.speed_multiplier,
+            state
+        );
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_bottom(" space pause  + add  - remove  ↑↓ speed ")
+    }
+}
+
+/// The arena inside the border, in simulation units.
+fn arena_size(area: Rect) -> (f64, f64) {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    (inner.width as f64, inner.height as f64 * ASPECT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_after_the_first_send_only_changes() {
+        let mut demo = Demo::new(40, 12, 1);
+        let first = demo.frame();
+        assert!(first.starts_with("\x1b[?25l\x1b[2J"));
+        assert!(first.contains("Balls:"));
+        assert_eq!(demo.frame(), "");
+        assert!(demo.key("a"));
+        assert!(!demo.key("z"));
+        assert!(demo.frame().contains('4'));
+        demo.resize(60, 20);
+        assert_eq!(demo.sim.area_width, 58.0);
+        assert!(demo.frame().starts_with("\x1b[?25l\x1b[2J"));
+    }
+}
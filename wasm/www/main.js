@@ -0,0 +1,36 @@
+// Runs the demo in xterm.js: ticks on a timer, draws on animation frames and
+// passes keys through. `pkg/` is the output of `wasm-pack build --target web`.
+import init, { Demo } from "./pkg/ball_bounce_wasm.js";
+
+// The app's default tick rate
+const TICK_MS = 16;
+
+await init();
+
+const term = new Terminal({ cursorBlink: false });
+const fit = new FitAddon.FitAddon();
+term.loadAddon(fit);
+term.open(document.getElementById("terminal"));
+fit.fit();
+
+const demo = new Demo(term.cols, term.rows, Math.floor(Math.random() * 2 ** 32));
+
+term.onResize(({ cols, rows }) => demo.resize(cols, rows));
+window.addEventListener("resize", () => fit.fit());
+term.onKey(({ domEvent }) => {
+  if (demo.key(domEvent.key)) {
+    domEvent.preventDefault();
+  }
+});
+
+setInterval(() => demo.tick(), TICK_MS);
+
+function draw() {
+  const output = demo.frame();
+  if (output) {
+    term.write(output);
+  }
+  requestAnimationFrame(draw);
+}
+requestAnimationFrame(draw);
+term.focus();